@@ -0,0 +1,93 @@
+//! Measures the encode and decode throughput of the built-in codecs on a representative message,
+//! independently of the network, to compare the cost of each format for a given schema.
+//!
+//! Run with `cargo bench --bench codecs` from the `benchmarks` directory. The payload size of
+//! the message defaults to 1024 bytes, and can be overridden via the `SELIUM_BENCH_PAYLOAD_SIZE`
+//! environment variable.
+
+use bytes::BytesMut;
+use clap::Parser;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use selium::codecs::{BincodeCodec, JsonCodec, MessagePackCodec, StringCodec};
+use selium::traits::{MessageDecoder, MessageEncoder};
+use selium_benchmarks::{args::Args, payload::Payloads};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PAYLOAD_SIZE: &str = "1024";
+
+/// A typical event, with a handful of small fields alongside its payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Event {
+    id: u64,
+    timestamp: u64,
+    source: String,
+    tags: Vec<String>,
+    payload: String,
+}
+
+fn payload() -> String {
+    let size = std::env::var("SELIUM_BENCH_PAYLOAD_SIZE")
+        .unwrap_or_else(|_| DEFAULT_PAYLOAD_SIZE.to_owned());
+    let args = Args::parse_from(["selium-benchmarks", "--payload-size", &size]);
+
+    Payloads::generate(&args)
+        .expect("Failed to generate payload")
+        .get(0)
+        .to_owned()
+}
+
+fn event(payload: String) -> Event {
+    Event {
+        id: 1_234_567,
+        timestamp: 1_700_000_000_000,
+        source: "/acmeco/sensors/temperature".to_owned(),
+        tags: vec![
+            "region:ap-southeast-2".to_owned(),
+            "build:release".to_owned(),
+        ],
+        payload,
+    }
+}
+
+/// Benchmarks encoding and decoding `item` with `codec`, measuring throughput in encoded bytes.
+fn bench_codec<C, Item>(c: &mut Criterion, name: &str, codec: C, item: Item)
+where
+    C: MessageEncoder<Item> + MessageDecoder<Item>,
+    Item: Clone,
+{
+    let encoded = BytesMut::from(&codec.encode(item.clone()).unwrap()[..]);
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+    group.bench_function("encode", |b| {
+        b.iter_batched(
+            || item.clone(),
+            |item| codec.encode(item).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("decode", |b| {
+        b.iter_batched(
+            || encoded.clone(),
+            |mut buffer| codec.decode(&mut buffer).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+fn codecs(c: &mut Criterion) {
+    let payload = payload();
+    let event = event(payload.clone());
+
+    bench_codec(c, "string", StringCodec, payload);
+    bench_codec(c, "bincode", BincodeCodec::default(), event.clone());
+    bench_codec(c, "json", JsonCodec::default(), event.clone());
+    bench_codec(c, "messagepack", MessagePackCodec::default(), event);
+}
+
+criterion_group!(benches, codecs);
+criterion_main!(benches);
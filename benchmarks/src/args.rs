@@ -1,4 +1,6 @@
+use crate::payload::PayloadDistribution;
 use clap::Parser;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 pub struct Args {
@@ -10,7 +12,34 @@ pub struct Args {
     #[arg(long, default_value_t = 10)]
     pub num_of_streams: u64,
 
-    /// Size (in bytes) of the message payload
-    #[arg(long, default_value_t = 32)]
-    pub message_size: u64,
+    /// Size (in bytes) of the message payload, or the maximum size for a uniform distribution
+    #[arg(long, alias = "message-size", default_value_t = 32)]
+    pub payload_size: u64,
+
+    /// The distribution of payload sizes
+    #[arg(long, value_enum, default_value_t = PayloadDistribution::Fixed)]
+    pub payload_distribution: PayloadDistribution,
+
+    /// A file containing the payloads to send, one per line, for the file distribution
+    #[arg(long)]
+    pub payload_file: Option<PathBuf>,
+
+    /// The number of bytes that the server may send on each stream before the client
+    /// acknowledges them
+    #[arg(long, default_value_t = selium::STREAM_RECEIVE_WINDOW_DEFAULT)]
+    pub stream_receive_window: u64,
+
+    /// The number of bytes that the server may send across every stream on the connection before
+    /// the client acknowledges them
+    #[arg(long, default_value_t = selium::RECEIVE_WINDOW_DEFAULT)]
+    pub receive_window: u64,
+
+    /// The address of an already running server to benchmark, such as a remote deployment,
+    /// rather than starting a local server
+    #[arg(long)]
+    pub external_addr: Option<String>,
+
+    /// The certificate authority that the server's certificate is verified against
+    #[arg(long, default_value = "certs/ca.crt")]
+    pub ca_cert: PathBuf,
 }
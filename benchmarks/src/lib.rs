@@ -1,3 +1,4 @@
 pub mod args;
+pub mod payload;
 pub mod results;
 pub mod runner;
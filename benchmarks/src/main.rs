@@ -5,7 +5,7 @@ use selium_benchmarks::{args::Args, runner::BenchmarkRunner};
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    let runner = BenchmarkRunner::init().await?;
+    let runner = BenchmarkRunner::init(&args).await?;
     let results = runner.run(args).await?;
 
     println!("{results}");
@@ -0,0 +1,136 @@
+use crate::args::Args;
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use rand::Rng;
+use std::fs;
+
+// The number of distinct payloads generated for the uniform distribution, which are cycled
+// through rather than generating a new payload for every message
+const UNIFORM_POOL_SIZE: usize = 1024;
+
+/// Determines the sizes of the payloads sent during a benchmark.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PayloadDistribution {
+    /// Every payload is exactly `--payload-size` bytes
+    #[default]
+    Fixed,
+    /// Payload sizes are uniformly distributed between 1 and `--payload-size` bytes
+    Uniform,
+    /// Payloads are read from `--payload-file`, one per line
+    File,
+}
+
+/// The payloads sent during a benchmark, generated before the benchmark starts so that
+/// generating them isn't measured.
+#[derive(Debug, Clone)]
+pub struct Payloads {
+    payloads: Vec<String>,
+}
+
+impl Payloads {
+    pub fn generate(args: &Args) -> Result<Self> {
+        let payloads = match args.payload_distribution {
+            PayloadDistribution::Fixed => vec![generate_payload(args.payload_size as usize)],
+            PayloadDistribution::Uniform => {
+                if args.payload_size == 0 {
+                    bail!("The payload size must be greater than zero for a uniform distribution");
+                }
+
+                let mut rng = rand::thread_rng();
+
+                (0..UNIFORM_POOL_SIZE)
+                    .map(|_| generate_payload(rng.gen_range(1..=args.payload_size) as usize))
+                    .collect()
+            }
+            PayloadDistribution::File => {
+                let path = args
+                    .payload_file
+                    .as_ref()
+                    .context("A --payload-file is required for the file distribution")?;
+                let contents = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read payload file {}", path.display()))?;
+
+                contents.lines().map(ToOwned::to_owned).collect()
+            }
+        };
+
+        if payloads.is_empty() {
+            bail!("At least one payload is required to run the benchmark");
+        }
+
+        Ok(Self { payloads })
+    }
+
+    /// Returns the payload of the message at `index`, cycling through the generated payloads.
+    pub fn get(&self, index: u64) -> &str {
+        &self.payloads[index as usize % self.payloads.len()]
+    }
+}
+
+fn generate_payload(payload_size: usize) -> String {
+    (0..payload_size)
+        .map(|i| (i % 25 + 97) as u8 as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::io::Write;
+
+    fn args(extra: &[&str]) -> Args {
+        Args::parse_from(["selium-benchmarks"].iter().chain(extra))
+    }
+
+    #[test]
+    fn generates_fixed_payloads() {
+        let payloads = Payloads::generate(&args(&["--payload-size", "4096"])).unwrap();
+
+        assert!((0..10).all(|i| payloads.get(i).len() == 4096));
+    }
+
+    #[test]
+    fn generates_uniform_payloads_within_bounds() {
+        let payloads = Payloads::generate(&args(&[
+            "--payload-size",
+            "100",
+            "--payload-distribution",
+            "uniform",
+        ]))
+        .unwrap();
+
+        let sizes: Vec<usize> = (0..UNIFORM_POOL_SIZE as u64)
+            .map(|i| payloads.get(i).len())
+            .collect();
+
+        assert!(sizes.iter().all(|&size| (1..=100).contains(&size)));
+        assert!(sizes.iter().any(|&size| size != sizes[0]));
+    }
+
+    #[test]
+    fn reads_payloads_from_file() {
+        let path = std::env::temp_dir().join("selium-benchmarks-payloads.txt");
+        let mut file = fs::File::create(&path).unwrap();
+        writeln!(file, "first\nsecond payload").unwrap();
+
+        let payloads = Payloads::generate(&args(&[
+            "--payload-distribution",
+            "file",
+            "--payload-file",
+            path.to_str().unwrap(),
+        ]))
+        .unwrap();
+
+        assert_eq!(payloads.get(0), "first");
+        assert_eq!(payloads.get(1), "second payload");
+        assert_eq!(payloads.get(2), "first");
+    }
+
+    #[test]
+    fn requires_payload_file_for_file_distribution() {
+        let result = Payloads::generate(&args(&["--payload-distribution", "file"]));
+
+        assert!(result.is_err());
+    }
+}
@@ -1,7 +1,38 @@
 use crate::args::Args;
 use num_format::{Locale, ToFormattedString};
+use selium::ConnectionStats;
 use std::{fmt::Display, time::Duration};
 
+/// Percentiles of the time taken for a message to be delivered from a publisher to the
+/// subscriber, from the moment it was sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyPercentiles {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyPercentiles {
+    /// Calculates the percentiles of the provided latencies by nearest rank, which are all zero
+    /// if there are none.
+    pub fn calculate(mut latencies: Vec<Duration>) -> Self {
+        latencies.sort_unstable();
+
+        let percentile = |p: usize| match latencies.len() {
+            0 => Duration::ZERO,
+            len => latencies[(len * p).div_ceil(100).max(1) - 1],
+        };
+
+        Self {
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: percentile(100),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BenchmarkResults {
     duration: Duration,
@@ -9,11 +40,21 @@ pub struct BenchmarkResults {
     total_mb_transferred: f64,
     avg_throughput: f64,
     avg_latency: f64,
+    latency: LatencyPercentiles,
+    stats: ConnectionStats,
 }
 
 impl BenchmarkResults {
-    pub fn calculate(duration: Duration, args: Args) -> Self {
-        let total_bytes_transferred = args.num_of_messages * args.message_size;
+    /// Calculates the results of a benchmark, where `total_bytes_transferred` is the sum of the
+    /// sizes of every message payload received, and `latencies` holds the time taken to deliver
+    /// each message.
+    pub fn calculate(
+        duration: Duration,
+        args: Args,
+        total_bytes_transferred: u64,
+        latencies: Vec<Duration>,
+        stats: ConnectionStats,
+    ) -> Self {
         let total_mb_transferred = total_bytes_transferred as f64 / 1024.0 / 1024.0;
         let avg_throughput = total_mb_transferred / duration.as_secs_f64();
         let avg_latency = duration.as_nanos() as f64 / args.num_of_messages as f64;
@@ -24,8 +65,14 @@ impl BenchmarkResults {
             total_mb_transferred,
             avg_throughput,
             avg_latency,
+            latency: LatencyPercentiles::calculate(latencies),
+            stats,
         }
     }
+
+    pub fn latency(&self) -> LatencyPercentiles {
+        self.latency
+    }
 }
 
 impl Display for BenchmarkResults {
@@ -41,10 +88,15 @@ Benchmark Results
 ---------------------
 Number of Messages: {}
 Number of Streams: {}
-Message Size (Bytes): {}",
+Payload Size (Bytes): {} ({:?})
+Stream Receive Window (Bytes): {}",
             self.args.num_of_messages.to_formatted_string(&Locale::en),
             self.args.num_of_streams.to_formatted_string(&Locale::en),
-            self.args.message_size.to_formatted_string(&Locale::en),
+            self.args.payload_size.to_formatted_string(&Locale::en),
+            self.args.payload_distribution,
+            self.args
+                .stream_receive_window
+                .to_formatted_string(&Locale::en),
         );
 
         let header = format!(
@@ -57,6 +109,79 @@ Message Size (Bytes): {}",
             duration, total_transferred, avg_throughput, avg_latency
         );
 
-        write!(f, "{summary}\n\n{header}\n{body}\n")
+        let connection = format!(
+            "
+Connection
+---------------------
+RTT: {:.2} ms
+Lost Packets: {} of {}
+Congestion Events: {}",
+            self.stats.rtt.as_secs_f64() * 1000.0,
+            self.stats.lost_packets.to_formatted_string(&Locale::en),
+            self.stats.sent_packets.to_formatted_string(&Locale::en),
+            self.stats
+                .congestion_events
+                .to_formatted_string(&Locale::en),
+        );
+
+        let latency = format!(
+            "
+Latency
+---------------------
+p50: {:.2} ms
+p90: {:.2} ms
+p99: {:.2} ms
+Max: {:.2} ms",
+            self.latency.p50.as_secs_f64() * 1000.0,
+            self.latency.p90.as_secs_f64() * 1000.0,
+            self.latency.p99.as_secs_f64() * 1000.0,
+            self.latency.max.as_secs_f64() * 1000.0,
+        );
+
+        write!(
+            f,
+            "{summary}\n\n{header}\n{body}\n{latency}\n{connection}\n"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn calculates_ordered_latency_percentiles() {
+        let latencies = (1..=1000).rev().map(Duration::from_micros).collect();
+        let latency = LatencyPercentiles::calculate(latencies);
+
+        assert_eq!(latency.p50, Duration::from_micros(500));
+        assert_eq!(latency.p90, Duration::from_micros(900));
+        assert_eq!(latency.p99, Duration::from_micros(990));
+        assert_eq!(latency.max, Duration::from_micros(1000));
+    }
+
+    #[test]
+    fn populates_latency_of_results() {
+        let args = Args::parse_from(["selium-benchmarks", "--num-of-messages", "3"]);
+        let latencies = vec![
+            Duration::from_millis(3),
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+        ];
+
+        let results = BenchmarkResults::calculate(
+            Duration::from_secs(1),
+            args,
+            96,
+            latencies,
+            ConnectionStats::default(),
+        );
+        let latency = results.latency();
+
+        assert!(latency.p50 > Duration::ZERO);
+        assert!(latency.p50 <= latency.p99);
+        assert!(latency.p99 <= latency.max);
+        assert_eq!(latency.max, Duration::from_millis(3));
     }
 }
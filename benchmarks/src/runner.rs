@@ -1,14 +1,18 @@
-use crate::{args::Args, results::BenchmarkResults};
+use crate::{args::Args, payload::Payloads, results::BenchmarkResults};
 use anyhow::Result;
-use futures::{future::join_all, SinkExt, StreamExt};
-use selium::{codecs::StringCodec, prelude::*, Client};
+use futures::{future::join_all, StreamExt};
+use selium::{codecs::StringCodec, prelude::*, Client, Headers};
 use std::{
     process::{Child, Command},
-    time::Instant,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 const SERVER_ADDR: &str = "127.0.0.1:7001";
 
+// The header carrying the time that a message was sent, in nanoseconds since the benchmark started
+const SENT_AT_HEADER: &str = "sent-at";
+
 fn start_server() -> Child {
     Command::new(env!("CARGO"))
         .args([
@@ -27,45 +31,66 @@ fn start_server() -> Child {
         .expect("Failed to start server")
 }
 
-fn generate_message(message_size: usize) -> String {
-    (0..message_size)
-        .map(|i| (i % 25 + 97) as u8 as char)
-        .collect()
+async fn connect(args: &Args, addr: &str) -> Result<Client> {
+    let connection = selium::client()
+        .stream_receive_window(args.stream_receive_window)
+        .receive_window(args.receive_window)
+        .with_certificate_authority(args.ca_cert.clone())?
+        .connect(addr)
+        .await?;
+
+    Ok(connection)
 }
 
 pub struct BenchmarkRunner {
-    server_handle: Child,
+    // Only present if the runner started a local server, rather than targeting an external one
+    server_handle: Option<Child>,
     connection: Client,
 }
 
 impl BenchmarkRunner {
-    pub async fn init() -> Result<Self> {
-        let server_handle = start_server();
+    /// Starts a local server, and connects to it, unless `--external-addr` is provided, in which
+    /// case the runner connects to the already running server at that address instead.
+    pub async fn init(args: &Args) -> Result<Self> {
+        if let Some(addr) = &args.external_addr {
+            return Ok(Self {
+                server_handle: None,
+                connection: connect(args, addr).await?,
+            });
+        }
 
-        let connection = selium::client()
-            .with_certificate_authority("certs/ca.crt")?
-            .connect(SERVER_ADDR)
-            .await?;
+        let mut server_handle = start_server();
+
+        let connection = match connect(args, SERVER_ADDR).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                let _ = server_handle.kill();
+                let _ = server_handle.wait();
+                return Err(err);
+            }
+        };
 
         Ok(Self {
-            server_handle,
+            server_handle: Some(server_handle),
             connection,
         })
     }
 
     pub async fn run(&self, args: Args) -> Result<BenchmarkResults> {
-        let mut tasks = Vec::with_capacity((args.num_of_streams + 1) as usize);
-        let message = generate_message(args.message_size as usize);
+        let mut tasks = Vec::with_capacity(args.num_of_streams as usize);
+        let payloads = Arc::new(Payloads::generate(&args)?);
+        let messages_per_stream = args.num_of_messages / args.num_of_streams;
         let start = Instant::now();
 
         let mut subscriber = self
             .connection
             .subscriber("/acmeco/stocks")
             .with_decoder(StringCodec)
+            .with_headers()
             .open()
             .await?;
 
-        for _ in 0..args.num_of_streams {
+        for stream in 0..args.num_of_streams {
             let mut publisher = self
                 .connection
                 .publisher("/acmeco/stocks")
@@ -73,11 +98,17 @@ impl BenchmarkRunner {
                 .open()
                 .await?;
 
-            let message = message.clone();
+            let payloads = payloads.clone();
 
             let handle = tokio::spawn(async move {
-                for _ in 0..args.num_of_messages / args.num_of_streams {
-                    publisher.send(message.to_owned()).await.unwrap();
+                let first = stream * messages_per_stream;
+
+                for index in first..first + messages_per_stream {
+                    let sent_at = start.elapsed().as_nanos().to_string();
+                    let headers = Headers::from([(SENT_AT_HEADER.to_owned(), sent_at)]);
+                    let payload = payloads.get(index).to_owned();
+
+                    publisher.send_with_headers(payload, headers).await.unwrap();
                 }
 
                 publisher.finish().await.unwrap();
@@ -86,22 +117,46 @@ impl BenchmarkRunner {
             tasks.push(handle);
         }
 
-        let handle = tokio::spawn(async move {
-            for _ in 0..args.num_of_messages {
-                let _ = subscriber.next().await.unwrap();
+        let received = tokio::spawn(async move {
+            let message_count = messages_per_stream * args.num_of_streams;
+            let mut total_bytes = 0;
+            let mut latencies = Vec::with_capacity(message_count as usize);
+
+            for _ in 0..message_count {
+                let message = subscriber.next().await.unwrap().unwrap();
+                let sent_at: u64 = message.headers[SENT_AT_HEADER].parse().unwrap();
+
+                total_bytes += message.payload.len() as u64;
+                latencies.push(
+                    start
+                        .elapsed()
+                        .saturating_sub(Duration::from_nanos(sent_at)),
+                );
             }
+
+            (total_bytes, latencies)
         });
 
-        tasks.push(handle);
         join_all(tasks).await;
+        let (total_bytes, latencies) = received.await?;
         let elapsed = start.elapsed();
-
-        Ok(BenchmarkResults::calculate(elapsed, args))
+        let stats = self.connection.connection_stats().await;
+
+        Ok(BenchmarkResults::calculate(
+            elapsed,
+            args,
+            total_bytes,
+            latencies,
+            stats,
+        ))
     }
 }
 
 impl Drop for BenchmarkRunner {
     fn drop(&mut self) {
-        self.server_handle.kill().unwrap();
+        if let Some(server_handle) = self.server_handle.as_mut() {
+            server_handle.kill().unwrap();
+            server_handle.wait().unwrap();
+        }
     }
 }
@@ -0,0 +1,30 @@
+use clap::Parser;
+use selium_benchmarks::{args::Args, runner::BenchmarkRunner};
+use std::env;
+
+// The address of the running server to benchmark, along with an optional `--ca-cert` override
+const ADDR_VAR: &str = "SELIUM_BENCHMARK_ADDR";
+const CA_CERT_VAR: &str = "SELIUM_BENCHMARK_CA_CERT";
+
+#[tokio::test]
+#[ignore = "requires a running server, whose address is provided via SELIUM_BENCHMARK_ADDR"]
+async fn benchmarks_external_server() {
+    let addr = env::var(ADDR_VAR).expect("SELIUM_BENCHMARK_ADDR must be set");
+    let mut args = vec![
+        "selium-benchmarks".to_owned(),
+        "--external-addr".to_owned(),
+        addr,
+        "--num-of-messages".to_owned(),
+        "1000".to_owned(),
+    ];
+
+    if let Ok(ca_cert) = env::var(CA_CERT_VAR) {
+        args.extend(["--ca-cert".to_owned(), ca_cert]);
+    }
+
+    let args = Args::parse_from(args);
+    let runner = BenchmarkRunner::init(&args).await.unwrap();
+    let results = runner.run(args).await.unwrap();
+
+    assert!(results.latency().p50 <= results.latency().p99);
+}
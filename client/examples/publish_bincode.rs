@@ -31,9 +31,9 @@ async fn main() -> Result<()> {
     let mut publisher = connection
         .publisher("/acmeco/stocks")
         .with_encoder(BincodeCodec::default())
+        .retain(Duration::from_secs(600))?
         // Coming soon...
         // .map("/acmeco/forge_numbers.wasm")
-        // .retain(Duration::from_secs(600))?
         .open()
         .await?;
 
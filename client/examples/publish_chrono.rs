@@ -14,9 +14,9 @@ async fn main() -> Result<()> {
     let mut publisher = connection
         .publisher("/acmeco/stocks")
         .with_encoder(StringCodec)
+        .retain(chrono::Duration::minutes(25))?
         // Coming soon...
         // .map("/acmeco/forge_numbers.wasm")
-        // .retain(chrono::Duration::minutes(25))?
         .open()
         .await?;
 
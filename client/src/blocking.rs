@@ -0,0 +1,468 @@
+//! A blocking facade over the asynchronous `Selium` client, for synchronous codebases.
+//!
+//! The blocking [Client], [Publisher] and [Subscriber] wrap their asynchronous counterparts,
+//! driving them to completion on an internal [tokio] runtime, which is created when the client
+//! connects and shared by every stream opened from it. The runtime keeps the connection alive in
+//! the background between calls, so keep-alives and reconnections behave as they do for the
+//! asynchronous client.
+//!
+//! Requires the `blocking` feature.
+//!
+//! **NOTE:** The blocking API must not be used from within an asynchronous context, such as a
+//! [tokio] task, as blocking on the internal runtime from within another runtime panics. Use the
+//! asynchronous API there instead.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use selium::codecs::StringCodec;
+//!
+//! # fn run() -> selium::Result<()> {
+//! let client = selium::blocking::client()
+//!     .with_certificate_authority("certs/client/ca.der")?
+//!     .connect("127.0.0.1:7001")?;
+//!
+//! let mut subscriber = client
+//!     .subscriber("/acmeco/stocks")
+//!     .with_decoder(StringCodec)
+//!     .open()?;
+//!
+//! while let Some(message) = subscriber.recv()? {
+//!     println!("NEW MESSAGE: \"{message}\"");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::codecs::CodecRegistry;
+use crate::traits::{
+    BorrowedMessageEncoder, MessageEncoder, Open, RegistryDecoder, SubscriberDecoder, SyncDecoder,
+};
+use crate::{
+    ClientWantsCert, ClientWantsConnect, Error, Headers, PublisherWantsEncoder, PublisherWantsOpen,
+    RawSubscriberWantsOpen, Result, SubscriberWantsDecoder, SubscriberWantsOpen,
+};
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::runtime::{self, Runtime};
+
+/// Constructs a blocking [ClientBuilder] in its initial state, equivalent to
+/// [selium::client](crate::client).
+pub fn client() -> ClientBuilder<ClientWantsCert> {
+    ClientBuilder {
+        inner: crate::client(),
+    }
+}
+
+/// A blocking wrapper around a [ClientBuilder](crate::ClientBuilder), which connects a blocking
+/// [Client].
+///
+/// The most common options are available directly, while any other option can be set on the
+/// underlying builder via [configure](ClientBuilder::configure).
+pub struct ClientBuilder<T> {
+    inner: crate::ClientBuilder<T>,
+}
+
+impl<T> ClientBuilder<T> {
+    /// Applies any configuration to the underlying asynchronous
+    /// [ClientBuilder](crate::ClientBuilder).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let builder = selium::blocking::client()
+    ///     .configure(|builder| Ok(builder.enable_0rtt()))
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the configuration fails.
+    pub fn configure<U, F>(self, f: F) -> Result<ClientBuilder<U>>
+    where
+        F: FnOnce(crate::ClientBuilder<T>) -> Result<crate::ClientBuilder<U>>,
+    {
+        Ok(ClientBuilder {
+            inner: f(self.inner)?,
+        })
+    }
+}
+
+impl<T> From<crate::ClientBuilder<T>> for ClientBuilder<T> {
+    fn from(inner: crate::ClientBuilder<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl ClientBuilder<ClientWantsCert> {
+    /// See [keep_alive](crate::ClientBuilder::keep_alive).
+    pub fn keep_alive<T: crate::traits::TryIntoU64>(self, interval: T) -> Result<Self> {
+        Ok(self.inner.keep_alive(interval)?.into())
+    }
+
+    /// See [with_client_certificate](crate::ClientBuilder::with_client_certificate).
+    pub fn with_client_certificate<C: Into<PathBuf>, K: Into<PathBuf>>(
+        self,
+        cert_path: C,
+        key_path: K,
+    ) -> Result<Self> {
+        Ok(self
+            .inner
+            .with_client_certificate(cert_path, key_path)?
+            .into())
+    }
+
+    /// See [with_certificate_authority](crate::ClientBuilder::with_certificate_authority).
+    pub fn with_certificate_authority<T: Into<PathBuf>>(
+        self,
+        ca_path: T,
+    ) -> Result<ClientBuilder<ClientWantsConnect>> {
+        Ok(self.inner.with_certificate_authority(ca_path)?.into())
+    }
+
+    /// See [with_rustls_config](crate::ClientBuilder::with_rustls_config).
+    pub fn with_rustls_config(
+        self,
+        config: rustls::ClientConfig,
+    ) -> ClientBuilder<ClientWantsConnect> {
+        self.inner.with_rustls_config(config).into()
+    }
+
+    /// See [dangerous_skip_verification](crate::ClientBuilder::dangerous_skip_verification).
+    #[cfg(feature = "dangerous-configuration")]
+    pub fn dangerous_skip_verification(self) -> ClientBuilder<ClientWantsConnect> {
+        self.inner.dangerous_skip_verification().into()
+    }
+}
+
+impl ClientBuilder<ClientWantsConnect> {
+    /// See [add_endpoint](crate::ClientBuilder::add_endpoint).
+    pub fn add_endpoint(self, addr: &str) -> Self {
+        self.inner.add_endpoint(addr).into()
+    }
+
+    /// Creates the internal runtime, and then blocks until a connection to the `Selium` server
+    /// at `addr` is established. See [connect](crate::ClientBuilder::connect).
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the runtime can't be created, or under the same conditions as
+    /// [connect](crate::ClientBuilder::connect).
+    pub fn connect(self, addr: &str) -> Result<Client> {
+        // A worker thread keeps driving the connection while the caller isn't blocked on it
+        let runtime = runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("selium-blocking")
+            .enable_all()
+            .build()
+            .map_err(Error::other)?;
+
+        let inner = runtime.block_on(self.inner.connect(addr))?;
+
+        Ok(Client {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+}
+
+/// A blocking wrapper around a connected [Client](crate::Client).
+#[derive(Clone)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Returns a blocking [StreamBuilder] for a [Subscriber]. See
+    /// [subscriber](crate::Client::subscriber).
+    pub fn subscriber(&self, topic: &str) -> StreamBuilder<SubscriberWantsDecoder> {
+        self.builder(self.inner.subscriber(topic))
+    }
+
+    /// Returns a blocking [StreamBuilder] for a [Publisher]. See
+    /// [publisher](crate::Client::publisher).
+    pub fn publisher(&self, topic: &str) -> StreamBuilder<PublisherWantsEncoder> {
+        self.builder(self.inner.publisher(topic))
+    }
+
+    /// Blocks until the client's streams have been gracefully closed, or the `timeout` has
+    /// elapsed. See [graceful_shutdown](crate::Client::graceful_shutdown).
+    pub fn graceful_shutdown<T: crate::traits::TryIntoU64>(self, timeout: T) -> Result<()> {
+        self.runtime.block_on(self.inner.graceful_shutdown(timeout))
+    }
+
+    fn builder<T>(&self, inner: crate::StreamBuilder<T>) -> StreamBuilder<T> {
+        StreamBuilder {
+            inner,
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+/// A blocking wrapper around a [StreamBuilder](crate::StreamBuilder), which opens a blocking
+/// [Publisher] or [Subscriber].
+///
+/// Besides the encoder or decoder, options are set on the underlying builder via
+/// [configure](StreamBuilder::configure).
+pub struct StreamBuilder<T> {
+    inner: crate::StreamBuilder<T>,
+    runtime: Arc<Runtime>,
+}
+
+impl<T> StreamBuilder<T> {
+    /// Applies any configuration to the underlying asynchronous
+    /// [StreamBuilder](crate::StreamBuilder).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # fn run(client: selium::blocking::Client) -> selium::Result<()> {
+    /// let publisher = client
+    ///     .publisher("/acmeco/stocks")
+    ///     .with_encoder(StringCodec)
+    ///     .configure(|builder| builder.retain(60_000))?
+    ///     .open()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the configuration fails.
+    pub fn configure<U, F>(self, f: F) -> Result<StreamBuilder<U>>
+    where
+        F: FnOnce(crate::StreamBuilder<T>) -> Result<crate::StreamBuilder<U>>,
+    {
+        Ok(StreamBuilder {
+            inner: f(self.inner)?,
+            runtime: self.runtime,
+        })
+    }
+}
+
+impl StreamBuilder<PublisherWantsEncoder> {
+    /// See [with_encoder](crate::StreamBuilder::with_encoder).
+    pub fn with_encoder<E, Item>(self, encoder: E) -> StreamBuilder<PublisherWantsOpen<E, Item>> {
+        StreamBuilder {
+            inner: self.inner.with_encoder(encoder),
+            runtime: self.runtime,
+        }
+    }
+}
+
+impl<E, Item> StreamBuilder<PublisherWantsOpen<E, Item>>
+where
+    E: MessageEncoder<Item> + Send + Clone + Unpin,
+    Item: Send + Unpin,
+{
+    /// Blocks until the [Publisher] is registered with the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to open.
+    pub fn open(self) -> Result<Publisher<E, Item>> {
+        let inner = self.runtime.block_on(self.inner.open())?;
+
+        Ok(Publisher {
+            inner,
+            runtime: self.runtime,
+        })
+    }
+}
+
+impl StreamBuilder<SubscriberWantsDecoder> {
+    /// See [with_decoder](crate::StreamBuilder::with_decoder).
+    pub fn with_decoder<D, Item, Kind>(
+        self,
+        decoder: D,
+    ) -> StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
+    where
+        D: SubscriberDecoder<Item, Kind>,
+    {
+        StreamBuilder {
+            inner: self.inner.with_decoder(decoder),
+            runtime: self.runtime,
+        }
+    }
+
+    /// See [raw](crate::StreamBuilder::raw).
+    pub fn raw(self) -> StreamBuilder<RawSubscriberWantsOpen> {
+        StreamBuilder {
+            inner: self.inner.raw(),
+            runtime: self.runtime,
+        }
+    }
+
+    /// See [with_codec_registry](crate::StreamBuilder::with_codec_registry).
+    pub fn with_codec_registry<Item>(
+        self,
+        registry: CodecRegistry<Item>,
+    ) -> StreamBuilder<SubscriberWantsOpen<CodecRegistry<Item>, Item, RegistryDecoder>> {
+        StreamBuilder {
+            inner: self.inner.with_codec_registry(registry),
+            runtime: self.runtime,
+        }
+    }
+}
+
+impl<D, Item, Kind> StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
+where
+    D: SubscriberDecoder<Item, Kind> + Send + Unpin,
+    Item: Send + Unpin,
+    Kind: Send + Unpin,
+{
+    /// Blocks until the [Subscriber] is registered with the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to open.
+    pub fn open(self) -> Result<Subscriber<D, Item, Kind>> {
+        let inner = self.runtime.block_on(self.inner.open())?;
+
+        Ok(Subscriber {
+            inner,
+            runtime: self.runtime,
+        })
+    }
+}
+
+/// A blocking wrapper around a [Publisher](crate::Publisher).
+pub struct Publisher<E, Item> {
+    inner: crate::Publisher<E, Item>,
+    runtime: Arc<Runtime>,
+}
+
+impl<E, Item> Publisher<E, Item>
+where
+    E: MessageEncoder<Item> + Send + Clone + Unpin,
+    Item: Unpin,
+{
+    /// Encodes and sends a single message, blocking until it has been written to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the item fails to encode, or if the message fails to be written to the
+    /// stream.
+    pub fn send(&mut self, item: Item) -> Result<()> {
+        self.runtime.block_on(self.inner.send(item))
+    }
+
+    /// Encodes and sends a single message to the topic, borrowing the `item` rather than taking
+    /// ownership of it.
+    ///
+    /// See [send_ref](crate::Publisher::send_ref).
+    pub fn send_ref<T>(&mut self, item: &T) -> Result<()>
+    where
+        T: ?Sized,
+        E: BorrowedMessageEncoder<T>,
+    {
+        self.runtime.block_on(self.inner.send_ref(item))
+    }
+
+    /// Sends a single message whose payload has already been encoded, bypassing the encoder.
+    ///
+    /// See [send_raw](crate::Publisher::send_raw).
+    pub fn send_raw(&mut self, bytes: Bytes) -> Result<()> {
+        self.runtime.block_on(self.inner.send_raw(bytes))
+    }
+
+    /// See [send_batch](crate::Publisher::send_batch).
+    pub fn send_batch(&mut self, items: Vec<Item>) -> Result<()> {
+        self.runtime.block_on(self.inner.send_batch(items))
+    }
+
+    /// See [send_with_headers](crate::Publisher::send_with_headers).
+    pub fn send_with_headers(&mut self, item: Item, headers: Headers) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.send_with_headers(item, headers))
+    }
+
+    /// See [send_with_key](crate::Publisher::send_with_key).
+    pub fn send_with_key(&mut self, item: Item, key: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.send_with_key(item, key))
+    }
+
+    /// Blocks until the server has confirmed receiving the message. See
+    /// [send_confirmed](crate::Publisher::send_confirmed).
+    pub fn send_confirmed(&mut self, item: Item) -> Result<()> {
+        self.runtime.block_on(self.inner.send_confirmed(item))
+    }
+
+    /// See [stream_id](crate::Publisher::stream_id).
+    pub fn stream_id(&self) -> u64 {
+        self.inner.stream_id()
+    }
+
+    /// Blocks until every message sent so far has been flushed, without closing the stream. See
+    /// [flush](crate::Publisher::flush).
+    pub fn flush(&mut self) -> Result<()> {
+        self.runtime.block_on(self.inner.flush())
+    }
+
+    /// Blocks until the stream is gracefully closed. See [finish](crate::Publisher::finish).
+    pub fn finish(self) -> Result<()> {
+        self.runtime.block_on(self.inner.finish())
+    }
+}
+
+/// A blocking wrapper around a [Subscriber](crate::Subscriber).
+///
+/// Messages can be received one at a time via [recv](Subscriber::recv), or by iterating over the
+/// Subscriber.
+pub struct Subscriber<D, Item, Kind = SyncDecoder> {
+    inner: crate::Subscriber<D, Item, Kind>,
+    runtime: Arc<Runtime>,
+}
+
+impl<D, Item, Kind> Subscriber<D, Item, Kind>
+where
+    D: SubscriberDecoder<Item, Kind> + Send + Unpin,
+    Item: Unpin,
+    Kind: Unpin,
+{
+    /// Blocks until the next message is received, returning [None] once the stream has ended.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message fails to be received or decoded.
+    pub fn recv(&mut self) -> Result<Option<Item>> {
+        self.runtime.block_on(self.inner.next()).transpose()
+    }
+
+    /// See [collect_with_timeout](crate::Subscriber::collect_with_timeout).
+    pub fn collect_with_timeout<T: crate::traits::TryIntoU64>(
+        &mut self,
+        n: usize,
+        timeout: T,
+    ) -> Result<Vec<Item>> {
+        self.runtime
+            .block_on(self.inner.collect_with_timeout(n, timeout))
+    }
+
+    /// See [stream_id](crate::Subscriber::stream_id).
+    pub fn stream_id(&self) -> u64 {
+        self.inner.stream_id()
+    }
+
+    /// See [commit](crate::Subscriber::commit).
+    pub fn commit(&self, offset: u64) -> Result<()> {
+        self.runtime.block_on(self.inner.commit(offset))
+    }
+}
+
+impl<D, Item, Kind> Iterator for Subscriber<D, Item, Kind>
+where
+    D: SubscriberDecoder<Item, Kind> + Send + Unpin,
+    Item: Unpin,
+    Kind: Unpin,
+{
+    type Item = Result<Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.inner.next())
+    }
+}
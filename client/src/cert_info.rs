@@ -0,0 +1,106 @@
+use ring::digest::{digest, SHA256};
+use std::time::{Duration, SystemTime};
+use x509_parser::prelude::{FromDer, X509Certificate};
+use x509_parser::time::ASN1Time;
+
+/// The details of a certificate presented by the `Selium` server during the TLS handshake.
+///
+/// Returned by [Client::peer_certificates](crate::Client::peer_certificates), in the order that
+/// the server presented them, starting with the server's own certificate, followed by any
+/// intermediate certificates in its chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CertInfo {
+    /// The distinguished name of the certificate's subject, e.g. `CN=selium.example.com`.
+    pub subject: String,
+    /// The distinguished name of the certificate's issuer.
+    pub issuer: String,
+    /// The time from which the certificate is valid.
+    pub not_before: SystemTime,
+    /// The time after which the certificate is no longer valid.
+    pub not_after: SystemTime,
+    /// The SHA-256 fingerprint of the certificate, formatted as colon-separated, uppercase hex
+    /// bytes, matching the output of `openssl x509 -fingerprint -sha256`.
+    pub fingerprint: String,
+    /// The DER-encoded certificate, for any further inspection.
+    pub der: Vec<u8>,
+}
+
+impl CertInfo {
+    /// Parses the certificates that the server presented on the `connection`, skipping any that
+    /// fail to parse.
+    pub(crate) fn of(connection: &quinn::Connection) -> Vec<Self> {
+        let certs = connection
+            .peer_identity()
+            .and_then(|identity| identity.downcast::<Vec<rustls::Certificate>>().ok());
+
+        certs
+            .into_iter()
+            .flat_map(|certs| certs.into_iter())
+            .filter_map(|cert| match Self::parse(cert.0) {
+                Ok(info) => Some(info),
+                Err(err) => {
+                    log::warn!("Failed to parse peer certificate: {err}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn parse(der: Vec<u8>) -> Result<Self, x509_parser::nom::Err<x509_parser::error::X509Error>> {
+        let (_, cert) = X509Certificate::from_der(&der)?;
+        let validity = cert.validity();
+
+        Ok(Self {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            not_before: system_time(validity.not_before),
+            not_after: system_time(validity.not_after),
+            fingerprint: fingerprint(&der),
+            der,
+        })
+    }
+}
+
+fn system_time(time: ASN1Time) -> SystemTime {
+    let secs = time.timestamp();
+
+    if secs >= 0 {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs.unsigned_abs())
+    } else {
+        SystemTime::UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    }
+}
+
+fn fingerprint(der: &[u8]) -> String {
+    digest(&SHA256, der)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_fingerprint_as_colon_separated_hex() {
+        // The SHA-256 digest of an empty input
+        let fingerprint = fingerprint(&[]);
+
+        assert!(fingerprint.starts_with("E3:B0:C4:42:98:FC:1C:14"));
+        assert_eq!(fingerprint.len(), 32 * 3 - 1);
+    }
+
+    #[test]
+    fn converts_timestamps_before_epoch() {
+        let time = ASN1Time::from_timestamp(-60).unwrap();
+
+        assert_eq!(
+            system_time(time),
+            SystemTime::UNIX_EPOCH - Duration::from_secs(60)
+        );
+    }
+}
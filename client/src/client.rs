@@ -1,26 +1,91 @@
-use crate::crypto::cert::load_root_store;
+use crate::connection::SharedConnection;
+use crate::crypto::cert::{
+    load_client_certificate, load_root_store, parse_client_certificate, parse_root_store_der,
+    parse_root_store_pem,
+};
+use crate::tasks;
 use crate::traits::TryIntoU64;
-use crate::utils::client::establish_connection;
-use crate::{PublisherWantsEncoder, StreamBuilder, StreamCommon, SubscriberWantsDecoder};
-use anyhow::Result;
-use quinn::{Connection, VarInt};
+use crate::utils::client::{configure_client, ClientOptions, ServerVerification};
+use crate::{
+    CertInfo, ConnectionEvent, ConnectionStats, PublisherWantsEncoder, ReconnectPolicy,
+    ReplierWantsDecoder, RequestorWantsEncoder, StreamBuilder, StreamCommon,
+    SubscriberWantsDecoder,
+};
+use crate::{Error, Result};
+use futures::{SinkExt, Stream, StreamExt};
+use quinn::VarInt;
 use rustls::RootCertStore;
+use selium_common::protocol::{AuthToken, Frame, ListTopicsPayload, TopicInfo, TopicListPayload};
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// The default `keep_alive` interval for a client connection.
 pub const KEEP_ALIVE_DEFAULT: u64 = 5_000;
 
+/// The default `max_idle_timeout` for a client connection.
+pub const MAX_IDLE_TIMEOUT_DEFAULT: u64 = 30_000;
+
+/// The default `max_concurrent_streams` for a client connection, matching the default applied by
+/// the `Selium` server.
+pub const MAX_CONCURRENT_STREAMS_DEFAULT: u32 = 100;
+
+/// The default `stream_receive_window` for a client connection in bytes, matching the default
+/// applied by [quinn](https://docs.rs/quinn), which is sized for 100Mbps at a 100ms round-trip
+/// time.
+pub const STREAM_RECEIVE_WINDOW_DEFAULT: u64 = 1_250_000;
+
+/// The default `receive_window` for a client connection in bytes, matching the default applied
+/// by [quinn](https://docs.rs/quinn), which leaves the connection as a whole unlimited.
+pub const RECEIVE_WINDOW_DEFAULT: u64 = (1 << 62) - 1;
+
+/// The default ALPN protocols advertised by a client connection, matching the protocols accepted
+/// by the `Selium` server by default.
+pub const ALPN_PROTOCOLS_DEFAULT: &[&[u8]] = &[b"hq-29"];
+
+/// The default name that the `Selium` server's certificate is verified against, when connecting
+/// to an IP address rather than a hostname.
+pub const SERVER_NAME_DEFAULT: &str = "localhost";
+
+/// The congestion control algorithm used by a client connection to pace the data it sends.
+///
+/// Each variant corresponds to one of the congestion controllers implemented by
+/// [quinn](https://docs.rs/quinn), which `Selium` uses for its QUIC transport.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CongestionController {
+    /// [BBR](https://datatracker.ietf.org/doc/html/draft-cardwell-iccrg-bbr-congestion-control),
+    /// which estimates the bandwidth and round-trip time of the link, rather than reacting to
+    /// packet loss. It's best suited to links with a high bandwidth-delay product, such as WANs.
+    ///
+    /// BBR is only available from quinn 0.10 onwards, where it's still marked as experimental.
+    Bbr,
+    /// [CUBIC](https://www.rfc-editor.org/rfc/rfc8312), the default congestion controller used
+    /// by quinn.
+    #[default]
+    Cubic,
+    /// [NewReno](https://www.rfc-editor.org/rfc/rfc6582), the congestion controller described
+    /// by the QUIC specification.
+    NewReno,
+}
+
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct ClientWantsCert {
-    keep_alive: u64,
+    options: ClientOptions,
+    connect_timeout: Option<u64>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    server_name: String,
 }
 
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct ClientWantsConnect {
-    keep_alive: u64,
-    root_store: RootCertStore,
+    options: ClientOptions,
+    connect_timeout: Option<u64>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    server_name: String,
+    verification: ServerVerification,
+    fallback_endpoints: Vec<String>,
 }
 
 /// A convenient builder struct used to build a [Client] instance.
@@ -46,46 +111,351 @@ pub struct ClientBuilder<T> {
 pub fn client() -> ClientBuilder<ClientWantsCert> {
     ClientBuilder {
         state: ClientWantsCert {
-            keep_alive: KEEP_ALIVE_DEFAULT,
+            options: ClientOptions {
+                keep_alive: KEEP_ALIVE_DEFAULT,
+                max_idle_timeout: MAX_IDLE_TIMEOUT_DEFAULT,
+                client_cert: None,
+                enable_0rtt: false,
+                congestion_controller: CongestionController::default(),
+                max_concurrent_streams: MAX_CONCURRENT_STREAMS_DEFAULT,
+                stream_receive_window: STREAM_RECEIVE_WINDOW_DEFAULT,
+                receive_window: RECEIVE_WINDOW_DEFAULT,
+                alpn_protocols: ALPN_PROTOCOLS_DEFAULT.iter().map(|&p| p.into()).collect(),
+            },
+            connect_timeout: None,
+            reconnect_policy: None,
+            server_name: SERVER_NAME_DEFAULT.to_owned(),
         },
     }
 }
 
 impl ClientBuilder<ClientWantsCert> {
-    /// Overrides the `keep_alive` interval for the client connection in milliseconds.
+    /// Overrides the interval at which keep-alives are sent on the client connection, preventing
+    /// it from idling out.
     ///
-    /// Accepts any `interval` argument that can be *fallibly* converted into a [u64] via the
-    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    /// Accepts any `interval` argument that can be *fallibly* converted into a [u64] of
+    /// milliseconds via the [TryIntoU64](crate::traits::TryIntoU64) trait, such as the standard
+    /// library's [Duration](std::time::Duration) type, which is recommended as it makes the unit
+    /// explicit. Plain integers are interpreted as milliseconds.
     ///
     /// **NOTE:** `Selium` already provides a reasonable default for the `keep_alive` interval (see
     /// [KEEP_ALIVE_DEFAULT]), so this setting should only be overridden if it's not suitable for
     /// your use-case.
     ///
+    /// The interval must be greater than zero, and less than the `max_idle_timeout`, as otherwise
+    /// the connection would be closed before a keep-alive is sent. This is validated when
+    /// [connect](ClientBuilder::connect) is invoked.
+    ///
     /// # Errors
     ///
     /// Returns [Err] if the provided interval fails to be convert to a [u64].
     ///
     /// # Examples
     ///
-    /// Overriding the default `keep_alive` interval as 6 seconds (represented in milliseconds).
-    ///  
+    /// Overriding the default `keep_alive` interval as 6 seconds.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = selium::client()
+    ///     .keep_alive(Duration::from_secs(6)).unwrap();
+    /// ```
+    ///
+    /// The same interval, represented in milliseconds.
+    ///
     /// ```
     /// let client = selium::client()
     ///     .keep_alive(6_000).unwrap();
     /// ```
+    pub fn keep_alive<T: TryIntoU64>(mut self, interval: T) -> Result<Self> {
+        self.state.options.keep_alive = interval.try_into_u64()?;
+        Ok(self)
+    }
+
+    /// Overrides the maximum time in milliseconds that the client connection can remain idle
+    /// before it is closed.
     ///
-    /// You can even use any other type that can be converted to a [u64] via the
-    /// [TryIntoU64](crate::traits::TryIntoU64) trait, such as the standard library's Duration
-    /// type.
+    /// The effective idle timeout is the lowest of the client's and server's configured
+    /// timeouts. While the connection is established, the `keep_alive` interval prevents it from
+    /// idling out, so the idle timeout determines how quickly an unresponsive server is detected.
+    ///
+    /// **NOTE:** The `keep_alive` interval must be less than the `max_idle_timeout`, as otherwise
+    /// the connection would be closed before a keep-alive is sent. This is validated when
+    /// [connect](ClientBuilder::connect) is invoked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be converted to a [u64].
+    ///
+    /// # Examples
     ///
     /// ```
     /// use std::time::Duration;
     ///
     /// let client = selium::client()
-    ///     .keep_alive(Duration::from_secs(6)).unwrap();
+    ///     .keep_alive(Duration::from_secs(2)).unwrap()
+    ///     .max_idle_timeout(Duration::from_secs(10)).unwrap();
     /// ```
-    pub fn keep_alive<T: TryIntoU64>(mut self, interval: T) -> Result<Self> {
-        self.state.keep_alive = interval.try_into_u64()?;
+    pub fn max_idle_timeout<T: TryIntoU64>(mut self, timeout: T) -> Result<Self> {
+        self.state.options.max_idle_timeout = timeout.try_into_u64()?;
+        Ok(self)
+    }
+
+    /// Sets a timeout in milliseconds for establishing the client connection.
+    ///
+    /// The timeout covers the full QUIC handshake, including certificate validation, and also
+    /// applies to each attempt to re-establish a lost connection when a
+    /// [ReconnectPolicy] is configured. By default, no timeout is applied, so connecting to an
+    /// unreachable server will only fail once the handshake idles out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be converted to a [u64].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = selium::client()
+    ///     .connect_timeout(Duration::from_secs(3)).unwrap();
+    /// ```
+    pub fn connect_timeout<T: TryIntoU64>(mut self, timeout: T) -> Result<Self> {
+        self.state.connect_timeout = Some(timeout.try_into_u64()?);
+        Ok(self)
+    }
+
+    /// Enables automatic reconnection for the client connection, using the provided
+    /// [ReconnectPolicy].
+    ///
+    /// When the connection to the `Selium` server is lost, the [Client] will attempt to
+    /// re-establish it with exponential backoff, until the policy's maximum number of retries is
+    /// exhausted. Streams opened after the connection is re-established will use the new
+    /// connection, and existing [Publisher](crate::Publisher) streams will either buffer messages
+    /// or return an error, depending on the policy's [PublisherStrategy](crate::PublisherStrategy).
+    ///
+    /// Messages that were already written to the connection before it was lost may not be
+    /// delivered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use selium::ReconnectPolicy;
+    ///
+    /// let client = selium::client()
+    ///     .reconnect_policy(ReconnectPolicy::default().max_retries(5));
+    /// ```
+    pub fn reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.state.reconnect_policy = Some(policy);
+        self
+    }
+
+    /// Enables 0-RTT connection resumption, which cuts the latency of reconnecting to a `Selium`
+    /// server that the process has connected to before.
+    ///
+    /// When enabled, TLS session tickets issued by the server are cached for the lifetime of the
+    /// process, and shared by every [Client] with 0-RTT enabled. If a ticket permitting early data
+    /// has been cached for the server, the connection is resumed without waiting for the
+    /// handshake to complete, so streams can be opened, and messages sent, immediately. This is
+    /// especially useful for short-lived invocations, such as serverless functions that
+    /// reconnect frequently. Reconnections made via a [ReconnectPolicy] are resumed in the same
+    /// way. Otherwise, a full handshake is performed, and its ticket cached for next time.
+    ///
+    /// The server must also accept early data, by running it with the `--enable-0rtt` argument.
+    ///
+    /// **NOTE:** Unlike the rest of the connection, data sent before the handshake completes is
+    /// not protected against replay, so an attacker that captures it could have the server
+    /// process it more than once. Only enable 0-RTT if the streams opened and messages published
+    /// immediately after connecting are idempotent.
+    ///
+    /// If the server rejects the early data, such as when it has restarted since issuing the
+    /// ticket, any streams opened before the handshake completes will fail with a
+    /// [Connection](crate::Error::Connection) error, and must be re-opened. As the connection is
+    /// usable before the handshake completes, a `connect_timeout` does not apply to resumed
+    /// connections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::client().enable_0rtt();
+    /// ```
+    pub fn enable_0rtt(mut self) -> Self {
+        self.state.options.enable_0rtt = true;
+        self
+    }
+
+    /// Overrides the congestion control algorithm used by the client connection, which defaults
+    /// to [CongestionController::Cubic].
+    ///
+    /// The congestion controller only paces data sent by the client, such as published
+    /// messages. Data sent by the `Selium` server, such as messages delivered to a
+    /// [Subscriber](crate::Subscriber), is paced by the server's own congestion controller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use selium::CongestionController;
+    ///
+    /// let client = selium::client().congestion_controller(CongestionController::Bbr);
+    /// ```
+    pub fn congestion_controller(mut self, controller: CongestionController) -> Self {
+        self.state.options.congestion_controller = controller;
+        self
+    }
+
+    /// Overrides the maximum number of concurrent bidirectional streams that the `Selium`
+    /// server may open on the client connection.
+    ///
+    /// **NOTE:** Each [Publisher](crate::Publisher), [Subscriber](crate::Subscriber) and other
+    /// stream is opened by the client, so the number of streams that can be open at once is
+    /// limited by the equivalent setting on the server, which is configured via its
+    /// `--max-concurrent-streams` argument, and defaults to [MAX_CONCURRENT_STREAMS_DEFAULT].
+    /// Opening a stream while the server's limit has been reached fails immediately with a
+    /// [Connection](crate::Error::Connection) error, rather than waiting for another stream to be
+    /// closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::client().max_concurrent_streams(500);
+    /// ```
+    pub fn max_concurrent_streams(mut self, streams: u32) -> Self {
+        self.state.options.max_concurrent_streams = streams;
+        self
+    }
+
+    /// Overrides the number of bytes that the `Selium` server may send on each stream before
+    /// the client acknowledges them, which defaults to [STREAM_RECEIVE_WINDOW_DEFAULT].
+    ///
+    /// The window limits the throughput of a single stream, such as the messages delivered to a
+    /// [Subscriber](crate::Subscriber), to roughly the window size per round-trip, so the
+    /// default of about 1.25MB allows up to 100Mbps over a link with a 100ms round-trip time.
+    /// Raising the window to the link's bandwidth-delay product allows a high-latency link to be
+    /// saturated, at the cost of the client buffering up to that many bytes for each stream
+    /// whose messages aren't being consumed.
+    ///
+    /// **NOTE:** The window only applies to data received by the client. Data sent by the client,
+    /// such as published messages, is limited by the equivalent windows of the server.
+    ///
+    /// The window is validated when connecting, which fails if it's zero, or exceeds the maximum
+    /// size of 2<sup>62</sup> - 1 bytes.
+    ///
+    /// # Examples
+    ///
+    /// Raising the window to 12.5MB, allowing 1Gbps over a link with a 100ms round-trip time.
+    ///
+    /// ```
+    /// let client = selium::client().stream_receive_window(12_500_000);
+    /// ```
+    pub fn stream_receive_window(mut self, bytes: u64) -> Self {
+        self.state.options.stream_receive_window = bytes;
+        self
+    }
+
+    /// Overrides the number of bytes that the `Selium` server may send across every stream on the
+    /// connection before the client acknowledges them, which defaults to
+    /// [RECEIVE_WINDOW_DEFAULT].
+    ///
+    /// By default, the connection as a whole is left unlimited, so the memory buffered by the
+    /// client is only bounded by the [stream_receive_window](ClientBuilder::stream_receive_window)
+    /// of each open stream. Lowering the window caps the total memory buffered for unconsumed
+    /// messages across all streams, at the cost of limiting the throughput of the connection as a
+    /// whole to roughly the window size per round-trip.
+    ///
+    /// The window is validated when connecting, which fails if it's zero, or exceeds the maximum
+    /// size of 2<sup>62</sup> - 1 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::client()
+    ///     .stream_receive_window(12_500_000)
+    ///     .receive_window(100_000_000);
+    /// ```
+    pub fn receive_window(mut self, bytes: u64) -> Self {
+        self.state.options.receive_window = bytes;
+        self
+    }
+
+    /// Overrides the ALPN protocols advertised by the client connection during the TLS
+    /// handshake, in order of preference. Defaults to [ALPN_PROTOCOLS_DEFAULT].
+    ///
+    /// **NOTE:** The `Selium` server only accepts the protocols configured via its `--alpn`
+    /// argument, which also defaults to `hq-29`. If none of the protocols advertised by the
+    /// client are accepted, the handshake fails, and [connect](ClientBuilder::connect) returns a
+    /// [Tls](crate::Error::Tls) error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::client().alpn(&[b"selium/1"]);
+    /// ```
+    pub fn alpn(mut self, protocols: &[&[u8]]) -> Self {
+        self.state.options.alpn_protocols = protocols.iter().map(|&p| p.into()).collect();
+        self
+    }
+
+    /// Overrides the name that the `Selium` server's certificate is verified against when
+    /// connecting to an IP address, such as `127.0.0.1:7001` or `[::1]:7001`, which defaults to
+    /// [SERVER_NAME_DEFAULT]. The name is also sent to the server via SNI.
+    ///
+    /// When connecting to a hostname, such as `selium.example.com:7001`, the certificate is
+    /// always verified against the hostname instead, so this setting has no effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = selium::client().server_name("selium.example.com");
+    /// ```
+    pub fn server_name(mut self, name: &str) -> Self {
+        self.state.server_name = name.to_owned();
+        self
+    }
+
+    /// Attempts to load a PEM-encoded client certificate chain and private key from the
+    /// filesystem, which are presented to the `Selium` server during the handshake for mutual TLS
+    /// authentication.
+    ///
+    /// This is only required when the server is configured to authenticate clients, e.g. by
+    /// running it with the `--client-ca` argument. The private key can be in PKCS #8, PKCS #1 or
+    /// SEC1 format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] under the following conditions:
+    ///
+    /// - If either file cannot be read, or does not contain a valid certificate or private key.
+    /// - If the private key does not match the certificate, in which case the error reads
+    ///   "Client private key does not match the client certificate".
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let client = selium::client()
+    ///     .with_client_certificate("certs/client.crt", "certs/client.key").unwrap();
+    /// ```
+    pub fn with_client_certificate<C: Into<PathBuf>, K: Into<PathBuf>>(
+        mut self,
+        cert_path: C,
+        key_path: K,
+    ) -> Result<Self> {
+        let client_cert =
+            load_client_certificate(&cert_path.into(), &key_path.into()).map_err(Error::tls)?;
+        self.state.options.client_cert = Some(client_cert);
+        Ok(self)
+    }
+
+    /// Equivalent to [with_client_certificate](ClientBuilder::with_client_certificate), but
+    /// accepts the PEM-encoded certificate chain and private key as bytes, rather than reading
+    /// them from the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the `cert` or `key` arguments cannot be parsed, or if the private key does
+    /// not match the certificate.
+    pub fn with_client_certificate_pem(mut self, cert: &[u8], key: &[u8]) -> Result<Self> {
+        let client_cert = parse_client_certificate(cert, key).map_err(Error::tls)?;
+        self.state.options.client_cert = Some(client_cert);
         Ok(self)
     }
 
@@ -103,38 +473,249 @@ impl ClientBuilder<ClientWantsCert> {
         self,
         ca_path: T,
     ) -> Result<ClientBuilder<ClientWantsConnect>> {
-        let root_store = load_root_store(&ca_path.into())?;
+        let root_store = load_root_store(&ca_path.into()).map_err(Error::tls)?;
+        Ok(self.with_root_store(root_store))
+    }
 
+    /// Equivalent to [with_certificate_authority](ClientBuilder::with_certificate_authority), but
+    /// accepts PEM-encoded CA certificates as bytes, rather than reading them from the
+    /// filesystem. This is useful when certificates are injected via environment variables or a
+    /// secrets manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided `ca` argument does not contain a valid certificate.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let ca = std::env::var("SELIUM_CA").unwrap();
+    ///
+    /// let client = selium::client()
+    ///     .with_certificate_authority_pem(ca.as_bytes()).unwrap();
+    /// ```
+    pub fn with_certificate_authority_pem(
+        self,
+        ca: &[u8],
+    ) -> Result<ClientBuilder<ClientWantsConnect>> {
+        let root_store = parse_root_store_pem(ca).map_err(Error::tls)?;
+        Ok(self.with_root_store(root_store))
+    }
+
+    /// Equivalent to [with_certificate_authority](ClientBuilder::with_certificate_authority), but
+    /// accepts a single DER-encoded CA certificate as bytes, rather than reading it from the
+    /// filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided `ca` argument is not a valid certificate.
+    pub fn with_certificate_authority_der(
+        self,
+        ca: &[u8],
+    ) -> Result<ClientBuilder<ClientWantsConnect>> {
+        let root_store = parse_root_store_der(ca).map_err(Error::tls)?;
+        Ok(self.with_root_store(root_store))
+    }
+
+    /// **DANGER:** Disables verification of the certificate presented by the `Selium` server, so
+    /// that the [Client] connects without a CA certificate, accepting any server certificate,
+    /// including self-signed ones.
+    ///
+    /// This leaves the connection wide open to person-in-the-middle attacks, as any party able to
+    /// intercept traffic can impersonate the server. It is only intended for local development
+    /// and throwaway experiments, and must never be used in production.
+    ///
+    /// This method is only available when the `dangerous-configuration` feature is enabled.
+    /// Following this method, the [ClientBuilder] will be in a pre-connection state, so any
+    /// additional configuration must take place before invoking this method.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let client = selium::client()
+    ///     .dangerous_skip_verification()
+    ///     .connect("127.0.0.1:7001")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "dangerous-configuration")]
+    pub fn dangerous_skip_verification(self) -> ClientBuilder<ClientWantsConnect> {
+        self.with_verification(ServerVerification::Skip)
+    }
+
+    /// Supplies a custom [rustls::ClientConfig] for the client connection, for TLS requirements
+    /// that the [ClientBuilder] doesn't cover, such as a custom certificate verifier, OCSP
+    /// stapling or pinned keys.
+    ///
+    /// The config takes precedence over the other TLS options on the [ClientBuilder], so any
+    /// client certificate configured via
+    /// [with_client_certificate](ClientBuilder::with_client_certificate) is ignored, and 0-RTT
+    /// is only attempted if the config itself enables early data and session resumption. The
+    /// ALPN protocols of the config are also advertised as is, unless it doesn't specify any, in
+    /// which case those configured via [alpn](ClientBuilder::alpn) are applied. Options that
+    /// aren't part of TLS, such as the `keep_alive` interval, still apply.
+    ///
+    /// **NOTE:** `Selium` communicates over QUIC, which requires TLS 1.3, so the config must
+    /// support TLS 1.3 for the handshake to succeed. Following this method, the [ClientBuilder]
+    /// will be in a pre-connection state, so any additional configuration must take place
+    /// before invoking this method.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let mut roots = rustls::RootCertStore::empty();
+    /// roots.add_parsable_certificates(&[std::fs::read("certs/client/ca.der")?]);
+    ///
+    /// let config = rustls::ClientConfig::builder()
+    ///     .with_safe_defaults()
+    ///     .with_root_certificates(roots)
+    ///     .with_no_client_auth();
+    ///
+    /// let client = selium::client()
+    ///     .with_rustls_config(config)
+    ///     .connect("127.0.0.1:7001")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_rustls_config(
+        self,
+        config: rustls::ClientConfig,
+    ) -> ClientBuilder<ClientWantsConnect> {
+        self.with_verification(ServerVerification::Custom(config))
+    }
+
+    fn with_root_store(self, root_store: RootCertStore) -> ClientBuilder<ClientWantsConnect> {
+        self.with_verification(ServerVerification::RootStore(root_store))
+    }
+
+    fn with_verification(
+        self,
+        verification: ServerVerification,
+    ) -> ClientBuilder<ClientWantsConnect> {
         let state = ClientWantsConnect {
-            keep_alive: self.state.keep_alive,
-            root_store,
+            options: self.state.options,
+            connect_timeout: self.state.connect_timeout,
+            reconnect_policy: self.state.reconnect_policy,
+            server_name: self.state.server_name,
+            verification,
+            fallback_endpoints: Vec::new(),
         };
 
-        Ok(ClientBuilder { state })
+        ClientBuilder { state }
     }
 }
 
 impl ClientBuilder<ClientWantsConnect> {
+    /// Adds a fallback endpoint for the `Selium` server, which is tried if the connection to the
+    /// address passed to [connect](ClientBuilder::connect) cannot be established. This improves
+    /// availability when several brokers are reachable at different addresses, without the need
+    /// for an external load balancer.
+    ///
+    /// Endpoints are tried one at a time, starting with the address passed to
+    /// [connect](ClientBuilder::connect), followed by each fallback endpoint in the order they
+    /// were added, until a handshake succeeds. If a `connect_timeout` is configured, it applies
+    /// to each endpoint separately.
+    ///
+    /// The [Client] remembers which endpoint it connected to. If a
+    /// [ReconnectPolicy](crate::ReconnectPolicy) is configured, each reconnection attempt starts
+    /// with that endpoint, and then moves on to the endpoints that follow it, wrapping around to
+    /// the first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let client = selium::client()
+    ///     .with_certificate_authority("certs/ca.crt")?
+    ///     .add_endpoint("10.0.0.2:7001")
+    ///     .add_endpoint("10.0.0.3:7001")
+    ///     .connect("10.0.0.1:7001")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_endpoint(mut self, addr: &str) -> Self {
+        self.state.fallback_endpoints.push(addr.to_owned());
+        self
+    }
+
     /// Attempts to establish a connection with the `Selium` server corresponding to the provided
-    /// `addr` argument. The [connect](ClientBuilder::connect) method will only be in scope if the
-    /// [ClientBuilder] is in a pre-connect state, `ClientWantsConnect`.
+    /// `addr` argument, or any fallback endpoints added via
+    /// [add_endpoint](ClientBuilder::add_endpoint). The [connect](ClientBuilder::connect) method
+    /// will only be in scope if the [ClientBuilder] is in a pre-connect state,
+    /// `ClientWantsConnect`.
+    ///
+    /// Each endpoint is either an IP address and port, such as `127.0.0.1:7001` or an IPv6
+    /// literal such as `[::1]:7001`, or a hostname and port, such as `selium.example.com:7001`.
+    /// Hostnames are resolved via DNS when connecting, and every address they resolve to is
+    /// tried in the order returned by the resolver, as though each were a fallback endpoint. The
+    /// server's certificate is verified against the hostname, rather than the resolved address,
+    /// while IP addresses are verified against the
+    /// [server_name](ClientBuilder::server_name). Endpoints aren't resolved again when
+    /// reconnecting. The local socket is bound to the same address family as each address.
+    ///
+    /// **NOTE:** An address that doesn't respond, such as an IPv6 address of a server that only
+    /// listens on IPv4, is only given up on once the handshake times out, so configure a
+    /// `connect_timeout` when connecting to hostnames that resolve to several addresses.
+    ///
+    /// When the `tracing` feature is enabled, connecting is instrumented with a `connect` span,
+    /// and opening a [Publisher](crate::Publisher) or [Subscriber](crate::Subscriber) with an
+    /// `open_publisher` or `open_subscriber` span carrying the `topic`. Each frame sent or
+    /// received on a stream is logged at the `trace` level, along with its stream id.
     ///
     /// # Errors
     ///
     /// Returns [Err] under the following conditions:
     ///
-    /// - If the provided `addr` argument does not resolve to a valid
-    /// [SocketAddr](std::net::SocketAddr).
-    /// - If the connection cannot be established.
+    /// - If the `keep_alive` interval is zero, or is not less than the `max_idle_timeout`.
+    /// - If the `stream_receive_window` or `receive_window` is zero, or exceeds 2<sup>62</sup> - 1
+    ///   bytes.
+    /// - If the provided `addr` argument, or any fallback endpoint, is missing a port, or fails
+    ///   to resolve to any [SocketAddr](std::net::SocketAddr).
+    /// - If the connection cannot be established, including when the server rejects the client
+    ///   certificate or requires one that was not provided, or accepts none of the ALPN protocols
+    ///   advertised by the client.
+    /// - If a `connect_timeout` is configured, and the connection is not established before it
+    ///   elapses.
+    ///
+    /// When fallback endpoints are configured, an error is only returned if no endpoint can be
+    /// connected to, and lists the failure of each endpoint.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "connect", skip_all, fields(addr = %addr), err)
+    )]
     pub async fn connect(self, addr: &str) -> Result<Client> {
-        let connection =
-            establish_connection(addr, &self.state.root_store, self.state.keep_alive).await?;
+        let config = configure_client(&self.state.verification, &self.state.options)?;
+
+        let connect_timeout = self.state.connect_timeout.map(Duration::from_millis);
+
+        let mut hosts = vec![addr.to_owned()];
+        hosts.extend(self.state.fallback_endpoints);
 
-        tokio::spawn({
+        let connection = SharedConnection::establish(
+            &hosts,
+            &self.state.server_name,
+            config,
+            connect_timeout,
+            self.state.reconnect_policy,
+            self.state.options.enable_0rtt,
+        )
+        .await?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(endpoint = %connection.endpoint(), "Connected to Selium server");
+
+        tasks::spawn(tasks::CTRL_C, {
             let connection = connection.clone();
             async move {
                 tokio::signal::ctrl_c().await.unwrap();
-                connection.close(VarInt::from_u32(0), b"Client forcefully closed connection");
+                connection
+                    .close(VarInt::from_u32(0), b"Client forcefully closed connection")
+                    .await;
             }
         });
 
@@ -150,16 +731,34 @@ impl ClientBuilder<ClientWantsConnect> {
 /// Multiple streams can be opened from a single connected [Client] without extinguishing the underlying
 /// connection, through the use of [QUIC](https://quicwg.org) multiplexing.
 ///
+/// # Shared Connections
+///
+/// Every stream opened from a [Client], or from any of its clones, is opened as a separate QUIC
+/// stream on the same connection, so the number of streams that can be open at once is limited
+/// only by the server's `--max-concurrent-streams` option. Streams on the same connection are
+/// independent of each other's ordering, but share the connection's flow control and congestion
+/// window, so a stream sending or receiving bulk traffic can delay the messages of every other
+/// stream on the connection. A latency-sensitive [Publisher](crate::Publisher) or
+/// [Subscriber](crate::Subscriber) can be isolated from this by opening it with
+/// `connection_per_stream` on its builder, which gives it a dedicated connection of its own.
+///
 /// **NOTE:** The [Client] struct should never be used directly, and is intended to be constructed by a
 /// [ClientBuilder], following a successfully established connection to the `Selium` server.
 #[derive(Clone)]
 pub struct Client {
-    connection: Connection,
+    connection: SharedConnection,
 }
 
 impl Client {
     /// Returns a new [StreamBuilder](crate::StreamBuilder) instance, with an initial `Subscriber`
     /// state.
+    ///
+    /// The topic may be a wildcard pattern, where `+` matches a single topic level and `#`
+    /// matches all remaining levels, e.g. `/acmeco/+/trades` or `/acmeco/#`. Use `with_topic`
+    /// on the builder to find out which topic each message was published to.
+    ///
+    /// The topic is validated with [validate_topic_pattern](crate::validate_topic_pattern) when
+    /// the Subscriber is opened.
     pub fn subscriber(&self, topic: &str) -> StreamBuilder<SubscriberWantsDecoder> {
         StreamBuilder {
             connection: self.connection.clone(),
@@ -171,6 +770,9 @@ impl Client {
 
     /// Returns a new [StreamBuilder](crate::StreamBuilder) instance, with an initial `Publisher`
     /// state.
+    ///
+    /// The topic is validated with [validate_topic](crate::validate_topic) when the Publisher is
+    /// opened.
     pub fn publisher(&self, topic: &str) -> StreamBuilder<PublisherWantsEncoder> {
         StreamBuilder {
             connection: self.connection.clone(),
@@ -179,4 +781,161 @@ impl Client {
             },
         }
     }
+
+    /// Returns a new [StreamBuilder](crate::StreamBuilder) instance, with an initial `Requestor`
+    /// state.
+    ///
+    /// A [Requestor](crate::Requestor) sends requests to the topic, and waits for a
+    /// [Replier](crate::Replier) subscribed to the same topic to reply to each.
+    pub fn requestor(&self, topic: &str) -> StreamBuilder<RequestorWantsEncoder> {
+        StreamBuilder {
+            connection: self.connection.clone(),
+            state: RequestorWantsEncoder {
+                common: StreamCommon::new(topic),
+            },
+        }
+    }
+
+    /// Returns a new [StreamBuilder](crate::StreamBuilder) instance, with an initial `Replier`
+    /// state.
+    pub fn replier(&self, topic: &str) -> StreamBuilder<ReplierWantsDecoder> {
+        StreamBuilder {
+            connection: self.connection.clone(),
+            state: ReplierWantsDecoder {
+                common: StreamCommon::new(topic),
+            },
+        }
+    }
+
+    /// Returns the address of the `Selium` server endpoint that the client is currently connected
+    /// to, which is useful for identifying which endpoint was chosen when fallback endpoints are
+    /// configured via [add_endpoint](ClientBuilder::add_endpoint).
+    pub fn endpoint(&self) -> SocketAddr {
+        self.connection.endpoint()
+    }
+
+    /// Returns a snapshot of the statistics for the client connection, such as its round-trip
+    /// time, congestion window and packet loss.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run(client: selium::Client) {
+    /// let stats = client.connection_stats().await;
+    /// println!("RTT: {:?}, lost packets: {}", stats.rtt, stats.lost_packets);
+    /// # }
+    /// ```
+    pub async fn connection_stats(&self) -> ConnectionStats {
+        self.connection.stats().await
+    }
+
+    /// Returns the details of the certificates presented by the `Selium` server when the client
+    /// connection was established, such as their subject, issuer, validity and fingerprint,
+    /// starting with the server's own certificate.
+    ///
+    /// This is useful for auditing which certificate a connection trusted. If the connection has
+    /// been re-established via a [ReconnectPolicy](crate::ReconnectPolicy), the certificates
+    /// presented on the current connection are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run(client: selium::Client) {
+    /// for cert in client.peer_certificates().await {
+    ///     println!("Trusted {} ({})", cert.subject, cert.fingerprint);
+    /// }
+    /// # }
+    /// ```
+    pub async fn peer_certificates(&self) -> Vec<CertInfo> {
+        self.connection.peer_certificates().await
+    }
+
+    /// Returns a stream of the [ConnectionEvent]s emitted by the client connection from now on,
+    /// such as when it's lost, or re-established via a [ReconnectPolicy](crate::ReconnectPolicy).
+    ///
+    /// Every stream receives every event, so the connection can be observed from several places
+    /// at once. The stream ends once the client, and every stream opened from it, is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use futures::StreamExt;
+    /// # use selium::ConnectionEvent;
+    /// # async fn run(client: selium::Client) {
+    /// let mut events = client.events();
+    ///
+    /// while let Some(event) = events.next().await {
+    ///     match event {
+    ///         ConnectionEvent::Disconnected { reason } => println!("Disconnected: {reason}"),
+    ///         ConnectionEvent::Reconnecting { attempt } => println!("Reconnecting ({attempt})"),
+    ///         ConnectionEvent::Connected => println!("Reconnected"),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn events(&self) -> impl Stream<Item = ConnectionEvent> + Send + Unpin + 'static {
+        self.connection.events()
+    }
+
+    /// Lists the topics that are active on the `Selium` server, along with the number of
+    /// publishers and subscribers currently attached to each, sorted by topic name.
+    ///
+    /// Listing topics is an administrative operation, so the `admin_token` must match the token
+    /// provided to the server's `--admin-token` option.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run(client: selium::Client) -> selium::Result<()> {
+    /// for info in client.list_topics("admin-token").await? {
+    ///     println!("{}: {} publishers", info.topic, info.publishers);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [Error::Unauthorized] if the server doesn't accept the `admin_token`, or hasn't
+    /// been provided with an admin token, or [Err] if the request fails to be sent.
+    pub async fn list_topics(&self, admin_token: &str) -> Result<Vec<TopicInfo>> {
+        let mut stream = self.connection.open_stream().await?;
+        let request = Frame::ListTopics(ListTopicsPayload {
+            token: Some(AuthToken::new(admin_token)),
+        });
+
+        stream.send(request).await?;
+        stream.finish().await?;
+
+        match stream.next().await {
+            Some(Ok(Frame::TopicList(TopicListPayload { topics }))) => Ok(topics),
+            Some(Ok(Frame::Unauthorized(_))) => Err(Error::unauthorized(
+                "Client is not authorized to list topics",
+            )),
+            Some(Err(err)) => Err(err.into()),
+            _ => Err(Error::protocol("Server did not reply with the topic list")),
+        }
+    }
+
+    /// Gracefully shuts down the client connection, waiting up to `timeout` milliseconds for all
+    /// open streams to be drained.
+    ///
+    /// Every [Publisher](crate::Publisher) opened from this client, including clones of the
+    /// [Client], is finished as if by [Publisher::finish](crate::Publisher::finish), flushing
+    /// any buffered messages and waiting for the `Selium` server to acknowledge them. The
+    /// connection is then closed, ending any open [Subscriber](crate::Subscriber) streams.
+    ///
+    /// Dropping a [Client] without shutting it down may lose buffered messages, so this method
+    /// should be invoked before the process exits. Any further attempts to publish messages on
+    /// the client's streams will fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be converted to a [u64], if any publisher
+    /// fails to finish, or if the publishers don't finish before the `timeout` elapses. The
+    /// connection is closed regardless.
+    pub async fn graceful_shutdown<T: TryIntoU64>(self, timeout: T) -> Result<()> {
+        let timeout = Duration::from_millis(timeout.try_into_u64()?);
+        Ok(self.connection.graceful_shutdown(timeout).await?)
+    }
 }
@@ -0,0 +1,197 @@
+use super::{Cbor, Json, SerdeFormat};
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::Result;
+use bytes::{Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// The serialization formats that an [AutoDetectCodec] distinguishes between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoDetectFormat {
+    Json,
+    Cbor,
+}
+
+/// A codec that decodes both JSON and [CBOR](https://cbor.io) message payloads, detecting the
+/// format of each message, and encodes messages in a preferred format.
+///
+/// This eases a gradual migration between the two formats on a single topic, as subscribers can
+/// decode messages from both the old and new publishers while the publishers are upgraded.
+///
+/// # Detection
+///
+/// After skipping any leading JSON whitespace, a payload beginning with `{` or `[` is decoded as
+/// JSON, and any other payload is decoded as CBOR. This correctly detects every JSON object and
+/// array, along with every CBOR payload written by a typical encoder. However:
+///
+/// - JSON payloads whose top-level value isn't an object or array, such as a bare string or
+///   number, are decoded as CBOR, and fail to decode.
+/// - CBOR payloads that begin with the bytes of `{` or `[` are decoded as JSON, and fail to decode.
+///   These bytes only begin strings whose length is encoded in 8 bytes, which encoders only write
+///   for strings of at least 4 GiB.
+///
+/// ```
+/// use selium::codecs::{AutoDetectCodec, AutoDetectFormat};
+///
+/// // Decodes both formats, and encodes new messages in CBOR
+/// let codec = AutoDetectCodec::<Vec<u64>>::new(AutoDetectFormat::Cbor);
+/// ```
+#[derive(Debug)]
+pub struct AutoDetectCodec<Item> {
+    preferred: AutoDetectFormat,
+    _marker: PhantomData<Item>,
+}
+
+impl<Item> AutoDetectCodec<Item> {
+    /// Constructs a new [AutoDetectCodec], which encodes messages in the `preferred` format.
+    pub fn new(preferred: AutoDetectFormat) -> Self {
+        Self {
+            preferred,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Item> Clone for AutoDetectCodec<Item> {
+    fn clone(&self) -> Self {
+        Self::new(self.preferred)
+    }
+}
+
+/// Detects the format of a payload, as described by [AutoDetectCodec].
+fn detect(bytes: &[u8]) -> AutoDetectFormat {
+    let first = bytes
+        .iter()
+        .find(|byte| !matches!(byte, b' ' | b'\t' | b'\n' | b'\r'));
+
+    match first {
+        Some(b'{' | b'[') => AutoDetectFormat::Json,
+        _ => AutoDetectFormat::Cbor,
+    }
+}
+
+/// Encodes any `Item` implementing [Serialize](serde::Serialize) in the preferred format.
+///
+/// # Errors
+///
+/// Returns [Err] if `item` fails to serialize.
+impl<Item: Serialize> MessageEncoder<Item> for AutoDetectCodec<Item> {
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        let encoded = match self.preferred {
+            AutoDetectFormat::Json => Json.serialize(&item)?,
+            AutoDetectFormat::Cbor => Cbor.serialize(&item)?,
+        };
+
+        Ok(encoded.into())
+    }
+
+    fn encode_into(&self, item: Item, dst: &mut BytesMut) -> Result<()> {
+        self.encode_ref(&item, dst)
+    }
+}
+
+/// Encodes a borrowed `Item` in the preferred format, as serializing never requires ownership.
+impl<Item: Serialize> BorrowedMessageEncoder<Item> for AutoDetectCodec<Item> {
+    fn encode_ref(&self, item: &Item, dst: &mut BytesMut) -> Result<()> {
+        match self.preferred {
+            AutoDetectFormat::Json => Json.serialize_into(item, dst),
+            AutoDetectFormat::Cbor => Cbor.serialize_into(item, dst),
+        }
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload into any `Item` implementing
+/// [DeserializeOwned](serde::de::DeserializeOwned), in the format detected from the payload.
+///
+/// # Errors
+///
+/// Returns [Err] if the payload fails to deserialize into `Item` in the detected format.
+impl<Item: DeserializeOwned> MessageDecoder<Item> for AutoDetectCodec<Item> {
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
+        match detect(buffer) {
+            AutoDetectFormat::Json => Json.deserialize(buffer),
+            AutoDetectFormat::Cbor => Cbor.deserialize(buffer),
+        }
+    }
+}
+
+impl<Item> SeliumCodec for AutoDetectCodec<Item> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::{CborCodec, JsonCodec};
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dummy {
+        foo: String,
+        bar: u64,
+    }
+
+    fn dummy() -> Dummy {
+        Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        }
+    }
+
+    #[test]
+    fn decodes_json_and_cbor() {
+        let codec = AutoDetectCodec::<Dummy>::new(AutoDetectFormat::Cbor);
+
+        let json = JsonCodec::default().encode(&dummy()).unwrap();
+        let cbor = CborCodec::default().encode(&dummy()).unwrap();
+
+        assert_eq!(
+            codec.decode(&mut BytesMut::from(&json[..])).unwrap(),
+            dummy()
+        );
+        assert_eq!(
+            codec.decode(&mut BytesMut::from(&cbor[..])).unwrap(),
+            dummy()
+        );
+    }
+
+    #[test]
+    fn decodes_json_with_leading_whitespace() {
+        let codec = AutoDetectCodec::<Vec<u64>>::new(AutoDetectFormat::Cbor);
+        let mut buffer = BytesMut::from("\n  [1, 2, 3]");
+
+        assert_eq!(codec.decode(&mut buffer).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn encodes_in_preferred_format() {
+        let json = AutoDetectCodec::new(AutoDetectFormat::Json);
+        let cbor = AutoDetectCodec::new(AutoDetectFormat::Cbor);
+
+        assert_eq!(
+            json.encode(&dummy()).unwrap(),
+            JsonCodec::default().encode(&dummy()).unwrap()
+        );
+        assert_eq!(
+            cbor.encode(&dummy()).unwrap(),
+            CborCodec::default().encode(&dummy()).unwrap()
+        );
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let codec = AutoDetectCodec::new(AutoDetectFormat::Cbor);
+        let mut buffer = BytesMut::new();
+        codec.encode_into(&dummy(), &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode(&dummy()).unwrap());
+    }
+
+    #[test]
+    fn fails_to_decode_json_scalar() {
+        let codec = AutoDetectCodec::<String>::new(AutoDetectFormat::Json);
+        let mut buffer = BytesMut::from(r#""foo""#);
+
+        let err = codec.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(err.to_string(), "Failed to decode CBOR payload");
+    }
+}
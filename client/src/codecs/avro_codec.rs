@@ -0,0 +1,349 @@
+use crate::traits::{MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::{bail, Context, Result};
+use apache_avro::{from_avro_datum, from_value, rabin::Rabin, to_avro_datum, to_value, Schema};
+use bytes::{Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+const MARKER: [u8; 2] = [0xC3, 0x01];
+const FINGERPRINT_LEN: usize = 8;
+const HEADER_LEN: usize = MARKER.len() + FINGERPRINT_LEN;
+
+type Fingerprint = [u8; FINGERPRINT_LEN];
+
+fn fingerprint(schema: &Schema) -> Fingerprint {
+    let bytes = schema.fingerprint::<Rabin>().bytes;
+    bytes
+        .try_into()
+        .expect("Rabin fingerprints are always 8 bytes")
+}
+
+fn format_fingerprint(fingerprint: &Fingerprint) -> String {
+    fingerprint
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// A codec that serializes and deserializes message payloads with [Apache Avro](apache_avro),
+/// using the schema that it's constructed with.
+///
+/// Each message is encoded in Avro's
+/// [single-object encoding](https://avro.apache.org/docs/current/specification/#single-object-encoding),
+/// so that its payload starts with a two byte marker, followed by the 64-bit Rabin fingerprint of
+/// the schema that it was written with, followed by the Avro-encoded `T`.
+///
+/// # Schema Evolution
+///
+/// When decoding, the fingerprint identifies the schema that the message was written with, and
+/// the message is resolved from that schema into the codec's own schema, following Avro's schema
+/// resolution rules. By default, only messages written with the codec's own schema are accepted.
+/// A subscriber can accept messages written by publishers using other versions of the schema by
+/// registering those versions with [with_writer_schema](AvroCodec::with_writer_schema).
+///
+/// # Errors
+///
+/// Decoding a message fails if its payload doesn't start with a single-object header, if it was
+/// written with a schema that hasn't been registered with the codec, or if it can't be resolved
+/// into the codec's schema. As with any other codec, the [Subscriber](crate::Subscriber) yields
+/// these failures as codec errors, which are handled according to its
+/// [DecodeErrorPolicy](crate::DecodeErrorPolicy).
+///
+/// ```
+/// use apache_avro::Schema;
+/// use selium::codecs::AvroCodec;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct StockEvent {
+///     ticker: String,
+///     change: f64,
+/// }
+///
+/// let schema = Schema::parse_str(r#"{
+///     "type": "record",
+///     "name": "StockEvent",
+///     "fields": [
+///         {"name": "ticker", "type": "string"},
+///         {"name": "change", "type": "double"}
+///     ]
+/// }"#)?;
+///
+/// let codec = AvroCodec::<StockEvent>::new(schema);
+/// # Ok::<(), apache_avro::Error>(())
+/// ```
+pub struct AvroCodec<T> {
+    schema: Schema,
+    header: [u8; HEADER_LEN],
+    writer_schemas: HashMap<Fingerprint, Schema>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> AvroCodec<T> {
+    /// Constructs a new [AvroCodec], which encodes messages with the writer `schema`, and
+    /// decodes messages written with it.
+    pub fn new(schema: Schema) -> Self {
+        let fingerprint = fingerprint(&schema);
+
+        let mut header = [0; HEADER_LEN];
+        header[..MARKER.len()].copy_from_slice(&MARKER);
+        header[MARKER.len()..].copy_from_slice(&fingerprint);
+
+        Self {
+            writer_schemas: HashMap::from([(fingerprint, schema.clone())]),
+            schema,
+            header,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Accepts messages written with `schema`, such as by publishers using an earlier or later
+    /// version of the codec's schema, resolving them into the codec's own schema when decoding.
+    pub fn with_writer_schema(mut self, schema: Schema) -> Self {
+        self.writer_schemas.insert(fingerprint(&schema), schema);
+        self
+    }
+}
+
+impl<T> Clone for AvroCodec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            schema: self.schema.clone(),
+            header: self.header,
+            writer_schemas: self.writer_schemas.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for AvroCodec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AvroCodec")
+            .field("schema", &self.schema)
+            .field("writer_schemas", &self.writer_schemas.len())
+            .finish()
+    }
+}
+
+/// Encodes any `T` implementing [Serialize](serde::Serialize) with the codec's schema, prefixed
+/// with the schema's fingerprint.
+///
+/// # Errors
+///
+/// Returns [Err] if `item` fails to serialize, or doesn't match the codec's schema.
+impl<T: Serialize> MessageEncoder<T> for AvroCodec<T> {
+    fn encode(&self, item: T) -> Result<Bytes> {
+        let value = to_value(item)?.resolve(&self.schema)?;
+        let datum = to_avro_datum(&self.schema, value)?;
+
+        let mut buffer = BytesMut::with_capacity(HEADER_LEN + datum.len());
+        buffer.extend_from_slice(&self.header);
+        buffer.extend_from_slice(&datum);
+
+        Ok(buffer.into())
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload written with any of the codec's writer schemas
+/// into any `T` implementing [DeserializeOwned](serde::de::DeserializeOwned).
+///
+/// # Errors
+///
+/// Returns [Err] if the payload is missing its single-object header, if it was written with an
+/// unknown schema, if it can't be resolved into the codec's schema, or if the resolved value
+/// fails to deserialize into `T`.
+impl<T: DeserializeOwned> MessageDecoder<T> for AvroCodec<T> {
+    fn decode(&self, buffer: &mut BytesMut) -> Result<T> {
+        if buffer.len() < HEADER_LEN || buffer[..MARKER.len()] != MARKER {
+            bail!("Message payload is missing an Avro single-object header");
+        }
+
+        let header = buffer.split_to(HEADER_LEN);
+        let fingerprint: Fingerprint = header[MARKER.len()..].try_into()?;
+
+        let Some(writer_schema) = self.writer_schemas.get(&fingerprint) else {
+            bail!(
+                "Message was written with an unknown Avro schema, with fingerprint {}",
+                format_fingerprint(&fingerprint)
+            );
+        };
+
+        let value = from_avro_datum(writer_schema, &mut &buffer[..], Some(&self.schema))
+            .with_context(|| {
+                format!(
+                    "Failed to resolve message written with Avro schema {} into the reader schema",
+                    format_fingerprint(&fingerprint)
+                )
+            })?;
+
+        Ok(from_value(&value)?)
+    }
+}
+
+impl<T> SeliumCodec for AvroCodec<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct UserV1 {
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct UserV2 {
+        name: String,
+        email: Option<String>,
+    }
+
+    fn schema_v1() -> Schema {
+        Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "User",
+                "fields": [{"name": "name", "type": "string"}]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn schema_v2() -> Schema {
+        Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "User",
+                "fields": [
+                    {"name": "name", "type": "string"},
+                    {"name": "email", "type": ["null", "string"], "default": null}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn user_v1() -> UserV1 {
+        UserV1 {
+            name: "Ferris".to_owned(),
+        }
+    }
+
+    fn user_v2() -> UserV2 {
+        UserV2 {
+            name: "Ferris".to_owned(),
+            email: Some("ferris@acmeco.com".to_owned()),
+        }
+    }
+
+    #[test]
+    fn encodes_with_single_object_header() {
+        let schema = schema_v1();
+        let codec = AvroCodec::new(schema.clone());
+
+        let encoded = codec.encode(user_v1()).unwrap();
+
+        assert_eq!(encoded[..2], MARKER);
+        assert_eq!(encoded[2..HEADER_LEN], fingerprint(&schema));
+        assert_eq!(encoded[HEADER_LEN..], *b"\x0cFerris");
+    }
+
+    #[test]
+    fn round_trips_with_same_schema() {
+        let codec = AvroCodec::new(schema_v2());
+
+        let encoded = codec.encode(user_v2()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, user_v2());
+    }
+
+    #[test]
+    fn resolves_messages_written_with_older_schema() {
+        let writer = AvroCodec::new(schema_v1());
+        let reader = AvroCodec::<UserV2>::new(schema_v2()).with_writer_schema(schema_v1());
+
+        let encoded = writer.encode(user_v1()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = reader.decode(&mut buffer).unwrap();
+
+        assert_eq!(
+            decoded,
+            UserV2 {
+                name: "Ferris".to_owned(),
+                email: None,
+            }
+        );
+    }
+
+    #[test]
+    fn resolves_messages_written_with_newer_schema() {
+        let writer = AvroCodec::new(schema_v2());
+        let reader = AvroCodec::<UserV1>::new(schema_v1()).with_writer_schema(schema_v2());
+
+        let encoded = writer.encode(user_v2()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = reader.decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, user_v1());
+    }
+
+    #[test]
+    fn fails_to_decode_unknown_writer_schema() {
+        let writer = AvroCodec::new(schema_v1());
+        let reader = AvroCodec::<UserV2>::new(schema_v2());
+
+        let encoded = writer.encode(user_v1()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let err = reader.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Message was written with an unknown Avro schema, with fingerprint {}",
+                format_fingerprint(&fingerprint(&schema_v1()))
+            )
+        );
+    }
+
+    #[test]
+    fn fails_to_resolve_incompatible_schema() {
+        // A new field without a default can't be resolved from messages that lack it
+        let incompatible = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "User",
+                "fields": [
+                    {"name": "name", "type": "string"},
+                    {"name": "age", "type": "int"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let writer = AvroCodec::new(schema_v1());
+        let reader = AvroCodec::<UserV1>::new(incompatible).with_writer_schema(schema_v1());
+
+        let encoded = writer.encode(user_v1()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let err = reader.decode(&mut buffer).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("Failed to resolve message written with Avro schema"));
+    }
+
+    #[test]
+    fn fails_to_decode_payload_without_header() {
+        let codec = AvroCodec::<UserV1>::new(schema_v1());
+        let mut buffer = BytesMut::from("\x0cFerris");
+
+        let err = codec.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Message payload is missing an Avro single-object header"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::{bail, Result};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+const LENGTH_PREFIX_LEN: usize = 4;
+
+/// A wrapper codec that encodes a batch of items into a single message, using an inner codec to
+/// encode each item.
+///
+/// Unlike [send_batch](crate::Publisher::send_batch), which publishes each item as its own
+/// message, a batch is published and delivered as one message, amortizing the per-message
+/// overhead across its items. Subscribers must decode the topic with a [BatchCodec] wrapping the
+/// same inner codec.
+///
+/// # Framing
+///
+/// Each item is encoded by the inner codec, and written after its encoded length as a 32-bit
+/// big-endian integer. The items are written one after the other in order, with nothing before
+/// or after them, so an empty batch is encoded as an empty payload.
+///
+/// ```
+/// use selium::codecs::{BatchCodec, StringCodec};
+///
+/// // Publishes and receives `Vec<String>` batches
+/// let codec = BatchCodec::new(StringCodec);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BatchCodec<C> {
+    inner: C,
+}
+
+impl<C> BatchCodec<C> {
+    /// Constructs a new [BatchCodec], encoding and decoding each item of a batch with the `inner`
+    /// codec.
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+}
+
+// Writes an item encoded by `encode` into `dst`, after its length
+fn encode_element<F>(dst: &mut BytesMut, encode: F) -> Result<()>
+where
+    F: FnOnce(&mut BytesMut) -> Result<()>,
+{
+    let start = dst.len();
+    dst.put_u32(0);
+    encode(dst)?;
+
+    let len = dst.len() - start - LENGTH_PREFIX_LEN;
+
+    let Ok(len) = u32::try_from(len) else {
+        bail!(
+            "Batched item of {len} bytes exceeds the maximum of {} bytes",
+            u32::MAX
+        );
+    };
+
+    dst[start..start + LENGTH_PREFIX_LEN].copy_from_slice(&len.to_be_bytes());
+
+    Ok(())
+}
+
+/// Encodes each item of a batch with the inner codec, prefixing each with its length.
+///
+/// # Errors
+///
+/// Returns [Err] if the inner codec fails to encode an item, or if an encoded item is larger
+/// than [u32::MAX] bytes.
+impl<C, Item> MessageEncoder<Vec<Item>> for BatchCodec<C>
+where
+    C: MessageEncoder<Item>,
+{
+    fn encode(&self, items: Vec<Item>) -> Result<Bytes> {
+        let mut buffer = BytesMut::new();
+        self.encode_into(items, &mut buffer)?;
+
+        Ok(buffer.into())
+    }
+
+    fn encode_into(&self, items: Vec<Item>, dst: &mut BytesMut) -> Result<()> {
+        for item in items {
+            encode_element(dst, |dst| self.inner.encode_into(item, dst))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes a borrowed slice of items as a batch, encoding each item by reference with the inner
+/// codec.
+impl<C, Item> BorrowedMessageEncoder<[Item]> for BatchCodec<C>
+where
+    C: BorrowedMessageEncoder<Item>,
+{
+    fn encode_ref(&self, items: &[Item], dst: &mut BytesMut) -> Result<()> {
+        for item in items {
+            encode_element(dst, |dst| self.inner.encode_ref(item, dst))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits a [BytesMut](bytes::BytesMut) payload into its length-prefixed items, and decodes each
+/// of them with the inner codec.
+///
+/// # Errors
+///
+/// Returns [Err] if the payload ends part way through an item or its length, or if the inner
+/// codec fails to decode an item.
+impl<C, Item> MessageDecoder<Vec<Item>> for BatchCodec<C>
+where
+    C: MessageDecoder<Item>,
+{
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Vec<Item>> {
+        let mut items = Vec::new();
+
+        while buffer.has_remaining() {
+            if buffer.len() < LENGTH_PREFIX_LEN {
+                bail!("Batch payload ends part way through the length of an item");
+            }
+
+            let len = buffer.get_u32() as usize;
+
+            if buffer.len() < len {
+                bail!(
+                    "Batched item of {len} bytes exceeds the {} bytes remaining in the payload",
+                    buffer.len()
+                );
+            }
+
+            let mut element = buffer.split_to(len);
+            items.push(self.inner.decode(&mut element)?);
+        }
+
+        Ok(items)
+    }
+}
+
+impl<C: SeliumCodec> SeliumCodec for BatchCodec<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::StringCodec;
+
+    fn batch(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn encodes_empty_batch_as_empty_payload() {
+        let codec = BatchCodec::new(StringCodec);
+
+        let encoded = codec.encode(batch(&[])).unwrap();
+
+        assert!(encoded.is_empty());
+    }
+
+    #[test]
+    fn encodes_each_item_with_length_prefix() {
+        let codec = BatchCodec::new(StringCodec);
+
+        let single = codec.encode(batch(&["Hello"])).unwrap();
+        let multiple = codec.encode(batch(&["Hello", "", "world"])).unwrap();
+
+        assert_eq!(single, Bytes::from("\0\0\0\x05Hello"));
+        assert_eq!(
+            multiple,
+            Bytes::from("\0\0\0\x05Hello\0\0\0\0\0\0\0\x05world")
+        );
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let codec = BatchCodec::new(StringCodec);
+        let mut buffer = BytesMut::from("prefix");
+
+        codec
+            .encode_into(batch(&["Hello", "world"]), &mut buffer)
+            .unwrap();
+
+        assert_eq!(&buffer[..6], b"prefix");
+        assert_eq!(
+            buffer[6..],
+            codec.encode(batch(&["Hello", "world"])).unwrap()
+        );
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn encodes_borrowed_slice_like_encode() {
+        let codec = BatchCodec::new(crate::codecs::BincodeCodec::default());
+        let items = vec![1u64, 2, 3];
+        let mut buffer = BytesMut::new();
+
+        codec.encode_ref(&items[..], &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode(items).unwrap());
+    }
+
+    #[test]
+    fn round_trips_batches() {
+        let codec = BatchCodec::new(StringCodec);
+
+        for items in [
+            batch(&[]),
+            batch(&["Hello"]),
+            batch(&["Hello", "", "world"]),
+        ] {
+            let encoded = codec.encode(items.clone()).unwrap();
+            let mut buffer = BytesMut::from(&encoded[..]);
+            let decoded: Vec<String> = codec.decode(&mut buffer).unwrap();
+
+            assert_eq!(decoded, items);
+        }
+    }
+
+    #[test]
+    fn fails_to_decode_truncated_payload() {
+        let codec = BatchCodec::new(StringCodec);
+
+        let mut truncated_length = BytesMut::from("\0\0\0\x05Hello\0\0");
+        let mut truncated_item = BytesMut::from("\0\0\0\x05Hell");
+
+        let err = MessageDecoder::<Vec<String>>::decode(&codec, &mut truncated_length).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Batch payload ends part way through the length of an item"
+        );
+
+        let err = MessageDecoder::<Vec<String>>::decode(&codec, &mut truncated_item).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Batched item of 5 bytes exceeds the 4 bytes remaining in the payload"
+        );
+    }
+}
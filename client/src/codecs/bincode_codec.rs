@@ -1,61 +1,216 @@
-use crate::traits::{MessageDecoder, MessageEncoder, SeliumCodec};
+use super::{SerdeCodec, SerdeFormat};
 use anyhow::Result;
-use bytes::{Buf, Bytes, BytesMut};
+use bincode::{DefaultOptions, Options};
+use bytes::{BufMut, BytesMut};
 use serde::{de::DeserializeOwned, Serialize};
 use std::marker::PhantomData;
 
-/// A basic codec that uses [bincode] to serialize and deserialize
-/// binary message payloads.
-#[derive(Debug)]
-pub struct BincodeCodec<Item> {
-    _marker: PhantomData<Item>,
+/// The byte order that [Bincode] serializes integers in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// How [Bincode] serializes integers, and the lengths of strings and collections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IntEncoding {
+    /// Each integer is serialized in the number of bytes of its type.
+    #[default]
+    Fixint,
+    /// Each integer is serialized in as few bytes as its value needs.
+    Varint,
+}
+
+/// The [bincode] options used by a [BincodeCodec], for matching the wire format of a peer that
+/// doesn't use bincode's defaults.
+///
+/// The default configuration matches [bincode::serialize] and [bincode::deserialize], which
+/// encode integers as little-endian [IntEncoding::Fixint], with no size limit, and allow trailing
+/// bytes after the payload.
+///
+/// **NOTE:** The configuration isn't sent along with messages, so the codecs encoding and
+/// decoding a topic's messages must be configured identically. A payload encoded with one
+/// configuration either fails to decode with another, or decodes into the wrong value.
+///
+/// # Examples
+///
+/// ```
+/// use selium::codecs::{BincodeCodec, BincodeConfig, Endianness, IntEncoding};
+///
+/// let config = BincodeConfig::default()
+///     .with_endianness(Endianness::Big)
+///     .with_int_encoding(IntEncoding::Varint)
+///     .with_limit(64 * 1024);
+///
+/// let codec = BincodeCodec::<Vec<u64>>::with_config(config);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BincodeConfig {
+    endianness: Endianness,
+    int_encoding: IntEncoding,
+    limit: Option<u64>,
+    allow_trailing_bytes: bool,
 }
 
-impl<Item> Clone for BincodeCodec<Item> {
-    fn clone(&self) -> Self {
+impl Default for BincodeConfig {
+    fn default() -> Self {
         Self {
-            _marker: PhantomData,
+            endianness: Endianness::default(),
+            int_encoding: IntEncoding::default(),
+            limit: None,
+            allow_trailing_bytes: true,
         }
     }
 }
 
-impl<T> Default for BincodeCodec<T> {
-    fn default() -> Self {
-        Self {
-            _marker: PhantomData,
+impl BincodeConfig {
+    /// Sets the byte order that integers are serialized in.
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Sets how integers, and the lengths of strings and collections, are serialized.
+    pub fn with_int_encoding(mut self, int_encoding: IntEncoding) -> Self {
+        self.int_encoding = int_encoding;
+        self
+    }
+
+    /// Limits the number of bytes that a message may be serialized into or deserialized from,
+    /// failing messages that exceed it rather than allocating for them.
+    pub fn with_limit(mut self, bytes: u64) -> Self {
+        self.limit = Some(bytes);
+        self
+    }
+
+    /// Fails to deserialize a payload with bytes left over once the message has been read.
+    pub fn reject_trailing_bytes(mut self) -> Self {
+        self.allow_trailing_bytes = false;
+        self
+    }
+
+    // Each option changes the type of the bincode options, so they're applied one at a time,
+    // before the operation is applied with the result
+    fn apply<Op: WithOptions>(&self, op: Op) -> Result<Op::Output> {
+        let options = DefaultOptions::new();
+
+        match self.endianness {
+            Endianness::Little => self.apply_int_encoding(options.with_little_endian(), op),
+            Endianness::Big => self.apply_int_encoding(options.with_big_endian(), op),
+        }
+    }
+
+    fn apply_int_encoding<O: Options, Op: WithOptions>(
+        &self,
+        options: O,
+        op: Op,
+    ) -> Result<Op::Output> {
+        match self.int_encoding {
+            IntEncoding::Fixint => self.apply_limit(options.with_fixint_encoding(), op),
+            IntEncoding::Varint => self.apply_limit(options.with_varint_encoding(), op),
+        }
+    }
+
+    fn apply_limit<O: Options, Op: WithOptions>(&self, options: O, op: Op) -> Result<Op::Output> {
+        match self.limit {
+            Some(limit) => self.apply_trailing_bytes(options.with_limit(limit), op),
+            None => self.apply_trailing_bytes(options.with_no_limit(), op),
+        }
+    }
+
+    fn apply_trailing_bytes<O: Options, Op: WithOptions>(
+        &self,
+        options: O,
+        op: Op,
+    ) -> Result<Op::Output> {
+        if self.allow_trailing_bytes {
+            op.apply(options.allow_trailing_bytes())
+        } else {
+            op.apply(options.reject_trailing_bytes())
         }
     }
 }
 
-/// Encodes any `Item` implementing [Serialize](serde::Serialize) into a binary format via
-/// [bincode].
-///
-/// # Errors
-///
-/// Returns [Err] if `item` fails to serialize.
-impl<Item: Serialize> MessageEncoder<Item> for BincodeCodec<Item> {
-    fn encode(&self, item: Item) -> Result<Bytes> {
-        Ok(bincode::serialize(&item)?.into())
+/// An operation performed with the [bincode::Options] built from a [BincodeConfig].
+trait WithOptions {
+    type Output;
+
+    fn apply<O: Options>(self, options: O) -> Result<Self::Output>;
+}
+
+struct SerializeOp<'a, T>(&'a T);
+
+impl<T: Serialize> WithOptions for SerializeOp<'_, T> {
+    type Output = Vec<u8>;
+
+    fn apply<O: Options>(self, options: O) -> Result<Vec<u8>> {
+        Ok(options.serialize(self.0)?)
     }
 }
 
-/// Decodes a [BytesMut](bytes::BytesMut) payload into any `Item` implementing
-/// [DeserializeOwned](serde::de::DeserializeOwned).
-///
-/// # Errors
-///
-/// Returns [Err] if the [BytesMut](bytes::BytesMut) payload fails to deserialize into `Item`.
-impl<Item: DeserializeOwned> MessageDecoder<Item> for BincodeCodec<Item> {
-    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
-        Ok(bincode::deserialize_from(buffer.reader())?)
+struct SerializeIntoOp<'a, T>(&'a T, &'a mut BytesMut);
+
+impl<T: Serialize> WithOptions for SerializeIntoOp<'_, T> {
+    type Output = ();
+
+    fn apply<O: Options>(self, options: O) -> Result<()> {
+        Ok(options.serialize_into(self.1.writer(), self.0)?)
+    }
+}
+
+struct DeserializeOp<'a, T>(&'a [u8], PhantomData<T>);
+
+impl<T: DeserializeOwned> WithOptions for DeserializeOp<'_, T> {
+    type Output = T;
+
+    fn apply<O: Options>(self, options: O) -> Result<T> {
+        Ok(options.deserialize(self.0)?)
+    }
+}
+
+/// A [SerdeFormat] for the [bincode] binary serialization format, with the options in its
+/// [BincodeConfig].
+#[derive(Debug, Clone, Default)]
+pub struct Bincode {
+    config: BincodeConfig,
+}
+
+impl SerdeFormat for Bincode {
+    fn serialize<T: Serialize>(&self, item: &T) -> Result<Vec<u8>> {
+        self.config.apply(SerializeOp(item))
+    }
+
+    fn serialize_into<T: Serialize>(&self, item: &T, dst: &mut BytesMut) -> Result<()> {
+        self.config.apply(SerializeIntoOp(item, dst))
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+        self.config.apply(DeserializeOp(bytes, PhantomData))
     }
 }
 
-impl<Item> SeliumCodec for BincodeCodec<Item> {}
+/// A basic codec that uses [bincode] to serialize and deserialize
+/// binary message payloads.
+pub type BincodeCodec<Item> = SerdeCodec<Item, Bincode>;
+
+impl<Item> BincodeCodec<Item> {
+    /// Constructs a [BincodeCodec] that serializes and deserializes message payloads with the
+    /// options in `config`, rather than bincode's defaults.
+    ///
+    /// Publishers and subscribers of the same topic must use the same `config`. See
+    /// [BincodeConfig] for details.
+    pub fn with_config(config: BincodeConfig) -> Self {
+        Self::with_format(Bincode { config })
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::traits::{MessageDecoder, MessageEncoder};
+    use bytes::{Bytes, BytesMut};
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -78,6 +233,20 @@ mod tests {
         assert_eq!(expected, bytes);
     }
 
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let codec = BincodeCodec::default();
+        let mut buffer = BytesMut::new();
+        codec.encode_into(&input, &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode(&input).unwrap());
+    }
+
     #[test]
     fn decodes_bincode_bytes() {
         let mut buffer = BytesMut::from("\x03\0\0\0\0\0\0\0foo*\0\0\0\0\0\0\0");
@@ -92,4 +261,70 @@ mod tests {
 
         assert_eq!(decoded, expected);
     }
+
+    #[test]
+    fn round_trips_bincode_bytes() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let encoded = BincodeCodec::default().encode(&input).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = BincodeCodec::<Dummy>::default()
+            .decode(&mut buffer)
+            .unwrap();
+
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn round_trips_with_matching_configs() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+        let config = BincodeConfig::default()
+            .with_endianness(Endianness::Big)
+            .with_int_encoding(IntEncoding::Varint)
+            .with_limit(64)
+            .reject_trailing_bytes();
+
+        let encoded = BincodeCodec::with_config(config).encode(&input).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = BincodeCodec::<Dummy>::with_config(config)
+            .decode(&mut buffer)
+            .unwrap();
+
+        // The varint-encoded length and value each fit into a single byte
+        assert_eq!(encoded, Bytes::from("\x03foo*"));
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn fails_to_decode_with_mismatched_config() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+        let varint = BincodeConfig::default().with_int_encoding(IntEncoding::Varint);
+
+        let encoded = BincodeCodec::with_config(varint).encode(&input).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+
+        assert!(BincodeCodec::<Dummy>::default()
+            .decode(&mut buffer)
+            .is_err());
+    }
+
+    #[test]
+    fn fails_to_encode_beyond_limit() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+        let codec = BincodeCodec::with_config(BincodeConfig::default().with_limit(8));
+
+        assert!(codec.encode(&input).is_err());
+    }
 }
@@ -0,0 +1,88 @@
+use super::{SerdeCodec, SerdeFormat};
+use anyhow::{Context, Result};
+use bytes::{BufMut, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A [SerdeFormat] marker for the [CBOR](https://cbor.io) serialization format, via [ciborium].
+///
+/// CBOR is a widely supported binary format, making it a good fit when consumers are written in
+/// other languages.
+#[derive(Debug, Clone, Default)]
+pub struct Cbor;
+
+impl SerdeFormat for Cbor {
+    fn serialize<T: Serialize>(&self, item: &T) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        ciborium::into_writer(item, &mut buffer).context("Failed to encode CBOR payload")?;
+
+        Ok(buffer)
+    }
+
+    fn serialize_into<T: Serialize>(&self, item: &T, dst: &mut BytesMut) -> Result<()> {
+        ciborium::into_writer(item, dst.writer()).context("Failed to encode CBOR payload")
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+        ciborium::from_reader(bytes).context("Failed to decode CBOR payload")
+    }
+}
+
+/// A basic codec that uses [ciborium] to serialize and deserialize
+/// [CBOR](https://cbor.io) message payloads.
+pub type CborCodec<Item> = SerdeCodec<Item, Cbor>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{MessageDecoder, MessageEncoder};
+    use bytes::BytesMut;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dummy {
+        price: f64,
+        #[serde(with = "serde_bytes")]
+        raw: Vec<u8>,
+    }
+
+    fn dummy() -> Dummy {
+        Dummy {
+            price: 3.5,
+            raw: vec![0xde, 0xad, 0xbe, 0xef],
+        }
+    }
+
+    #[test]
+    fn round_trips_cbor_bytes() {
+        let input = dummy();
+
+        let encoded = CborCodec::default().encode(&input).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = CborCodec::<Dummy>::default().decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let input = dummy();
+
+        let codec = CborCodec::default();
+        let mut buffer = BytesMut::new();
+        codec.encode_into(&input, &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode(&input).unwrap());
+    }
+
+    #[test]
+    fn fails_to_decode_truncated_cbor() {
+        let encoded = CborCodec::default().encode(&dummy()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..encoded.len() - 2]);
+
+        let err = CborCodec::<Dummy>::default()
+            .decode(&mut buffer)
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "Failed to decode CBOR payload");
+    }
+}
@@ -0,0 +1,176 @@
+use crate::traits::{MessageDecoder, SeliumCodec};
+use anyhow::{anyhow, Result};
+use bytes::BytesMut;
+use selium_common::protocol::Headers;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// The name of the header that a [CodecRegistry] selects the decoder of each message by.
+pub const CONTENT_TYPE: &str = "content-type";
+
+type BoxedDecoder<Item> = Arc<dyn MessageDecoder<Item> + Send + Sync>;
+
+/// A set of decoders for a single `Item` type, keyed by content type, allowing a
+/// [Subscriber](crate::Subscriber) to receive messages in several formats on the same topic.
+///
+/// Each message is decoded by the decoder registered for the value of its [CONTENT_TYPE] header,
+/// which publishers attach via [send_with_headers](crate::Publisher::send_with_headers). Messages
+/// without the header, or with a content type that isn't registered, are decoded by the default
+/// decoder if one is provided, and otherwise fail to decode, which is handled according to the
+/// Subscriber's [DecodeErrorPolicy](crate::DecodeErrorPolicy).
+///
+/// The registry is provided to a Subscriber via the `with_codec_registry` builder method.
+///
+/// ```
+/// use selium::codecs::{CodecRegistry, StringCodec};
+///
+/// let registry = CodecRegistry::new()
+///     .register("text/plain", StringCodec)
+///     .with_default(StringCodec);
+/// ```
+pub struct CodecRegistry<Item> {
+    decoders: HashMap<String, BoxedDecoder<Item>>,
+    default: Option<BoxedDecoder<Item>>,
+}
+
+impl<Item> CodecRegistry<Item> {
+    /// Constructs an empty [CodecRegistry], without a default decoder.
+    pub fn new() -> Self {
+        Self {
+            decoders: HashMap::new(),
+            default: None,
+        }
+    }
+
+    /// Registers the `decoder` used for messages whose [CONTENT_TYPE] header is `content_type`,
+    /// replacing any decoder already registered for it.
+    pub fn register<D>(mut self, content_type: &str, decoder: D) -> Self
+    where
+        D: MessageDecoder<Item> + Send + Sync + 'static,
+    {
+        self.decoders
+            .insert(content_type.to_owned(), Arc::new(decoder));
+        self
+    }
+
+    /// Sets the `decoder` used for messages without a [CONTENT_TYPE] header, or whose content
+    /// type isn't registered.
+    pub fn with_default<D>(mut self, decoder: D) -> Self
+    where
+        D: MessageDecoder<Item> + Send + Sync + 'static,
+    {
+        self.default = Some(Arc::new(decoder));
+        self
+    }
+
+    /// Decodes `buffer` with the decoder selected by the message's `headers`.
+    pub(crate) fn decode(&self, headers: &Headers, buffer: &mut BytesMut) -> Result<Item> {
+        let content_type = headers.get(CONTENT_TYPE);
+        let decoder = content_type
+            .and_then(|content_type| self.decoders.get(content_type))
+            .or(self.default.as_ref())
+            .ok_or_else(|| match content_type {
+                Some(content_type) => {
+                    anyhow!("No decoder is registered for content type {content_type}")
+                }
+                None => anyhow!("Message has no {CONTENT_TYPE} header to select a decoder by"),
+            })?;
+
+        decoder.decode(buffer)
+    }
+}
+
+impl<Item> Default for CodecRegistry<Item> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Item> fmt::Debug for CodecRegistry<Item> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CodecRegistry")
+            .field("content_types", &self.decoders.keys().collect::<Vec<_>>())
+            .field("default", &self.default.is_some())
+            .finish()
+    }
+}
+
+impl<Item> SeliumCodec for CodecRegistry<Item> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::StringCodec;
+    use anyhow::bail;
+
+    struct UppercaseCodec;
+
+    impl MessageDecoder<String> for UppercaseCodec {
+        fn decode(&self, buffer: &mut BytesMut) -> Result<String> {
+            match std::str::from_utf8(buffer) {
+                Ok(decoded) => Ok(decoded.to_uppercase()),
+                Err(_) => bail!("Invalid UTF-8"),
+            }
+        }
+    }
+
+    fn headers(content_type: &str) -> Headers {
+        Headers::from([(CONTENT_TYPE.to_owned(), content_type.to_owned())])
+    }
+
+    #[test]
+    fn selects_decoder_by_content_type() {
+        let registry = CodecRegistry::new()
+            .register("text/plain", StringCodec)
+            .register("text/uppercase", UppercaseCodec);
+
+        let plain = registry
+            .decode(&headers("text/plain"), &mut BytesMut::from("hello"))
+            .unwrap();
+        let uppercase = registry
+            .decode(&headers("text/uppercase"), &mut BytesMut::from("hello"))
+            .unwrap();
+
+        assert_eq!(plain, "hello");
+        assert_eq!(uppercase, "HELLO");
+    }
+
+    #[test]
+    fn falls_back_to_default_decoder() {
+        let registry = CodecRegistry::new()
+            .register("text/plain", StringCodec)
+            .with_default(UppercaseCodec);
+
+        let missing = registry
+            .decode(&Headers::new(), &mut BytesMut::from("hello"))
+            .unwrap();
+        let unknown = registry
+            .decode(&headers("text/html"), &mut BytesMut::from("hello"))
+            .unwrap();
+
+        assert_eq!(missing, "HELLO");
+        assert_eq!(unknown, "HELLO");
+    }
+
+    #[test]
+    fn fails_to_decode_without_default_decoder() {
+        let registry = CodecRegistry::new().register("text/plain", StringCodec);
+
+        let missing = registry
+            .decode(&Headers::new(), &mut BytesMut::from("hello"))
+            .unwrap_err();
+        let unknown = registry
+            .decode(&headers("text/html"), &mut BytesMut::from("hello"))
+            .unwrap_err();
+
+        assert_eq!(
+            missing.to_string(),
+            "Message has no content-type header to select a decoder by"
+        );
+        assert_eq!(
+            unknown.to_string(),
+            "No decoder is registered for content type text/html"
+        );
+    }
+}
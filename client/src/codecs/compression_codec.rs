@@ -0,0 +1,146 @@
+use crate::traits::{MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::{bail, Context, Result};
+use bytes::{Bytes, BytesMut};
+use selium_common::protocol::DEFAULT_MAX_MESSAGE_SIZE;
+use std::io::Read;
+
+/// A wrapper codec that transparently compresses the output of an inner codec using
+/// [zstd](https://facebook.github.io/zstd).
+///
+/// Messages are first encoded by the inner codec, and the resulting bytes are then compressed
+/// before being sent over the wire. Decoding reverses this process, decompressing the payload
+/// before handing it to the inner codec. Payloads that decompress to more than
+/// [DEFAULT_MAX_MESSAGE_SIZE] bytes fail to decode, unless the limit is overridden with
+/// [max_decompressed_size](CompressionCodec::max_decompressed_size).
+///
+/// ```
+/// use selium::codecs::{CompressionCodec, StringCodec};
+///
+/// let codec = CompressionCodec::new(StringCodec, 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompressionCodec<C> {
+    inner: C,
+    level: i32,
+    max_decompressed_size: u64,
+}
+
+impl<C> CompressionCodec<C> {
+    /// Constructs a new [CompressionCodec], wrapping the `inner` codec and compressing payloads
+    /// at the provided zstd compression `level`.
+    pub fn new(inner: C, level: i32) -> Self {
+        Self {
+            inner,
+            level,
+            max_decompressed_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Overrides the maximum size of a decompressed payload, in bytes, which defaults to
+    /// [DEFAULT_MAX_MESSAGE_SIZE]. Decompression stops as soon as the limit is exceeded, so a
+    /// small payload can't be used to exhaust the subscriber's memory.
+    pub fn max_decompressed_size(mut self, size: u64) -> Self {
+        self.max_decompressed_size = size;
+        self
+    }
+}
+
+/// Encodes `Item` with the inner codec, and compresses the resulting bytes.
+///
+/// # Errors
+///
+/// Returns [Err] if the inner codec fails to encode `item`, or if compression fails.
+impl<C, Item> MessageEncoder<Item> for CompressionCodec<C>
+where
+    C: MessageEncoder<Item>,
+{
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        let encoded = self.inner.encode(item)?;
+        let compressed = zstd::encode_all(&encoded[..], self.level)
+            .context("Failed to compress message payload")?;
+
+        Ok(compressed.into())
+    }
+}
+
+/// Decompresses a [BytesMut](bytes::BytesMut) payload, and decodes the result with the inner
+/// codec.
+///
+/// # Errors
+///
+/// Returns [Err] if the payload is not a valid zstd frame, if it decompresses to more than the
+/// maximum decompressed size, or if the inner codec fails to decode the decompressed bytes.
+impl<C, Item> MessageDecoder<Item> for CompressionCodec<C>
+where
+    C: MessageDecoder<Item>,
+{
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
+        // Reads one byte past the limit, so that an oversized payload is detected without
+        // decompressing the rest of it
+        let mut decompressed = Vec::new();
+        zstd::Decoder::new(&buffer[..])
+            .and_then(|decoder| {
+                decoder
+                    .take(self.max_decompressed_size.saturating_add(1))
+                    .read_to_end(&mut decompressed)
+            })
+            .context("Failed to decompress message payload")?;
+
+        if decompressed.len() as u64 > self.max_decompressed_size {
+            bail!(
+                "The decompressed message payload exceeds {} bytes",
+                self.max_decompressed_size
+            );
+        }
+
+        let mut decompressed = BytesMut::from(&decompressed[..]);
+
+        self.inner.decode(&mut decompressed)
+    }
+}
+
+impl<C: SeliumCodec> SeliumCodec for CompressionCodec<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::StringCodec;
+
+    #[test]
+    fn round_trips_compressed_payload() {
+        let input = "compressed string ".repeat(100);
+        let codec = CompressionCodec::new(StringCodec, 3);
+
+        let encoded = codec.encode(input.clone()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert!(encoded.len() < input.len());
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn fails_to_decode_corrupt_frame() {
+        let codec = CompressionCodec::new(StringCodec, 3);
+        let mut buffer = BytesMut::from("not a zstd frame");
+
+        let err = codec.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(err.to_string(), "Failed to decompress message payload");
+    }
+
+    #[test]
+    fn fails_to_decode_payload_larger_than_max_decompressed_size() {
+        let input = "a".repeat(64 * 1024);
+        let codec = CompressionCodec::new(StringCodec, 3).max_decompressed_size(1024);
+
+        let encoded = codec.encode(input).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let err = codec.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "The decompressed message payload exceeds 1024 bytes"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use crate::traits::{MessageDecoder, MessageEncoder, SeliumCodec};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Result};
+use bytes::{BufMut, Bytes, BytesMut};
+
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+
+/// A wrapper codec that encrypts the output of an inner codec using AES-256-GCM.
+///
+/// As the `Selium` server treats message payloads as opaque bytes, wrapping a codec with an
+/// [EncryptionCodec] provides end-to-end confidentiality between publishers and subscribers
+/// sharing the same key.
+///
+/// Each encoded payload consists of a randomly generated 12-byte nonce, followed by the
+/// ciphertext and its 16-byte authentication tag.
+///
+/// ```
+/// use selium::codecs::{EncryptionCodec, StringCodec};
+///
+/// let key = [0u8; 32]; // load your shared key from a secure location
+/// let codec = EncryptionCodec::new(StringCodec, key);
+/// ```
+#[derive(Clone)]
+pub struct EncryptionCodec<C> {
+    inner: C,
+    cipher: Aes256Gcm,
+}
+
+impl<C> EncryptionCodec<C> {
+    /// Constructs a new [EncryptionCodec], wrapping the `inner` codec and encrypting payloads
+    /// with the provided 256-bit `key`.
+    pub fn new(inner: C, key: [u8; 32]) -> Self {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        Self { inner, cipher }
+    }
+}
+
+/// Encodes `Item` with the inner codec, and encrypts the resulting bytes.
+///
+/// # Errors
+///
+/// Returns [Err] if the inner codec fails to encode `item`, or if encryption fails.
+impl<C, Item> MessageEncoder<Item> for EncryptionCodec<C>
+where
+    C: MessageEncoder<Item>,
+{
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        let encoded = self.inner.encode(item)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, &encoded[..])
+            .map_err(|_| anyhow!("Failed to encrypt message payload"))?;
+
+        let mut buffer = BytesMut::with_capacity(NONCE_SIZE + ciphertext.len());
+        buffer.put_slice(&nonce);
+        buffer.put_slice(&ciphertext);
+
+        Ok(buffer.into())
+    }
+}
+
+/// Authenticates and decrypts a [BytesMut](bytes::BytesMut) payload, and decodes the result with
+/// the inner codec.
+///
+/// # Errors
+///
+/// Returns [Err] if the payload is too short to contain a nonce and tag, if authentication fails
+/// due to a mismatched key or tampered ciphertext, or if the inner codec fails to decode the
+/// decrypted bytes.
+impl<C, Item> MessageDecoder<Item> for EncryptionCodec<C>
+where
+    C: MessageDecoder<Item>,
+{
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
+        if buffer.len() < NONCE_SIZE + TAG_SIZE {
+            bail!("Encrypted message payload is too short");
+        }
+
+        let ciphertext = buffer.split_off(NONCE_SIZE);
+        let nonce = Nonce::from_slice(&buffer[..]);
+        let plaintext = self
+            .cipher
+            .decrypt(nonce, &ciphertext[..])
+            .map_err(|_| anyhow!("Failed to authenticate encrypted message payload"))?;
+        let mut plaintext = BytesMut::from(&plaintext[..]);
+
+        self.inner.decode(&mut plaintext)
+    }
+}
+
+impl<C: SeliumCodec> SeliumCodec for EncryptionCodec<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::StringCodec;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    #[test]
+    fn round_trips_encrypted_payload() {
+        let codec = EncryptionCodec::new(StringCodec, KEY);
+
+        let encoded = codec.encode("top secret".to_owned()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert_eq!(encoded.len(), NONCE_SIZE + "top secret".len() + TAG_SIZE);
+        assert_eq!(decoded, "top secret");
+    }
+
+    #[test]
+    fn fails_to_decode_tampered_ciphertext() {
+        let codec = EncryptionCodec::new(StringCodec, KEY);
+
+        let encoded = codec.encode("top secret".to_owned()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        buffer[NONCE_SIZE] ^= 0xff;
+
+        let err = codec.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Failed to authenticate encrypted message payload"
+        );
+    }
+
+    #[test]
+    fn fails_to_decode_with_wrong_key() {
+        let encoded = EncryptionCodec::new(StringCodec, KEY)
+            .encode("top secret".to_owned())
+            .unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+
+        let result = EncryptionCodec::new(StringCodec, [8; 32]).decode(&mut buffer);
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use super::{SerdeCodec, SerdeFormat};
+use anyhow::Result;
+use bytes::{BufMut, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A [SerdeFormat] marker for the JSON serialization format, via [serde_json].
+#[derive(Debug, Clone, Default)]
+pub struct Json;
+
+impl SerdeFormat for Json {
+    fn serialize<T: Serialize>(&self, item: &T) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(item)?)
+    }
+
+    fn serialize_into<T: Serialize>(&self, item: &T, dst: &mut BytesMut) -> Result<()> {
+        Ok(serde_json::to_writer(dst.writer(), item)?)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// A basic codec that uses [serde_json] to serialize and deserialize
+/// human-readable JSON message payloads.
+pub type JsonCodec<Item> = SerdeCodec<Item, Json>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{MessageDecoder, MessageEncoder};
+    use bytes::{Bytes, BytesMut};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Inner {
+        baz: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dummy {
+        foo: String,
+        bar: u64,
+        inner: Inner,
+    }
+
+    fn dummy() -> Dummy {
+        Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+            inner: Inner { baz: vec![1, 2, 3] },
+        }
+    }
+
+    #[test]
+    fn encodes_to_json_bytes() {
+        let input = dummy();
+
+        let codec = JsonCodec::default();
+        let bytes = codec.encode(&input).unwrap();
+        let expected = Bytes::from(r#"{"foo":"foo","bar":42,"inner":{"baz":[1,2,3]}}"#);
+
+        assert_eq!(expected, bytes);
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let input = dummy();
+
+        let codec = JsonCodec::default();
+        let mut buffer = BytesMut::new();
+        codec.encode_into(&input, &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode(&input).unwrap());
+    }
+
+    #[test]
+    fn decodes_json_bytes() {
+        let mut buffer = BytesMut::from(r#"{"foo":"foo","bar":42,"inner":{"baz":[1,2,3]}}"#);
+        let decoder = JsonCodec::<Dummy>::default();
+
+        let decoded = decoder.decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, dummy());
+    }
+
+    #[test]
+    fn round_trips_nested_struct() {
+        let input = dummy();
+
+        let encoded = JsonCodec::default().encode(&input).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = JsonCodec::<Dummy>::default().decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn fails_to_decode_invalid_json() {
+        let mut buffer = BytesMut::from(r#"{"foo":"foo","bar":"#);
+        let decoder = JsonCodec::<Dummy>::default();
+
+        let err = decoder.decode(&mut buffer).unwrap_err();
+
+        assert!(err.to_string().contains("EOF"));
+    }
+}
@@ -0,0 +1,215 @@
+use super::{lines_codec::LineBuffer, MAX_LINE_LENGTH_DEFAULT};
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::Result;
+use bytes::{BufMut, Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// A codec for encoding/decoding newline-delimited JSON, also known as JSON Lines, via
+/// [serde_json].
+///
+/// Unlike the [JsonCodec](crate::codecs::JsonCodec), which treats each frame as a single JSON
+/// document, the [JsonLinesCodec] treats the payload as a continuous stream of JSON objects, as
+/// produced and consumed by many log shippers. Each encoded `Item` is serialized as a single line
+/// of JSON terminated with a `\n` character, and decoding yields every complete object contained
+/// in the frame. Blank lines are skipped.
+///
+/// Any partial line at the end of a frame is buffered, and prepended to the next frame decoded by
+/// the codec.
+#[derive(Debug)]
+pub struct JsonLinesCodec<Item> {
+    lines: LineBuffer,
+    _marker: PhantomData<Item>,
+}
+
+impl<Item> JsonLinesCodec<Item> {
+    /// Constructs a new [JsonLinesCodec] that will fail to decode any line exceeding `max_length`
+    /// bytes, rather than buffering it indefinitely.
+    pub fn with_max_length(max_length: usize) -> Self {
+        Self {
+            lines: LineBuffer::new(max_length),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Item> Default for JsonLinesCodec<Item> {
+    fn default() -> Self {
+        Self::with_max_length(MAX_LINE_LENGTH_DEFAULT)
+    }
+}
+
+/// Clones the codec configuration. Buffered partial lines are not carried over to the clone.
+impl<Item> Clone for JsonLinesCodec<Item> {
+    fn clone(&self) -> Self {
+        Self::with_max_length(self.lines.max_length())
+    }
+}
+
+/// Encodes any `Item` implementing [Serialize](serde::Serialize) as a single line of JSON,
+/// appending a `\n` line terminator.
+///
+/// # Errors
+///
+/// Returns [Err] if `item` fails to serialize.
+impl<Item: Serialize> MessageEncoder<Item> for JsonLinesCodec<Item> {
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        let mut buffer = BytesMut::new();
+        self.encode_ref(&item, &mut buffer)?;
+
+        Ok(buffer.into())
+    }
+
+    fn encode_into(&self, item: Item, dst: &mut BytesMut) -> Result<()> {
+        self.encode_ref(&item, dst)
+    }
+}
+
+/// Encodes a borrowed `Item`, as serializing never requires ownership.
+impl<Item: Serialize> BorrowedMessageEncoder<Item> for JsonLinesCodec<Item> {
+    fn encode_ref(&self, item: &Item, dst: &mut BytesMut) -> Result<()> {
+        // Compact JSON escapes any newlines within strings, so the object occupies a single line
+        serde_json::to_writer(dst.writer(), item)?;
+        dst.put_u8(b'\n');
+
+        Ok(())
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload into each complete JSON object it contains.
+///
+/// # Errors
+///
+/// Returns [Err] if a line exceeds the maximum line length, or fails to deserialize into `Item`.
+/// In either case, any buffered partial line is discarded.
+impl<Item: DeserializeOwned> MessageDecoder<Vec<Item>> for JsonLinesCodec<Item> {
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Vec<Item>> {
+        self.lines.decode(buffer, |line| {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                return Ok(None);
+            }
+
+            Ok(Some(serde_json::from_slice(line)?))
+        })
+    }
+}
+
+impl<Item> SeliumCodec for JsonLinesCodec<Item> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LogEntry {
+        level: String,
+        message: String,
+    }
+
+    fn entry(level: &str, message: &str) -> LogEntry {
+        LogEntry {
+            level: level.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn encodes_object_with_terminator() {
+        let codec = JsonLinesCodec::default();
+        let encoded = codec.encode(entry("info", "multi\nline")).unwrap();
+
+        assert_eq!(
+            encoded,
+            Bytes::from("{\"level\":\"info\",\"message\":\"multi\\nline\"}\n")
+        );
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let codec = JsonLinesCodec::default();
+        let mut buffer = BytesMut::from("{}\n");
+
+        codec
+            .encode_into(entry("info", "started"), &mut buffer)
+            .unwrap();
+        let encoded = codec.encode(entry("info", "started")).unwrap();
+
+        assert_eq!(&buffer[3..], &encoded[..]);
+    }
+
+    #[test]
+    fn decodes_concatenated_objects_in_frame() {
+        let codec = JsonLinesCodec::<LogEntry>::default();
+        let mut buffer = BytesMut::from(concat!(
+            "{\"level\":\"info\",\"message\":\"started\"}\n",
+            "\n",
+            "{\"level\":\"warn\",\"message\":\"slow\"}\r\n",
+            "{\"level\":\"error\",\"message\":\"failed\"}\n",
+        ));
+
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert_eq!(
+            decoded,
+            vec![
+                entry("info", "started"),
+                entry("warn", "slow"),
+                entry("error", "failed")
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_object_split_across_frames() {
+        let codec = JsonLinesCodec::<LogEntry>::default();
+
+        let first = codec
+            .decode(&mut BytesMut::from(
+                "{\"level\":\"info\",\"message\":\"started\"}\n{\"level\":",
+            ))
+            .unwrap();
+        let second = codec.decode(&mut BytesMut::from("\"warn\",")).unwrap();
+        let third = codec
+            .decode(&mut BytesMut::from("\"message\":\"slow\"}\n"))
+            .unwrap();
+
+        assert_eq!(first, vec![entry("info", "started")]);
+        assert!(second.is_empty());
+        assert_eq!(third, vec![entry("warn", "slow")]);
+    }
+
+    #[test]
+    fn round_trips_encoded_objects() {
+        let codec = JsonLinesCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec
+            .encode_into(entry("info", "one"), &mut buffer)
+            .unwrap();
+        codec
+            .encode_into(entry("info", "two"), &mut buffer)
+            .unwrap();
+
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, vec![entry("info", "one"), entry("info", "two")]);
+    }
+
+    #[test]
+    fn fails_to_decode_invalid_object() {
+        let codec = JsonLinesCodec::<LogEntry>::default();
+
+        let err = codec
+            .decode(&mut BytesMut::from("{\"level\":}\n{\"lev"))
+            .unwrap_err();
+        let recovered = codec
+            .decode(&mut BytesMut::from(
+                "{\"level\":\"info\",\"message\":\"ok\"}\n",
+            ))
+            .unwrap();
+
+        assert!(err.is::<serde_json::Error>());
+        assert_eq!(recovered, vec![entry("info", "ok")]);
+    }
+}
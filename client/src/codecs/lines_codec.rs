@@ -0,0 +1,212 @@
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::{bail, Result};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::sync::Mutex;
+
+/// The default maximum length (in bytes) of a single line decoded by a [LinesCodec].
+pub const MAX_LINE_LENGTH_DEFAULT: usize = 64 * 1024;
+
+/// A codec for encoding/decoding newline-delimited UTF-8 text streams.
+///
+/// Unlike the [StringCodec](crate::codecs::StringCodec), which treats each frame as a single
+/// message, the [LinesCodec] treats the payload as a continuous stream of text. Each encoded
+/// [String] is terminated with a `\n` character, and decoding yields every complete line
+/// contained in the frame, without the trailing `\n` or `\r\n`.
+///
+/// Any partial line at the end of a frame is buffered, and prepended to the next frame decoded by
+/// the codec.
+#[derive(Debug)]
+pub struct LinesCodec {
+    lines: LineBuffer,
+}
+
+impl LinesCodec {
+    /// Constructs a new [LinesCodec] that will fail to decode any line exceeding `max_length`
+    /// bytes, rather than buffering it indefinitely.
+    pub fn with_max_length(max_length: usize) -> Self {
+        Self {
+            lines: LineBuffer::new(max_length),
+        }
+    }
+}
+
+impl Default for LinesCodec {
+    fn default() -> Self {
+        Self::with_max_length(MAX_LINE_LENGTH_DEFAULT)
+    }
+}
+
+/// Clones the codec configuration. Buffered partial lines are not carried over to the clone.
+impl Clone for LinesCodec {
+    fn clone(&self) -> Self {
+        Self::with_max_length(self.lines.max_length())
+    }
+}
+
+/// Encodes a [String] into [Bytes](bytes::Bytes), appending a `\n` line terminator.
+impl MessageEncoder<String> for LinesCodec {
+    fn encode(&self, item: String) -> Result<Bytes> {
+        let mut buffer = BytesMut::with_capacity(item.len() + 1);
+        buffer.put_slice(item.as_bytes());
+        buffer.put_u8(b'\n');
+
+        Ok(buffer.into())
+    }
+
+    fn encode_into(&self, item: String, dst: &mut BytesMut) -> Result<()> {
+        self.encode_ref(item.as_str(), dst)
+    }
+}
+
+/// Encodes a borrowed [&str] slice, appending a `\n` line terminator.
+impl BorrowedMessageEncoder<str> for LinesCodec {
+    fn encode_ref(&self, item: &str, dst: &mut BytesMut) -> Result<()> {
+        dst.reserve(item.len() + 1);
+        dst.put_slice(item.as_bytes());
+        dst.put_u8(b'\n');
+
+        Ok(())
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload into each complete line it contains.
+///
+/// # Errors
+///
+/// Returns [Err] if a line exceeds the maximum line length, or is not valid UTF-8. In either
+/// case, any buffered partial line is discarded.
+impl MessageDecoder<Vec<String>> for LinesCodec {
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Vec<String>> {
+        self.lines
+            .decode(buffer, |line| Ok(Some(String::from_utf8(line.to_vec())?)))
+    }
+}
+
+/// Buffers newline-delimited data across frames, for codecs that decode a stream of lines.
+#[derive(Debug)]
+pub(super) struct LineBuffer {
+    max_length: usize,
+    pending: Mutex<BytesMut>,
+}
+
+impl LineBuffer {
+    pub(super) fn new(max_length: usize) -> Self {
+        Self {
+            max_length,
+            pending: Mutex::new(BytesMut::new()),
+        }
+    }
+
+    pub(super) fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    /// Appends `buffer` to any buffered partial line, and decodes each complete line via
+    /// `decode_line`, without the trailing `\n` or `\r\n`. Lines for which `decode_line` returns
+    /// [None] are skipped.
+    ///
+    /// If a line exceeds the maximum length, or fails to decode, any buffered partial line is
+    /// discarded.
+    pub(super) fn decode<T>(
+        &self,
+        buffer: &[u8],
+        mut decode_line: impl FnMut(&[u8]) -> Result<Option<T>>,
+    ) -> Result<Vec<T>> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.extend_from_slice(buffer);
+
+        let mut items = Vec::new();
+
+        while let Some(idx) = pending.iter().position(|&b| b == b'\n') {
+            let mut line = pending.split_to(idx + 1);
+            line.truncate(idx);
+
+            if line.last() == Some(&b'\r') {
+                line.truncate(idx - 1);
+            }
+
+            if line.len() > self.max_length {
+                pending.clear();
+                bail!("Line exceeds maximum length of {} bytes", self.max_length);
+            }
+
+            match decode_line(&line) {
+                Ok(Some(item)) => items.push(item),
+                Ok(None) => (),
+                Err(err) => {
+                    pending.clear();
+                    return Err(err);
+                }
+            }
+        }
+
+        if pending.len() > self.max_length {
+            pending.clear();
+            bail!("Line exceeds maximum length of {} bytes", self.max_length);
+        }
+
+        Ok(items)
+    }
+}
+
+impl SeliumCodec for LinesCodec {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_line_with_terminator() {
+        let codec = LinesCodec::default();
+        let encoded = codec.encode("a log line".to_owned()).unwrap();
+
+        assert_eq!(encoded, Bytes::from("a log line\n"));
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let codec = LinesCodec::default();
+        let mut buffer = BytesMut::from("first\n");
+
+        codec
+            .encode_into("a log line".to_owned(), &mut buffer)
+            .unwrap();
+        let encoded = codec.encode("a log line".to_owned()).unwrap();
+
+        assert_eq!(&buffer[6..], &encoded[..]);
+    }
+
+    #[test]
+    fn decodes_multiple_lines_in_frame() {
+        let codec = LinesCodec::default();
+        let mut buffer = BytesMut::from("first\nsecond\r\nthird\n");
+
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn decodes_line_split_across_frames() {
+        let codec = LinesCodec::default();
+
+        let first = codec.decode(&mut BytesMut::from("one\ntw")).unwrap();
+        let second = codec.decode(&mut BytesMut::from("o and")).unwrap();
+        let third = codec.decode(&mut BytesMut::from(" a bit\n")).unwrap();
+
+        assert_eq!(first, vec!["one"]);
+        assert!(second.is_empty());
+        assert_eq!(third, vec!["two and a bit"]);
+    }
+
+    #[test]
+    fn fails_to_decode_line_exceeding_max_length() {
+        let codec = LinesCodec::with_max_length(4);
+
+        let err = codec.decode(&mut BytesMut::from("toolong")).unwrap_err();
+        let recovered = codec.decode(&mut BytesMut::from("ok\n")).unwrap();
+
+        assert_eq!(err.to_string(), "Line exceeds maximum length of 4 bytes");
+        assert_eq!(recovered, vec!["ok"]);
+    }
+}
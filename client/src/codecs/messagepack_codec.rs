@@ -0,0 +1,88 @@
+use super::{SerdeCodec, SerdeFormat};
+use anyhow::Result;
+use bytes::{BufMut, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A [SerdeFormat] marker for the [MessagePack](https://msgpack.org) serialization format, via
+/// [rmp_serde].
+///
+/// Structs are encoded as maps with named fields, so payloads can be consumed by MessagePack
+/// implementations in other languages.
+#[derive(Debug, Clone, Default)]
+pub struct MessagePack;
+
+impl SerdeFormat for MessagePack {
+    fn serialize<T: Serialize>(&self, item: &T) -> Result<Vec<u8>> {
+        Ok(rmp_serde::to_vec_named(item)?)
+    }
+
+    fn serialize_into<T: Serialize>(&self, item: &T, dst: &mut BytesMut) -> Result<()> {
+        Ok(rmp_serde::encode::write_named(&mut dst.writer(), item)?)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+/// A basic codec that uses [rmp_serde] to serialize and deserialize
+/// [MessagePack](https://msgpack.org) message payloads.
+pub type MessagePackCodec<Item> = SerdeCodec<Item, MessagePack>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{MessageDecoder, MessageEncoder};
+    use bytes::{Bytes, BytesMut};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dummy {
+        foo: String,
+        bar: Option<u64>,
+    }
+
+    #[test]
+    fn encodes_to_named_messagepack_bytes() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: Some(42),
+        };
+
+        let codec = MessagePackCodec::default();
+        let bytes = codec.encode(&input).unwrap();
+        let expected = Bytes::from_static(b"\x82\xa3foo\xa3foo\xa3bar*");
+
+        assert_eq!(expected, bytes);
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: Some(42),
+        };
+
+        let codec = MessagePackCodec::default();
+        let mut buffer = BytesMut::new();
+        codec.encode_into(&input, &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode(&input).unwrap());
+    }
+
+    #[test]
+    fn decodes_named_messagepack_bytes() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: None,
+        };
+
+        let encoded = MessagePackCodec::default().encode(&input).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = MessagePackCodec::<Dummy>::default()
+            .decode(&mut buffer)
+            .unwrap();
+
+        assert_eq!(decoded, input);
+    }
+}
@@ -26,8 +26,16 @@
 //! receiving a generic input of type `Item`, and condensing it down into a [BytesMut](bytes::BytesMut)
 //! value.
 //!
-//! [MessageEncoder](crate::traits::MessageEncoder) exposes a single method to implementors,
-//! [encode](crate::traits::MessageEncoder::encode).
+//! [MessageEncoder](crate::traits::MessageEncoder) exposes a single required method to
+//! implementors, [encode](crate::traits::MessageEncoder::encode). Encoders on a hot path can also
+//! override [encode_into](crate::traits::MessageEncoder::encode_into) to write directly into the
+//! buffer that a [Publisher](crate::Publisher) encodes messages into, rather than allocating a new
+//! buffer for each message.
+//!
+//! Encoders that can encode an item by reference can also implement
+//! [BorrowedMessageEncoder](crate::traits::BorrowedMessageEncoder), allowing borrowed data to be
+//! published via [send_ref](crate::Publisher::send_ref) without first cloning it into an owned
+//! `Item`. Each of the codecs provided by Selium implements it.
 
 //!
 //! # Decoder
@@ -43,11 +51,19 @@
 //! [MessageDecoder](crate::traits::MessageDecoder) exposes a single method to implementors,
 //! [decode](crate::traits::MessageDecoder::decode).
 //!
+//! ### The AsyncMessageDecoder Trait
+//!
+//! For decoders that need to await external resources, such as a schema registry, the
+//! [AsyncMessageDecoder](crate::traits::AsyncMessageDecoder) trait exposes an asynchronous
+//! [decode](crate::traits::AsyncMessageDecoder::decode) method that takes ownership of the
+//! received [BytesMut](bytes::BytesMut) value. Either kind of decoder can be provided to a
+//! [Subscriber](crate::Subscriber).
+//!
 //! # Custom Codecs
 //!
 //! `Selium` aims to provide a suitable collection of codecs for various message payload formats,
 //! including UTF-8 [String] encoding/decoding, various [serde] binary serialization formats, such
-//! as [bincode], and many others.
+//! as [bincode], Protocol Buffers via [prost], and many others.
 //!
 //! However, when the provided codecs are either not suitable for your needs, or lack support for
 //! a specific serialization format, it is trivial to create a custom codec via the
@@ -179,11 +195,81 @@
 //! }
 //! ```
 
+#[cfg(all(feature = "cbor", feature = "json"))]
+mod auto_detect_codec;
+#[cfg(feature = "avro")]
+mod avro_codec;
+mod batch_codec;
 #[cfg(feature = "bincode")]
 mod bincode_codec;
+#[cfg(feature = "cbor")]
+mod cbor_codec;
+mod codec_registry;
+#[cfg(feature = "compression")]
+mod compression_codec;
+#[cfg(feature = "encryption")]
+mod encryption_codec;
+#[cfg(feature = "json")]
+mod json_codec;
+#[cfg(feature = "json")]
+mod json_lines_codec;
+mod lines_codec;
+#[cfg(feature = "messagepack")]
+mod messagepack_codec;
+#[cfg(feature = "prost")]
+mod prost_codec;
+mod raw_bytes_codec;
+#[cfg(any(
+    feature = "bincode",
+    feature = "cbor",
+    feature = "json",
+    feature = "messagepack"
+))]
+mod serde_codec;
 mod string_codec;
+mod versioned_codec;
+
+#[cfg(all(feature = "cbor", feature = "json"))]
+pub use auto_detect_codec::*;
+
+#[cfg(feature = "avro")]
+pub use avro_codec::*;
 
 #[cfg(feature = "bincode")]
 pub use bincode_codec::*;
 
+#[cfg(feature = "cbor")]
+pub use cbor_codec::*;
+
+#[cfg(feature = "compression")]
+pub use compression_codec::*;
+
+#[cfg(feature = "encryption")]
+pub use encryption_codec::*;
+
+#[cfg(feature = "json")]
+pub use json_codec::*;
+
+#[cfg(feature = "json")]
+pub use json_lines_codec::*;
+
+#[cfg(feature = "messagepack")]
+pub use messagepack_codec::*;
+
+#[cfg(feature = "prost")]
+pub use prost_codec::*;
+
+#[cfg(any(
+    feature = "bincode",
+    feature = "cbor",
+    feature = "json",
+    feature = "messagepack"
+))]
+pub use serde_codec::*;
+
+pub use batch_codec::*;
+pub use codec_registry::*;
+pub use lines_codec::*;
+pub use raw_bytes_codec::*;
 pub use string_codec::*;
+pub use versioned_codec::*;
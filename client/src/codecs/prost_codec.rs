@@ -0,0 +1,127 @@
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::Result;
+use bytes::{Bytes, BytesMut};
+use prost::Message;
+use std::marker::PhantomData;
+
+/// A codec that uses [prost] to encode and decode message payloads as Protocol Buffers, so that
+/// existing protobuf schemas can be reused over `Selium`.
+///
+/// `Item` can be any type implementing [prost::Message], such as those generated from a `.proto`
+/// file by `prost-build`.
+#[derive(Debug)]
+pub struct ProstCodec<Item> {
+    _marker: PhantomData<Item>,
+}
+
+impl<Item> Clone for ProstCodec<Item> {
+    fn clone(&self) -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Item> Default for ProstCodec<Item> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Encodes any `Item` implementing [prost::Message] into its protobuf representation.
+impl<Item: Message> MessageEncoder<Item> for ProstCodec<Item> {
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        Ok(item.encode_to_vec().into())
+    }
+
+    fn encode_into(&self, item: Item, dst: &mut BytesMut) -> Result<()> {
+        self.encode_ref(&item, dst)
+    }
+}
+
+/// Encodes a borrowed `Item` implementing [prost::Message].
+impl<Item: Message> BorrowedMessageEncoder<Item> for ProstCodec<Item> {
+    fn encode_ref(&self, item: &Item, dst: &mut BytesMut) -> Result<()> {
+        // Reserving the encoded length up front means encoding can't run out of capacity
+        dst.reserve(item.encoded_len());
+        Ok(item.encode(dst)?)
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload into any `Item` implementing [prost::Message].
+///
+/// # Errors
+///
+/// Returns [Err] if the [BytesMut](bytes::BytesMut) payload isn't a valid protobuf encoding of
+/// `Item`.
+impl<Item: Message + Default> MessageDecoder<Item> for ProstCodec<Item> {
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
+        Ok(Item::decode(buffer)?)
+    }
+}
+
+impl<Item> SeliumCodec for ProstCodec<Item> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Message)]
+    struct Dummy {
+        #[prost(string, tag = "1")]
+        foo: String,
+        #[prost(uint64, tag = "2")]
+        bar: u64,
+    }
+
+    #[test]
+    fn encodes_to_protobuf_bytes() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let bytes = ProstCodec::default().encode(input).unwrap();
+        let expected = Bytes::from("\x0a\x03foo\x10*");
+
+        assert_eq!(expected, bytes);
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let codec = ProstCodec::default();
+        let mut buffer = BytesMut::new();
+        codec.encode_into(input.clone(), &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode(input).unwrap());
+    }
+
+    #[test]
+    fn round_trips_protobuf_bytes() {
+        let input = Dummy {
+            foo: "foo".to_owned(),
+            bar: 42,
+        };
+
+        let encoded = ProstCodec::default().encode(input.clone()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = ProstCodec::<Dummy>::default().decode(&mut buffer).unwrap();
+
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn fails_to_decode_invalid_protobuf_bytes() {
+        let mut buffer = BytesMut::from("\x0a\x09foo");
+        let result = ProstCodec::<Dummy>::default().decode(&mut buffer);
+
+        assert!(result.is_err());
+    }
+}
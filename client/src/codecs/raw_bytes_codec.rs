@@ -0,0 +1,51 @@
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::Result;
+use bytes::{Bytes, BytesMut};
+
+/// An identity codec that passes raw [Bytes](bytes::Bytes) message payloads through unchanged.
+///
+/// Useful when payloads have already been serialized upstream.
+#[derive(Default, Clone)]
+pub struct RawBytesCodec;
+
+/// Returns the input [Bytes](bytes::Bytes) unchanged.
+impl MessageEncoder<Bytes> for RawBytesCodec {
+    fn encode(&self, item: Bytes) -> Result<Bytes> {
+        Ok(item)
+    }
+}
+
+/// Copies a borrowed byte slice.
+impl BorrowedMessageEncoder<[u8]> for RawBytesCodec {
+    fn encode_ref(&self, item: &[u8], dst: &mut BytesMut) -> Result<()> {
+        dst.extend_from_slice(item);
+        Ok(())
+    }
+}
+
+/// Copies a [BytesMut](bytes::BytesMut) payload into [Bytes](bytes::Bytes).
+impl MessageDecoder<Bytes> for RawBytesCodec {
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Bytes> {
+        Ok(Bytes::copy_from_slice(buffer))
+    }
+}
+
+impl SeliumCodec for RawBytesCodec {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_arbitrary_bytes() {
+        let input = Bytes::from_static(&[0x00, 0xff, 0x7f, 0x80, b'\n', 0xfe]);
+
+        let codec = RawBytesCodec;
+        let encoded = codec.encode(input.clone()).unwrap();
+        let mut buffer = BytesMut::from(&encoded[..]);
+        let decoded = codec.decode(&mut buffer).unwrap();
+
+        assert_eq!(encoded, input);
+        assert_eq!(decoded, input);
+    }
+}
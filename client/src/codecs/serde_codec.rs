@@ -0,0 +1,97 @@
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::Result;
+use bytes::{Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// Describes a [serde] serialization format that can be used with a [SerdeCodec].
+///
+/// `Selium` provides format markers for each of its supported serialization formats, such as
+/// [Bincode](crate::codecs::Bincode) and [Json](crate::codecs::Json), each gated behind its
+/// respective feature flag. A format may carry its own configuration, which the [SerdeCodec]
+/// applies to every message it serializes and deserializes.
+pub trait SerdeFormat {
+    /// Serializes `item` into a sequence of bytes.
+    fn serialize<T: Serialize>(&self, item: &T) -> Result<Vec<u8>>;
+
+    /// Serializes `item` directly into `dst`. The default implementation calls
+    /// [serialize](SerdeFormat::serialize), and copies the result into `dst`.
+    fn serialize_into<T: Serialize>(&self, item: &T, dst: &mut BytesMut) -> Result<()> {
+        dst.extend_from_slice(&self.serialize(item)?);
+        Ok(())
+    }
+
+    /// Deserializes a sequence of bytes into the target type `T`.
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T>;
+}
+
+/// A generic codec that serializes and deserializes message payloads using any [serde]
+/// serialization format implementing [SerdeFormat].
+///
+/// Each of the `serde` based codecs provided by `Selium`, such as
+/// [BincodeCodec](crate::codecs::BincodeCodec), are type aliases over a [SerdeCodec], so
+/// swapping formats is as simple as changing the `F` type parameter.
+#[derive(Debug)]
+pub struct SerdeCodec<Item, F> {
+    format: F,
+    _marker: PhantomData<Item>,
+}
+
+impl<Item, F> SerdeCodec<Item, F> {
+    /// Constructs a codec that serializes and deserializes message payloads with the configured
+    /// `format`.
+    pub fn with_format(format: F) -> Self {
+        Self {
+            format,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Item, F: Clone> Clone for SerdeCodec<Item, F> {
+    fn clone(&self) -> Self {
+        Self::with_format(self.format.clone())
+    }
+}
+
+impl<Item, F: Default> Default for SerdeCodec<Item, F> {
+    fn default() -> Self {
+        Self::with_format(F::default())
+    }
+}
+
+/// Encodes any `Item` implementing [Serialize](serde::Serialize) using the format `F`.
+///
+/// # Errors
+///
+/// Returns [Err] if `item` fails to serialize.
+impl<Item: Serialize, F: SerdeFormat> MessageEncoder<Item> for SerdeCodec<Item, F> {
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        Ok(self.format.serialize(&item)?.into())
+    }
+
+    fn encode_into(&self, item: Item, dst: &mut BytesMut) -> Result<()> {
+        self.format.serialize_into(&item, dst)
+    }
+}
+
+/// Encodes a borrowed `Item` using the format `F`, as serializing never requires ownership.
+impl<Item: Serialize, F: SerdeFormat> BorrowedMessageEncoder<Item> for SerdeCodec<Item, F> {
+    fn encode_ref(&self, item: &Item, dst: &mut BytesMut) -> Result<()> {
+        self.format.serialize_into(item, dst)
+    }
+}
+
+/// Decodes a [BytesMut](bytes::BytesMut) payload into any `Item` implementing
+/// [DeserializeOwned](serde::de::DeserializeOwned) using the format `F`.
+///
+/// # Errors
+///
+/// Returns [Err] if the [BytesMut](bytes::BytesMut) payload fails to deserialize into `Item`.
+impl<Item: DeserializeOwned, F: SerdeFormat> MessageDecoder<Item> for SerdeCodec<Item, F> {
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
+        self.format.deserialize(buffer)
+    }
+}
+
+impl<Item, F> SeliumCodec for SerdeCodec<Item, F> {}
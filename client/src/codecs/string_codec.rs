@@ -1,5 +1,5 @@
-use crate::traits::{MessageDecoder, MessageEncoder, SeliumCodec};
-use anyhow::Result;
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::{anyhow, Result};
 use bytes::{Bytes, BytesMut};
 
 /// A basic codec for encoding/decoding UTF-8 [String] message payloads.
@@ -13,15 +13,31 @@ impl MessageEncoder<String> for StringCodec {
     }
 }
 
+/// Encodes a borrowed [&str] slice.
+impl BorrowedMessageEncoder<str> for StringCodec {
+    fn encode_ref(&self, item: &str, dst: &mut BytesMut) -> Result<()> {
+        dst.extend_from_slice(item.as_bytes());
+        Ok(())
+    }
+}
+
 /// Decodes a [BytesMut](bytes::BytesMut) payload into an owned [String]
 ///
 /// # Errors
 ///
 /// Returns [Err] if a valid UTF-8 [String] cannot be constructed from the
-/// [BytesMut](bytes::BytesMut) slice.
+/// [BytesMut](bytes::BytesMut) slice. The error describes the byte offset of the first invalid
+/// UTF-8 sequence.
 impl MessageDecoder<String> for StringCodec {
     fn decode(&self, buffer: &mut BytesMut) -> Result<String> {
-        Ok(String::from_utf8(buffer[..].into())?)
+        let decoded = std::str::from_utf8(&buffer[..]).map_err(|err| {
+            anyhow!(
+                "Invalid UTF-8 sequence at byte offset {}: {err}",
+                err.valid_up_to()
+            )
+        })?;
+
+        Ok(decoded.to_owned())
     }
 }
 
@@ -42,6 +58,18 @@ mod tests {
         assert_eq!(expected, encoded);
     }
 
+    #[test]
+    fn encodes_borrowed_str_like_owned_string() {
+        let input = "decoded string";
+        let codec = StringCodec;
+
+        let encoded = codec.encode(input.to_owned()).unwrap();
+        let mut buffer = BytesMut::new();
+        codec.encode_ref(input, &mut buffer).unwrap();
+
+        assert_eq!(encoded, buffer);
+    }
+
     #[test]
     fn decodes_string_bytes() {
         let expected = "encoded string";
@@ -52,4 +80,17 @@ mod tests {
 
         assert_eq!(decoded, expected);
     }
+
+    #[test]
+    fn fails_to_decode_invalid_utf8() {
+        let mut buffer = BytesMut::from(&[0xff, 0xfe][..]);
+
+        let decoder = StringCodec;
+        let err = decoder.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Invalid UTF-8 sequence at byte offset 0: invalid utf-8 sequence of 1 bytes from index 0"
+        );
+    }
 }
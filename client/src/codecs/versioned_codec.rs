@@ -0,0 +1,174 @@
+use crate::traits::{BorrowedMessageEncoder, MessageDecoder, MessageEncoder, SeliumCodec};
+use anyhow::{bail, Result};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::collections::BTreeSet;
+
+/// A wrapper codec that prefixes the output of an inner codec with a schema version byte, so that
+/// message formats can evolve safely.
+///
+/// Messages are encoded by the inner codec, and the resulting bytes are written after the current
+/// schema version. Decoding reads the version first, rejecting any message whose version isn't
+/// accepted, before handing the remaining bytes to the inner codec.
+///
+/// A subscriber upgrading to a new format can accept both the old and new versions while its
+/// publishers are upgraded, and stop accepting the old version once they have been.
+///
+/// ```
+/// use selium::codecs::{StringCodec, VersionedCodec};
+///
+/// // Publishes version 2, and still accepts version 1
+/// let codec = VersionedCodec::new(StringCodec, 2, [1, 2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VersionedCodec<C> {
+    inner: C,
+    version: u8,
+    accepted: BTreeSet<u8>,
+}
+
+impl<C> VersionedCodec<C> {
+    /// Constructs a new [VersionedCodec], wrapping the `inner` codec. Messages are encoded with
+    /// the current `version`, and decoded if their version is within the `accepted` versions. The
+    /// current version is always accepted.
+    pub fn new(inner: C, version: u8, accepted: impl IntoIterator<Item = u8>) -> Self {
+        let mut accepted: BTreeSet<u8> = accepted.into_iter().collect();
+        accepted.insert(version);
+
+        Self {
+            inner,
+            version,
+            accepted,
+        }
+    }
+}
+
+/// Encodes `Item` with the inner codec, prefixing the resulting bytes with the current version.
+///
+/// # Errors
+///
+/// Returns [Err] if the inner codec fails to encode `item`.
+impl<C, Item> MessageEncoder<Item> for VersionedCodec<C>
+where
+    C: MessageEncoder<Item>,
+{
+    fn encode(&self, item: Item) -> Result<Bytes> {
+        let encoded = self.inner.encode(item)?;
+        let mut buffer = BytesMut::with_capacity(encoded.len() + 1);
+
+        buffer.put_u8(self.version);
+        buffer.extend_from_slice(&encoded);
+
+        Ok(buffer.into())
+    }
+
+    fn encode_into(&self, item: Item, dst: &mut BytesMut) -> Result<()> {
+        dst.put_u8(self.version);
+        self.inner.encode_into(item, dst)
+    }
+}
+
+/// Prefixes a borrowed item with the current schema version, encoding it with the inner codec.
+impl<C, Item> BorrowedMessageEncoder<Item> for VersionedCodec<C>
+where
+    C: BorrowedMessageEncoder<Item>,
+    Item: ?Sized,
+{
+    fn encode_ref(&self, item: &Item, dst: &mut BytesMut) -> Result<()> {
+        dst.put_u8(self.version);
+        self.inner.encode_ref(item, dst)
+    }
+}
+
+/// Validates the version of a [BytesMut](bytes::BytesMut) payload, and decodes the rest of the
+/// payload with the inner codec.
+///
+/// # Errors
+///
+/// Returns [Err] if the payload is empty, if its version isn't accepted, or if the inner codec
+/// fails to decode the remaining bytes.
+impl<C, Item> MessageDecoder<Item> for VersionedCodec<C>
+where
+    C: MessageDecoder<Item>,
+{
+    fn decode(&self, buffer: &mut BytesMut) -> Result<Item> {
+        if buffer.is_empty() {
+            bail!("Message payload is missing a schema version");
+        }
+
+        let version = buffer.get_u8();
+
+        if !self.accepted.contains(&version) {
+            bail!(
+                "Unsupported schema version {version}, expected one of {:?}",
+                self.accepted
+            );
+        }
+
+        self.inner.decode(buffer)
+    }
+}
+
+impl<C: SeliumCodec> SeliumCodec for VersionedCodec<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::StringCodec;
+
+    #[test]
+    fn encodes_with_version_prefix() {
+        let codec = VersionedCodec::new(StringCodec, 2, []);
+
+        let encoded = codec.encode("Hello".to_owned()).unwrap();
+
+        assert_eq!(encoded, Bytes::from("\x02Hello"));
+    }
+
+    #[test]
+    fn encodes_into_buffer_like_encode() {
+        let codec = VersionedCodec::new(StringCodec, 2, []);
+        let mut buffer = BytesMut::new();
+
+        codec.encode_into("Hello".to_owned(), &mut buffer).unwrap();
+
+        assert_eq!(buffer, codec.encode("Hello".to_owned()).unwrap());
+    }
+
+    #[test]
+    fn decodes_accepted_versions() {
+        let codec = VersionedCodec::new(StringCodec, 2, [1]);
+
+        for payload in ["\x01Hello", "\x02Hello"] {
+            let mut buffer = BytesMut::from(payload);
+            let decoded = codec.decode(&mut buffer).unwrap();
+
+            assert_eq!(decoded, "Hello");
+        }
+    }
+
+    #[test]
+    fn fails_to_decode_unsupported_version() {
+        let codec = VersionedCodec::new(StringCodec, 2, [1]);
+        let mut buffer = BytesMut::from("\x03Hello");
+
+        let err = codec.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Unsupported schema version 3, expected one of {1, 2}"
+        );
+    }
+
+    #[test]
+    fn fails_to_decode_empty_payload() {
+        let codec = VersionedCodec::new(StringCodec, 1, []);
+        let mut buffer = BytesMut::new();
+
+        let err = codec.decode(&mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Message payload is missing a schema version"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Compresses and decompresses message payloads with the algorithm negotiated by a
+//! [Publisher](crate::Publisher).
+//!
+//! Compression requires the `compression` feature. Without it, publishers always send
+//! uncompressed messages, and subscribers fail to decode any compressed messages they receive.
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use selium_common::protocol::{Compression, MessagePayload};
+use std::ops::RangeInclusive;
+
+/// The levels accepted for [Compression::Zstd], from fastest to smallest.
+const ZSTD_LEVELS: RangeInclusive<i32> = 1..=22;
+
+#[cfg(feature = "compression")]
+mod algorithms {
+    use super::*;
+    use std::io::Read;
+
+    pub(crate) fn compress(
+        compression: Compression,
+        level: Option<i32>,
+        message: &[u8],
+    ) -> Result<Bytes> {
+        let compressed = match compression {
+            Compression::None => message.to_vec(),
+            Compression::Zstd => {
+                zstd::encode_all(message, level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL))?
+            }
+            Compression::Lz4 => lz4_flex::compress_prepend_size(message),
+        };
+
+        Ok(compressed.into())
+    }
+
+    pub(crate) fn decompress(
+        compression: Compression,
+        message: &[u8],
+        max_size: u64,
+    ) -> Result<Bytes> {
+        let decompressed = match compression {
+            Compression::None => message.to_vec(),
+            Compression::Zstd => {
+                // Reads one byte past the limit, so that an oversized message is detected without
+                // decompressing the rest of it
+                let mut decompressed = Vec::new();
+                zstd::Decoder::new(message)?
+                    .take(max_size.saturating_add(1))
+                    .read_to_end(&mut decompressed)?;
+                check_size(decompressed.len(), max_size)?;
+                decompressed
+            }
+            Compression::Lz4 => {
+                // The size is checked before the buffer is allocated from it
+                let (size, _) = lz4_flex::block::uncompressed_size(message)?;
+                check_size(size, max_size)?;
+                lz4_flex::decompress_size_prepended(message)?
+            }
+        };
+
+        Ok(decompressed.into())
+    }
+
+    fn check_size(size: usize, max_size: u64) -> Result<()> {
+        if size as u64 > max_size {
+            bail!("The decompressed message exceeds the max_message_size of {max_size} bytes");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+mod algorithms {
+    use super::*;
+
+    pub(crate) fn compress(
+        compression: Compression,
+        _level: Option<i32>,
+        message: &[u8],
+    ) -> Result<Bytes> {
+        match compression {
+            Compression::None => Ok(Bytes::copy_from_slice(message)),
+            _ => bail!("Compressing messages requires the `compression` feature"),
+        }
+    }
+
+    pub(crate) fn decompress(
+        compression: Compression,
+        message: &[u8],
+        _max_size: u64,
+    ) -> Result<Bytes> {
+        match compression {
+            Compression::None => Ok(Bytes::copy_from_slice(message)),
+            _ => bail!("Decompressing {compression} messages requires the `compression` feature"),
+        }
+    }
+}
+
+use algorithms::*;
+
+/// Checks that the compression `level`, if any, is supported by the algorithm.
+pub(crate) fn validate_level(compression: Compression, level: Option<i32>) -> Result<()> {
+    let Some(level) = level else {
+        return Ok(());
+    };
+
+    match compression {
+        Compression::None => bail!("A compression level of {level} was set without an algorithm"),
+        Compression::Zstd if !ZSTD_LEVELS.contains(&level) => bail!(
+            "Compression level {level} is out of range for zstd, which accepts levels {} to {}",
+            ZSTD_LEVELS.start(),
+            ZSTD_LEVELS.end()
+        ),
+        Compression::Zstd => Ok(()),
+        Compression::Lz4 => bail!("LZ4 compression doesn't support levels, but {level} was set"),
+    }
+}
+
+/// Compresses the message at the provided `level`, or the algorithm's default level, tagging it
+/// with the algorithm it was compressed with.
+pub(crate) fn compress_payload(
+    compression: Compression,
+    level: Option<i32>,
+    payload: MessagePayload,
+) -> Result<MessagePayload> {
+    if compression.is_none() {
+        return Ok(payload);
+    }
+
+    Ok(MessagePayload {
+        message: compress(compression, level, &payload.message)
+            .map_err(|err| err.context("Failed to compress message payload"))?,
+        compression,
+        ..payload
+    })
+}
+
+/// Decompresses the message, if it was compressed by the publisher, failing if it decompresses to
+/// more than `max_size` bytes.
+pub(crate) fn decompress_payload(payload: MessagePayload, max_size: u64) -> Result<MessagePayload> {
+    if payload.compression.is_none() {
+        return Ok(payload);
+    }
+
+    Ok(MessagePayload {
+        message: decompress(payload.compression, &payload.message, max_size)
+            .map_err(|err| err.context("Failed to decompress message payload"))?,
+        compression: Compression::None,
+        ..payload
+    })
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use super::*;
+    use selium_common::protocol::DEFAULT_MAX_MESSAGE_SIZE;
+
+    #[test]
+    fn round_trips_compressed_payloads() {
+        let message = Bytes::from("compressed message ".repeat(100));
+
+        for compression in [Compression::None, Compression::Zstd, Compression::Lz4] {
+            let payload = MessagePayload::new(message.clone());
+
+            let compressed = compress_payload(compression, None, payload).unwrap();
+            assert_eq!(compressed.compression, compression);
+
+            let decompressed = decompress_payload(compressed, DEFAULT_MAX_MESSAGE_SIZE).unwrap();
+            assert_eq!(decompressed, MessagePayload::new(message.clone()));
+        }
+    }
+
+    #[test]
+    fn round_trips_payloads_compressed_at_level() {
+        let message = Bytes::from("compressed message ".repeat(100));
+        let payload = MessagePayload::new(message.clone());
+
+        let compressed = compress_payload(Compression::Zstd, Some(19), payload).unwrap();
+        let decompressed = decompress_payload(compressed, DEFAULT_MAX_MESSAGE_SIZE).unwrap();
+
+        assert_eq!(decompressed, MessagePayload::new(message));
+    }
+
+    #[test]
+    fn validates_compression_levels() {
+        assert!(validate_level(Compression::None, None).is_ok());
+        assert!(validate_level(Compression::Zstd, Some(1)).is_ok());
+        assert!(validate_level(Compression::Zstd, Some(22)).is_ok());
+        assert!(validate_level(Compression::Zstd, Some(0)).is_err());
+        assert!(validate_level(Compression::Zstd, Some(23)).is_err());
+        assert!(validate_level(Compression::Lz4, Some(1)).is_err());
+        assert!(validate_level(Compression::None, Some(3)).is_err());
+    }
+
+    #[test]
+    fn fails_to_decompress_corrupt_payload() {
+        let payload = MessagePayload {
+            compression: Compression::Zstd,
+            ..MessagePayload::new(Bytes::from("not zstd"))
+        };
+
+        assert!(decompress_payload(payload, DEFAULT_MAX_MESSAGE_SIZE).is_err());
+    }
+
+    #[test]
+    fn fails_to_decompress_payload_larger_than_max_size() {
+        let message = Bytes::from(vec![0; 64 * 1024]);
+
+        for compression in [Compression::Zstd, Compression::Lz4] {
+            let payload = MessagePayload::new(message.clone());
+            let compressed = compress_payload(compression, None, payload).unwrap();
+            assert!(compressed.message.len() < 1024);
+
+            assert!(decompress_payload(compressed.clone(), 64 * 1024).is_ok());
+            assert!(decompress_payload(compressed, 64 * 1024 - 1).is_err());
+        }
+    }
+
+    #[test]
+    fn checks_lz4_size_prefix_before_allocating() {
+        // Claims to decompress to 4GiB, which would be allocated up front if it wasn't checked
+        let payload = MessagePayload {
+            compression: Compression::Lz4,
+            ..MessagePayload::new(Bytes::from_static(&[0xff, 0xff, 0xff, 0xff, 0x00]))
+        };
+
+        let err = decompress_payload(payload, DEFAULT_MAX_MESSAGE_SIZE).unwrap_err();
+        assert!(format!("{err:#}").contains("max_message_size"));
+    }
+}
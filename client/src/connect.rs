@@ -0,0 +1,103 @@
+use crate::{Client, ReconnectPolicy, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The options most commonly used to connect a [Client], for use with [connect_with].
+///
+/// Any option that isn't covered here can be configured via the full
+/// [ClientBuilder](crate::ClientBuilder), constructed with the [client](crate::client) function.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use selium::{ConnectConfig, ReconnectPolicy};
+/// # use std::time::Duration;
+/// # async fn run() -> anyhow::Result<()> {
+/// let client = selium::connect_with(ConnectConfig {
+///     client_cert: Some(("certs/client.crt".into(), "certs/client.key".into())),
+///     connect_timeout: Some(Duration::from_secs(5)),
+///     reconnect_policy: Some(ReconnectPolicy::default()),
+///     ..ConnectConfig::new("127.0.0.1:7001", "certs/ca.crt")
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectConfig {
+    /// The address of the `Selium` server, as accepted by [connect](crate::ClientBuilder::connect).
+    pub addr: String,
+    /// The path to the certificate authority that the server's certificate is verified against.
+    pub ca_path: PathBuf,
+    /// The paths to a client certificate and its private key, for servers that require clients
+    /// to authenticate themselves.
+    pub client_cert: Option<(PathBuf, PathBuf)>,
+    /// How long to wait for the connection to be established before giving up.
+    pub connect_timeout: Option<Duration>,
+    /// How to re-establish the connection if it's lost.
+    pub reconnect_policy: Option<ReconnectPolicy>,
+}
+
+impl ConnectConfig {
+    /// Creates a config that connects to `addr`, verifying the server's certificate against the
+    /// certificate authority at `ca_path`, with every other option left as its default.
+    pub fn new<T: Into<PathBuf>>(addr: &str, ca_path: T) -> Self {
+        Self {
+            addr: addr.to_owned(),
+            ca_path: ca_path.into(),
+            client_cert: None,
+            connect_timeout: None,
+            reconnect_policy: None,
+        }
+    }
+}
+
+/// Connects to the `Selium` server at `addr`, verifying its certificate against the certificate
+/// authority at `ca_path`, with every other option left as its default.
+///
+/// This is shorthand for the most common [ClientBuilder](crate::ClientBuilder) chain, for quick
+/// scripts, examples and tests.
+///
+/// # Errors
+///
+/// Returns [Err] if the certificate authority cannot be read, or for any of the reasons that
+/// [connect](crate::ClientBuilder::connect) fails.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run() -> anyhow::Result<()> {
+/// let client = selium::connect("127.0.0.1:7001", "certs/ca.crt").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn connect<T: Into<PathBuf>>(addr: &str, ca_path: T) -> Result<Client> {
+    connect_with(ConnectConfig::new(addr, ca_path)).await
+}
+
+/// Connects to the `Selium` server with the options in `config`.
+///
+/// # Errors
+///
+/// Returns [Err] if the certificate authority or client certificate cannot be read, or for any
+/// of the reasons that [connect](crate::ClientBuilder::connect) fails.
+pub async fn connect_with(config: ConnectConfig) -> Result<Client> {
+    let mut builder = crate::client();
+
+    if let Some((cert, key)) = config.client_cert {
+        builder = builder.with_client_certificate(cert, key)?;
+    }
+
+    if let Some(timeout) = config.connect_timeout {
+        builder = builder.connect_timeout(timeout)?;
+    }
+
+    if let Some(policy) = config.reconnect_policy {
+        builder = builder.reconnect_policy(policy);
+    }
+
+    builder
+        .with_certificate_authority(config.ca_path)?
+        .connect(&config.addr)
+        .await
+}
@@ -0,0 +1,398 @@
+use crate::events::ConnectionEvents;
+use crate::metrics;
+use crate::streams::publisher_stream::{SharedPublisherStream, WeakPublisherStream};
+use crate::tasks;
+use crate::utils::client::connect_to_endpoint;
+use crate::utils::net::{resolve, ServerAddr};
+use crate::{CertInfo, ConnectionEvent, ConnectionStats, Error, ReconnectPolicy};
+use anyhow::{Context, Result};
+use futures::stream::BoxStream;
+use quinn::{ClientConfig, Connection, ConnectionError, Endpoint, VarInt};
+use selium_common::types::BiStream;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as SyncMutex, Weak};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+use tokio::time::Instant;
+
+/// A handle to the connection shared by a [Client](crate::Client) and all of the streams opened
+/// from it.
+///
+/// If the [Client](crate::Client) was configured with a [ReconnectPolicy], a lost connection is
+/// transparently re-established the next time the connection is retrieved, so that new streams
+/// are always opened on a live connection.
+///
+/// The connection may be established with any of several server endpoints, which are tried in
+/// order until one succeeds. Each endpoint is resolved once, when the connection is first
+/// established, and every address it resolves to is tried in turn. Reconnection attempts start
+/// with the address that was last connected to.
+///
+/// A stream opened with `connection_per_stream` is given a dedicated connection instead, which is
+/// established with the same configuration, and tracked by the shared connection so that it's
+/// closed along with it.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedConnection {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    addrs: Vec<ServerAddr>,
+    // Index into `addrs` of the endpoint that the current connection was established with
+    active: AtomicUsize,
+    config: ClientConfig,
+    connect_timeout: Option<Duration>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    enable_0rtt: bool,
+    current: Mutex<(Endpoint, Connection)>,
+    // Publishers opened on this connection, which are finished on graceful shutdown
+    publishers: SyncMutex<Vec<WeakPublisherStream>>,
+    events: ConnectionEvents,
+    // Task emitting a disconnected event once the current connection closes
+    watcher: SyncMutex<Option<AbortHandle>>,
+    // Whether a disconnected event has been emitted for the current connection
+    disconnected: Arc<AtomicBool>,
+    // Connections dedicated to individual streams, which are closed along with this connection
+    dedicated: SyncMutex<Vec<Weak<Inner>>>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // The watcher holds a handle to the connection, which would otherwise keep it open
+        if let Some(watcher) = self.watcher.get_mut().unwrap().take() {
+            watcher.abort();
+        }
+    }
+}
+
+impl SharedConnection {
+    pub async fn establish(
+        hosts: &[String],
+        server_name: &str,
+        config: ClientConfig,
+        connect_timeout: Option<Duration>,
+        reconnect_policy: Option<ReconnectPolicy>,
+        enable_0rtt: bool,
+    ) -> Result<Self> {
+        let mut addrs = Vec::new();
+
+        for host in hosts {
+            addrs.extend(resolve(host, server_name).await?);
+        }
+
+        let (active, current) =
+            connect_to_any(&config, &addrs, 0, connect_timeout, enable_0rtt).await?;
+
+        Ok(Self::new(
+            addrs,
+            active,
+            current,
+            config,
+            connect_timeout,
+            reconnect_policy,
+            enable_0rtt,
+        )
+        .await)
+    }
+
+    /// Establishes a new connection with the same configuration, starting with the endpoint that
+    /// the current connection was established with, for a single stream that shouldn't share
+    /// this connection's flow control.
+    ///
+    /// The dedicated connection reconnects independently, and emits its own events, but is
+    /// closed, or gracefully shut down, along with this connection.
+    pub async fn dedicated(&self) -> Result<Self> {
+        let inner = &self.inner;
+        let start = inner.active.load(Ordering::Relaxed);
+
+        let (active, current) = connect_to_any(
+            &inner.config,
+            &inner.addrs,
+            start,
+            inner.connect_timeout,
+            inner.enable_0rtt,
+        )
+        .await?;
+
+        let shared = Self::new(
+            inner.addrs.clone(),
+            active,
+            current,
+            inner.config.clone(),
+            inner.connect_timeout,
+            inner.reconnect_policy.clone(),
+            inner.enable_0rtt,
+        )
+        .await;
+
+        let mut connections = inner.dedicated.lock().unwrap();
+        connections.retain(|connection| connection.strong_count() > 0);
+        connections.push(Arc::downgrade(&shared.inner));
+
+        Ok(shared)
+    }
+
+    async fn new(
+        addrs: Vec<ServerAddr>,
+        active: usize,
+        current: (Endpoint, Connection),
+        config: ClientConfig,
+        connect_timeout: Option<Duration>,
+        reconnect_policy: Option<ReconnectPolicy>,
+        enable_0rtt: bool,
+    ) -> Self {
+        let inner = Inner {
+            addrs,
+            active: AtomicUsize::new(active),
+            config,
+            connect_timeout,
+            reconnect_policy,
+            enable_0rtt,
+            current: Mutex::new(current),
+            publishers: SyncMutex::new(Vec::new()),
+            events: ConnectionEvents::new(),
+            watcher: SyncMutex::new(None),
+            disconnected: Arc::new(AtomicBool::new(false)),
+            dedicated: SyncMutex::new(Vec::new()),
+        };
+
+        let shared = Self {
+            inner: Arc::new(inner),
+        };
+
+        shared.watch(&shared.inner.current.lock().await.1);
+
+        shared
+    }
+
+    /// Returns the address of the endpoint that the current connection was established with.
+    pub fn endpoint(&self) -> SocketAddr {
+        self.inner.addrs[self.inner.active.load(Ordering::Relaxed)].addr
+    }
+
+    pub fn reconnect_policy(&self) -> Option<&ReconnectPolicy> {
+        self.inner.reconnect_policy.as_ref()
+    }
+
+    /// Returns the current connection, first re-establishing it if it has been lost and a
+    /// [ReconnectPolicy] is configured.
+    pub async fn get(&self) -> Result<Connection> {
+        let mut current = self.inner.current.lock().await;
+
+        if let (Some(reason), Some(policy)) = (current.1.close_reason(), self.reconnect_policy()) {
+            // Emitted here too, so that it always precedes the reconnection events
+            disconnected(&self.inner.events, &self.inner.disconnected, &reason);
+            *current = self.reconnect(policy).await?;
+        }
+
+        Ok(current.1.clone())
+    }
+
+    pub async fn open_stream(&self) -> Result<BiStream> {
+        let connection = self.get().await?;
+        BiStream::try_from_connection(&connection).await
+    }
+
+    /// Returns a stream of the [ConnectionEvent]s emitted from now on.
+    pub fn events(&self) -> BoxStream<'static, ConnectionEvent> {
+        self.inner.events.subscribe()
+    }
+
+    /// Emits a [ConnectionEvent::Disconnected] event once the `connection` closes.
+    fn watch(&self, connection: &Connection) {
+        let connection = connection.clone();
+        let events = self.inner.events.clone();
+        let flag = self.inner.disconnected.clone();
+
+        flag.store(false, Ordering::SeqCst);
+
+        let watcher = tasks::spawn(tasks::CONNECTION_WATCHER, async move {
+            let reason = connection.closed().await;
+            disconnected(&events, &flag, &reason);
+        });
+
+        *self.inner.watcher.lock().unwrap() = Some(watcher.abort_handle());
+    }
+
+    /// Returns the statistics of the current connection, without re-establishing it if lost.
+    pub async fn stats(&self) -> ConnectionStats {
+        ConnectionStats::of(&self.inner.current.lock().await.1)
+    }
+
+    /// Returns the certificates presented by the server on the current connection, without
+    /// re-establishing it if lost.
+    pub async fn peer_certificates(&self) -> Vec<CertInfo> {
+        CertInfo::of(&self.inner.current.lock().await.1)
+    }
+
+    pub async fn close(&self, error_code: VarInt, reason: &[u8]) {
+        let mut connections = self.dedicated_connections();
+        connections.push(self.clone());
+
+        for connection in connections {
+            let current = connection.inner.current.lock().await;
+            current.1.close(error_code, reason);
+        }
+    }
+
+    /// Returns the connections dedicated to individual streams that are still open.
+    fn dedicated_connections(&self) -> Vec<SharedConnection> {
+        self.inner
+            .dedicated
+            .lock()
+            .unwrap()
+            .drain(..)
+            .filter_map(|inner| inner.upgrade())
+            .map(|inner| SharedConnection { inner })
+            .collect()
+    }
+
+    /// Registers a publisher stream to be finished when the connection is gracefully shut down.
+    pub fn track_publisher(&self, stream: &SharedPublisherStream) {
+        let mut publishers = self.inner.publishers.lock().unwrap();
+        publishers.retain(|publisher| !publisher.is_dropped());
+        publishers.push(stream.downgrade());
+    }
+
+    /// Finishes all open publisher streams, waiting for the server to acknowledge them, before
+    /// closing the connection, along with any connections dedicated to individual streams. The
+    /// connections are closed even if the streams fail to finish before the `timeout` elapses.
+    pub async fn graceful_shutdown(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+
+        let mut connections = self.dedicated_connections();
+        connections.push(self.clone());
+
+        let publishers: Vec<SharedPublisherStream> = connections
+            .iter()
+            .flat_map(|connection| {
+                let mut publishers = connection.inner.publishers.lock().unwrap();
+                publishers.drain(..).collect::<Vec<_>>()
+            })
+            .filter_map(|publisher| publisher.upgrade())
+            .collect();
+
+        let finished = futures::future::join_all(publishers.iter().map(|p| p.finish()));
+        let finished = tokio::time::timeout_at(deadline, finished).await;
+
+        let mut endpoints = Vec::with_capacity(connections.len());
+
+        for connection in &connections {
+            let current = connection.inner.current.lock().await;
+            current
+                .1
+                .close(VarInt::from_u32(0), b"Client gracefully closed connection");
+            endpoints.push(current.0.clone());
+        }
+
+        // Wait for the server to be notified that the connections have closed
+        let idle = futures::future::join_all(endpoints.iter().map(Endpoint::wait_idle));
+        let _ = tokio::time::timeout_at(deadline, idle).await;
+
+        finished
+            .map_err(|_| {
+                Error::timeout(format!(
+                    "Timed out waiting for publishers to finish after {timeout:?}"
+                ))
+            })?
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to finish publisher during graceful shutdown")?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    async fn reconnect(&self, policy: &ReconnectPolicy) -> Result<(Endpoint, Connection)> {
+        let mut attempt = 0;
+
+        loop {
+            self.inner.events.emit(ConnectionEvent::Reconnecting {
+                attempt: attempt + 1,
+            });
+
+            tokio::time::sleep(policy.backoff(attempt)).await;
+
+            let inner = &self.inner;
+            let active = inner.active.load(Ordering::Relaxed);
+
+            let connected = connect_to_any(
+                &inner.config,
+                &inner.addrs,
+                active,
+                inner.connect_timeout,
+                inner.enable_0rtt,
+            )
+            .await;
+
+            match connected {
+                Ok((active, current)) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(
+                        endpoint = %inner.addrs[active].addr,
+                        attempts = attempt + 1,
+                        "Reconnected to Selium server"
+                    );
+
+                    inner.active.store(active, Ordering::Relaxed);
+                    metrics::record_reconnect();
+                    self.watch(&current.1);
+                    inner.events.emit(ConnectionEvent::Connected);
+                    return Ok(current);
+                }
+                Err(err) if attempt >= policy.get_max_retries() => {
+                    return Err(err).with_context(|| {
+                        format!("Failed to reconnect after {} attempt(s)", attempt + 1)
+                    });
+                }
+                Err(_) => attempt += 1,
+            }
+        }
+    }
+}
+
+/// Emits a [ConnectionEvent::Disconnected] event, unless one has already been emitted for the
+/// current connection.
+fn disconnected(events: &ConnectionEvents, flag: &AtomicBool, reason: &ConnectionError) {
+    if !flag.swap(true, Ordering::SeqCst) {
+        events.emit(ConnectionEvent::Disconnected {
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// Attempts to connect to each of the endpoints in turn, starting with the endpoint at `start`
+/// and wrapping around, returning the index of the first endpoint that could be connected to.
+async fn connect_to_any(
+    config: &ClientConfig,
+    addrs: &[ServerAddr],
+    start: usize,
+    connect_timeout: Option<Duration>,
+    enable_0rtt: bool,
+) -> Result<(usize, (Endpoint, Connection))> {
+    // With a single endpoint, its error is returned as-is
+    if let [addr] = addrs {
+        let current =
+            connect_to_endpoint(config.clone(), addr, connect_timeout, enable_0rtt).await?;
+        return Ok((0, current));
+    }
+
+    let mut failures = Vec::with_capacity(addrs.len());
+
+    for idx in (start..addrs.len()).chain(0..start) {
+        let addr = &addrs[idx];
+
+        match connect_to_endpoint(config.clone(), addr, connect_timeout, enable_0rtt).await {
+            Ok(current) => return Ok((idx, current)),
+            Err(err) => failures.push(format!("{}: {err:#}", addr.addr)),
+        }
+    }
+
+    Err(Error::connection(format!(
+        "Failed to connect to any endpoint:\n{}",
+        failures.join("\n")
+    ))
+    .into())
+}
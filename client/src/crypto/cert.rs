@@ -1,32 +1,199 @@
-use anyhow::{bail, Context, Result};
-use rustls::{Certificate, RootCertStore};
-use rustls_pemfile::certs;
-use std::{fs::File, io::BufReader, path::PathBuf};
+use anyhow::{anyhow, bail, Context, Result};
+use rustls::{Certificate, PrivateKey, RootCertStore, SignatureScheme};
+use rustls_pemfile::{certs, read_one, Item};
+use std::{fs, path::PathBuf};
 
-fn load_certs(cert_file: &PathBuf) -> Result<Vec<Certificate>> {
-    certs(&mut BufReader::new(File::open(cert_file)?))
-        .with_context(|| format!("Failed to load valid certificates from {cert_file:?}"))
-        .map(|mut certs| certs.drain(..).map(Certificate).collect())
+const KEY_CHECK_MESSAGE: &[u8] = b"selium client certificate key check";
+
+const KEY_CHECK_SCHEMES: &[SignatureScheme] = &[
+    SignatureScheme::ECDSA_NISTP256_SHA256,
+    SignatureScheme::ECDSA_NISTP384_SHA384,
+    SignatureScheme::ED25519,
+    SignatureScheme::RSA_PSS_SHA256,
+    SignatureScheme::RSA_PKCS1_SHA256,
+];
+
+/// A client certificate chain, along with its matching private key, that is presented to the
+/// server during the handshake for mutual TLS authentication.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientCertificate {
+    pub certs: Vec<Certificate>,
+    pub key: PrivateKey,
 }
 
 /// Creates and returns a RootCertStore via certificates parsed from the provided
-/// filepath pointing to a Certificate Authority file.
+/// filepath pointing to a PEM-encoded Certificate Authority file.
 ///
-/// This function will fail if no certificates can be successfully parsed from
-/// the CA input file.
+/// This function will fail if the file cannot be read, or if no certificates can be
+/// successfully parsed from the CA input file.
 ///
 /// # Arguments
 ///
 /// * `ca_file` - The filepath to the CA file.
 ///
 pub(crate) fn load_root_store(ca_file: &PathBuf) -> Result<RootCertStore> {
+    let ca = fs::read(ca_file).with_context(|| format!("Failed to read CA file {ca_file:?}"))?;
+    parse_root_store_pem(&ca).with_context(|| format!("No valid certs found in file {ca_file:?}"))
+}
+
+/// Creates and returns a RootCertStore via certificates parsed from the provided
+/// PEM-encoded Certificate Authority bytes.
+///
+/// This function will fail if no certificates can be successfully parsed from the input.
+///
+/// # Arguments
+///
+/// * `ca` - The PEM-encoded CA certificates.
+///
+pub(crate) fn parse_root_store_pem(ca: &[u8]) -> Result<RootCertStore> {
+    let certs: Vec<Vec<u8>> = certs(&mut &*ca).context("Failed to parse CA certificates")?;
+    build_root_store(&certs)
+}
+
+/// Creates and returns a RootCertStore via a single DER-encoded Certificate Authority
+/// certificate.
+///
+/// This function will fail if the certificate cannot be successfully parsed.
+///
+/// # Arguments
+///
+/// * `ca` - The DER-encoded CA certificate.
+///
+pub(crate) fn parse_root_store_der(ca: &[u8]) -> Result<RootCertStore> {
+    build_root_store(&[ca.to_vec()])
+}
+
+fn build_root_store(certs: &[Vec<u8>]) -> Result<RootCertStore> {
     let mut store = RootCertStore::empty();
-    let certs = load_certs(ca_file)?;
-    store.add_parsable_certificates(&certs);
+    store.add_parsable_certificates(certs);
 
     if store.is_empty() {
-        bail!("No valid certs found in file {ca_file:?}");
+        bail!("No valid CA certificates found");
     }
 
     Ok(store)
 }
+
+/// Loads a client certificate chain and private key from the provided PEM-encoded files.
+///
+/// See [parse_client_certificate] for the conditions under which this function will fail.
+///
+/// # Arguments
+///
+/// * `cert_file` - The filepath to the client certificate chain.
+/// * `key_file` - The filepath to the client private key.
+///
+pub(crate) fn load_client_certificate(
+    cert_file: &PathBuf,
+    key_file: &PathBuf,
+) -> Result<ClientCertificate> {
+    let cert = fs::read(cert_file)
+        .with_context(|| format!("Failed to read client certificate from {cert_file:?}"))?;
+    let key = fs::read(key_file)
+        .with_context(|| format!("Failed to read client private key from {key_file:?}"))?;
+
+    parse_client_certificate(&cert, &key)
+}
+
+/// Parses a client certificate chain and private key from PEM-encoded bytes.
+///
+/// This function will fail if no certificates or private key can be parsed from the input, or
+/// if the private key does not match the public key of the leaf certificate.
+///
+/// # Arguments
+///
+/// * `cert` - The PEM-encoded client certificate chain.
+/// * `key` - The PEM-encoded client private key, in PKCS #8, PKCS #1 or SEC1 format.
+///
+pub(crate) fn parse_client_certificate(cert: &[u8], key: &[u8]) -> Result<ClientCertificate> {
+    let certs: Vec<Certificate> = certs(&mut &*cert)
+        .context("Failed to parse client certificate")?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+
+    let leaf = certs
+        .first()
+        .context("No valid certs found in client certificate")?;
+    let key = parse_private_key(key)?;
+    verify_key_matches(leaf, &key)?;
+
+    Ok(ClientCertificate { certs, key })
+}
+
+fn parse_private_key(mut key: &[u8]) -> Result<PrivateKey> {
+    loop {
+        match read_one(&mut key).context("Failed to parse client private key")? {
+            Some(Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key)) => {
+                return Ok(PrivateKey(key))
+            }
+            Some(_) => continue,
+            None => bail!("No valid private key found in client private key"),
+        }
+    }
+}
+
+/// Verifies that the private key belongs to the certificate, by signing a message with the key
+/// and verifying the signature against the public key of the certificate.
+fn verify_key_matches(cert: &Certificate, key: &PrivateKey) -> Result<()> {
+    let signer = rustls::sign::any_supported_type(key)
+        .ok()
+        .and_then(|key| key.choose_scheme(KEY_CHECK_SCHEMES))
+        .context("Unsupported client private key type")?;
+
+    let signature = signer.sign(KEY_CHECK_MESSAGE)?;
+
+    let algorithm = match signer.scheme() {
+        SignatureScheme::ECDSA_NISTP256_SHA256 => &webpki::ECDSA_P256_SHA256,
+        SignatureScheme::ECDSA_NISTP384_SHA384 => &webpki::ECDSA_P384_SHA384,
+        SignatureScheme::ED25519 => &webpki::ED25519,
+        SignatureScheme::RSA_PSS_SHA256 => &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+        SignatureScheme::RSA_PKCS1_SHA256 => &webpki::RSA_PKCS1_2048_8192_SHA256,
+        _ => bail!("Unsupported client private key type"),
+    };
+
+    let cert = webpki::EndEntityCert::try_from(cert.0.as_ref())
+        .map_err(|err| anyhow!("Invalid client certificate: {err:?}"))?;
+
+    cert.verify_signature(algorithm, KEY_CHECK_MESSAGE, &signature)
+        .map_err(|_| anyhow!("Client private key does not match the client certificate"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA_FILE: &str = "../tests/certs/ca.crt";
+
+    fn ca_der() -> Vec<u8> {
+        let pem = fs::read(CA_FILE).unwrap();
+        certs(&mut &*pem).unwrap().remove(0)
+    }
+
+    // OwnedTrustAnchor doesn't implement PartialEq, so compare its subject and key instead
+    fn anchors(store: &RootCertStore) -> Vec<String> {
+        store.roots.iter().map(|root| format!("{root:?}")).collect()
+    }
+
+    #[test]
+    fn root_store_from_pem_matches_file() {
+        let from_file = load_root_store(&CA_FILE.into()).unwrap();
+        let from_pem = parse_root_store_pem(&fs::read(CA_FILE).unwrap()).unwrap();
+
+        assert_eq!(anchors(&from_file), anchors(&from_pem));
+    }
+
+    #[test]
+    fn root_store_from_der_matches_file() {
+        let from_file = load_root_store(&CA_FILE.into()).unwrap();
+        let from_der = parse_root_store_der(&ca_der()).unwrap();
+
+        assert_eq!(anchors(&from_file), anchors(&from_der));
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_root_store() {
+        assert!(parse_root_store_pem(b"not a certificate").is_err());
+        assert!(parse_root_store_der(b"not a certificate").is_err());
+    }
+}
@@ -0,0 +1,24 @@
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, Error, ServerName};
+use std::time::SystemTime;
+
+/// A [ServerCertVerifier] that accepts any server certificate, without checking that it was
+/// issued by a trusted CA, or that it is valid for the server name.
+///
+/// Handshake signatures are still verified, so the server must hold the private key for the
+/// certificate it presents, but this offers no protection from person-in-the-middle attacks.
+pub(crate) struct SkipServerVerification;
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
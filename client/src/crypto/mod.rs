@@ -1 +1,3 @@
 pub mod cert;
+#[cfg(feature = "dangerous-configuration")]
+pub mod dangerous;
@@ -0,0 +1,345 @@
+use selium_common::protocol::{BufferCapacityExceeded, MessageTooLarge};
+use selium_common::types::{StreamLimitReached, StreamReset};
+use std::error::Error as StdError;
+use std::fmt;
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// A specialized [Result](std::result::Result) type for fallible `Selium` client operations.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The error type returned by the public methods of the `Selium` client.
+///
+/// Each variant classifies the failure, so that callers can decide how to handle it, such as
+/// retrying a [Connection](Error::Connection) or [Timeout](Error::Timeout) error, while treating
+/// a [Config](Error::Config) error as fatal. The underlying error is preserved, and is used for
+/// the [Display](fmt::Display) and [source](StdError::source) implementations, so that `Selium`
+/// errors read the same as the error they wrap.
+///
+/// Errors returned by user-implemented traits, such as a
+/// [MessageDecoder](crate::traits::MessageDecoder), are wrapped in the [Codec](Error::Codec)
+/// variant.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The connection to the `Selium` server could not be established, or was lost, or a stream
+    /// could not be opened, as the server's limit on concurrent streams has been reached.
+    Connection(BoxError),
+    /// The server stopped answering a [Subscriber](crate::Subscriber)'s heartbeats, so its
+    /// connection was closed as it's presumed to have been lost, even though QUIC may not have
+    /// detected it yet.
+    ConnectionLost(BoxError),
+    /// The server reset a stream, while its connection remained open, such as after rejecting
+    /// the stream's registration, carrying the code that the server reset the stream with.
+    StreamReset(StreamReset),
+    /// A TLS certificate or key could not be loaded, or the TLS handshake with the server failed.
+    Tls(BoxError),
+    /// The server rejected a stream, as the token it presented doesn't authorize it to use its
+    /// topic, or it didn't present one.
+    Unauthorized(BoxError),
+    /// An operation did not complete within its configured timeout.
+    Timeout(BoxError),
+    /// The client or a stream was configured with invalid options.
+    Config(BoxError),
+    /// A message failed to be encoded or decoded by the stream's codec.
+    Codec(BoxError),
+    /// A message exceeded the maximum message size of the stream, either when being sent, or
+    /// when being received, carrying the length of the message and the limit it exceeded.
+    MessageTooLarge(MessageTooLarge),
+    /// A frame would not fit within the maximum buffer capacity of a
+    /// [Subscriber](crate::Subscriber), carrying the number of bytes the frame needed and the
+    /// capacity it exceeded.
+    BufferCapacityExceeded(BufferCapacityExceeded),
+    /// The server sent a frame that could not be parsed, or that was unexpected.
+    Protocol(BoxError),
+    /// An error returned by user code, such as a [Replier](crate::Replier) handler.
+    Other(BoxError),
+}
+
+impl Error {
+    pub(crate) fn connection(err: impl Into<BoxError>) -> Self {
+        Self::Connection(err.into())
+    }
+
+    pub(crate) fn connection_lost(err: impl Into<BoxError>) -> Self {
+        Self::ConnectionLost(err.into())
+    }
+
+    pub(crate) fn tls(err: impl Into<BoxError>) -> Self {
+        Self::Tls(err.into())
+    }
+
+    pub(crate) fn unauthorized(err: impl Into<BoxError>) -> Self {
+        Self::Unauthorized(err.into())
+    }
+
+    pub(crate) fn timeout(err: impl Into<BoxError>) -> Self {
+        Self::Timeout(err.into())
+    }
+
+    pub(crate) fn config(err: impl Into<BoxError>) -> Self {
+        Self::Config(err.into())
+    }
+
+    pub(crate) fn codec(err: impl Into<BoxError>) -> Self {
+        Self::Codec(err.into())
+    }
+
+    pub(crate) fn protocol(err: impl Into<BoxError>) -> Self {
+        Self::Protocol(err.into())
+    }
+
+    pub(crate) fn other(err: impl Into<BoxError>) -> Self {
+        Self::Other(err.into())
+    }
+
+    fn inner(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        match self {
+            Self::StreamReset(err) => err,
+            Self::MessageTooLarge(err) => err,
+            Self::BufferCapacityExceeded(err) => err,
+            Self::Connection(err)
+            | Self::ConnectionLost(err)
+            | Self::Tls(err)
+            | Self::Unauthorized(err)
+            | Self::Timeout(err)
+            | Self::Config(err)
+            | Self::Codec(err)
+            | Self::Protocol(err)
+            | Self::Other(err) => err.as_ref(),
+        }
+    }
+
+    /// Returns the constructor of the variant matching the first error in the chain that can be
+    /// classified, falling back to [Error::Protocol].
+    fn classify(err: &anyhow::Error) -> fn(BoxError) -> Self {
+        for cause in err.chain() {
+            if let Some(variant) = cause.downcast_ref::<Error>().and_then(Error::variant) {
+                return variant;
+            }
+
+            if cause.is::<tokio::time::error::Elapsed>() {
+                return Self::Timeout;
+            }
+
+            if cause.is::<rustls::Error>() {
+                return Self::Tls;
+            }
+
+            if cause.is::<std::num::TryFromIntError>() {
+                return Self::Config;
+            }
+
+            if let Some(err) = cause.downcast_ref::<quinn::ConnectionError>() {
+                return match err {
+                    quinn::ConnectionError::TransportError(err) if is_crypto(err.code) => Self::Tls,
+                    quinn::ConnectionError::ConnectionClosed(close)
+                        if is_crypto(close.error_code) =>
+                    {
+                        Self::Tls
+                    }
+                    _ => Self::Connection,
+                };
+            }
+
+            if cause.is::<StreamLimitReached>()
+                || cause.is::<quinn::ConnectError>()
+                || cause.is::<quinn::WriteError>()
+                || cause.is::<quinn::ReadError>()
+                || cause.is::<std::io::Error>()
+            {
+                return Self::Connection;
+            }
+        }
+
+        Self::Protocol
+    }
+
+    /// Returns the constructor of the variant, unless it carries a concrete error rather than a
+    /// wrapped one, in which case it's extracted from the chain before classifying it.
+    fn variant(&self) -> Option<fn(BoxError) -> Self> {
+        let variant = match self {
+            Self::Connection(_) => Self::Connection,
+            Self::ConnectionLost(_) => Self::ConnectionLost,
+            Self::StreamReset(_) => return None,
+            Self::Tls(_) => Self::Tls,
+            Self::Unauthorized(_) => Self::Unauthorized,
+            Self::Timeout(_) => Self::Timeout,
+            Self::Config(_) => Self::Config,
+            Self::Codec(_) => Self::Codec,
+            Self::MessageTooLarge(_) => return None,
+            Self::BufferCapacityExceeded(_) => return None,
+            Self::Protocol(_) => Self::Protocol,
+            Self::Other(_) => Self::Other,
+        };
+
+        Some(variant)
+    }
+}
+
+/// Returns the first [MessageTooLarge] error in the chain, including any wrapped by an [Error].
+fn find_message_too_large(err: &anyhow::Error) -> Option<MessageTooLarge> {
+    err.chain()
+        .find_map(|cause| match cause.downcast_ref::<Error>() {
+            Some(Error::MessageTooLarge(err)) => Some(err),
+            _ => cause.downcast_ref::<MessageTooLarge>(),
+        })
+        .copied()
+}
+
+/// Returns the first [StreamReset] error in the chain, including any wrapped by an [Error].
+fn find_stream_reset(err: &anyhow::Error) -> Option<StreamReset> {
+    err.chain()
+        .find_map(|cause| match cause.downcast_ref::<Error>() {
+            Some(Error::StreamReset(err)) => Some(err),
+            _ => cause.downcast_ref::<StreamReset>(),
+        })
+        .copied()
+}
+
+/// Returns the first [BufferCapacityExceeded] error in the chain, including any wrapped by an
+/// [Error].
+fn find_buffer_capacity_exceeded(err: &anyhow::Error) -> Option<BufferCapacityExceeded> {
+    err.chain()
+        .find_map(|cause| match cause.downcast_ref::<Error>() {
+            Some(Error::BufferCapacityExceeded(err)) => Some(err),
+            _ => cause.downcast_ref::<BufferCapacityExceeded>(),
+        })
+        .copied()
+}
+
+/// Returns true if the QUIC transport error code was raised by the TLS handshake.
+fn is_crypto(code: impl Into<u64>) -> bool {
+    (0x100..0x200).contains(&code.into())
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.inner(), f)
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.inner().source()
+    }
+}
+
+/// Converts an internal error at the public API boundary, classifying it by the errors in its
+/// chain.
+///
+/// If the chain contains an [Error] that was classified where it occurred, its variant is kept,
+/// and any context added since is preserved.
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        // Unwrap an error that was classified where it occurred, unless context has been added
+        let err = match err.chain().next() {
+            Some(cause) if cause.is::<Error>() => match err.downcast::<Error>() {
+                Ok(err) => return err,
+                Err(err) => err,
+            },
+            _ => err,
+        };
+
+        // Replaced by the underlying errors, so that callers can downcast to them
+        if let Some(err) = find_message_too_large(&err) {
+            return Self::MessageTooLarge(err);
+        }
+
+        if let Some(err) = find_stream_reset(&err) {
+            return Self::StreamReset(err);
+        }
+
+        if let Some(err) = find_buffer_capacity_exceeded(&err) {
+            return Self::BufferCapacityExceeded(err);
+        }
+
+        Self::classify(&err)(err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn classifies_elapsed_timeout() {
+        let err = tokio::time::timeout(Duration::ZERO, std::future::pending::<()>())
+            .await
+            .context("Timed out connecting")
+            .unwrap_err();
+
+        let err = Error::from(err);
+
+        assert!(matches!(err, Error::Timeout(_)));
+        assert_eq!(err.to_string(), "Timed out connecting");
+    }
+
+    #[test]
+    fn classifies_failed_integer_conversion() {
+        let err = anyhow::Error::from(u64::try_from(-1i64).unwrap_err());
+
+        assert!(matches!(Error::from(err), Error::Config(_)));
+    }
+
+    #[test]
+    fn keeps_variant_of_nested_error() {
+        let err = anyhow!(Error::codec("Invalid UTF-8")).context("Failed to decode message");
+
+        let err = Error::from(err);
+
+        assert!(matches!(err, Error::Codec(_)));
+        assert_eq!(err.to_string(), "Failed to decode message");
+    }
+
+    #[test]
+    fn unwraps_message_too_large() {
+        let too_large = MessageTooLarge {
+            length: 2048,
+            max_message_size: 1024,
+        };
+        let err = anyhow!(Error::from(anyhow!(too_large))).context("Failed to encode batch item");
+
+        match Error::from(err) {
+            Error::MessageTooLarge(err) => assert_eq!(err, too_large),
+            err => panic!("Unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn unwraps_buffer_capacity_exceeded() {
+        let exceeded = BufferCapacityExceeded {
+            required: 2048,
+            max_buffer_capacity: 1024,
+        };
+        let err = Error::from(anyhow!(exceeded).context("Failed to receive message"));
+
+        match err {
+            Error::BufferCapacityExceeded(err) => assert_eq!(err, exceeded),
+            err => panic!("Unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn classifies_stream_reset() {
+        let reset = StreamReset {
+            code: StreamReset::REJECTED,
+        };
+        let err = Error::from(anyhow!(reset).context("Failed to receive message"));
+
+        match err {
+            Error::StreamReset(err) => assert_eq!(err, reset),
+            err => panic!("Unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn falls_back_to_protocol_error() {
+        let err = Error::from(anyhow!("Unknown message type"));
+
+        assert!(matches!(err, Error::Protocol(_)));
+        assert_eq!(err.to_string(), "Unknown message type");
+    }
+}
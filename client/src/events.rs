@@ -0,0 +1,113 @@
+use futures::stream::{BoxStream, StreamExt};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+// The number of events buffered for each receiver, beyond which the oldest events are dropped
+pub(crate) const CONNECTION_EVENTS_CAPACITY: usize = 64;
+
+/// An event in the lifecycle of a [Client](crate::Client) connection, yielded by
+/// [Client::events](crate::Client::events).
+///
+/// Events are useful for reporting the health of the connection, such as via a health check or
+/// metrics, particularly when a [ReconnectPolicy](crate::ReconnectPolicy) is configured, which
+/// otherwise re-establishes lost connections silently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The connection to the `Selium` server was re-established after being lost.
+    Connected,
+    /// The connection to the `Selium` server was lost or closed.
+    Disconnected {
+        /// A description of why the connection was closed, such as it timing out, or being
+        /// closed by the server.
+        reason: String,
+    },
+    /// An attempt is being made to re-establish the connection, starting from attempt `1`.
+    Reconnecting {
+        /// The number of the attempt being made.
+        attempt: u32,
+    },
+}
+
+/// Broadcasts [ConnectionEvent]s to every stream returned by
+/// [Client::events](crate::Client::events).
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionEvents {
+    sender: broadcast::Sender<ConnectionEvent>,
+}
+
+impl ConnectionEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CONNECTION_EVENTS_CAPACITY);
+        Self { sender }
+    }
+
+    /// Sends an event to every current subscriber. Events sent without any subscribers are
+    /// dropped.
+    pub fn emit(&self, event: ConnectionEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Returns a stream of every event emitted from now on, which ends once the connection is
+    /// dropped.
+    ///
+    /// A subscriber that falls more than [CONNECTION_EVENTS_CAPACITY] events behind skips the
+    /// oldest events it missed.
+    pub fn subscribe(&self) -> BoxStream<'static, ConnectionEvent> {
+        futures::stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Skipped {skipped} connection event(s) that weren't received");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn broadcasts_events_to_every_subscriber() {
+        let events = ConnectionEvents::new();
+        let first = events.subscribe();
+        let mut second = events.subscribe();
+
+        events.emit(ConnectionEvent::Reconnecting { attempt: 1 });
+        events.emit(ConnectionEvent::Connected);
+        drop(events);
+
+        assert_eq!(
+            first.collect::<Vec<_>>().await,
+            vec![
+                ConnectionEvent::Reconnecting { attempt: 1 },
+                ConnectionEvent::Connected
+            ]
+        );
+        assert_eq!(
+            second.next().await,
+            Some(ConnectionEvent::Reconnecting { attempt: 1 })
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_events_when_lagging() {
+        let events = ConnectionEvents::new();
+        let subscriber = events.subscribe();
+
+        for attempt in 0..CONNECTION_EVENTS_CAPACITY as u32 + 1 {
+            events.emit(ConnectionEvent::Reconnecting { attempt });
+        }
+
+        drop(events);
+
+        let received = subscriber.collect::<Vec<_>>().await;
+
+        assert_eq!(received.len(), CONNECTION_EVENTS_CAPACITY);
+        assert_eq!(received[0], ConnectionEvent::Reconnecting { attempt: 1 });
+    }
+}
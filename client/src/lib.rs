@@ -1,11 +1,36 @@
+mod cert_info;
 mod client;
+mod compression;
+mod connect;
+mod connection;
+mod error;
+mod events;
+mod reconnect;
+mod stats;
 mod streams;
+mod topic;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod codecs;
 pub(crate) mod crypto;
+pub mod metrics;
 pub mod prelude;
+pub mod tasks;
 pub mod traits;
 pub(crate) mod utils;
 
+pub use cert_info::*;
 pub use client::*;
+pub use connect::*;
+pub use error::*;
+pub use events::ConnectionEvent;
+pub use reconnect::*;
+pub use selium_common::protocol::{
+    BufferCapacityExceeded, Compression, FramingErrorPolicy, Headers, MessageTooLarge, ReplayStart,
+    StartPosition, TopicInfo, DEFAULT_MAX_MESSAGE_SIZE,
+};
+pub use selium_common::types::StreamReset;
+pub use stats::*;
 pub use streams::*;
+pub use topic::*;
@@ -0,0 +1,80 @@
+//! Names of the metrics recorded by the client when the `metrics` feature is enabled.
+//!
+//! Metrics are recorded through the [metrics](https://docs.rs/metrics) facade, so they can be
+//! exported to Prometheus, or any other backend, by installing the corresponding exporter as the
+//! global recorder. Nothing is recorded until a recorder is installed.
+//!
+//! | Name | Type | Labels | Description |
+//! |------|------|--------|-------------|
+//! | `selium_messages_published_total` | Counter | `topic` | Messages written to a [Publisher](crate::Publisher) stream |
+//! | `selium_messages_consumed_total` | Counter | `topic` | Messages received by a [Subscriber](crate::Subscriber) stream, including any that fail to decode |
+//! | `selium_encode_duration_seconds` | Histogram | `topic` | Time taken to encode each published message |
+//! | `selium_decode_duration_seconds` | Histogram | `topic` | Time taken to decode each consumed message |
+//! | `selium_reconnects_total` | Counter | | Connections re-established via a [ReconnectPolicy](crate::ReconnectPolicy) |
+//!
+//! The `topic` label is the topic that the stream was opened with, which may be a wildcard
+//! pattern for a [Subscriber](crate::Subscriber).
+
+use std::time::Instant;
+
+/// Counts the messages written to a [Publisher](crate::Publisher) stream.
+pub const MESSAGES_PUBLISHED: &str = "selium_messages_published_total";
+
+/// Counts the messages received by a [Subscriber](crate::Subscriber) stream.
+pub const MESSAGES_CONSUMED: &str = "selium_messages_consumed_total";
+
+/// Records the time taken to encode each published message, in seconds.
+pub const ENCODE_DURATION: &str = "selium_encode_duration_seconds";
+
+/// Records the time taken to decode each consumed message, in seconds.
+pub const DECODE_DURATION: &str = "selium_decode_duration_seconds";
+
+/// Counts the connections re-established via a [ReconnectPolicy](crate::ReconnectPolicy).
+pub const RECONNECTS: &str = "selium_reconnects_total";
+
+/// The label identifying the topic of the stream that recorded a metric.
+pub const TOPIC_LABEL: &str = "topic";
+
+#[cfg(feature = "metrics")]
+mod recorder {
+    use super::*;
+
+    pub(crate) fn record_published(topic: &str, count: u64) {
+        ::metrics::counter!(MESSAGES_PUBLISHED, TOPIC_LABEL => topic.to_owned()).increment(count);
+    }
+
+    pub(crate) fn record_consumed(topic: &str) {
+        ::metrics::counter!(MESSAGES_CONSUMED, TOPIC_LABEL => topic.to_owned()).increment(1);
+    }
+
+    pub(crate) fn record_encode(topic: &str, started: Instant) {
+        ::metrics::histogram!(ENCODE_DURATION, TOPIC_LABEL => topic.to_owned())
+            .record(started.elapsed());
+    }
+
+    pub(crate) fn record_decode(topic: &str, started: Instant) {
+        ::metrics::histogram!(DECODE_DURATION, TOPIC_LABEL => topic.to_owned())
+            .record(started.elapsed());
+    }
+
+    pub(crate) fn record_reconnect() {
+        ::metrics::counter!(RECONNECTS).increment(1);
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod recorder {
+    use super::*;
+
+    pub(crate) fn record_published(_topic: &str, _count: u64) {}
+
+    pub(crate) fn record_consumed(_topic: &str) {}
+
+    pub(crate) fn record_encode(_topic: &str, _started: Instant) {}
+
+    pub(crate) fn record_decode(_topic: &str, _started: Instant) {}
+
+    pub(crate) fn record_reconnect() {}
+}
+
+pub(crate) use recorder::*;
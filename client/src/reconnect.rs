@@ -0,0 +1,173 @@
+use crate::traits::TryIntoU64;
+use crate::Result;
+use rand::Rng;
+use std::time::Duration;
+
+/// The default delay in milliseconds before the first reconnection attempt.
+pub const RECONNECT_INITIAL_BACKOFF_DEFAULT: u64 = 100;
+
+/// The default upper bound in milliseconds for the delay between reconnection attempts.
+pub const RECONNECT_MAX_BACKOFF_DEFAULT: u64 = 10_000;
+
+/// The default number of times to retry establishing a connection before giving up.
+pub const RECONNECT_MAX_RETRIES_DEFAULT: u32 = 10;
+
+/// Determines how a [Publisher](crate::Publisher) behaves when its connection is lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublisherStrategy {
+    /// Messages are buffered while the connection is re-established, and then written to a
+    /// re-opened stream.
+    Buffer,
+    /// The [Publisher](crate::Publisher) returns an error on the first failed write, and must be
+    /// re-opened by the user.
+    Error,
+}
+
+/// A policy used by a [Client](crate::Client) to transparently re-establish its connection to the
+/// `Selium` server after it is lost.
+///
+/// Reconnection attempts are delayed using exponential backoff, starting from the
+/// `initial_backoff` delay and doubling after each failed attempt, up to the `max_backoff` delay.
+/// When `jitter` is enabled, each delay is randomized between half and all of its value, to avoid
+/// many clients reconnecting in lockstep.
+///
+/// # Examples
+///
+/// ```
+/// use selium::{PublisherStrategy, ReconnectPolicy};
+/// use std::time::Duration;
+///
+/// let policy = ReconnectPolicy::default()
+///     .initial_backoff(Duration::from_millis(50)).unwrap()
+///     .max_backoff(Duration::from_secs(5)).unwrap()
+///     .max_retries(20)
+///     .publisher_strategy(PublisherStrategy::Buffer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    initial_backoff: u64,
+    max_backoff: u64,
+    max_retries: u32,
+    jitter: bool,
+    publisher_strategy: PublisherStrategy,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: RECONNECT_INITIAL_BACKOFF_DEFAULT,
+            max_backoff: RECONNECT_MAX_BACKOFF_DEFAULT,
+            max_retries: RECONNECT_MAX_RETRIES_DEFAULT,
+            jitter: true,
+            publisher_strategy: PublisherStrategy::Buffer,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Overrides the delay in milliseconds before the first reconnection attempt.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided backoff fails to be converted to a [u64].
+    pub fn initial_backoff<T: TryIntoU64>(mut self, backoff: T) -> Result<Self> {
+        self.initial_backoff = backoff.try_into_u64()?;
+        Ok(self)
+    }
+
+    /// Overrides the upper bound in milliseconds for the delay between reconnection attempts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided backoff fails to be converted to a [u64].
+    pub fn max_backoff<T: TryIntoU64>(mut self, backoff: T) -> Result<Self> {
+        self.max_backoff = backoff.try_into_u64()?;
+        Ok(self)
+    }
+
+    /// Overrides the number of times to retry establishing a connection before giving up.
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Enables or disables randomized jitter on reconnection delays.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Overrides how a [Publisher](crate::Publisher) behaves when its connection is lost. See
+    /// [PublisherStrategy] for more information.
+    pub fn publisher_strategy(mut self, strategy: PublisherStrategy) -> Self {
+        self.publisher_strategy = strategy;
+        self
+    }
+
+    pub(crate) fn get_max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub(crate) fn get_publisher_strategy(&self) -> PublisherStrategy {
+        self.publisher_strategy
+    }
+
+    /// Returns the delay to wait before the provided reconnection `attempt`, starting from 0.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(self.max_backoff);
+
+        let backoff = if self.jitter && backoff > 0 {
+            let half = backoff / 2;
+            half + rand::thread_rng().gen_range(0..=backoff - half)
+        } else {
+            backoff
+        };
+
+        Duration::from_millis(backoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_max() {
+        let policy = ReconnectPolicy::default()
+            .initial_backoff(100u64)
+            .unwrap()
+            .max_backoff(1_000u64)
+            .unwrap()
+            .jitter(false);
+
+        let delays: Vec<u128> = (0..6).map(|i| policy.backoff(i).as_millis()).collect();
+
+        assert_eq!(delays, vec![100, 200, 400, 800, 1_000, 1_000]);
+    }
+
+    #[test]
+    fn backoff_does_not_overflow() {
+        let policy = ReconnectPolicy::default().jitter(false);
+
+        assert_eq!(
+            policy.backoff(u32::MAX),
+            Duration::from_millis(RECONNECT_MAX_BACKOFF_DEFAULT)
+        );
+    }
+
+    #[test]
+    fn jittered_backoff_stays_within_bounds() {
+        let policy = ReconnectPolicy::default()
+            .initial_backoff(400u64)
+            .unwrap()
+            .jitter(true);
+
+        for _ in 0..100 {
+            let delay = policy.backoff(0).as_millis();
+            assert!((200..=400).contains(&delay));
+        }
+    }
+}
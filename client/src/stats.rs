@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+/// A snapshot of the statistics for a [Client](crate::Client) connection, which are useful for
+/// diagnosing throughput problems.
+///
+/// Counters are cumulative since the connection was established. If the connection has been
+/// re-established via a [ReconnectPolicy](crate::ReconnectPolicy), they only cover the current
+/// connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConnectionStats {
+    /// The current best estimate of the connection's round-trip time.
+    pub rtt: Duration,
+    /// The current congestion window of the connection, in bytes.
+    pub congestion_window: u64,
+    /// The number of times the congestion window has been reduced.
+    pub congestion_events: u64,
+    /// The number of packets sent on the connection.
+    pub sent_packets: u64,
+    /// The number of packets that were deemed lost.
+    pub lost_packets: u64,
+    /// The number of bytes contained in packets that were deemed lost.
+    pub lost_bytes: u64,
+    /// The number of UDP datagrams sent on the connection.
+    pub datagrams_sent: u64,
+    /// The number of UDP datagrams received on the connection.
+    pub datagrams_received: u64,
+    /// The total size of the UDP datagrams sent on the connection, in bytes.
+    pub bytes_sent: u64,
+    /// The total size of the UDP datagrams received on the connection, in bytes.
+    pub bytes_received: u64,
+}
+
+impl ConnectionStats {
+    pub(crate) fn of(connection: &quinn::Connection) -> Self {
+        let stats = connection.stats();
+
+        Self {
+            rtt: stats.path.rtt,
+            congestion_window: stats.path.cwnd,
+            congestion_events: stats.path.congestion_events,
+            sent_packets: stats.path.sent_packets,
+            lost_packets: stats.path.lost_packets,
+            lost_bytes: stats.path.lost_bytes,
+            datagrams_sent: stats.udp_tx.datagrams,
+            datagrams_received: stats.udp_rx.datagrams,
+            bytes_sent: stats.udp_tx.bytes,
+            bytes_received: stats.udp_rx.bytes,
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use super::subscriber::Acker;
+use crate::traits::{Open, SubscriberDecoder, SyncDecoder};
+use crate::{Error, Result, StreamBuilder, Subscriber, SubscriberWantsOpen};
+use async_trait::async_trait;
+use futures::{SinkExt, Stream, StreamExt};
+use selium_common::protocol::{AckPayload, AckThroughPayload, Frame};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct AckSubscriberWantsOpen<D, Item, Kind = SyncDecoder> {
+    inner: SubscriberWantsOpen<D, Item, Kind>,
+}
+
+impl<D, Item, Kind> StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
+where
+    D: SubscriberDecoder<Item, Kind>,
+{
+    /// Enables at-least-once delivery, where the server holds onto each message until the
+    /// [Subscriber](crate::Subscriber) acknowledges it.
+    ///
+    /// The stream then yields each message as a [Delivery], which is acknowledged via
+    /// [Delivery::ack]. Any messages that haven't been acknowledged when the subscriber
+    /// disconnects are redelivered, in their original order, to the next subscriber that opens
+    /// an acknowledged subscription to the same topic and consumer group, ahead of any new
+    /// messages. This includes the same subscriber, when it is re-opened after the
+    /// [Client](crate::Client) reconnects.
+    ///
+    /// Messages are never redelivered while the subscriber remains connected, no matter how long
+    /// they go unacknowledged. A subscriber is considered disconnected once its stream is closed,
+    /// or once the server's idle timeout elapses after its connection is lost, so a subscriber
+    /// that fails to acknowledge a message will only see it again after reconnecting.
+    ///
+    /// As messages can be delivered more than once, they should be processed idempotently.
+    /// Acknowledgements cannot be used with wildcard topics.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # use futures::TryStreamExt;
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut subscriber = client
+    ///     .subscriber("/acmeco/jobs")
+    ///     .with_decoder(StringCodec)
+    ///     .with_acks()
+    ///     .open()
+    ///     .await?;
+    ///
+    /// while let Some(job) = subscriber.try_next().await? {
+    ///     println!("Processing {}", job.payload);
+    ///     job.ack().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_acks(self) -> StreamBuilder<AckSubscriberWantsOpen<D, Item, Kind>> {
+        StreamBuilder {
+            state: AckSubscriberWantsOpen { inner: self.state },
+            connection: self.connection,
+        }
+    }
+}
+
+#[async_trait]
+impl<D, Item, Kind> Open for StreamBuilder<AckSubscriberWantsOpen<D, Item, Kind>>
+where
+    D: SubscriberDecoder<Item, Kind> + Send,
+    Item: Send,
+    Kind: Send,
+{
+    type Output = AckSubscriber<D, Item, Kind>;
+
+    async fn open(self) -> Result<Self::Output> {
+        let connection = self
+            .state
+            .inner
+            .common
+            .stream_connection(self.connection)
+            .await?;
+
+        let (mut headers, decoder, policy, codec, heartbeat) = self.state.inner.into_parts();
+        headers.acks = true;
+
+        let inner =
+            Subscriber::spawn(connection, headers, decoder, policy, codec, heartbeat).await?;
+
+        Ok(AckSubscriber { inner })
+    }
+}
+
+/// A [Subscriber](crate::Subscriber) with acknowledgements enabled, which yields each message as
+/// a [Delivery] to be acknowledged once it has been processed.
+///
+/// **Note:** The AckSubscriber struct is never constructed directly, but rather, via a
+/// [StreamBuilder](crate::StreamBuilder).
+pub struct AckSubscriber<D, Item, Kind = SyncDecoder> {
+    inner: Subscriber<D, Item, Kind>,
+}
+
+impl<D, Item, Kind> AckSubscriber<D, Item, Kind>
+where
+    D: SubscriberDecoder<Item, Kind>,
+{
+    /// See [stream_id](crate::Subscriber::stream_id).
+    pub fn stream_id(&self) -> u64 {
+        self.inner.stream_id()
+    }
+
+    /// See [commit](crate::Subscriber::commit).
+    pub async fn commit(&self, offset: u64) -> Result<()> {
+        self.inner.commit(offset).await
+    }
+
+    /// Acknowledges every message received on the subscriber's stream up to and including the
+    /// one identified by `delivery_id`, in a single frame, rather than acknowledging each
+    /// [Delivery] individually.
+    ///
+    /// Delivery ids are assigned in the order messages are delivered, starting from `0`, so
+    /// acknowledging through the delivery id of the 50th message acknowledges all 50, including
+    /// any that weren't acknowledged individually. Acknowledging messages that were already
+    /// acknowledged has no effect.
+    ///
+    /// Like [Delivery::ack], the acknowledgement is sent without waiting for the server to
+    /// process it. If it's lost, such as when the connection is closed immediately afterwards,
+    /// none of the messages it covers are acknowledged, and every one of them is redelivered
+    /// once the subscriber reconnects. Messages are never partially acknowledged by a lost
+    /// acknowledgement.
+    ///
+    /// Delivery ids restart from `0` when the stream is re-opened after the
+    /// [Client](crate::Client) reconnects, at which point any unacknowledged messages are
+    /// redelivered with new delivery ids, so only the ids of messages received since then may be
+    /// acknowledged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if no message with `delivery_id` has been received on the current stream,
+    /// or if the acknowledgement fails to be sent.
+    pub async fn ack_through(&self, delivery_id: u64) -> Result<()> {
+        let acker = match self.inner.last_delivery() {
+            Some((last, acker)) if delivery_id <= last => acker,
+            _ => {
+                return Err(Error::config(
+                    "Cannot acknowledge messages that haven't been received",
+                ))
+            }
+        };
+
+        let frame = Frame::AckThrough(AckThroughPayload { delivery_id });
+        acker.lock().await.send(frame).await?;
+
+        Ok(())
+    }
+}
+
+impl<D, Item, Kind> Stream for AckSubscriber<D, Item, Kind>
+where
+    D: SubscriberDecoder<Item, Kind> + Send + Unpin,
+    Item: Unpin,
+    Kind: Unpin,
+{
+    type Item = Result<Delivery<Item>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let payload = match futures::ready!(self.inner.poll_next_unpin(cx)) {
+            Some(Ok(payload)) => payload,
+            Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+            None => return Poll::Ready(None),
+        };
+
+        let delivery = self
+            .inner
+            .last_delivery()
+            .map(|(delivery_id, acker)| Delivery {
+                payload,
+                delivery_id,
+                acker,
+            })
+            .ok_or_else(|| Error::protocol("Received a message without a delivery id"));
+
+        Poll::Ready(Some(delivery))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A message received by an [AckSubscriber], which the server holds for redelivery until it is
+/// acknowledged.
+pub struct Delivery<T> {
+    /// The decoded message payload.
+    pub payload: T,
+    delivery_id: u64,
+    acker: Acker,
+}
+
+impl<T> Delivery<T> {
+    /// Returns the id that the server assigned to the message as it was delivered, which
+    /// identifies it to [AckSubscriber::ack_through].
+    pub fn delivery_id(&self) -> u64 {
+        self.delivery_id
+    }
+
+    /// Acknowledges the message, so that the server won't redeliver it.
+    ///
+    /// The acknowledgement is sent without waiting for the server to process it, so if the
+    /// connection is closed immediately afterwards, the message may still be redelivered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the acknowledgement fails to be sent. This happens if the subscriber's
+    /// connection was lost since the message was received, in which case the message will be
+    /// redelivered.
+    pub async fn ack(&self) -> Result<()> {
+        let frame = Frame::Ack(AckPayload {
+            delivery_id: self.delivery_id,
+        });
+
+        Ok(self.acker.lock().await.send(frame).await?)
+    }
+}
@@ -1,7 +1,9 @@
+use crate::connection::SharedConnection;
 use crate::traits::TryIntoU64;
-use anyhow::Result;
-use quinn::Connection;
-use selium_common::types::Operation;
+use crate::{Error, Result};
+use futures::StreamExt;
+use selium_common::protocol::{AuthToken, Frame};
+use selium_common::types::{BiStream, Operation};
 
 /// The default `retention_policy` setting for messages.
 pub const RETENTION_POLICY_DEFAULT: u64 = 0;
@@ -22,7 +24,7 @@ pub const RETENTION_POLICY_DEFAULT: u64 = 0;
 #[derive(Debug)]
 pub struct StreamBuilder<T> {
     pub(crate) state: T,
-    pub(crate) connection: Connection,
+    pub(crate) connection: SharedConnection,
 }
 
 #[doc(hidden)]
@@ -31,6 +33,8 @@ pub struct StreamCommon {
     pub(crate) topic: String,
     pub(crate) retention_policy: u64,
     pub(crate) operations: Vec<Operation>,
+    pub(crate) token: Option<AuthToken>,
+    pub(crate) connection_per_stream: bool,
 }
 
 impl StreamCommon {
@@ -39,6 +43,8 @@ impl StreamCommon {
             topic: topic.to_owned(),
             retention_policy: RETENTION_POLICY_DEFAULT,
             operations: Vec::new(),
+            token: None,
+            connection_per_stream: false,
         }
     }
 
@@ -52,9 +58,45 @@ impl StreamCommon {
         self.operations.push(Operation::Filter(module_path.into()));
     }
 
+    #[doc(hidden)]
+    pub fn token(&mut self, token: &str) {
+        self.token = Some(AuthToken::new(token));
+    }
+
+    #[doc(hidden)]
+    pub fn connection_per_stream(&mut self, enabled: bool) {
+        self.connection_per_stream = enabled;
+    }
+
+    /// Returns the connection to open the stream on, which is a new connection dedicated to the
+    /// stream if `connection_per_stream` is enabled, or otherwise the client's `connection`.
+    pub(crate) async fn stream_connection(
+        &self,
+        connection: SharedConnection,
+    ) -> Result<SharedConnection> {
+        match self.connection_per_stream {
+            true => Ok(connection.dedicated().await?),
+            false => Ok(connection),
+        }
+    }
+
     #[doc(hidden)]
     pub fn retain<T: TryIntoU64>(&mut self, policy: T) -> Result<()> {
         self.retention_policy = policy.try_into_u64()?;
         Ok(())
     }
 }
+
+/// Waits for the server to accept a stream that presented a token on `stream`, failing with an
+/// [Error::Unauthorized] error naming the `kind` of stream if the token isn't authorized.
+pub(crate) async fn await_accept(stream: &mut BiStream, kind: &str) -> Result<()> {
+    match stream.next().await {
+        Some(Ok(Frame::Accept(_))) => Ok(()),
+        Some(Ok(Frame::Unauthorized(payload))) => Err(Error::unauthorized(format!(
+            "{kind} is not authorized to use topic {}",
+            payload.topic
+        ))),
+        Some(Err(err)) => Err(err.into()),
+        _ => Err(Error::protocol(format!("Server did not accept the {kind}"))),
+    }
+}
@@ -0,0 +1,79 @@
+use crate::Result;
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A stream that applies a closure to each message yielded by a
+/// [Subscriber](crate::Subscriber), skipping any messages for which it returns [None].
+///
+/// Errors yielded by the Subscriber are passed through as is, without invoking the closure.
+///
+/// **Note:** The FilterMap struct is never constructed directly, but rather, via
+/// [Subscriber::filter_map](crate::Subscriber::filter_map).
+pub struct FilterMap<S, F> {
+    stream: S,
+    f: F,
+}
+
+impl<S, F> FilterMap<S, F> {
+    pub(crate) fn new(stream: S, f: F) -> Self {
+        Self { stream, f }
+    }
+
+    /// Consumes the FilterMap, returning the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+// The closure is never pinned, so it doesn't need to be Unpin
+impl<S: Unpin, F> Unpin for FilterMap<S, F> {}
+
+impl<S, F, T, U> Stream for FilterMap<S, F>
+where
+    S: Stream<Item = Result<T>> + Unpin,
+    F: FnMut(T) -> Option<U>,
+{
+    type Item = Result<U>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match futures::ready!(this.stream.poll_next_unpin(cx)) {
+                Some(Ok(item)) => {
+                    if let Some(mapped) = (this.f)(item) {
+                        return Poll::Ready(Some(Ok(mapped)));
+                    }
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Any number of messages may be skipped
+        (0, self.stream.size_hint().1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use futures::stream;
+
+    #[tokio::test]
+    async fn skips_none_and_passes_errors_through() {
+        let messages = stream::iter([Ok(1), Ok(2), Err(Error::codec("bad")), Ok(3), Ok(4)]);
+        let evens = FilterMap::new(messages, |n: u32| n.is_multiple_of(2).then_some(n * 10));
+
+        let results: Vec<_> = evens.collect().await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().ok(), Some(&20));
+        assert!(matches!(results[1], Err(Error::Codec(_))));
+        assert_eq!(results[2].as_ref().ok(), Some(&40));
+    }
+}
@@ -0,0 +1,82 @@
+use super::subscriber::Acker;
+use crate::{tasks, Error, Result};
+use futures::SinkExt;
+use selium_common::protocol::{Frame, HeartbeatPayload};
+use std::task::Context;
+use std::time::Duration;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+/// How often a [Subscriber](crate::Subscriber) sends heartbeats, and how many of them may go
+/// unanswered before its connection is considered lost.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HeartbeatOptions {
+    pub interval: Duration,
+    pub max_missed: u32,
+}
+
+/// Sends heartbeats on a subscriber's stream at a fixed interval, keeping track of how many are
+/// yet to be echoed by the server.
+pub(crate) struct Heartbeats {
+    options: HeartbeatOptions,
+    ticker: Interval,
+    // The id of the next heartbeat to send
+    next_id: u64,
+    // The number of heartbeats echoed, counting those skipped by a later echo
+    echoed: u64,
+}
+
+impl Heartbeats {
+    pub fn new(options: HeartbeatOptions) -> Self {
+        Self {
+            options,
+            ticker: ticker(options.interval),
+            next_id: 0,
+            echoed: 0,
+        }
+    }
+
+    /// Starts over on a newly opened stream.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.options);
+    }
+
+    /// Records the echo of the heartbeat with the provided `id`. An echo also answers any earlier
+    /// heartbeats that have yet to be echoed, as they're echoed in the order they were sent.
+    pub fn echoed(&mut self, id: u64) {
+        self.echoed = self.echoed.max(id + 1);
+    }
+
+    /// Sends a heartbeat on the `stream` if the interval has elapsed, waking the task when the
+    /// next heartbeat is due.
+    ///
+    /// Returns [Error::ConnectionLost] if the maximum number of heartbeats have gone unanswered
+    /// by the time the next one is due.
+    pub fn poll_send(&mut self, cx: &mut Context<'_>, stream: &Acker) -> Result<()> {
+        while self.ticker.poll_tick(cx).is_ready() {
+            if self.next_id - self.echoed >= u64::from(self.options.max_missed) {
+                return Err(Error::connection_lost(format!(
+                    "{} heartbeat(s) sent at an interval of {:?} went unanswered",
+                    self.options.max_missed, self.options.interval
+                )));
+            }
+
+            let heartbeat = Frame::Heartbeat(HeartbeatPayload { id: self.next_id });
+            let stream = stream.clone();
+            self.next_id += 1;
+
+            // A failed heartbeat is only detected once it goes unanswered
+            tasks::spawn(tasks::HEARTBEAT, async move {
+                let _ = stream.lock().await.send(heartbeat).await;
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn ticker(interval: Duration) -> Interval {
+    let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+    // Heartbeats aren't sent while the subscriber isn't being polled, so they aren't caught up on
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
@@ -1,7 +1,18 @@
+mod ack_subscriber;
 mod builder;
+mod filter_map;
+mod heartbeat;
 mod publisher;
+pub(crate) mod publisher_stream;
+mod rate_limit;
+mod replier;
+mod requestor;
 mod subscriber;
 
+pub use ack_subscriber::*;
 pub use builder::*;
+pub use filter_map::*;
 pub use publisher::*;
+pub use replier::*;
+pub use requestor::*;
 pub use subscriber::*;
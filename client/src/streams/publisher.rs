@@ -1,14 +1,35 @@
 use super::builder::{StreamBuilder, StreamCommon};
-use crate::traits::{MessageEncoder, Open, Operations, Retain, SeliumCodec, TryIntoU64};
-use anyhow::Result;
+use super::publisher_stream::{PublisherStream, SharedPublisherStream};
+use super::rate_limit::RateLimiter;
+use crate::compression::{compress_payload, validate_level};
+use crate::connection::SharedConnection;
+use crate::metrics;
+use crate::traits::{
+    BorrowedMessageEncoder, MessageEncoder, Open, Operations, Retain, SeliumCodec, TryIntoU64,
+};
+use crate::{validate_topic, Error, Result};
+use anyhow::Context as _;
 use async_trait::async_trait;
-use futures::{Sink, SinkExt};
-use quinn::Connection;
-use selium_common::protocol::{Frame, PublisherPayload};
-use selium_common::types::BiStream;
+use bytes::{Bytes, BytesMut};
+use futures::future::poll_fn;
+use futures::{ready, Sink, SinkExt};
+use selium_common::protocol::{
+    check_message_size, Compression, Frame, Headers, MessagePayload, PublisherPayload,
+    DEFAULT_MAX_MESSAGE_SIZE,
+};
+use std::fmt::{self, Debug, Display};
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+
+// The capacity reserved for encoding messages into at once, which is shared by each message
+// encoded into it until it runs low
+const ENCODE_BUFFER_CAPACITY: usize = 8 * 1024;
+
+/// The default `priority` of a [Publisher]'s stream.
+pub const PRIORITY_DEFAULT: i32 = 0;
 
 #[doc(hidden)]
 #[derive(Debug)]
@@ -21,9 +42,37 @@ pub struct PublisherWantsEncoder {
 pub struct PublisherWantsOpen<E, Item> {
     common: StreamCommon,
     encoder: E,
+    compression: Compression,
+    options: PublisherOptions,
     _marker: PhantomData<Item>,
 }
 
+// The options a Publisher's stream is opened with, which its duplicates are opened with too
+#[derive(Clone, Debug)]
+struct PublisherOptions {
+    flush_interval: Option<Duration>,
+    max_message_size: u64,
+    compression_level: Option<i32>,
+    finish_on_drop: bool,
+    rate_limit: Option<u64>,
+    priority: i32,
+    connection_per_stream: bool,
+}
+
+impl Default for PublisherOptions {
+    fn default() -> Self {
+        Self {
+            flush_interval: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            compression_level: None,
+            finish_on_drop: false,
+            rate_limit: None,
+            priority: PRIORITY_DEFAULT,
+            connection_per_stream: false,
+        }
+    }
+}
+
 impl StreamBuilder<PublisherWantsEncoder> {
     /// Specifies the encoder a [Publisher](crate::Publisher) uses for encoding produced messages prior
     /// to being sent over the wire.
@@ -35,6 +84,8 @@ impl StreamBuilder<PublisherWantsEncoder> {
         let state = PublisherWantsOpen {
             common: self.state.common,
             encoder,
+            compression: Compression::None,
+            options: PublisherOptions::default(),
             _marker: PhantomData,
         };
 
@@ -45,6 +96,231 @@ impl StreamBuilder<PublisherWantsEncoder> {
     }
 }
 
+impl<E, Item> StreamBuilder<PublisherWantsOpen<E, Item>> {
+    /// Buffers sent messages, and flushes them to the `Selium` server on a periodic interval
+    /// specified in milliseconds, rather than after every send.
+    ///
+    /// By default, a [Publisher](crate::Publisher) flushes the underlying stream each time a
+    /// message is sent. Configuring a flush interval trades a small amount of latency for
+    /// increased throughput at high message rates. Invoking [flush](crate::Publisher::flush) or
+    /// [finish](crate::Publisher::finish) will always flush any buffered messages.
+    ///
+    /// Accepts any `interval` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided interval fails to be converted to a [u64].
+    pub fn flush_interval<T: TryIntoU64>(mut self, interval: T) -> Result<Self> {
+        let interval = Duration::from_millis(interval.try_into_u64()?);
+        self.state.options.flush_interval = Some(interval);
+        Ok(self)
+    }
+
+    /// Finishes the [Publisher](crate::Publisher) automatically if it's dropped without
+    /// [finish](crate::Publisher::finish) being invoked, such as when returning early on an
+    /// error.
+    ///
+    /// Dropping a Publisher otherwise abandons its stream, so any messages that are still
+    /// buffered, such as those awaiting the next flush interval, or that the server has yet to
+    /// acknowledge, may be lost. With this option enabled, the stream is handed to a background
+    /// task when the Publisher is dropped, which flushes any buffered messages and finishes the
+    /// stream. Errors are logged rather than returned, so prefer invoking
+    /// [finish](crate::Publisher::finish) wherever the outcome needs to be handled.
+    ///
+    /// **NOTE:** The background task is spawned on the [tokio] runtime that the Publisher was
+    /// opened on, which must still be running when the Publisher is dropped. If the runtime has
+    /// shut down, or shuts down before the task completes, the stream is dropped without being
+    /// finished.
+    pub fn finish_on_drop(mut self) -> Self {
+        self.state.options.finish_on_drop = true;
+        self
+    }
+
+    /// Limits the [Publisher](crate::Publisher) to sending at most `messages_per_sec` messages
+    /// per second, to avoid overwhelming a shared `Selium` server.
+    ///
+    /// Messages are paced at evenly spaced intervals, rather than sent in bursts, so sending a
+    /// message waits until the interval since the previous message has elapsed. This applies to
+    /// every method of sending messages, including each message of a
+    /// [send_batch](crate::Publisher::send_batch), whereas [try_send](crate::Publisher::try_send)
+    /// returns the item without sending it if the interval hasn't elapsed yet. The limit applies
+    /// to each Publisher separately, including those created via
+    /// [duplicate](crate::Publisher::duplicate).
+    ///
+    /// **Note:** The limit is enforced by the client, so it only protects the server from
+    /// cooperative publishers.
+    ///
+    /// Accepts any `messages_per_sec` argument that can be *fallibly* converted into a [u64] via
+    /// the [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided limit fails to be converted to a [u64], or
+    /// [Error::Config](crate::Error::Config) if it's zero.
+    pub fn rate_limit<T: TryIntoU64>(mut self, messages_per_sec: T) -> Result<Self> {
+        let messages_per_sec = messages_per_sec.try_into_u64()?;
+
+        if messages_per_sec == 0 {
+            return Err(Error::config("Rate limit must be greater than zero"));
+        }
+
+        self.state.options.rate_limit = Some(messages_per_sec);
+        Ok(self)
+    }
+
+    /// Overrides the maximum size of a message sent by the [Publisher](crate::Publisher), in
+    /// bytes, which defaults to [DEFAULT_MAX_MESSAGE_SIZE].
+    ///
+    /// The size of a message includes its encoded payload, and any topic or headers sent along
+    /// with it. Sending a message that exceeds this size fails with an
+    /// [Error::MessageTooLarge](crate::Error::MessageTooLarge) error, without writing anything to
+    /// the stream, so the [Publisher](crate::Publisher) remains usable.
+    ///
+    /// **Note:** The `Selium` server enforces its own limit on the size of the messages it
+    /// receives, so raising the limit beyond [DEFAULT_MAX_MESSAGE_SIZE] has no effect.
+    ///
+    /// Accepts any `size` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided size fails to be converted to a [u64].
+    pub fn max_message_size<T: TryIntoU64>(mut self, size: T) -> Result<Self> {
+        self.state.options.max_message_size = size.try_into_u64()?;
+        Ok(self)
+    }
+
+    /// Requests that the [Publisher](crate::Publisher) compresses its messages with the provided
+    /// [Compression] algorithm, which is negotiated with the `Selium` server when the stream is
+    /// opened.
+    ///
+    /// Messages are compressed after being encoded, and are stored and forwarded by the server
+    /// in their compressed form. Each message is tagged with the algorithm it was compressed
+    /// with, so [Subscribers](crate::Subscriber) decompress them automatically, without any
+    /// additional configuration.
+    ///
+    /// If the server doesn't permit the requested algorithm, the [Publisher](crate::Publisher)
+    /// falls back to sending uncompressed messages, rather than failing to open.
+    ///
+    /// **Note:** Subscribers must also enable the `compression` feature to decompress messages.
+    /// This differs from [CompressionCodec](crate::codecs::CompressionCodec), which requires
+    /// subscribers to decode messages with the same codec.
+    #[cfg(feature = "compression")]
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.state.compression = compression;
+        self
+    }
+
+    /// Overrides the level that the [Publisher](crate::Publisher) compresses its messages at,
+    /// trading compression speed for size. Valid levels depend on the requested
+    /// [compression](StreamBuilder::compression) algorithm:
+    ///
+    /// - [Compression::Zstd] accepts levels from 1 (fastest) to 22 (smallest), defaulting to 3.
+    /// - [Compression::Lz4] doesn't support levels.
+    ///
+    /// The level is validated when the [Publisher](crate::Publisher) is opened, so an invalid
+    /// level fails [open](crate::traits::Open::open) with an
+    /// [Error::Config](crate::Error::Config) error before the stream is opened, rather than
+    /// failing the first send. Setting a level without an algorithm is also rejected. If the
+    /// server falls back to sending uncompressed messages, the level is ignored.
+    #[cfg(feature = "compression")]
+    pub fn compression_level(mut self, level: i32) -> Self {
+        self.state.options.compression_level = Some(level);
+        self
+    }
+
+    /// Presents a bearer `token` to the `Selium` server when the [Publisher](crate::Publisher) is
+    /// opened, authorizing it to publish to its topic.
+    ///
+    /// Servers started with the `--auth-tokens` option only accept streams presenting a token
+    /// that is permitted to use their topic. The server validates the token before the stream is
+    /// accepted, so an unauthorized [Publisher](crate::Publisher) fails
+    /// [open](crate::traits::Open::open) with an [Error::Unauthorized](crate::Error::Unauthorized)
+    /// error. The token is redacted from the client's and the server's logs.
+    ///
+    /// **Note:** A [Publisher](crate::Publisher) that doesn't present a token doesn't wait for
+    /// the server to accept it, so if the server requires one, the failure is only detected when
+    /// the first message is sent.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.state.common.token(token);
+        self
+    }
+
+    /// Sets the send priority of the [Publisher](crate::Publisher)'s stream, relative to the
+    /// other streams on its connection, so that the messages of an urgent topic preempt those of
+    /// bulk topics when the connection is congested.
+    ///
+    /// Priorities can be any [i32], and default to [PRIORITY_DEFAULT](crate::PRIORITY_DEFAULT).
+    /// Whenever the connection can't send every stream's buffered messages at once, messages on
+    /// streams with a higher priority are sent first, while streams with the same priority take
+    /// turns. Priorities only take effect under contention, so an idle connection sends messages
+    /// as soon as they're written, regardless of priority.
+    ///
+    /// **Note:** Priorities only order the streams sent from this client on the same connection,
+    /// via QUIC's [stream priorities](quinn::SendStream::set_priority). They don't affect how
+    /// the `Selium` server delivers messages to subscribers, and have no effect on a Publisher
+    /// opened with [connection_per_stream](Self::connection_per_stream), which has its
+    /// connection to itself. The priority is kept if the stream is re-opened after a reconnect,
+    /// and by Publishers created via [duplicate](crate::Publisher::duplicate).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let alerts = client
+    ///     .publisher("/acmeco/alerts")
+    ///     .with_encoder(StringCodec)
+    ///     .priority(10)
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.state.options.priority = priority;
+        self
+    }
+
+    /// Opens the [Publisher](crate::Publisher) on a connection of its own, rather than on the
+    /// [Client](crate::Client)'s connection, which is shared by every other stream opened from
+    /// the client by default.
+    ///
+    /// Streams sharing a connection are multiplexed over it, so they also share its flow control
+    /// and congestion window. A Publisher sending large volumes of bulk messages can fill the
+    /// connection's send window, delaying the messages of every other stream on the connection
+    /// until the server has read enough to open it again. A dedicated connection isolates a
+    /// latency-sensitive Publisher from this, at the cost of a separate handshake when it's opened,
+    /// and the resources of an additional connection for as long as it's open.
+    ///
+    /// The dedicated connection is established with the same configuration as the client's
+    /// connection, and is closed along with it, including by
+    /// [graceful_shutdown](crate::Client::graceful_shutdown). Publishers created via
+    /// [duplicate](crate::Publisher::duplicate) are given a dedicated connection of their own.
+    /// The client's [connection_stats](crate::Client::connection_stats) and
+    /// [events](crate::Client::events) only cover the client's connection. Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let alerts = client
+    ///     .publisher("/acmeco/alerts")
+    ///     .with_encoder(StringCodec)
+    ///     .connection_per_stream(true)
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn connection_per_stream(mut self, enabled: bool) -> Self {
+        self.state.common.connection_per_stream(enabled);
+        self
+    }
+}
+
 impl<E, Item> Retain for StreamBuilder<PublisherWantsOpen<E, Item>>
 where
     E: MessageEncoder<Item>,
@@ -79,13 +355,24 @@ where
     type Output = Publisher<E, Item>;
 
     async fn open(self) -> Result<Self::Output> {
+        validate_topic(&self.state.common.topic)?;
+        validate_level(self.state.compression, self.state.options.compression_level)
+            .map_err(Error::config)?;
+
+        let options = PublisherOptions {
+            connection_per_stream: self.state.common.connection_per_stream,
+            ..self.state.options
+        };
         let headers = PublisherPayload {
             topic: self.state.common.topic,
             retention_policy: self.state.common.retention_policy,
             operations: self.state.common.operations,
+            compression: self.state.compression,
+            token: self.state.common.token,
         };
 
-        let publisher = Publisher::spawn(self.connection, headers, self.state.encoder).await?;
+        let publisher =
+            Publisher::spawn(self.connection, headers, self.state.encoder, options).await?;
 
         Ok(publisher)
     }
@@ -101,13 +388,51 @@ where
 /// contexts as a [Sink](futures::Sink). Any messages sent to the sink will be encoded with the
 /// provided encoder, before being sent over the wire.
 ///
+/// The [Sink](futures::Sink) implementation also exposes the stream's write backpressure to
+/// callers that drive the Publisher by hand, such as from a custom executor or another `Sink`.
+/// [poll_ready](futures::Sink::poll_ready) is pending until the stream, and any configured
+/// [rate limit](crate::StreamBuilder::rate_limit), can accept another message, which is then
+/// written by [start_send](futures::Sink::start_send), and delivered once
+/// [poll_flush](futures::Sink::poll_flush) completes. The [SinkExt](futures::SinkExt) helpers,
+/// such as [poll_ready_unpin](futures::SinkExt::poll_ready_unpin), avoid pinning the Publisher.
+///
+/// ```no_run
+/// # use futures::{future::poll_fn, SinkExt};
+/// # use selium::{codecs::StringCodec, prelude::*};
+/// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+/// let mut publisher = client
+///     .publisher("/acmeco/stocks")
+///     .with_encoder(StringCodec)
+///     .open()
+///     .await?;
+///
+/// poll_fn(|cx| publisher.poll_ready_unpin(cx)).await?;
+/// publisher.start_send_unpin("AAPL".to_owned())?;
+/// poll_fn(|cx| publisher.poll_flush_unpin(cx)).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
 /// **Note:** The Publisher struct is never constructed directly, but rather, via a
 /// [StreamBuilder](crate::StreamBuilder).
 pub struct Publisher<E, Item> {
-    connection: Connection,
-    stream: BiStream,
+    connection: SharedConnection,
+    // The connection dedicated to the stream, when opened with connection_per_stream, which is
+    // held to keep it open for as long as the Publisher
+    _dedicated: Option<SharedConnection>,
+    stream: SharedPublisherStream,
     headers: PublisherPayload,
     encoder: E,
+    options: PublisherOptions,
+    // The compression algorithm negotiated with the server
+    compression: Compression,
+    // The runtime to finish the stream on if the Publisher is dropped, when enabled
+    finish_on_drop: Option<Handle>,
+    // Reused to encode each message into, via the encoder's encode_into method
+    buffer: BytesMut,
+    rate_limiter: Option<RateLimiter>,
+    // The id of the next receipt sent by send_confirmed
+    next_receipt: u64,
     _marker: PhantomData<Item>,
 }
 
@@ -115,16 +440,45 @@ impl<E, Item> Publisher<E, Item>
 where
     E: MessageEncoder<Item> + Clone,
 {
-    async fn spawn(connection: Connection, headers: PublisherPayload, encoder: E) -> Result<Self> {
-        let mut stream = BiStream::try_from_connection(&connection).await?;
-        let frame = Frame::RegisterPublisher(headers.clone());
-        stream.send(frame).await?;
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "open_publisher", skip_all, fields(topic = %headers.topic), err)
+    )]
+    async fn spawn(
+        connection: SharedConnection,
+        headers: PublisherPayload,
+        encoder: E,
+        options: PublisherOptions,
+    ) -> Result<Self> {
+        let dedicated = match options.connection_per_stream {
+            true => Some(connection.dedicated().await?),
+            false => None,
+        };
+        let stream_connection = dedicated.as_ref().unwrap_or(&connection);
+
+        let (stream, compression) = PublisherStream::open(
+            stream_connection,
+            &headers,
+            options.flush_interval,
+            options.max_message_size,
+            options.priority,
+        )
+        .await?;
+        let stream = SharedPublisherStream::new(stream);
+        stream_connection.track_publisher(&stream);
 
         Ok(Self {
             connection,
+            _dedicated: dedicated,
             stream,
             headers,
             encoder,
+            compression,
+            finish_on_drop: options.finish_on_drop.then(Handle::current),
+            buffer: BytesMut::new(),
+            rate_limiter: options.rate_limit.map(RateLimiter::new),
+            options,
+            next_receipt: 0,
             _marker: PhantomData,
         })
     }
@@ -146,12 +500,436 @@ where
             self.connection.clone(),
             self.headers.clone(),
             self.encoder.clone(),
+            self.options.clone(),
         )
         .await?;
 
         Ok(publisher)
     }
 
+    /// Returns the id of the QUIC stream that the Publisher is writing to, as it's known to the
+    /// server.
+    ///
+    /// This is useful for correlating client-side logs with the server's logs when debugging.
+    /// The id stays the same for the lifetime of the Publisher, unless the stream is re-opened
+    /// after a reconnect, in which case the id of the new stream is returned.
+    pub fn stream_id(&self) -> u64 {
+        self.stream.stream_id()
+    }
+
+    /// Encodes and sends a batch of messages to the topic, flushing the underlying stream once
+    /// after all messages have been written.
+    ///
+    /// Messages are sent in the same order as they appear in `items`. Every item is encoded
+    /// before any are written, so a batch that fails to encode is not partially sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if any item fails to encode or exceeds the maximum message size,
+    /// identifying the index of the offending item, or if the batch fails to be written to the
+    /// stream.
+    pub async fn send_batch(&mut self, items: Vec<Item>) -> Result<()> {
+        let frames = items
+            .into_iter()
+            .enumerate()
+            .map(|(idx, item)| {
+                self.encode(item)
+                    .and_then(|bytes| self.message(MessagePayload::new(bytes)))
+                    .with_context(|| format!("Failed to encode batch item at index {idx}"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let count = frames.len() as u64;
+
+        for frame in frames {
+            self.acquire().await;
+            self.stream.feed(frame).await?;
+            self.consume();
+        }
+
+        self.stream.flush().await?;
+        metrics::record_published(&self.headers.topic, count);
+
+        Ok(())
+    }
+
+    /// Encodes and sends a single message to the topic, borrowing the `item` rather than taking
+    /// ownership of it, for encoders implementing [BorrowedMessageEncoder].
+    ///
+    /// This avoids cloning the data into an owned `Item` for each message when it's already held
+    /// elsewhere, such as sending a `&str` with a [StringCodec](crate::codecs::StringCodec) on a
+    /// Publisher of [String] items. Owned and borrowed items can be sent on the same Publisher.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the item fails to encode, or if the message fails to be written to the
+    /// stream.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut publisher = client
+    ///     .publisher("/acmeco/stocks")
+    ///     .with_encoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// let message = "Hello, world!".repeat(10);
+    ///
+    /// for _ in 0..100 {
+    ///     publisher.send_ref(message.as_str()).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_ref<T>(&mut self, item: &T) -> Result<()>
+    where
+        T: ?Sized,
+        E: BorrowedMessageEncoder<T>,
+    {
+        let bytes = self.encode_with(|encoder, dst| encoder.encode_ref(item, dst))?;
+        let frame = self.message(MessagePayload::new(bytes))?;
+
+        self.send_frame(frame).await?;
+        metrics::record_published(&self.headers.topic, 1);
+
+        Ok(())
+    }
+
+    /// Sends a single message to the topic whose payload has already been encoded, bypassing the
+    /// Publisher's encoder.
+    ///
+    /// This is useful for forwarding messages received by a [Subscriber](crate::Subscriber) in
+    /// [raw](crate::StreamBuilder::raw) mode, or sending payloads that were batch-encoded
+    /// upstream. The payload is otherwise treated like any other message, so it is still
+    /// compressed if compression was negotiated, and checked against the maximum message size.
+    /// Subscribers receive `bytes` exactly as if they had been produced by an encoder.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the message exceeds the maximum message size, or fails to be written to
+    /// the stream.
+    pub async fn send_raw(&mut self, bytes: Bytes) -> Result<()> {
+        let frame = self.message(MessagePayload::new(bytes))?;
+
+        self.send_frame(frame).await?;
+        metrics::record_published(&self.headers.topic, 1);
+
+        Ok(())
+    }
+
+    /// Encodes and sends a single message to the topic, with a set of [Headers] attached.
+    ///
+    /// Headers are delivered to subscribers alongside the message, and are useful for carrying
+    /// metadata, such as a `content-type` or `trace-id`, without altering the message payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the item fails to encode, or if the message fails to be written to the
+    /// stream.
+    pub async fn send_with_headers(&mut self, item: Item, headers: Headers) -> Result<()> {
+        let bytes = self.encode(item)?;
+        let frame = self.message(MessagePayload::with_headers(bytes, headers))?;
+
+        self.send_frame(frame).await?;
+        metrics::record_published(&self.headers.topic, 1);
+
+        Ok(())
+    }
+
+    /// Encodes and sends a single message to the topic, tagged with an idempotency `key`.
+    ///
+    /// The server drops the message if a message with the same key was already published to
+    /// the topic within its deduplication window, which defaults to 60 seconds, and is
+    /// configured via the server's `--dedup-window` option. This makes it safe to retry sending
+    /// the message after an ambiguous failure, such as a timeout or lost connection, without it
+    /// being delivered twice, provided the retry is sent within the window.
+    ///
+    /// Keys are shared by every publisher on the topic, so they should be unique to the message,
+    /// such as an order or event id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the item fails to encode, or if the message fails to be written to the
+    /// stream.
+    pub async fn send_with_key(&mut self, item: Item, key: &str) -> Result<()> {
+        let bytes = self.encode(item)?;
+        let frame = self.message(MessagePayload {
+            idempotency_key: Some(key.to_owned()),
+            ..MessagePayload::new(bytes)
+        })?;
+
+        self.send_frame(frame).await?;
+        metrics::record_published(&self.headers.topic, 1);
+
+        Ok(())
+    }
+
+    /// Encodes and sends a single message to the topic, which the server holds for `delay`
+    /// milliseconds before delivering it to subscribers, e.g. to schedule a retry or a reminder.
+    ///
+    /// The delay starts once the server receives the message, and delivery happens as soon as
+    /// possible once it has elapsed. Subscribers receive the message as if it had just been
+    /// published, so messages sent after it without a delay, or with a shorter one, may be
+    /// delivered ahead of it. The server accepts delays of up to a year, and drops messages with
+    /// longer delays.
+    ///
+    /// Delayed messages are held in the server's memory, rather than its topic logs, so a message
+    /// whose delay is still pending when the server restarts is lost. Once held, the message is
+    /// delivered even if the [Publisher] is closed in the meantime.
+    ///
+    /// Accepts any `delay` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided delay fails to be converted to a [u64], if the item fails to
+    /// encode, or if the message fails to be written to the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # use std::time::Duration;
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut publisher = client
+    ///     .publisher("/acmeco/reminders")
+    ///     .with_encoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// publisher
+    ///     .send_delayed("Stand up!".to_owned(), Duration::from_secs(60))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_delayed<T: TryIntoU64>(&mut self, item: Item, delay: T) -> Result<()> {
+        let delay = Duration::from_millis(delay.try_into_u64()?);
+        let bytes = self.encode(item)?;
+        let frame = self.message(MessagePayload {
+            delay: Some(delay),
+            ..MessagePayload::new(bytes)
+        })?;
+
+        self.send_frame(frame).await?;
+        metrics::record_published(&self.headers.topic, 1);
+
+        Ok(())
+    }
+
+    /// Attempts to send a single message to the topic without waiting, returning the item to the
+    /// caller if the stream isn't ready to accept it.
+    ///
+    /// This is useful for latency-sensitive producers that would rather drop or reroute messages
+    /// under backpressure than block. The message is written to the stream only if it's
+    /// immediately ready, and is then flushed as far as possible without waiting. Any remainder
+    /// is flushed by the next send, or by [flush](Publisher::flush).
+    ///
+    /// # Errors
+    ///
+    /// Returns [TrySendError::Full] with the unsent item if the stream isn't ready, or
+    /// [TrySendError::Failed] if the item fails to encode or the stream fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*, TrySendError};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut publisher = client
+    ///     .publisher("/acmeco/stocks")
+    ///     .with_encoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// match publisher.try_send("AAPL".to_owned()) {
+    ///     Ok(()) => (),
+    ///     Err(TrySendError::Full(item)) => println!("Dropping {item} under backpressure"),
+    ///     Err(TrySendError::Failed(e)) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_send(&mut self, item: Item) -> Result<(), TrySendError<Item>> {
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+        if self.poll_acquire(&mut cx).is_pending() {
+            return Err(TrySendError::Full(item));
+        }
+
+        match self.stream.poll_ready_unpin(&mut cx) {
+            Poll::Pending => return Err(TrySendError::Full(item)),
+            Poll::Ready(Err(e)) => return Err(TrySendError::Failed(e.into())),
+            Poll::Ready(Ok(())) => (),
+        }
+
+        let frame = self
+            .encode(item)
+            .and_then(|bytes| self.message(MessagePayload::new(bytes)))
+            .map_err(TrySendError::Failed)?;
+
+        self.stream
+            .start_send_unpin(frame)
+            .map_err(|e| TrySendError::Failed(e.into()))?;
+
+        self.consume();
+        metrics::record_published(&self.headers.topic, 1);
+
+        match self.stream.poll_flush_unpin(&mut cx) {
+            Poll::Ready(Err(e)) => Err(TrySendError::Failed(e.into())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Encodes and sends a single message to the topic, failing if it isn't sent within `timeout`
+    /// milliseconds.
+    ///
+    /// The timeout covers waiting for the stream to be ready, writing the message, and flushing
+    /// it, so a stalled flush, such as when the `Selium` server applies backpressure, will not
+    /// block the caller indefinitely.
+    ///
+    /// The [Publisher] remains usable after a timeout. However, if the timeout elapsed after the
+    /// message was written, it remains buffered in the stream, and will still be sent once the
+    /// stream is next flushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be converted to a [u64], if the item fails to
+    /// encode, if the message fails to be written to the stream, or if the `timeout` elapses.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # use std::time::Duration;
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut publisher = client
+    ///     .publisher("/acmeco/stocks")
+    ///     .with_encoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// publisher
+    ///     .send_with_timeout("AAPL".to_owned(), Duration::from_millis(500))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_with_timeout<T: TryIntoU64>(&mut self, item: Item, timeout: T) -> Result<()> {
+        let timeout = Duration::from_millis(timeout.try_into_u64()?);
+        let bytes = self.encode(item)?;
+        let frame = self.message(MessagePayload::new(bytes))?;
+
+        tokio::time::timeout(timeout, self.send_frame(frame))
+            .await
+            .map_err(|_| {
+                Error::timeout(format!("Timed out sending message after {timeout:?}"))
+            })??;
+
+        metrics::record_published(&self.headers.topic, 1);
+
+        Ok(())
+    }
+
+    /// Encodes and sends a single message to the topic, waiting for the `Selium` server to
+    /// confirm that it has received the message before returning.
+    ///
+    /// Whereas the other `send` methods return once the message has been written to the stream,
+    /// `send_confirmed` follows the message with a receipt, which the server echoes back once it
+    /// has received the message, and every message sent before it, and handed them on to the
+    /// topic. This is useful for producers that must know a message reached the server before
+    /// moving on, such as when committing an offset in an upstream system.
+    ///
+    /// Confirmation costs a full round trip to the server for each message, so `send_confirmed`
+    /// is considerably slower than sending messages without waiting. If a flush interval is
+    /// configured, the message is flushed immediately rather than waiting for the next interval.
+    /// To confirm a batch of messages at once, send them as usual and confirm only the last.
+    ///
+    /// A confirmed message has been received by the server, but not necessarily delivered to
+    /// subscribers, and may still be dropped as a duplicate, or held if it was delayed.
+    ///
+    /// **NOTE:** Receipts require a `Selium` server that supports them. Older servers reject the
+    /// receipt, and close the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the item fails to encode, if the message fails to be written to the
+    /// stream, or if the stream fails before the server confirms receipt. In the latter case, the
+    /// message may or may not have been received, and is not sent again automatically. Messages
+    /// that must not be delivered twice when retried should be sent with
+    /// [send_with_key](Publisher::send_with_key) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut publisher = client
+    ///     .publisher("/acmeco/orders")
+    ///     .with_encoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// publisher.send_confirmed("order-1234".to_owned()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_confirmed(&mut self, item: Item) -> Result<()> {
+        let bytes = self.encode(item)?;
+        let frame = self.message(MessagePayload::new(bytes))?;
+
+        self.send_frame(frame).await?;
+        metrics::record_published(&self.headers.topic, 1);
+
+        let id = self.next_receipt;
+        self.next_receipt = self.next_receipt.wrapping_add(1);
+
+        Ok(self.stream.confirm_sent(id).await?)
+    }
+
+    /// Flushes every message sent so far to the `Selium` server, without closing the stream.
+    ///
+    /// This allows delivery to be checkpointed while the [Publisher] remains open, such as after
+    /// sending a batch of messages with a flush interval configured, which would otherwise
+    /// remain buffered until the next interval elapses. Once `flush` returns, the messages have
+    /// been written to the underlying QUIC stream, and are delivered to subscribers without
+    /// waiting for the [Publisher] to be finished.
+    ///
+    /// Unlike [finish](Publisher::finish), the stream remains open, so the [Publisher] can
+    /// continue to send messages afterwards. Flushing doesn't wait for the server to acknowledge
+    /// the messages, which only `finish` does before the stream is closed.
+    ///
+    /// **NOTE:** This differs from [SinkExt::flush](futures::SinkExt::flush) when a flush
+    /// interval is configured, which leaves buffered messages to be flushed on the next interval.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the messages fail to be written to the stream, or if the stream has
+    /// already been finished.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut publisher = client
+    ///     .publisher("/acmeco/stocks")
+    ///     .with_encoder(StringCodec)
+    ///     .flush_interval(1_000)?
+    ///     .open()
+    ///     .await?;
+    ///
+    /// publisher.send_batch(vec!["AAPL".to_owned(), "MSFT".to_owned()]).await?;
+    /// publisher.flush().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn flush(&mut self) -> Result<()> {
+        Ok(self.stream.flush_sent().await?)
+    }
+
     /// Gracefully closes the stream.
     ///
     /// It is highly recommended to invoke this method after no new messages will be
@@ -159,13 +937,91 @@ where
     /// sent data prior to closing the connection.
     ///
     /// Under the hood, `finish` calls the [finish](quinn::SendStream::finish) on the underlying
-    /// [SendStream](quinn::SendStream).
+    /// [SendStream](quinn::SendStream). If a flush interval has been configured, any buffered
+    /// messages are flushed prior to closing the stream.
+    ///
+    /// If the stream has already been finished by a
+    /// [graceful_shutdown](crate::Client::graceful_shutdown) of the client, this method does
+    /// nothing.
     ///
     /// # Errors
     ///
     /// Returns [Err] if the stream fails to close gracefully.
-    pub async fn finish(mut self) -> Result<()> {
-        self.stream.finish().await
+    pub async fn finish(self) -> Result<()> {
+        Ok(self.stream.finish().await?)
+    }
+}
+
+impl<E, Item> Publisher<E, Item>
+where
+    E: MessageEncoder<Item>,
+{
+    /// Encodes a single message, recording the time taken to do so.
+    ///
+    /// The message is encoded into the publisher's buffer, and split off from it, so that the
+    /// buffer's allocation is shared by many messages rather than allocated for each one.
+    fn encode(&mut self, item: Item) -> Result<Bytes> {
+        self.encode_with(|encoder, dst| encoder.encode_into(item, dst))
+    }
+
+    /// Encodes a single message into the publisher's buffer with the provided `encode` function.
+    fn encode_with<F>(&mut self, encode: F) -> Result<Bytes>
+    where
+        F: FnOnce(&E, &mut BytesMut) -> anyhow::Result<()>,
+    {
+        let started = Instant::now();
+
+        if self.buffer.capacity() < ENCODE_BUFFER_CAPACITY / 4 {
+            self.buffer.reserve(ENCODE_BUFFER_CAPACITY);
+        }
+
+        if let Err(err) = encode(&self.encoder, &mut self.buffer) {
+            // Discard any part of the message that was encoded before the failure
+            self.buffer.clear();
+            return Err(Error::codec(err));
+        }
+
+        let bytes = self.buffer.split().freeze();
+        metrics::record_encode(&self.headers.topic, started);
+
+        Ok(bytes)
+    }
+
+    /// Waits for the rate limiter, if any, before sending a single frame to the stream.
+    async fn send_frame(&mut self, frame: Frame) -> Result<()> {
+        self.acquire().await;
+        self.stream.send(frame).await?;
+        self.consume();
+
+        Ok(())
+    }
+
+    async fn acquire(&mut self) {
+        poll_fn(|cx| self.poll_acquire(cx)).await
+    }
+
+    fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match &mut self.rate_limiter {
+            Some(limiter) => limiter.poll_acquire(cx),
+            None => Poll::Ready(()),
+        }
+    }
+
+    fn consume(&mut self) {
+        if let Some(limiter) = &mut self.rate_limiter {
+            limiter.consume();
+        }
+    }
+
+    /// Compresses a payload and wraps it in a frame, checking that it doesn't exceed the maximum
+    /// message size before it's handed to the stream.
+    fn message(&self, payload: MessagePayload) -> Result<Frame> {
+        let payload = compress_payload(self.compression, self.options.compression_level, payload)
+            .map_err(Error::codec)?;
+        let frame = Frame::Message(payload);
+        check_message_size(&frame, self.options.max_message_size)?;
+
+        Ok(frame)
     }
 }
 
@@ -174,22 +1030,83 @@ where
     E: MessageEncoder<Item> + Send + Unpin,
     Item: Unpin,
 {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        self.stream.poll_ready_unpin(cx)
+        ready!(self.poll_acquire(cx));
+        self.stream.poll_ready_unpin(cx).map_err(Error::from)
     }
 
     fn start_send(mut self: Pin<&mut Self>, item: Item) -> Result<()> {
-        let bytes = self.encoder.encode(item)?;
-        self.stream.start_send_unpin(Frame::Message(bytes))
+        let bytes = self.encode(item)?;
+        let frame = self.message(MessagePayload::new(bytes))?;
+        self.stream.start_send_unpin(frame)?;
+        self.consume();
+
+        metrics::record_published(&self.headers.topic, 1);
+
+        Ok(())
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        self.stream.poll_flush_unpin(cx)
+        self.stream.poll_flush_unpin(cx).map_err(Error::from)
     }
 
     fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        self.stream.poll_close_unpin(cx)
+        self.stream.poll_close_unpin(cx).map_err(Error::from)
+    }
+}
+
+impl<E, Item> Drop for Publisher<E, Item> {
+    fn drop(&mut self) {
+        if let Some(runtime) = &self.finish_on_drop {
+            self.stream.finish_on(runtime);
+        }
+    }
+}
+
+/// The error returned by [Publisher::try_send].
+pub enum TrySendError<T> {
+    /// The stream wasn't ready to accept the message without waiting. The unsent item is
+    /// returned, so that it can be retried or discarded.
+    Full(T),
+    /// The item failed to encode, or the stream failed.
+    Failed(Error),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns the unsent item, if the stream wasn't ready to accept it.
+    pub fn into_inner(self) -> Option<T> {
+        match self {
+            Self::Full(item) => Some(item),
+            Self::Failed(_) => None,
+        }
+    }
+}
+
+impl<T> Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Full(..)"),
+            Self::Failed(e) => f.debug_tuple("Failed").field(e).finish(),
+        }
+    }
+}
+
+impl<T> Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Publisher stream is not ready to send"),
+            Self::Failed(e) => write!(f, "Failed to send message: {e}"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Full(_) => None,
+            Self::Failed(e) => Some(e),
+        }
     }
 }
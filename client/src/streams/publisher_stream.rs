@@ -0,0 +1,545 @@
+use crate::connection::SharedConnection;
+use crate::tasks;
+use crate::utils::net::stream_id;
+use crate::{Error, PublisherStrategy};
+use anyhow::{anyhow, bail, Context as _, Result};
+use futures::channel::mpsc::{self, Sender};
+use futures::channel::oneshot;
+use futures::future::poll_fn;
+use futures::{ready, Sink, SinkExt, StreamExt};
+use selium_common::protocol::{Compression, Frame, PublisherPayload, ReceiptPayload};
+use selium_common::types::BiStream;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+const FLUSH_CHANNEL_SIZE: usize = 1024;
+
+/// The write side of a [Publisher](crate::Publisher).
+///
+/// By default, frames are written directly to the underlying [BiStream], which is flushed
+/// whenever the [Publisher](crate::Publisher) is flushed. When a flush interval is configured,
+/// or the connection is configured to reconnect with the [PublisherStrategy::Buffer] strategy,
+/// frames are instead handed to a background task that owns the [BiStream].
+///
+/// The background task flushes the [BiStream] periodically if a flush interval is configured,
+/// otherwise as soon as no more frames are waiting to be written. The task can also be asked to
+/// flush immediately, via [flush_sent](SharedPublisherStream::flush_sent), or to wait for the
+/// server to confirm receipt of the frames written so far, via
+/// [confirm_sent](SharedPublisherStream::confirm_sent). If the stream fails while reconnection is
+/// enabled, the task re-opens it and replays any frames that were not yet flushed.
+pub(crate) enum PublisherStream {
+    Direct(BiStream),
+    Buffered {
+        sender: Sender<Command>,
+        handle: JoinHandle<Result<()>>,
+        // The id of the stream owned by the background task, which changes if it's re-opened
+        stream_id: Arc<AtomicU64>,
+    },
+}
+
+impl PublisherStream {
+    /// Opens the stream, returning it along with the compression algorithm negotiated with the
+    /// server.
+    pub async fn open(
+        connection: &SharedConnection,
+        headers: &PublisherPayload,
+        flush_interval: Option<Duration>,
+        max_message_size: u64,
+        priority: i32,
+    ) -> Result<(Self, Compression)> {
+        let (stream, compression) =
+            register(connection, headers, max_message_size, priority).await?;
+
+        let reopen = connection
+            .reconnect_policy()
+            .filter(|policy| policy.get_publisher_strategy() == PublisherStrategy::Buffer)
+            .map(|_| Reopen {
+                connection: connection.clone(),
+                headers: headers.clone(),
+                max_message_size,
+                priority,
+            });
+
+        if flush_interval.is_none() && reopen.is_none() {
+            return Ok((Self::Direct(stream), compression));
+        }
+
+        let stream_id = Arc::new(AtomicU64::new(stream_id(stream.get_send_stream_id())));
+        let writer = Writer {
+            stream,
+            stream_id: stream_id.clone(),
+            reopen,
+            unflushed: Vec::new(),
+        };
+
+        let (sender, receiver) = mpsc::channel(FLUSH_CHANNEL_SIZE);
+        let handle = tasks::spawn(
+            tasks::PUBLISHER_WRITER,
+            run_writer(writer, receiver, flush_interval),
+        );
+
+        let stream = Self::Buffered {
+            sender,
+            handle,
+            stream_id,
+        };
+
+        Ok((stream, compression))
+    }
+
+    /// Returns a handle to the id of the underlying stream, which stays current if the stream is
+    /// re-opened.
+    fn stream_id(&self) -> Arc<AtomicU64> {
+        match self {
+            Self::Direct(stream) => {
+                Arc::new(AtomicU64::new(stream_id(stream.get_send_stream_id())))
+            }
+            Self::Buffered { stream_id, .. } => stream_id.clone(),
+        }
+    }
+
+    /// Returns a sender for requests to the background task, if the stream is buffered.
+    fn command_sender(&self) -> Option<Sender<Command>> {
+        match self {
+            Self::Direct(_) => None,
+            Self::Buffered { sender, .. } => Some(sender.clone()),
+        }
+    }
+
+    pub async fn finish(self) -> Result<()> {
+        match self {
+            Self::Direct(mut stream) => stream.finish().await,
+            Self::Buffered {
+                mut sender, handle, ..
+            } => {
+                sender.close_channel();
+                handle.await.context("Background flush task panicked")?
+            }
+        }
+    }
+}
+
+/// A handle to a [PublisherStream], shared between a [Publisher](crate::Publisher) and its
+/// [SharedConnection], so that the stream can be finished when the connection is gracefully shut
+/// down.
+///
+/// Once the stream has been finished, any further writes to it will fail.
+pub(crate) struct SharedPublisherStream {
+    inner: Arc<Mutex<Option<PublisherStream>>>,
+    stream_id: Arc<AtomicU64>,
+}
+
+/// A weak reference to a [SharedPublisherStream], which does not keep the stream alive.
+#[derive(Debug)]
+pub(crate) struct WeakPublisherStream {
+    inner: Weak<Mutex<Option<PublisherStream>>>,
+    stream_id: Arc<AtomicU64>,
+}
+
+impl SharedPublisherStream {
+    pub fn new(stream: PublisherStream) -> Self {
+        Self {
+            stream_id: stream.stream_id(),
+            inner: Arc::new(Mutex::new(Some(stream))),
+        }
+    }
+
+    pub fn downgrade(&self) -> WeakPublisherStream {
+        WeakPublisherStream {
+            inner: Arc::downgrade(&self.inner),
+            stream_id: self.stream_id.clone(),
+        }
+    }
+
+    /// Returns the id of the underlying stream, or of the stream it was last open on if it has
+    /// been finished.
+    pub fn stream_id(&self) -> u64 {
+        self.stream_id.load(Ordering::Relaxed)
+    }
+
+    /// Flushes every frame sent so far to the server, without closing the stream.
+    ///
+    /// Unlike [poll_flush](Sink::poll_flush), which leaves flushing a buffered stream to the
+    /// background task, this waits for the background task to flush the frames sent before it.
+    pub async fn flush_sent(&mut self) -> Result<()> {
+        let sender = self.with_stream(|stream| Ok(stream.command_sender()))?;
+
+        let Some(mut sender) = sender else {
+            return self.flush().await;
+        };
+
+        let (reply, flushed) = oneshot::channel();
+        sender
+            .send(Command::Flush(reply))
+            .await
+            .map_err(|_| anyhow!("Background flush task has stopped"))?;
+
+        flushed
+            .await
+            .map_err(|_| anyhow!("Background flush task has stopped"))?
+    }
+
+    /// Sends a receipt with the provided `id` after every frame sent so far, and waits for the
+    /// server to echo it back, confirming that it has received each of those frames.
+    pub async fn confirm_sent(&mut self, id: u64) -> Result<()> {
+        let sender = self.with_stream(|stream| Ok(stream.command_sender()))?;
+
+        let Some(mut sender) = sender else {
+            self.send(Frame::Receipt(ReceiptPayload { id })).await?;
+
+            return poll_fn(|cx| {
+                self.poll_stream(|stream| match stream {
+                    PublisherStream::Direct(stream) => poll_receipt(stream, cx, id),
+                    PublisherStream::Buffered { .. } => {
+                        unreachable!("Buffered streams are read by their background task")
+                    }
+                })
+            })
+            .await;
+        };
+
+        let (reply, confirmed) = oneshot::channel();
+        sender
+            .send(Command::Confirm(id, reply))
+            .await
+            .map_err(|_| anyhow!("Background flush task has stopped"))?;
+
+        confirmed
+            .await
+            .map_err(|_| anyhow!("Background flush task has stopped"))?
+    }
+
+    /// Finishes the stream, unless it has already been finished.
+    pub async fn finish(&self) -> Result<()> {
+        let stream = self.inner.lock().unwrap().take();
+
+        match stream {
+            Some(stream) => stream.finish().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Finishes the stream on a task spawned on the `runtime`, unless it has already been
+    /// finished, logging any failure to do so.
+    pub fn finish_on(&self, runtime: &Handle) {
+        let Some(stream) = self.inner.lock().unwrap().take() else {
+            return;
+        };
+
+        let finish = async move {
+            if let Err(err) = stream.finish().await {
+                log::warn!("Failed to finish dropped Publisher stream: {err:?}");
+            }
+        };
+
+        tasks::spawn_on(tasks::PUBLISHER_FINISH, finish, runtime);
+    }
+
+    fn with_stream<T>(&self, f: impl FnOnce(&mut PublisherStream) -> Result<T>) -> Result<T> {
+        match self.inner.lock().unwrap().as_mut() {
+            Some(stream) => f(stream),
+            None => Err(anyhow!("Publisher stream has already been finished")),
+        }
+    }
+
+    fn poll_stream(
+        &self,
+        f: impl FnOnce(&mut PublisherStream) -> Poll<Result<()>>,
+    ) -> Poll<Result<()>> {
+        match self.inner.lock().unwrap().as_mut() {
+            Some(stream) => f(stream),
+            None => Poll::Ready(Err(anyhow!("Publisher stream has already been finished"))),
+        }
+    }
+}
+
+impl WeakPublisherStream {
+    pub fn upgrade(&self) -> Option<SharedPublisherStream> {
+        self.inner.upgrade().map(|inner| SharedPublisherStream {
+            inner,
+            stream_id: self.stream_id.clone(),
+        })
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.inner.strong_count() == 0
+    }
+}
+
+impl Sink<Frame> for SharedPublisherStream {
+    type Error = anyhow::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_stream(|stream| stream.poll_ready_unpin(cx))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Frame) -> Result<()> {
+        self.with_stream(|stream| stream.start_send_unpin(item))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_stream(|stream| stream.poll_flush_unpin(cx))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.poll_stream(|stream| stream.poll_close_unpin(cx))
+    }
+}
+
+/// Opens a new stream and registers it as a publisher for the topic.
+///
+/// If the publisher requests compression or presents a token, the server replies with the
+/// algorithm that it should compress its messages with, which is returned along with the stream.
+/// If the token doesn't authorize the publisher, the server rejects it instead.
+async fn register(
+    connection: &SharedConnection,
+    headers: &PublisherPayload,
+    max_message_size: u64,
+    priority: i32,
+) -> Result<(BiStream, Compression)> {
+    let mut stream = connection.open_stream().await?;
+    stream.set_priority(priority)?;
+    let frame = Frame::RegisterPublisher(headers.clone());
+    stream.send(frame).await?;
+
+    let compression = if headers.compression.is_none() && headers.token.is_none() {
+        Compression::None
+    } else {
+        match stream.next().await {
+            Some(Ok(Frame::Accept(payload))) => payload.compression,
+            Some(Ok(Frame::Unauthorized(payload))) => {
+                return Err(Error::unauthorized(format!(
+                    "Publisher is not authorized to publish to topic {}",
+                    payload.topic
+                ))
+                .into())
+            }
+            Some(Err(err)) => return Err(err),
+            _ => bail!("Server did not accept the Publisher's registration"),
+        }
+    };
+
+    // Only applied once registered, so that the limit doesn't apply to the registration frame
+    stream.set_max_message_size(max_message_size);
+
+    Ok((stream, compression))
+}
+
+/// Polls `stream` for the echo of the receipt with the provided `id`, skipping the echoes of any
+/// earlier receipts that are no longer being waited for.
+fn poll_receipt(stream: &mut BiStream, cx: &mut Context<'_>, id: u64) -> Poll<Result<()>> {
+    loop {
+        match ready!(stream.poll_next_unpin(cx)) {
+            Some(Ok(Frame::Receipt(receipt))) if receipt.id == id => return Poll::Ready(Ok(())),
+            Some(Ok(_)) => (),
+            Some(Err(err)) => return Poll::Ready(Err(err)),
+            None => {
+                return Poll::Ready(Err(anyhow!(
+                    "Server closed the Publisher stream before confirming receipt"
+                )))
+            }
+        }
+    }
+}
+
+/// The configuration required to re-open a publisher stream after it fails.
+struct Reopen {
+    connection: SharedConnection,
+    headers: PublisherPayload,
+    max_message_size: u64,
+    priority: i32,
+}
+
+/// A request handed to the background task that owns a buffered stream.
+// Frames are written far more often than flushes are requested, so they aren't boxed
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Command {
+    Write(Frame),
+    // Flushes the frames written before it, replying once they've been flushed
+    Flush(oneshot::Sender<Result<()>>),
+    // Sends a receipt with the given id after the frames written before it, replying once the
+    // server has confirmed receiving them
+    Confirm(u64, oneshot::Sender<Result<()>>),
+}
+
+struct Writer {
+    stream: BiStream,
+    stream_id: Arc<AtomicU64>,
+    reopen: Option<Reopen>,
+    // Frames written since the last successful flush, which are replayed if the stream fails
+    unflushed: Vec<Frame>,
+}
+
+impl Writer {
+    async fn feed(&mut self, frame: Frame) -> Result<()> {
+        if self.reopen.is_some() {
+            self.unflushed.push(frame.clone());
+        }
+
+        if let Err(err) = self.stream.feed(frame).await {
+            self.recover(err).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if let Err(err) = self.stream.flush().await {
+            self.recover(err).await?;
+        }
+
+        self.unflushed.clear();
+        Ok(())
+    }
+
+    /// Sends a receipt after the frames written so far, and waits for the server to echo it.
+    ///
+    /// The frames are no longer replayed once they've been flushed, so if the stream fails while
+    /// waiting, the caller is left to decide whether to send them again.
+    async fn confirm(&mut self, id: u64) -> Result<()> {
+        self.feed(Frame::Receipt(ReceiptPayload { id })).await?;
+        self.flush().await?;
+
+        poll_fn(|cx| poll_receipt(&mut self.stream, cx, id)).await
+    }
+
+    async fn handle(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Write(frame) => self.feed(frame).await,
+            Command::Flush(reply) => match self.flush().await {
+                Ok(()) => {
+                    let _ = reply.send(Ok(()));
+                    Ok(())
+                }
+                Err(err) => {
+                    // The error is handed to the caller awaiting the flush, so the task stops
+                    // with a summary of it
+                    let _ = reply.send(Err(err));
+                    bail!("Failed to flush Publisher stream")
+                }
+            },
+            Command::Confirm(id, reply) => match self.confirm(id).await {
+                Ok(()) => {
+                    let _ = reply.send(Ok(()));
+                    Ok(())
+                }
+                Err(err) => {
+                    let _ = reply.send(Err(err));
+                    bail!("Failed to confirm receipt of Publisher stream")
+                }
+            },
+        }
+    }
+
+    async fn recover(&mut self, err: anyhow::Error) -> Result<()> {
+        let reopen = match &self.reopen {
+            Some(reopen) => reopen,
+            None => return Err(err),
+        };
+
+        // Messages are tagged with the algorithm they were compressed with, so they remain
+        // readable even if the server negotiates a different algorithm for the new stream
+        let (mut stream, _) = register(
+            &reopen.connection,
+            &reopen.headers,
+            reopen.max_message_size,
+            reopen.priority,
+        )
+        .await
+        .context("Failed to re-open Publisher stream")?;
+
+        for frame in self.unflushed.iter() {
+            stream.feed(frame.clone()).await?;
+        }
+
+        stream.flush().await?;
+        self.stream_id
+            .store(stream_id(stream.get_send_stream_id()), Ordering::Relaxed);
+        self.stream = stream;
+        self.unflushed.clear();
+
+        Ok(())
+    }
+}
+
+async fn run_writer(
+    mut writer: Writer,
+    mut receiver: mpsc::Receiver<Command>,
+    flush_interval: Option<Duration>,
+) -> Result<()> {
+    match flush_interval {
+        Some(interval) => {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    command = receiver.next() => match command {
+                        Some(command) => writer.handle(command).await?,
+                        None => break,
+                    },
+                    _ = ticker.tick() => writer.flush().await?,
+                }
+            }
+        }
+        None => {
+            while let Some(command) = receiver.next().await {
+                writer.handle(command).await?;
+
+                while let Ok(Some(command)) = receiver.try_next() {
+                    writer.handle(command).await?;
+                }
+
+                writer.flush().await?;
+            }
+        }
+    }
+
+    writer.flush().await?;
+    writer.stream.finish().await
+}
+
+impl Sink<Frame> for PublisherStream {
+    type Error = anyhow::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.get_mut() {
+            Self::Direct(stream) => stream.poll_ready_unpin(cx),
+            Self::Buffered { sender, .. } => sender
+                .poll_ready_unpin(cx)
+                .map_err(|_| anyhow::anyhow!("Background flush task has stopped")),
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Frame) -> Result<()> {
+        match self.get_mut() {
+            Self::Direct(stream) => stream.start_send_unpin(item),
+            Self::Buffered { sender, .. } => sender
+                .start_send_unpin(Command::Write(item))
+                .context("Background flush task has stopped"),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.get_mut() {
+            Self::Direct(stream) => stream.poll_flush_unpin(cx),
+            // Flushing is deferred to the background task
+            Self::Buffered { .. } => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self.get_mut() {
+            Self::Direct(stream) => stream.poll_close_unpin(cx),
+            Self::Buffered { sender, .. } => sender
+                .poll_close_unpin(cx)
+                .map_err(|_| anyhow::anyhow!("Background flush task has stopped")),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{Instant, Sleep};
+
+/// Paces the messages sent by a [Publisher](crate::Publisher) with a token bucket that holds a
+/// single token, so that messages are sent at evenly spaced intervals, rather than in bursts.
+///
+/// A token is acquired before each message is written to the stream, and consumed once the
+/// message has been written, so a message that fails to be written doesn't use up the token.
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    // The time at which the next token is added to the bucket
+    next: Instant,
+    acquired: bool,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl RateLimiter {
+    pub fn new(messages_per_sec: u64) -> Self {
+        let now = Instant::now();
+
+        Self {
+            interval: Duration::from_nanos(1_000_000_000 / messages_per_sec),
+            next: now,
+            acquired: false,
+            sleep: Box::pin(tokio::time::sleep_until(now)),
+        }
+    }
+
+    /// Acquires a token, waking the task once one is added to the bucket if it's empty. Acquiring
+    /// a token again before it's consumed returns the same token.
+    pub fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.acquired {
+            return Poll::Ready(());
+        }
+
+        let now = Instant::now();
+
+        if now < self.next {
+            self.sleep.as_mut().reset(self.next);
+
+            if self.sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+
+        // An idle publisher's bucket holds no more than one token, so it doesn't burst once it
+        // resumes sending
+        self.next = self.next.max(now) + self.interval;
+        self.acquired = true;
+
+        Poll::Ready(())
+    }
+
+    /// Consumes the acquired token, once the message it was acquired for has been written.
+    pub fn consume(&mut self) {
+        self.acquired = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::poll_fn;
+
+    #[tokio::test]
+    async fn paces_tokens_at_rate() {
+        let mut limiter = RateLimiter::new(100);
+        let started = Instant::now();
+
+        for _ in 0..11 {
+            poll_fn(|cx| limiter.poll_acquire(cx)).await;
+            limiter.consume();
+        }
+
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn reacquires_same_token_until_consumed() {
+        let mut limiter = RateLimiter::new(1);
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+        assert!(limiter.poll_acquire(&mut cx).is_ready());
+        assert!(limiter.poll_acquire(&mut cx).is_ready());
+
+        limiter.consume();
+
+        assert!(limiter.poll_acquire(&mut cx).is_pending());
+    }
+}
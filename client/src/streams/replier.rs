@@ -0,0 +1,250 @@
+use super::builder::{await_accept, StreamBuilder, StreamCommon};
+use crate::connection::SharedConnection;
+use crate::traits::{MessageDecoder, MessageEncoder, Open};
+use crate::{validate_topic, Error, Result};
+use async_trait::async_trait;
+use bytes::BytesMut;
+use futures::{Future, SinkExt, TryStreamExt};
+use selium_common::protocol::{
+    AuthToken, Frame, MessagePayload, PublisherPayload, StartPosition, SubscriberPayload,
+};
+use selium_common::types::BiStream;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ReplierWantsDecoder {
+    pub(crate) common: StreamCommon,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ReplierWantsEncoder<D, ReqItem> {
+    common: StreamCommon,
+    decoder: D,
+    _marker: PhantomData<ReqItem>,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ReplierWantsOpen<D, E, ReqItem, ResItem> {
+    common: StreamCommon,
+    decoder: D,
+    encoder: E,
+    _marker: PhantomData<(ReqItem, ResItem)>,
+}
+
+impl StreamBuilder<ReplierWantsDecoder> {
+    /// Specifies the decoder a [Replier](crate::Replier) uses for decoding requests received over
+    /// the wire.
+    pub fn with_decoder<D, ReqItem>(
+        self,
+        decoder: D,
+    ) -> StreamBuilder<ReplierWantsEncoder<D, ReqItem>> {
+        let state = ReplierWantsEncoder {
+            common: self.state.common,
+            decoder,
+            _marker: PhantomData,
+        };
+
+        StreamBuilder {
+            state,
+            connection: self.connection,
+        }
+    }
+}
+
+impl<D, ReqItem> StreamBuilder<ReplierWantsEncoder<D, ReqItem>> {
+    /// Specifies the encoder a [Replier](crate::Replier) uses for encoding replies prior to
+    /// sending them over the wire.
+    pub fn with_encoder<E, ResItem>(
+        self,
+        encoder: E,
+    ) -> StreamBuilder<ReplierWantsOpen<D, E, ReqItem, ResItem>> {
+        let state = ReplierWantsOpen {
+            common: self.state.common,
+            decoder: self.state.decoder,
+            encoder,
+            _marker: PhantomData,
+        };
+
+        StreamBuilder {
+            state,
+            connection: self.connection,
+        }
+    }
+}
+
+impl<D, E, ReqItem, ResItem> StreamBuilder<ReplierWantsOpen<D, E, ReqItem, ResItem>> {
+    /// Presents a bearer `token` to the `Selium` server when the [Replier](crate::Replier) is
+    /// opened, authorizing it to subscribe to requests on its topic, and to publish its replies.
+    ///
+    /// Servers started with the `--auth-tokens` option only accept streams presenting a token
+    /// that is permitted to use their topic. As replies are published to a topic under
+    /// `/selium/replies`, the token must be permitted to use those topics as well as the
+    /// request topic. An unauthorized [Replier](crate::Replier) fails
+    /// [open](crate::traits::Open::open) with an [Error::Unauthorized](crate::Error::Unauthorized)
+    /// error, or fails to [serve](crate::Replier::serve) its first reply.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.state.common.token(token);
+        self
+    }
+}
+
+#[async_trait]
+impl<D, E, ReqItem, ResItem> Open for StreamBuilder<ReplierWantsOpen<D, E, ReqItem, ResItem>>
+where
+    D: MessageDecoder<ReqItem> + Send,
+    E: MessageEncoder<ResItem> + Send,
+    ReqItem: Send,
+    ResItem: Send,
+{
+    type Output = Replier<D, E, ReqItem, ResItem>;
+
+    async fn open(self) -> Result<Self::Output> {
+        let common = self.state.common;
+        validate_topic(&common.topic)?;
+
+        // The server accepts the subscription once it's attached, so the Replier is ready to
+        // handle requests as soon as it's opened
+        let mut requests = self.connection.open_stream().await?;
+        requests
+            .send(Frame::RegisterSubscriber(SubscriberPayload {
+                topic: common.topic,
+                retention_policy: common.retention_policy,
+                operations: common.operations,
+                token: common.token.clone(),
+                start_position: Some(StartPosition::FromConnect),
+                ..Default::default()
+            }))
+            .await?;
+
+        await_accept(&mut requests, "Replier").await?;
+        requests.finish().await?;
+
+        Ok(Replier {
+            connection: self.connection,
+            requests,
+            replies: HashMap::new(),
+            retention_policy: common.retention_policy,
+            token: common.token,
+            decoder: self.state.decoder,
+            encoder: self.state.encoder,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// The replying half of the request/reply pattern, which subscribes to requests on a topic, and
+/// publishes a reply to each.
+///
+/// Replies are published to the topic nominated by each [Requestor](crate::Requestor), tagged
+/// with the correlation id of the request. Messages on the topic that were not sent by a
+/// [Requestor](crate::Requestor) are ignored.
+///
+/// **Note:** The Replier struct is never constructed directly, but rather, via a
+/// [StreamBuilder](crate::StreamBuilder).
+pub struct Replier<D, E, ReqItem, ResItem> {
+    connection: SharedConnection,
+    requests: BiStream,
+    // Publisher streams for each reply topic, opened on the first reply to that topic
+    replies: HashMap<String, BiStream>,
+    retention_policy: u64,
+    token: Option<AuthToken>,
+    decoder: D,
+    encoder: E,
+    _marker: PhantomData<(ReqItem, ResItem)>,
+}
+
+impl<D, E, ReqItem, ResItem> Replier<D, E, ReqItem, ResItem>
+where
+    D: MessageDecoder<ReqItem>,
+    E: MessageEncoder<ResItem>,
+{
+    /// Handles requests until the request stream ends, publishing the result of the provided
+    /// `handler` as the reply to each request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if a request fails to be decoded, if the `handler` returns an error, or if a
+    /// reply fails to be encoded or sent.
+    ///
+    /// # Examples
+    ///
+    /// An echo service, which replies to each request with the request itself.
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let replier = client
+    ///     .replier("/acmeco/echo")
+    ///     .with_decoder(StringCodec)
+    ///     .with_encoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// replier.serve(|request: String| async move { Ok(request) }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn serve<F, Fut>(mut self, mut handler: F) -> Result<()>
+    where
+        F: FnMut(ReqItem) -> Fut,
+        Fut: Future<Output = anyhow::Result<ResItem>>,
+    {
+        while let Some(frame) = self.requests.try_next().await? {
+            let (id, reply_to, message) = match frame {
+                Frame::Message(MessagePayload {
+                    correlation_id: Some(id),
+                    reply_to: Some(reply_to),
+                    message,
+                    ..
+                }) => (id, reply_to, message),
+                _ => continue,
+            };
+
+            let request = self
+                .decoder
+                .decode(&mut BytesMut::from(&message[..]))
+                .map_err(Error::codec)?;
+            let reply = handler(request).await.map_err(Error::other)?;
+            let reply = self.encoder.encode(reply).map_err(Error::codec)?;
+
+            let payload = MessagePayload {
+                correlation_id: Some(id),
+                ..MessagePayload::new(reply)
+            };
+
+            self.reply_stream(reply_to)
+                .await?
+                .send(Frame::Message(payload))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn reply_stream(&mut self, topic: String) -> anyhow::Result<&mut BiStream> {
+        if !self.replies.contains_key(&topic) {
+            let mut stream = self.connection.open_stream().await?;
+            stream
+                .send(Frame::RegisterPublisher(PublisherPayload {
+                    topic: topic.clone(),
+                    retention_policy: self.retention_policy,
+                    operations: Vec::new(),
+                    token: self.token.clone(),
+                    ..Default::default()
+                }))
+                .await?;
+
+            if self.token.is_some() {
+                await_accept(&mut stream, "Replier").await?;
+            }
+
+            self.replies.insert(topic.clone(), stream);
+        }
+
+        Ok(self.replies.get_mut(&topic).unwrap())
+    }
+}
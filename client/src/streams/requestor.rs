@@ -0,0 +1,257 @@
+use super::builder::{await_accept, StreamBuilder, StreamCommon};
+use crate::traits::{MessageDecoder, MessageEncoder, Open, TryIntoU64};
+use crate::{validate_topic, Error, Result};
+use async_trait::async_trait;
+use bytes::BytesMut;
+use futures::{SinkExt, TryStreamExt};
+use selium_common::protocol::{
+    Frame, MessagePayload, PublisherPayload, StartPosition, SubscriberPayload,
+};
+use selium_common::types::BiStream;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// The default `request_timeout` for a [Requestor](crate::Requestor), in milliseconds.
+pub const REQUEST_TIMEOUT_DEFAULT: u64 = 5_000;
+
+/// The namespace under which each [Requestor](crate::Requestor) subscribes to its replies.
+const REPLY_TOPIC_PREFIX: &str = "/selium/replies";
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct RequestorWantsEncoder {
+    pub(crate) common: StreamCommon,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct RequestorWantsDecoder<E, ReqItem> {
+    common: StreamCommon,
+    encoder: E,
+    _marker: PhantomData<ReqItem>,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct RequestorWantsOpen<E, D, ReqItem, ResItem> {
+    common: StreamCommon,
+    encoder: E,
+    decoder: D,
+    request_timeout: u64,
+    _marker: PhantomData<(ReqItem, ResItem)>,
+}
+
+impl StreamBuilder<RequestorWantsEncoder> {
+    /// Specifies the encoder a [Requestor](crate::Requestor) uses for encoding requests prior to
+    /// sending them over the wire.
+    pub fn with_encoder<E, ReqItem>(
+        self,
+        encoder: E,
+    ) -> StreamBuilder<RequestorWantsDecoder<E, ReqItem>> {
+        let state = RequestorWantsDecoder {
+            common: self.state.common,
+            encoder,
+            _marker: PhantomData,
+        };
+
+        StreamBuilder {
+            state,
+            connection: self.connection,
+        }
+    }
+}
+
+impl<E, ReqItem> StreamBuilder<RequestorWantsDecoder<E, ReqItem>> {
+    /// Specifies the decoder a [Requestor](crate::Requestor) uses for decoding replies received
+    /// over the wire.
+    pub fn with_decoder<D, ResItem>(
+        self,
+        decoder: D,
+    ) -> StreamBuilder<RequestorWantsOpen<E, D, ReqItem, ResItem>> {
+        let state = RequestorWantsOpen {
+            common: self.state.common,
+            encoder: self.state.encoder,
+            decoder,
+            request_timeout: REQUEST_TIMEOUT_DEFAULT,
+            _marker: PhantomData,
+        };
+
+        StreamBuilder {
+            state,
+            connection: self.connection,
+        }
+    }
+}
+
+impl<E, D, ReqItem, ResItem> StreamBuilder<RequestorWantsOpen<E, D, ReqItem, ResItem>> {
+    /// Overrides the time in milliseconds that the [Requestor](crate::Requestor) waits for a
+    /// reply to each request, before returning an error. See [REQUEST_TIMEOUT_DEFAULT] for the
+    /// default timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be converted to a [u64].
+    pub fn request_timeout<T: TryIntoU64>(mut self, timeout: T) -> Result<Self> {
+        self.state.request_timeout = timeout.try_into_u64()?;
+        Ok(self)
+    }
+
+    /// Presents a bearer `token` to the `Selium` server when the [Requestor](crate::Requestor)
+    /// is opened, authorizing it to send requests to its topic, and to subscribe to its replies.
+    ///
+    /// Servers started with the `--auth-tokens` option only accept streams presenting a token
+    /// that is permitted to use their topic. As replies are received on a topic under
+    /// `/selium/replies`, the token must be permitted to use those topics as well as the
+    /// request topic. An unauthorized [Requestor](crate::Requestor) fails
+    /// [open](crate::traits::Open::open) with an [Error::Unauthorized](crate::Error::Unauthorized)
+    /// error.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.state.common.token(token);
+        self
+    }
+}
+
+#[async_trait]
+impl<E, D, ReqItem, ResItem> Open for StreamBuilder<RequestorWantsOpen<E, D, ReqItem, ResItem>>
+where
+    E: MessageEncoder<ReqItem> + Send,
+    D: MessageDecoder<ResItem> + Send,
+    ReqItem: Send,
+    ResItem: Send,
+{
+    type Output = Requestor<E, D, ReqItem, ResItem>;
+
+    async fn open(self) -> Result<Self::Output> {
+        let common = self.state.common;
+        validate_topic(&common.topic)?;
+
+        let reply_topic = format!("{REPLY_TOPIC_PREFIX}/{:016x}", rand::random::<u64>());
+
+        // Subscribe to replies before any requests can be sent. The server accepts the
+        // subscription once it's attached, so no reply can be missed.
+        let mut replies = self.connection.open_stream().await?;
+        replies
+            .send(Frame::RegisterSubscriber(SubscriberPayload {
+                topic: reply_topic.clone(),
+                retention_policy: common.retention_policy,
+                operations: Vec::new(),
+                token: common.token.clone(),
+                start_position: Some(StartPosition::FromConnect),
+                ..Default::default()
+            }))
+            .await?;
+
+        await_accept(&mut replies, "Requestor").await?;
+        replies.finish().await?;
+
+        let mut requests = self.connection.open_stream().await?;
+        requests
+            .send(Frame::RegisterPublisher(PublisherPayload {
+                topic: common.topic,
+                retention_policy: common.retention_policy,
+                operations: common.operations,
+                token: common.token.clone(),
+                ..Default::default()
+            }))
+            .await?;
+
+        if common.token.is_some() {
+            await_accept(&mut requests, "Requestor").await?;
+        }
+
+        Ok(Requestor {
+            requests,
+            replies,
+            reply_topic,
+            encoder: self.state.encoder,
+            decoder: self.state.decoder,
+            request_timeout: Duration::from_millis(self.state.request_timeout),
+            next_id: 0,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// The requesting half of the request/reply pattern, which sends requests to a topic and awaits
+/// the reply to each.
+///
+/// Each request is published to the topic with a unique correlation id, along with the topic
+/// that the [Requestor] receives its replies on. A [Replier](crate::Replier) subscribed to the
+/// topic handles the request, and publishes its reply back with the same correlation id.
+///
+/// Requests are sent one at a time, so to make concurrent requests, open multiple [Requestor]
+/// streams from the same [Client](crate::Client).
+///
+/// **Note:** The Requestor struct is never constructed directly, but rather, via a
+/// [StreamBuilder](crate::StreamBuilder).
+pub struct Requestor<E, D, ReqItem, ResItem> {
+    requests: BiStream,
+    replies: BiStream,
+    reply_topic: String,
+    encoder: E,
+    decoder: D,
+    request_timeout: Duration,
+    next_id: u64,
+    _marker: PhantomData<(ReqItem, ResItem)>,
+}
+
+impl<E, D, ReqItem, ResItem> Requestor<E, D, ReqItem, ResItem>
+where
+    E: MessageEncoder<ReqItem>,
+    D: MessageDecoder<ResItem>,
+{
+    /// Encodes and sends a request to the topic, and waits for the matching reply.
+    ///
+    /// Replies that arrive after their request has timed out are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the request fails to be encoded or sent, if the reply fails to be
+    /// decoded, or if no reply is received before the `request_timeout` elapses.
+    pub async fn request(&mut self, item: ReqItem) -> Result<ResItem> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let payload = MessagePayload {
+            correlation_id: Some(id),
+            reply_to: Some(self.reply_topic.clone()),
+            ..MessagePayload::new(self.encoder.encode(item).map_err(Error::codec)?)
+        };
+
+        self.requests.send(Frame::Message(payload)).await?;
+
+        let timeout = self.request_timeout;
+
+        tokio::time::timeout(timeout, self.await_reply(id))
+            .await
+            .map_err(|_| {
+                Error::timeout(format!("Timed out waiting for a reply after {timeout:?}"))
+            })?
+    }
+
+    async fn await_reply(&mut self, id: u64) -> Result<ResItem> {
+        while let Some(frame) = self.replies.try_next().await? {
+            match frame {
+                Frame::Message(payload) if payload.correlation_id == Some(id) => {
+                    let mut buffer = BytesMut::from(&payload.message[..]);
+                    return self.decoder.decode(&mut buffer).map_err(Error::codec);
+                }
+                // A late reply to an earlier request that has already timed out
+                _ => continue,
+            }
+        }
+
+        Err(Error::connection(
+            "Reply stream closed before a reply was received",
+        ))
+    }
+
+    /// Gracefully closes the request stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the stream fails to close gracefully.
+    pub async fn finish(mut self) -> Result<()> {
+        Ok(self.requests.finish().await?)
+    }
+}
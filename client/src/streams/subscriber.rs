@@ -1,15 +1,47 @@
-use crate::traits::{MessageDecoder, Open, Operations, Retain, SeliumCodec, TryIntoU64};
-use crate::{StreamBuilder, StreamCommon};
-use anyhow::Result;
+use super::heartbeat::{HeartbeatOptions, Heartbeats};
+use crate::codecs::CodecRegistry;
+use crate::compression::decompress_payload;
+use crate::connection::SharedConnection;
+use crate::metrics;
+use crate::tasks;
+use crate::traits::{
+    DecodedFrame, FromMessageParts, Open, Operations, RawDecoder, RawFrames, RegistryDecoder,
+    Retain, SeliumCodec, SubscriberDecoder, SyncDecoder, TryIntoU64, WithMetadata,
+    WithMetadataDecoder,
+};
+use crate::utils::net::stream_id;
+use crate::{validate_topic_pattern, Error, FilterMap, Result, StreamBuilder, StreamCommon};
 use async_trait::async_trait;
-use bytes::BytesMut;
+use bytes::Bytes;
+use futures::future::BoxFuture;
 use futures::{SinkExt, Stream, StreamExt};
-use quinn::Connection;
-use selium_common::protocol::{Frame, SubscriberPayload};
-use selium_common::types::BiStream;
+use quinn::{Connection, VarInt};
+use selium_common::protocol::{
+    CommitPayload, Frame, FramingErrorPolicy, Headers, HeartbeatPayload, MessageCodec, ReplayStart,
+    StartPosition, SubscriberPayload, UnauthorizedPayload, DEFAULT_MAX_MESSAGE_SIZE,
+};
+use selium_common::types::{BiStream, BiStreamRead, BiStreamWrite};
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+
+/// The write half of a subscriber's stream, shared with each [Delivery](crate::Delivery) that
+/// needs to be acknowledged on it, and used to send heartbeats.
+pub(crate) type Acker = Arc<Mutex<BiStreamWrite>>;
+
+/// Determines how a [Subscriber](crate::Subscriber) handles a message that fails to be decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecodeErrorPolicy {
+    /// The message is logged and skipped, and the stream continues with the next message.
+    SkipAndContinue,
+    /// The stream yields an [Error::Codec](crate::Error::Codec) for the message. The stream is
+    /// not ended by the error, so it can continue to be polled for subsequent messages.
+    #[default]
+    Fail,
+}
 
 #[doc(hidden)]
 #[derive(Debug)]
@@ -19,23 +51,612 @@ pub struct SubscriberWantsDecoder {
 
 #[doc(hidden)]
 #[derive(Debug)]
-pub struct SubscriberWantsOpen<D, Item> {
-    common: StreamCommon,
+pub struct SubscriberWantsOpen<D, Item, Kind = SyncDecoder> {
+    pub(crate) common: StreamCommon,
     decoder: D,
-    _marker: PhantomData<Item>,
+    group: Option<String>,
+    replay: Option<ReplayStart>,
+    start_position: Option<StartPosition>,
+    ordered: bool,
+    heartbeat: Option<HeartbeatOptions>,
+    decode_error_policy: DecodeErrorPolicy,
+    max_message_size: u64,
+    max_buffer_capacity: Option<u64>,
+    framing_error_policy: FramingErrorPolicy,
+    _marker: PhantomData<(Item, Kind)>,
+}
+
+/// The builder state of a [Subscriber](crate::Subscriber) that combines each message with its
+/// metadata.
+#[doc(hidden)]
+pub type MetadataSubscriberWantsOpen<D, Item, Kind, Out> =
+    SubscriberWantsOpen<WithMetadata<D>, Out, WithMetadataDecoder<Kind, Item>>;
+
+/// The builder state of a [Subscriber](crate::Subscriber) that yields undecoded message payloads.
+#[doc(hidden)]
+pub type RawSubscriberWantsOpen = SubscriberWantsOpen<RawFrames, Bytes, RawDecoder>;
+
+/// A decoded message, along with the topic it was published to.
+///
+/// Yielded by a [Subscriber](crate::Subscriber) opened with `with_topic_metadata`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicMessage<T> {
+    /// The topic the message was published to.
+    pub topic: String,
+    /// The decoded message payload.
+    pub payload: T,
+}
+
+impl<T> FromMessageParts<T> for TopicMessage<T> {
+    fn from_parts(topic: String, _headers: Headers, _offset: Option<u64>, payload: T) -> Self {
+        Self { topic, payload }
+    }
+}
+
+/// A decoded message, along with the [Headers] attached to it by the publisher.
+///
+/// Yielded by a [Subscriber](crate::Subscriber) opened with `with_headers`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageWithHeaders<T> {
+    /// The topic the message was published to.
+    pub topic: String,
+    /// The headers attached to the message, which are empty if none were sent.
+    pub headers: Headers,
+    /// The decoded message payload.
+    pub payload: T,
+}
+
+impl<T> FromMessageParts<T> for MessageWithHeaders<T> {
+    fn from_parts(topic: String, headers: Headers, _offset: Option<u64>, payload: T) -> Self {
+        Self {
+            topic,
+            headers,
+            payload,
+        }
+    }
+}
+
+/// A decoded message, along with the offset assigned to it in the topic's log.
+///
+/// Yielded by a [Subscriber](crate::Subscriber) opened with `with_offsets`, so that a consumer
+/// group can [commit](crate::Subscriber::commit) the offsets of the messages it has processed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetMessage<T> {
+    /// The offset of the message in the topic's log, which is [None] if the server isn't
+    /// persisting the topic's log.
+    pub offset: Option<u64>,
+    /// The decoded message payload.
+    pub payload: T,
+}
+
+impl<T> FromMessageParts<T> for OffsetMessage<T> {
+    fn from_parts(_topic: String, _headers: Headers, offset: Option<u64>, payload: T) -> Self {
+        Self { offset, payload }
+    }
 }
 
 impl StreamBuilder<SubscriberWantsDecoder> {
     /// Specifies the decoder a [Subscriber](crate::Subscriber) uses for decoding messages
     /// received over the wire.
     ///
-    /// A decoder can be any type implementing
-    /// [MessageDecoder](crate::traits::MessageDecoder). See [codecs](crate::codecs) for a list of
-    /// codecs available in `Selium`, along with tutorials for creating your own decoders.
-    pub fn with_decoder<D, Item>(self, decoder: D) -> StreamBuilder<SubscriberWantsOpen<D, Item>> {
+    /// A decoder can be any type implementing either
+    /// [MessageDecoder](crate::traits::MessageDecoder) or
+    /// [AsyncMessageDecoder](crate::traits::AsyncMessageDecoder). See [codecs](crate::codecs) for
+    /// a list of codecs available in `Selium`, along with tutorials for creating your own
+    /// decoders.
+    pub fn with_decoder<D, Item, Kind>(
+        self,
+        decoder: D,
+    ) -> StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
+    where
+        D: SubscriberDecoder<Item, Kind>,
+    {
         let state = SubscriberWantsOpen {
             common: self.state.common,
             decoder,
+            group: None,
+            replay: None,
+            start_position: None,
+            ordered: false,
+            heartbeat: None,
+            decode_error_policy: DecodeErrorPolicy::default(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_buffer_capacity: None,
+            framing_error_policy: FramingErrorPolicy::default(),
+            _marker: PhantomData,
+        };
+
+        StreamBuilder {
+            state,
+            connection: self.connection,
+        }
+    }
+
+    /// Yields the payload of each message as the [Bytes](bytes::Bytes) it was received as,
+    /// bypassing decoding entirely.
+    ///
+    /// This is useful for proxying messages elsewhere, or deferring decoding until a message is
+    /// known to be needed, and avoids paying the cost of decoding messages that are only routed.
+    /// Compressed payloads are still decompressed, so each payload is exactly what the publisher's
+    /// encoder produced.
+    ///
+    /// Raw mode can be combined with the metadata adaptors, such as `with_headers`, to route
+    /// messages by their topic or headers.
+    pub fn raw(self) -> StreamBuilder<RawSubscriberWantsOpen> {
+        self.with_decoder(RawFrames)
+    }
+
+    /// Decodes each message with the decoder that the [CodecRegistry] selects by its
+    /// [content-type](crate::codecs::CONTENT_TYPE) header, for topics whose publishers encode
+    /// messages in different formats.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::{CodecRegistry, StringCodec}, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let registry = CodecRegistry::new()
+    ///     .register("text/plain", StringCodec)
+    ///     .with_default(StringCodec);
+    ///
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/events")
+    ///     .with_codec_registry(registry)
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_codec_registry<Item>(
+        self,
+        registry: CodecRegistry<Item>,
+    ) -> StreamBuilder<SubscriberWantsOpen<CodecRegistry<Item>, Item, RegistryDecoder>> {
+        self.with_decoder(registry)
+    }
+}
+
+impl<D, Item, Kind> StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
+where
+    D: SubscriberDecoder<Item, Kind>,
+    Item: Send + 'static,
+{
+    /// Pairs each message yielded by the [Subscriber](crate::Subscriber) with the topic it was
+    /// published to, changing the stream item to `(String, Item)`.
+    ///
+    /// This is most useful when subscribing to a wildcard topic, such as `/acmeco/+/trades`,
+    /// where messages can originate from any matching topic.
+    pub fn with_topic(
+        self,
+    ) -> StreamBuilder<MetadataSubscriberWantsOpen<D, Item, Kind, (String, Item)>> {
+        self.wrap_with_metadata()
+    }
+
+    /// Yields each message from the [Subscriber](crate::Subscriber) as a [TopicMessage],
+    /// carrying the topic it was published to alongside the decoded payload.
+    ///
+    /// This is useful when fanning in messages from several topics, such as via a wildcard
+    /// subscription.
+    pub fn with_topic_metadata(
+        self,
+    ) -> StreamBuilder<MetadataSubscriberWantsOpen<D, Item, Kind, TopicMessage<Item>>> {
+        self.wrap_with_metadata()
+    }
+
+    /// Yields each message from the [Subscriber](crate::Subscriber) as a [MessageWithHeaders],
+    /// carrying any [Headers] sent via
+    /// [send_with_headers](crate::Publisher::send_with_headers) alongside the decoded payload.
+    pub fn with_headers(
+        self,
+    ) -> StreamBuilder<MetadataSubscriberWantsOpen<D, Item, Kind, MessageWithHeaders<Item>>> {
+        self.wrap_with_metadata()
+    }
+
+    /// Yields each message from the [Subscriber](crate::Subscriber) as an [OffsetMessage],
+    /// carrying the offset assigned to it in the topic's log alongside the decoded payload.
+    pub fn with_offsets(
+        self,
+    ) -> StreamBuilder<MetadataSubscriberWantsOpen<D, Item, Kind, OffsetMessage<Item>>> {
+        self.wrap_with_metadata()
+    }
+
+    /// Joins the [Subscriber](crate::Subscriber) to the named consumer group, which shares the
+    /// topic's messages between its members rather than delivering every message to each.
+    ///
+    /// The server dispatches each message published to the topic to exactly one member of the
+    /// group, in round-robin order. Subscribers outside of the group, or in other groups, are
+    /// unaffected and continue to receive every message.
+    ///
+    /// When a member disconnects, it is removed from the group, and subsequent messages are
+    /// shared between the remaining members. Messages that were already dispatched to the
+    /// departing member, but not yet received by it, are lost. If the
+    /// [Client](crate::Client) reconnects, the Subscriber rejoins its group.
+    ///
+    /// If the server persists topic logs, the group can [commit](crate::Subscriber::commit) the
+    /// offsets of the messages it has processed, and resume after them via
+    /// [ReplayStart::Committed](crate::ReplayStart::Committed).
+    ///
+    /// Consumer groups cannot be used with wildcard topics.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/jobs")
+    ///     .with_decoder(StringCodec)
+    ///     .group("workers")
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn group(mut self, name: &str) -> Self {
+        self.state.group = Some(name.to_owned());
+        self
+    }
+
+    /// Replays the messages persisted to the topic's log from `start`, before the
+    /// [Subscriber](crate::Subscriber) begins receiving live messages, e.g. for auditing or to
+    /// recover state after a restart.
+    ///
+    /// The server must be started with the `--log-dir` option to persist topic logs, otherwise
+    /// the subscription is rejected. Replay cannot be used with wildcard topics, and consumer
+    /// groups may only replay from [ReplayStart::Committed](crate::ReplayStart::Committed).
+    ///
+    /// # Ordering
+    ///
+    /// Replayed messages are delivered in the order they were published, followed by live
+    /// messages in the order they're published. The switch from replay to live happens within
+    /// the server's topic, so no message published across the boundary is missed or delivered
+    /// twice: each message is either replayed from the log, or delivered live. Live messages
+    /// published while the log is being replayed are queued behind it. Messages retained via
+    /// `retain` are not delivered separately, as they're already part of the log.
+    ///
+    /// If the [Client](crate::Client) reconnects, the Subscriber resumes with live messages,
+    /// rather than replaying the log again, unless it replays from the group's committed offset,
+    /// in which case it resumes after the offset most recently committed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*, ReplayStart};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/audit")
+    ///     .with_decoder(StringCodec)
+    ///     .replay_from(ReplayStart::Earliest)
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn replay_from(mut self, start: ReplayStart) -> Self {
+        self.state.replay = Some(start);
+        self
+    }
+
+    /// Chooses where the [Subscriber](crate::Subscriber)'s live messages start from, and waits
+    /// for the server to attach the Subscriber to its topic before
+    /// [open](crate::traits::Open::open) returns.
+    ///
+    /// # Cut-off
+    ///
+    /// Once `open` returns, the Subscriber is attached to the topic, so every message published
+    /// to the topic from then on is delivered to it. A message published while the Subscriber
+    /// was being opened is delivered if the topic dispatches it after the Subscriber is
+    /// attached, and missed otherwise.
+    ///
+    /// With [StartPosition::Latest](crate::StartPosition::Latest), the Subscriber receives only
+    /// those live messages. With [StartPosition::FromConnect](crate::StartPosition::FromConnect),
+    /// it first receives the messages retained on the topic via `retain` as it connects, i.e.
+    /// those published before it connected whose retention period hasn't elapsed.
+    ///
+    /// Without a start position, the Subscriber behaves as it does with
+    /// [StartPosition::FromConnect](crate::StartPosition::FromConnect), except that `open`
+    /// returns without waiting to be attached, so messages published shortly after `open`
+    /// returns may be missed.
+    ///
+    /// Consumer groups, replaying Subscribers and wildcard topics never receive retained
+    /// messages, so both positions only differ in when `open` returns for them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*, StartPosition};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/stocks")
+    ///     .with_decoder(StringCodec)
+    ///     .start_position(StartPosition::Latest)
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_position(mut self, position: StartPosition) -> Self {
+        self.state.start_position = Some(position);
+        self
+    }
+
+    /// Requests strict per-topic ordering, so that the [Subscriber](crate::Subscriber) receives
+    /// messages in exactly the order the server sequenced them as they were published to the
+    /// topic, e.g. for event-sourcing consumers that must apply events in order.
+    ///
+    /// The server assigns each message a position within its topic as it's published, and
+    /// serializes delivery to an ordered subscriber: each message is written and flushed before
+    /// the next one is sent, and any message positioned before the last one delivered, such as a
+    /// retained message that is also delivered live as the subscriber joins, is dropped.
+    ///
+    /// # Throughput
+    ///
+    /// By default, the server batches messages for each subscriber, flushing them together once
+    /// the topic has no more messages ready to dispatch. Ordered delivery flushes every message
+    /// individually, which reduces throughput for topics with a high rate of small messages,
+    /// and lets a slow ordered subscriber hold up the topic's other subscribers sooner. Only
+    /// enable it for consumers that need the guarantee.
+    ///
+    /// Ordered delivery cannot be used with wildcard topics, as each topic is sequenced
+    /// independently.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/events")
+    ///     .with_decoder(StringCodec)
+    ///     .ordered()
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ordered(mut self) -> Self {
+        self.state.ordered = true;
+        self
+    }
+
+    /// Sends a heartbeat to the server every `interval` milliseconds, which the server echoes
+    /// back, so that a connection that has stopped responding is detected faster than by the
+    /// QUIC idle timeout alone, e.g. a half-open connection to a server that has crashed.
+    ///
+    /// Once `max_missed` heartbeats have gone unanswered when the next one is due, the connection
+    /// is presumed lost and closed, which happens within `(max_missed + 1) * interval` of the
+    /// server failing. The interval should comfortably exceed the round trip time to the server,
+    /// as heartbeats are echoed behind any messages already in flight to the subscriber.
+    /// Heartbeats are only sent while the [Subscriber](crate::Subscriber) is being polled, so a
+    /// subscriber that stops polling isn't disconnected, and catches up on the missed interval
+    /// with a single heartbeat once it resumes.
+    ///
+    /// # Reconnection
+    ///
+    /// As the connection is shared, closing it also closes any other streams opened from the
+    /// same [Client](crate::Client). If the [Client](crate::Client) was configured with a
+    /// [ReconnectPolicy](crate::ReconnectPolicy), the connection is re-established, and the
+    /// Subscriber re-opens its subscription as it would for any other lost connection, restarting
+    /// its heartbeats. Otherwise, the Subscriber yields an
+    /// [Error::ConnectionLost](crate::Error::ConnectionLost), after which the stream ends.
+    ///
+    /// Accepts any `interval` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided interval fails to be converted to a [u64], or if either the
+    /// interval or `max_missed` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # use std::time::Duration;
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/stocks")
+    ///     .with_decoder(StringCodec)
+    ///     .heartbeat(Duration::from_secs(1), 3)?
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn heartbeat<T: TryIntoU64>(mut self, interval: T, max_missed: u32) -> Result<Self> {
+        let interval = Duration::from_millis(interval.try_into_u64()?);
+
+        if interval.is_zero() || max_missed == 0 {
+            return Err(Error::config(
+                "Heartbeat interval and maximum missed heartbeats must be greater than zero",
+            ));
+        }
+
+        self.state.heartbeat = Some(HeartbeatOptions {
+            interval,
+            max_missed,
+        });
+
+        Ok(self)
+    }
+
+    /// Overrides how the [Subscriber](crate::Subscriber) handles a message that fails to be
+    /// decoded. See [DecodeErrorPolicy] for the available policies, which default to
+    /// [DecodeErrorPolicy::Fail].
+    ///
+    /// With [DecodeErrorPolicy::SkipAndContinue], a malformed message doesn't interrupt loops
+    /// that stop on the first error, such as `while let Some(Ok(item)) = subscriber.next().await`,
+    /// so the stream only yields [None] once it has ended. Skipped messages are logged as a
+    /// warning via the [log] crate. If acknowledgements are enabled, skipped messages are never
+    /// acknowledged, so they will be redelivered after the subscriber reconnects.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*, DecodeErrorPolicy};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/events")
+    ///     .with_decoder(StringCodec)
+    ///     .on_decode_error(DecodeErrorPolicy::SkipAndContinue)
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_decode_error(mut self, policy: DecodeErrorPolicy) -> Self {
+        self.state.decode_error_policy = policy;
+        self
+    }
+
+    /// Overrides the maximum size of a message received by the [Subscriber](crate::Subscriber),
+    /// in bytes, which defaults to [DEFAULT_MAX_MESSAGE_SIZE].
+    ///
+    /// The size of a message includes its payload, and any topic or headers delivered along with
+    /// it. The size is checked before the message is buffered, so an oversized message never
+    /// causes a buffer larger than the limit to be allocated. Instead, the stream yields an
+    /// [Error::MessageTooLarge](crate::Error::MessageTooLarge) error, and then ends, as the rest
+    /// of the message cannot be skipped.
+    ///
+    /// Accepts any `size` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided size fails to be converted to a [u64].
+    pub fn max_message_size<T: TryIntoU64>(mut self, size: T) -> Result<Self> {
+        self.state.max_message_size = size.try_into_u64()?;
+        Ok(self)
+    }
+
+    /// Caps the buffer that the [Subscriber](crate::Subscriber) reads frames into at `bytes`,
+    /// which is otherwise only bounded by the [max_message_size](Self::max_message_size).
+    ///
+    /// The capacity covers a whole frame, including its header, as well as the message along
+    /// with any topic or headers delivered with it. A frame that wouldn't fit is rejected as soon
+    /// as its length has been read, before any of its body is buffered, so a malicious or buggy
+    /// producer cannot exhaust the client's memory. Instead, the stream yields an
+    /// [Error::BufferCapacityExceeded](crate::Error::BufferCapacityExceeded) error, and then ends,
+    /// as the rest of the frame cannot be skipped. While a frame is being received, the buffer is
+    /// only grown by as much as the frame needs.
+    ///
+    /// Accepts any `bytes` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// **Note:** Under [FramingErrorPolicy::Resync], a frame exceeding the capacity is treated
+    /// as corrupt and skipped, rather than yielding an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided capacity fails to be converted to a [u64].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/events")
+    ///     .with_decoder(StringCodec)
+    ///     .max_buffer_capacity(1024 * 1024)?
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn max_buffer_capacity<T: TryIntoU64>(mut self, bytes: T) -> Result<Self> {
+        self.state.max_buffer_capacity = Some(bytes.try_into_u64()?);
+        Ok(self)
+    }
+
+    /// Overrides how the [Subscriber](crate::Subscriber) handles a frame that cannot be decoded
+    /// from its stream, such as one with a corrupt header, which defaults to
+    /// [FramingErrorPolicy::Fail].
+    ///
+    /// Unlike a message that fails to be decoded by the decoder, which is handled by
+    /// [on_decode_error](Self::on_decode_error), a framing error leaves the position of the next
+    /// message unknown, so by default the stream yields the error, and then ends. With
+    /// [FramingErrorPolicy::Resync], the stream instead scans ahead for the next frame header
+    /// with a valid checksum, discarding the corrupt bytes, and continues with the next message.
+    /// Resynchronizing is a best effort, so it's intended for recovering from the occasional
+    /// corrupt frame, rather than as a substitute for a reliable producer.
+    ///
+    /// **Note:** Under [FramingErrorPolicy::Resync], a message exceeding the
+    /// [max_message_size](Self::max_message_size) is treated as corrupt and skipped, rather than
+    /// yielding an [Error::MessageTooLarge](crate::Error::MessageTooLarge) error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*, FramingErrorPolicy};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/events")
+    ///     .with_decoder(StringCodec)
+    ///     .on_framing_error(FramingErrorPolicy::Resync)
+    ///     .open()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_framing_error(mut self, policy: FramingErrorPolicy) -> Self {
+        self.state.framing_error_policy = policy;
+        self
+    }
+
+    /// Presents a bearer `token` to the `Selium` server when the [Subscriber](crate::Subscriber)
+    /// is opened, authorizing it to subscribe to its topic.
+    ///
+    /// Servers started with the `--auth-tokens` option only accept streams presenting a token
+    /// that is permitted to use their topic. The server validates the token before the stream is
+    /// accepted, so an unauthorized [Subscriber](crate::Subscriber) fails
+    /// [open](crate::traits::Open::open) with an [Error::Unauthorized](crate::Error::Unauthorized)
+    /// error. The token is redacted from the client's and the server's logs.
+    ///
+    /// **Note:** A [Subscriber](crate::Subscriber) that doesn't present a token doesn't wait for
+    /// the server to accept it, so if the server requires one, the stream instead yields an
+    /// [Error::Unauthorized](crate::Error::Unauthorized) error, and then ends.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.state.common.token(token);
+        self
+    }
+
+    /// Opens the [Subscriber](crate::Subscriber) on a connection of its own, rather than on the
+    /// [Client](crate::Client)'s connection, which is shared by every other stream opened from
+    /// the client by default.
+    ///
+    /// Streams sharing a connection are multiplexed over it, so they also share its flow control
+    /// and congestion window. A Subscriber receiving messages on a busy connection competes with
+    /// every other stream for the connection's receive window, so a slow or high-volume stream
+    /// can delay its messages. A dedicated connection isolates a latency-sensitive Subscriber
+    /// from this, at the cost of a separate handshake when it's opened, and the resources of an
+    /// additional connection for as long as it's open.
+    ///
+    /// The dedicated connection is established with the same configuration as the client's
+    /// connection, and is closed along with it. It's re-established independently if lost, and
+    /// missed heartbeats only close the dedicated connection, rather than the client's. The
+    /// client's [connection_stats](crate::Client::connection_stats) and
+    /// [events](crate::Client::events) only cover the client's connection. Defaults to `false`.
+    pub fn connection_per_stream(mut self, enabled: bool) -> Self {
+        self.state.common.connection_per_stream(enabled);
+        self
+    }
+
+    fn wrap_with_metadata<Out>(
+        self,
+    ) -> StreamBuilder<MetadataSubscriberWantsOpen<D, Item, Kind, Out>> {
+        let topic = self.state.common.topic.clone();
+        let state = SubscriberWantsOpen {
+            common: self.state.common,
+            decoder: WithMetadata::new(self.state.decoder, topic),
+            group: self.state.group,
+            replay: self.state.replay,
+            start_position: self.state.start_position,
+            ordered: self.state.ordered,
+            heartbeat: self.state.heartbeat,
+            decode_error_policy: self.state.decode_error_policy,
+            max_message_size: self.state.max_message_size,
+            max_buffer_capacity: self.state.max_buffer_capacity,
+            framing_error_policy: self.state.framing_error_policy,
             _marker: PhantomData,
         };
 
@@ -46,9 +667,9 @@ impl StreamBuilder<SubscriberWantsDecoder> {
     }
 }
 
-impl<D, Item> Retain for StreamBuilder<SubscriberWantsOpen<D, Item>>
+impl<D, Item, Kind> Retain for StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
 where
-    D: MessageDecoder<Item>,
+    D: SubscriberDecoder<Item, Kind>,
 {
     fn retain<T: TryIntoU64>(mut self, policy: T) -> Result<Self> {
         self.state.common.retain(policy)?;
@@ -56,9 +677,9 @@ where
     }
 }
 
-impl<D, Item> Operations for StreamBuilder<SubscriberWantsOpen<D, Item>>
+impl<D, Item, Kind> Operations for StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
 where
-    D: MessageDecoder<Item> + SeliumCodec,
+    D: SubscriberDecoder<Item, Kind> + SeliumCodec,
 {
     fn map(mut self, module_path: &str) -> Self {
         self.state.common.map(module_path);
@@ -72,23 +693,62 @@ where
 }
 
 #[async_trait]
-impl<D, Item> Open for StreamBuilder<SubscriberWantsOpen<D, Item>>
+impl<D, Item, Kind> Open for StreamBuilder<SubscriberWantsOpen<D, Item, Kind>>
 where
-    D: MessageDecoder<Item> + Send,
+    D: SubscriberDecoder<Item, Kind> + Send,
     Item: Send,
+    Kind: Send,
 {
-    type Output = Subscriber<D, Item>;
+    type Output = Subscriber<D, Item, Kind>;
 
     async fn open(self) -> Result<Self::Output> {
+        let connection = self.state.common.stream_connection(self.connection).await?;
+
+        let (headers, decoder, policy, codec, heartbeat) = self.state.into_parts();
+        let subscriber =
+            Subscriber::spawn(connection, headers, decoder, policy, codec, heartbeat).await?;
+
+        Ok(subscriber)
+    }
+}
+
+impl<D, Item, Kind> SubscriberWantsOpen<D, Item, Kind> {
+    pub(crate) fn into_parts(
+        self,
+    ) -> (
+        SubscriberPayload,
+        D,
+        DecodeErrorPolicy,
+        MessageCodec,
+        Option<HeartbeatOptions>,
+    ) {
         let headers = SubscriberPayload {
-            topic: self.state.common.topic,
-            retention_policy: self.state.common.retention_policy,
-            operations: self.state.common.operations,
+            topic: self.common.topic,
+            retention_policy: self.common.retention_policy,
+            operations: self.common.operations,
+            group: self.group,
+            acks: false,
+            replay: self.replay,
+            ordered: self.ordered,
+            heartbeat: self.heartbeat.is_some(),
+            token: self.common.token,
+            start_position: self.start_position,
         };
 
-        let subscriber = Subscriber::spawn(self.connection, headers, self.state.decoder).await?;
+        let mut codec = MessageCodec::new(self.max_message_size)
+            .with_framing_error_policy(self.framing_error_policy);
 
-        Ok(subscriber)
+        if let Some(capacity) = self.max_buffer_capacity {
+            codec = codec.with_max_buffer_capacity(capacity);
+        }
+
+        (
+            headers,
+            self.decoder,
+            self.decode_error_policy,
+            codec,
+            self.heartbeat,
+        )
     }
 }
 
@@ -96,61 +756,555 @@ where
 ///
 /// The Subscriber struct implements the [futures::Stream] trait, and can thus be used in the same
 /// contexts as a [Stream](futures::Stream). Any messages polled on the stream will be decoded
-/// using the provided decoder.
+/// using the provided decoder. If the decoder is an
+/// [AsyncMessageDecoder](crate::traits::AsyncMessageDecoder), the stream will poll the decoding
+/// future to completion before yielding the next message.
+///
+/// # Reconnection
+///
+/// If the [Client](crate::Client) was configured with a
+/// [ReconnectPolicy](crate::ReconnectPolicy), the Subscriber retains its configuration, and
+/// transparently re-opens its subscription on the re-established connection if the connection is
+/// lost, continuing to yield messages from the same topic.
+///
+/// Delivery is at-most-once across a reconnection: any messages that were in flight when the
+/// connection was lost, or that were published while the Subscriber was disconnected, are
+/// dropped rather than redelivered. For at-least-once delivery of messages that were in flight,
+/// enable acknowledgements via `with_acks` on the builder. To detect a lost connection sooner,
+/// enable heartbeats via `heartbeat` on the builder.
+///
+/// # Backpressure
+///
+/// The server only reads from a topic's publishers while every subscriber's stream accepts more
+/// data, so a Subscriber that isn't polled fast enough slows down the whole topic rather than the
+/// server buffering messages for it without bound. Once the Subscriber's QUIC flow control
+/// window fills, the server stops reading from the publishers, which are in turn blocked by their
+/// own flow control windows, so that `send` waits until the Subscriber catches up.
+///
+/// A publisher that's blocked by backpressure sends no messages, so its connection relies on the
+/// client's `keep_alive` pings to stay open while the Subscriber is stalled. The `keep_alive`
+/// interval must therefore be shorter than the server's idle timeout (its `--max-idle-timeout`
+/// option, 15 seconds by default), as well as the client's own, or blocked publishers are
+/// disconnected once they've been idle for the timeout. The same applies to the stalled
+/// Subscriber's connection.
+///
+/// # Cancel safety
+///
+/// Polling the Subscriber is cancel safe, so a [next](futures::StreamExt::next) future can be
+/// dropped before it completes, such as when another branch of [tokio::select!] completes first,
+/// without losing a message. Any part of a frame that has been read is kept in the Subscriber's
+/// buffer, and a message that's still being decoded by an
+/// [AsyncMessageDecoder](crate::traits::AsyncMessageDecoder), or a subscription that's still
+/// being re-opened, is kept by the Subscriber too. Each is resumed the next time it's polled.
+///
+/// ```no_run
+/// # use selium::{codecs::StringCodec, prelude::*};
+/// # use futures::TryStreamExt;
+/// # use std::time::Duration;
+/// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+/// let mut subscriber = client
+///     .subscriber("/acmeco/stocks")
+///     .with_decoder(StringCodec)
+///     .open()
+///     .await?;
+///
+/// let mut ticker = tokio::time::interval(Duration::from_secs(1));
+///
+/// loop {
+///     tokio::select! {
+///         message = subscriber.try_next() => match message? {
+///             Some(message) => println!("{message}"),
+///             None => break,
+///         },
+///         _ = ticker.tick() => println!("Still listening..."),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
 ///
 /// **Note:** The Subscriber struct is never constructed directly, but rather, via a
 /// [StreamBuilder](crate::StreamBuilder).
-pub struct Subscriber<D, Item> {
-    stream: BiStream,
-    decoder: D,
-    _marker: PhantomData<Item>,
+pub struct Subscriber<D, Item, Kind = SyncDecoder> {
+    connection: SharedConnection,
+    headers: SubscriberPayload,
+    // The connection that the current stream was opened on
+    stream_connection: Connection,
+    stream: BiStreamRead,
+    // The write half of the current stream, kept open for acknowledgements or heartbeats if
+    // enabled
+    acker: Option<Acker>,
+    // The delivery id of the message most recently received on the stream
+    delivery_id: Option<u64>,
+    decoder: Arc<D>,
+    decode_error_policy: DecodeErrorPolicy,
+    codec: MessageCodec,
+    heartbeats: Option<Heartbeats>,
+    // Whether the connection was lost after heartbeats went unanswered, ending the stream
+    lost: bool,
+    // A message being decoded by an asynchronous decoder, along with when decoding started
+    pending: Option<(BoxFuture<'static, anyhow::Result<Item>>, Instant)>,
+    reopening: Option<BoxFuture<'static, Result<Registration>>>,
+    _marker: PhantomData<Kind>,
 }
 
-impl<D, Item> Subscriber<D, Item>
+type Registration = (Connection, BiStreamRead, Option<Acker>);
+
+impl<D, Item, Kind> Subscriber<D, Item, Kind>
 where
-    D: MessageDecoder<Item>,
+    D: SubscriberDecoder<Item, Kind>,
 {
-    async fn spawn(connection: Connection, headers: SubscriberPayload, decoder: D) -> Result<Self> {
-        let mut stream = BiStream::try_from_connection(&connection).await?;
-        let frame = Frame::RegisterSubscriber(headers);
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "open_subscriber",
+            skip_all,
+            fields(topic = %headers.topic, group = headers.group.as_deref(), acks = headers.acks),
+            err
+        )
+    )]
+    pub(crate) async fn spawn(
+        connection: SharedConnection,
+        headers: SubscriberPayload,
+        decoder: D,
+        decode_error_policy: DecodeErrorPolicy,
+        codec: MessageCodec,
+        heartbeat: Option<HeartbeatOptions>,
+    ) -> Result<Self> {
+        validate_topic_pattern(&headers.topic)?;
 
-        stream.send(frame).await?;
-        stream.finish().await?;
+        let (stream_connection, stream, acker) =
+            register(connection.clone(), headers.clone(), codec).await?;
 
         Ok(Self {
+            connection,
+            // Re-opened subscriptions resume with live messages, rather than replaying again,
+            // unless they resume from their group's committed offset
+            headers: SubscriberPayload {
+                replay: headers
+                    .replay
+                    .filter(|start| *start == ReplayStart::Committed),
+                ..headers
+            },
+            stream_connection,
             stream,
-            decoder,
+            acker,
+            delivery_id: None,
+            decoder: Arc::new(decoder),
+            decode_error_policy,
+            codec,
+            heartbeats: heartbeat.map(Heartbeats::new),
+            lost: false,
+            pending: None,
+            reopening: None,
             _marker: PhantomData,
         })
     }
+
+    /// Returns true if the stream was lost along with its connection, and the connection is
+    /// configured to reconnect.
+    fn should_reopen(&self) -> bool {
+        self.connection.reconnect_policy().is_some()
+            && self.stream_connection.close_reason().is_some()
+    }
+
+    /// Returns the id of the QUIC stream that the Subscriber is receiving messages on, as it's
+    /// known to the server.
+    ///
+    /// This is useful for correlating client-side logs with the server's logs when debugging.
+    /// The id stays the same for the lifetime of the Subscriber, unless the stream is re-opened
+    /// after a reconnect, in which case the id of the new stream is returned.
+    pub fn stream_id(&self) -> u64 {
+        stream_id(self.stream.get_recv_stream_id())
+    }
+
+    /// Commits `offset` for the Subscriber's consumer group, so that members of the group
+    /// replaying from [ReplayStart::Committed](crate::ReplayStart::Committed) resume from the
+    /// message after it, such as after reconnecting.
+    ///
+    /// Offsets are assigned to each message in the topic's log, and are yielded by Subscribers
+    /// opened with `with_offsets`. The commit is sent without waiting for the server to persist
+    /// it, and is ignored if the server isn't persisting topic logs.
+    ///
+    /// # Delivery guarantees
+    ///
+    /// The members of a group share a single committed offset, which only moves forwards: a
+    /// commit that isn't ahead of the group's committed offset is ignored. As each member is
+    /// delivered a share of the group's messages, the committed offset may pass messages that
+    /// another member hasn't processed yet, which the group skips when it resumes if that member
+    /// disconnects first. To process every message at least once, also enable acknowledgements via
+    /// `with_acks` on the builder, so that messages a member leaves unacknowledged are redelivered
+    /// to the rest of its group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the Subscriber isn't in a consumer group, or if the commit fails to be
+    /// sent.
+    pub async fn commit(&self, offset: u64) -> Result<()> {
+        let acker = match (&self.headers.group, &self.acker) {
+            (Some(_), Some(acker)) => acker,
+            _ => return Err(Error::config("Only consumer groups may commit offsets")),
+        };
+
+        let frame = Frame::Commit(CommitPayload { offset });
+
+        Ok(acker.lock().await.send(frame).await?)
+    }
+
+    /// Returns the delivery id of the message most recently yielded by the stream, along with
+    /// the stream to acknowledge it on.
+    pub(crate) fn last_delivery(&self) -> Option<(u64, Acker)> {
+        Some((self.delivery_id?, self.acker.clone()?))
+    }
+}
+
+fn unauthorized(payload: &UnauthorizedPayload) -> Error {
+    Error::unauthorized(format!(
+        "Subscriber is not authorized to subscribe to topic {}",
+        payload.topic
+    ))
 }
 
-impl<D, Item> Stream for Subscriber<D, Item>
+/// Opens a new stream and registers it as a subscriber to the topic.
+///
+/// The write half of the stream is closed once registered, unless the subscriber acknowledges
+/// its messages, sends heartbeats, or commits offsets for its consumer group.
+async fn register(
+    connection: SharedConnection,
+    headers: SubscriberPayload,
+    codec: MessageCodec,
+) -> Result<Registration> {
+    let connection = connection.get().await?;
+    let mut stream = BiStream::try_from_connection(&connection).await?;
+    let keep_open = headers.acks || headers.heartbeat || headers.group.is_some();
+    let awaits_accept = headers.token.is_some() || headers.start_position.is_some();
+
+    stream.send(Frame::RegisterSubscriber(headers)).await?;
+
+    // Subscribers presenting a token or choosing a start position are accepted by the server
+    // before any messages are sent
+    if awaits_accept {
+        match stream.next().await {
+            Some(Ok(Frame::Accept(_))) => (),
+            Some(Ok(Frame::Unauthorized(payload))) => return Err(unauthorized(&payload)),
+            Some(Err(err)) => return Err(err.into()),
+            _ => return Err(Error::protocol("Server did not accept the Subscriber")),
+        }
+    }
+
+    // Only applied once registered, so that the limit doesn't apply to the registration frame
+    stream.set_codec(codec);
+
+    let (mut write, read) = stream.split();
+
+    let acker = if keep_open {
+        Some(Arc::new(Mutex::new(write)))
+    } else {
+        write.finish().await?;
+        None
+    };
+
+    Ok((connection, read, acker))
+}
+
+impl<D, Item, Kind> Subscriber<D, Item, Kind>
 where
-    D: MessageDecoder<Item> + Send + Unpin,
+    D: SubscriberDecoder<Item, Kind> + Send + Unpin,
     Item: Unpin,
+    Kind: Unpin,
+{
+    /// Collects up to `n` messages, waiting no longer than `timeout` for them to arrive.
+    ///
+    /// Returns as soon as `n` messages have been received. Otherwise, once the `timeout` elapses
+    /// or the stream ends, returns whichever messages arrived in the meantime, which may be none.
+    /// This is useful for micro-batching, or for reading a bounded number of messages in tests.
+    ///
+    /// A message that's still being received or decoded when the `timeout` elapses is not lost,
+    /// and is yielded by the next call to the Subscriber.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided timeout fails to be converted to a [u64], or if the stream
+    /// yields an error before `n` messages are collected, in which case the messages collected
+    /// so far are discarded. Decode errors skipped via
+    /// [DecodeErrorPolicy::SkipAndContinue] do not interrupt collection.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # use std::time::Duration;
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let mut subscriber = client
+    ///     .subscriber("/acmeco/stocks")
+    ///     .with_decoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// let batch = subscriber
+    ///     .collect_with_timeout(100, Duration::from_millis(50))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn collect_with_timeout<T: TryIntoU64>(
+        &mut self,
+        n: usize,
+        timeout: T,
+    ) -> Result<Vec<Item>> {
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout.try_into_u64()?);
+        let mut items = Vec::with_capacity(n);
+
+        while items.len() < n {
+            match tokio::time::timeout_at(deadline, self.next()).await {
+                Ok(Some(item)) => items.push(item?),
+                // The stream has ended, or the timeout has elapsed
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Applies the closure `f` to each message received by the Subscriber, yielding the values
+    /// it returns, and skipping any messages for which it returns [None].
+    ///
+    /// This is a lightweight, client-side alternative to filtering and mapping messages on the
+    /// server via `filter` and `map` on the builder, which avoids writing a WebAssembly module,
+    /// at the cost of every message still being delivered to the client. Unlike
+    /// [StreamExt::filter_map](futures::StreamExt::filter_map), the closure is synchronous, and
+    /// errors yielded by the Subscriber are passed through without invoking it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # use futures::TryStreamExt;
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/stocks")
+    ///     .with_decoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// let mut prices = subscriber.filter_map(|message| message.parse::<f64>().ok());
+    ///
+    /// while let Some(price) = prices.try_next().await? {
+    ///     println!("{price}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn filter_map<F, U>(self, f: F) -> FilterMap<Self, F>
+    where
+        F: FnMut(Item) -> Option<U>,
+    {
+        FilterMap::new(self, f)
+    }
+
+    /// Spawns a task that drains the Subscriber into a bounded channel holding up to `buffer`
+    /// messages, returning the receiving half.
+    ///
+    /// This decouples processing messages from receiving them over the network. Once the channel
+    /// is full, the task stops receiving until a message is taken from it, leaving the server to
+    /// apply backpressure as usual. Errors yielded by the Subscriber are sent through the channel
+    /// in order with its messages, and the channel is closed once the Subscriber's stream ends.
+    /// The task ends when the stream does, or when the receiver is dropped.
+    ///
+    /// The receiver can be shared between several consumers by wrapping it in a
+    /// [Mutex](tokio::sync::Mutex).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is zero, or if called outside of a [tokio] runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use selium::{codecs::StringCodec, prelude::*};
+    /// # async fn run(client: selium::Client) -> anyhow::Result<()> {
+    /// let subscriber = client
+    ///     .subscriber("/acmeco/stocks")
+    ///     .with_decoder(StringCodec)
+    ///     .open()
+    ///     .await?;
+    ///
+    /// let mut receiver = subscriber.into_channel(100);
+    ///
+    /// while let Some(message) = receiver.recv().await {
+    ///     println!("{}", message?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_channel(mut self, buffer: usize) -> mpsc::Receiver<Result<Item>>
+    where
+        Self: Send + 'static,
+        Item: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(buffer);
+
+        tasks::spawn(tasks::SUBSCRIBER_CHANNEL, async move {
+            while let Some(item) = self.next().await {
+                if sender.send(item).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+impl<D, Item, Kind> Stream for Subscriber<D, Item, Kind>
+where
+    D: SubscriberDecoder<Item, Kind> + Send + Unpin,
+    Item: Unpin,
+    Kind: Unpin,
 {
     type Item = Result<Item>;
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let frame = match futures::ready!(self.stream.poll_next_unpin(cx)) {
-            Some(Ok(frame)) => frame,
-            Some(Err(err)) => return Poll::Ready(Some(Err(err))),
-            None => return Poll::Ready(None),
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match futures::ready!(this.poll_decoded(cx)) {
+                Some(Err(Error::Codec(err)))
+                    if this.decode_error_policy == DecodeErrorPolicy::SkipAndContinue =>
+                {
+                    log::warn!(
+                        "Skipping message on topic {} that failed to decode: {err}",
+                        this.headers.topic
+                    );
+                }
+                decoded => return Poll::Ready(decoded),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+impl<D, Item, Kind> Subscriber<D, Item, Kind>
+where
+    D: SubscriberDecoder<Item, Kind>,
+{
+    /// Polls for the next message, and decodes it, regardless of the [DecodeErrorPolicy].
+    fn poll_decoded(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Item>>> {
+        if self.lost {
+            return Poll::Ready(None);
+        }
+
+        if let Some((pending, started)) = self.pending.as_mut() {
+            let decoded = futures::ready!(pending.as_mut().poll(cx));
+            let started = *started;
+            self.pending = None;
+            return Poll::Ready(Some(self.decoded(decoded, started)));
+        }
+
+        let frame = loop {
+            if let Some(reopening) = self.reopening.as_mut() {
+                let result = futures::ready!(reopening.as_mut().poll(cx));
+                self.reopening = None;
+
+                match result {
+                    Ok((connection, stream, acker)) => {
+                        self.stream_connection = connection;
+                        self.stream = stream;
+                        self.acker = acker;
+                        // Delivery ids restart with each stream
+                        self.delivery_id = None;
+
+                        if let Some(heartbeats) = self.heartbeats.as_mut() {
+                            heartbeats.reset();
+                        }
+                    }
+                    Err(err) => return Poll::Ready(Some(Err(err))),
+                }
+            }
+
+            let polled = match self.stream.poll_next_unpin(cx) {
+                Poll::Ready(polled) => polled,
+                Poll::Pending => match self.poll_heartbeats(cx) {
+                    Ok(()) => return Poll::Pending,
+                    // Reopened on the re-established connection below, like any other lost stream
+                    Err(_) if self.should_reopen() => None,
+                    Err(err) => {
+                        self.lost = true;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                },
+            };
+
+            match polled {
+                Some(Ok(Frame::Heartbeat(HeartbeatPayload { id }))) => {
+                    if let Some(heartbeats) = self.heartbeats.as_mut() {
+                        heartbeats.echoed(id);
+                    }
+                }
+                Some(Ok(frame)) => break frame,
+                _ if self.should_reopen() => {
+                    let reopening =
+                        register(self.connection.clone(), self.headers.clone(), self.codec);
+                    self.reopening = Some(Box::pin(reopening));
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                None => return Poll::Ready(None),
+            }
         };
 
-        let bytes = match frame {
-            Frame::Message(bytes) => bytes,
+        let payload = match frame {
+            Frame::Message(payload) => payload,
+            // The server closes the stream after rejecting it
+            Frame::Unauthorized(payload) => return Poll::Ready(Some(Err(unauthorized(&payload)))),
             _ => return Poll::Ready(None),
         };
 
-        let mut mut_bytes = BytesMut::with_capacity(bytes.len());
-        mut_bytes.extend_from_slice(&bytes[..]);
+        self.delivery_id = payload.delivery_id;
+        metrics::record_consumed(&self.headers.topic);
+
+        let started = Instant::now();
+
+        let payload = match decompress_payload(payload, self.codec.max_message_size()) {
+            Ok(payload) => payload,
+            Err(err) => return Poll::Ready(Some(self.decoded(Err(err), started))),
+        };
+
+        match D::decode_frame(&self.decoder, payload) {
+            DecodedFrame::Ready(decoded) => Poll::Ready(Some(self.decoded(decoded, started))),
+            DecodedFrame::Pending(mut pending) => match pending.as_mut().poll(cx) {
+                Poll::Ready(decoded) => Poll::Ready(Some(self.decoded(decoded, started))),
+                Poll::Pending => {
+                    self.pending = Some((pending, started));
+                    Poll::Pending
+                }
+            },
+        }
+    }
+
+    /// Sends any heartbeat that's due, closing the connection if too many have gone unanswered.
+    fn poll_heartbeats(&mut self, cx: &mut Context<'_>) -> Result<()> {
+        let (Some(heartbeats), Some(acker)) = (self.heartbeats.as_mut(), self.acker.as_ref())
+        else {
+            return Ok(());
+        };
 
-        Poll::Ready(Some(self.decoder.decode(&mut mut_bytes)))
+        heartbeats.poll_send(cx, acker).map_err(|err| {
+            log::warn!(
+                "Closing connection of Subscriber on topic {}: {err}",
+                self.headers.topic
+            );
+            self.stream_connection
+                .close(VarInt::from_u32(0), b"Heartbeats went unanswered");
+            err
+        })
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.stream.size_hint()
+    /// Completes the decoding of a message, recording the time taken to decode it.
+    fn decoded(&self, decoded: anyhow::Result<Item>, started: Instant) -> Result<Item> {
+        metrics::record_decode(&self.headers.topic, started);
+        decoded.map_err(Error::codec)
     }
 }
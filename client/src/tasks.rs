@@ -0,0 +1,78 @@
+//! Names of the background tasks spawned by the client.
+//!
+//! With the `tokio-console` feature enabled, each task is spawned with its name via tokio's
+//! [task::Builder](https://docs.rs/tokio/latest/tokio/task/struct.Builder.html), so that it can be
+//! identified in [tokio-console](https://github.com/tokio-rs/console), such as to find the task
+//! that a stuck [Publisher](crate::Publisher) is blocked on. As tokio only names tasks when built
+//! with its unstable features, the crate must also be built with
+//! `RUSTFLAGS="--cfg tokio_unstable"`, as tokio-console itself requires. Without it, tasks are
+//! spawned unnamed.
+//!
+//! With the `tracing` feature enabled, which `tokio-console` implies, each task also runs within a
+//! `selium_task` span, whose `task` field is the name of the task.
+//!
+//! | Name | Description |
+//! |------|-------------|
+//! | `selium::publisher_writer` | Writes and flushes the messages of a [Publisher](crate::Publisher) with a flush interval or reconnection enabled, re-opening its stream if it fails |
+//! | `selium::publisher_finish` | Finishes a [Publisher](crate::Publisher) dropped with `finish_on_drop` enabled |
+//! | `selium::connection_watcher` | Emits a [Disconnected](crate::ConnectionEvent::Disconnected) event once the connection closes |
+//! | `selium::ctrl_c` | Closes the connection once the process receives Ctrl-C |
+//! | `selium::heartbeat` | Sends a heartbeat on the stream of a [Subscriber](crate::Subscriber) |
+//! | `selium::subscriber_channel` | Forwards the messages of a [Subscriber](crate::Subscriber) to the channel returned by [into_channel](crate::Subscriber::into_channel) |
+//!
+//! Reconnecting isn't a task of its own, so it shows up as part of the task that lost its
+//! connection, such as the `selium::publisher_writer` of a buffered Publisher.
+
+use std::future::Future;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+/// Writes and flushes the messages of a buffered [Publisher](crate::Publisher).
+pub const PUBLISHER_WRITER: &str = "selium::publisher_writer";
+
+/// Finishes a dropped [Publisher](crate::Publisher).
+pub const PUBLISHER_FINISH: &str = "selium::publisher_finish";
+
+/// Emits a [Disconnected](crate::ConnectionEvent::Disconnected) event once the connection closes.
+pub const CONNECTION_WATCHER: &str = "selium::connection_watcher";
+
+/// Closes the connection once the process receives Ctrl-C.
+pub const CTRL_C: &str = "selium::ctrl_c";
+
+/// Sends a heartbeat on the stream of a [Subscriber](crate::Subscriber).
+pub const HEARTBEAT: &str = "selium::heartbeat";
+
+/// Forwards the messages of a [Subscriber](crate::Subscriber) to a channel.
+pub const SUBSCRIBER_CHANNEL: &str = "selium::subscriber_channel";
+
+/// Spawns the task `name` on the current runtime.
+pub(crate) fn spawn<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    spawn_on(name, future, &Handle::current())
+}
+
+/// Spawns the task `name` on the `runtime`.
+pub(crate) fn spawn_on<F>(name: &'static str, future: F, runtime: &Handle) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(feature = "tracing")]
+    let future =
+        tracing::Instrument::instrument(future, tracing::debug_span!("selium_task", task = name));
+
+    #[cfg(all(feature = "tokio-console", tokio_unstable))]
+    return tokio::task::Builder::new()
+        .name(name)
+        .spawn_on(future, runtime)
+        .expect("Failed to spawn task");
+
+    #[cfg(not(all(feature = "tokio-console", tokio_unstable)))]
+    {
+        let _ = name;
+        runtime.spawn(future)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{Error, Result};
+
+const LEVEL_SEPARATOR: char = '/';
+const SINGLE_LEVEL: &str = "+";
+const MULTI_LEVEL: &str = "#";
+
+/// Validates a topic name, returning a [Config](Error::Config) error describing the first rule
+/// that it breaks.
+///
+/// A topic is made up of one or more levels, each preceded by a `/`, such as `/acmeco/stocks`.
+/// A valid topic:
+///
+/// - Starts with a `/`.
+/// - Doesn't end with a `/`.
+/// - Doesn't contain any empty levels, such as `/acmeco//stocks`.
+/// - Only contains ASCII letters and digits, `-`, `_` and `.` within each level.
+///
+/// Topics are validated when a stream is opened, before it's sent to the `Selium` server, so a
+/// typo such as `acmeco/stocks` fails with a descriptive error rather than a rejection from the
+/// server. Subscribers may also use wildcards, which are validated by [validate_topic_pattern].
+///
+/// # Examples
+///
+/// ```
+/// assert!(selium::validate_topic("/acmeco/stocks").is_ok());
+/// assert!(selium::validate_topic("acmeco/stocks").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns [Err] if the topic breaks any of the rules above.
+pub fn validate_topic(topic: &str) -> Result<()> {
+    validate(topic, false)
+}
+
+/// Validates a topic name that may contain wildcards, as accepted by
+/// [subscriber](crate::Client::subscriber).
+///
+/// Patterns follow the same rules as [validate_topic], except that a level may also be a `+`
+/// wildcard, which matches exactly one level, or a `#` wildcard, which matches any number of
+/// trailing levels, and so may only be used as the final level. A wildcard must occupy a whole
+/// level.
+///
+/// # Examples
+///
+/// ```
+/// assert!(selium::validate_topic_pattern("/acmeco/+/trades").is_ok());
+/// assert!(selium::validate_topic_pattern("/acmeco/#/trades").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns [Err] if the pattern breaks any of the rules above.
+pub fn validate_topic_pattern(pattern: &str) -> Result<()> {
+    validate(pattern, true)
+}
+
+fn validate(topic: &str, wildcards: bool) -> Result<()> {
+    let invalid = |reason: &str| Error::config(format!("Invalid topic \"{topic}\": {reason}"));
+
+    if topic.is_empty() {
+        return Err(invalid("topics must not be empty"));
+    }
+
+    let Some(levels) = topic.strip_prefix(LEVEL_SEPARATOR) else {
+        return Err(invalid("topics must start with a '/'"));
+    };
+
+    if levels.is_empty() {
+        return Err(invalid("topics must contain at least one level"));
+    }
+
+    if levels.ends_with(LEVEL_SEPARATOR) {
+        return Err(invalid("topics must not end with a '/'"));
+    }
+
+    let levels: Vec<&str> = levels.split(LEVEL_SEPARATOR).collect();
+    let last = levels.len() - 1;
+
+    for (idx, level) in levels.into_iter().enumerate() {
+        match level {
+            "" => return Err(invalid("topics must not contain empty levels")),
+            SINGLE_LEVEL if wildcards => (),
+            MULTI_LEVEL if wildcards && idx == last => (),
+            MULTI_LEVEL if wildcards => {
+                return Err(invalid("the '#' wildcard must be the last level"))
+            }
+            level if wildcards && level.contains(['+', '#']) => {
+                return Err(invalid("wildcards must occupy an entire level"))
+            }
+            level => {
+                if let Some(c) = level.chars().find(|&c| !is_allowed(c)) {
+                    return Err(invalid(&format!("'{c}' is not allowed in topics")));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reason(topic: &str) -> String {
+        validate_topic(topic).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn accepts_valid_topics() {
+        for topic in ["/acmeco", "/acmeco/stocks", "/acme-co/stock_prices/v1.2"] {
+            assert!(validate_topic(topic).is_ok(), "{topic} should be valid");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_topics() {
+        assert_eq!(reason(""), "Invalid topic \"\": topics must not be empty");
+        assert_eq!(
+            reason("acmeco/stocks"),
+            "Invalid topic \"acmeco/stocks\": topics must start with a '/'"
+        );
+        assert_eq!(
+            reason("/"),
+            "Invalid topic \"/\": topics must contain at least one level"
+        );
+        assert_eq!(
+            reason("/acmeco/stocks/"),
+            "Invalid topic \"/acmeco/stocks/\": topics must not end with a '/'"
+        );
+        assert_eq!(
+            reason("/acmeco//stocks"),
+            "Invalid topic \"/acmeco//stocks\": topics must not contain empty levels"
+        );
+        assert_eq!(
+            reason("/acmeco/stock prices"),
+            "Invalid topic \"/acmeco/stock prices\": ' ' is not allowed in topics"
+        );
+        assert!(matches!(validate_topic("/acmeco/#"), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn validates_wildcard_patterns() {
+        assert!(validate_topic_pattern("/acmeco/+/trades").is_ok());
+        assert!(validate_topic_pattern("/acmeco/#").is_ok());
+        assert!(validate_topic_pattern("/acmeco/#/trades").is_err());
+        assert!(validate_topic_pattern("/acmeco/stocks+").is_err());
+        assert!(validate_topic_pattern("acmeco/#").is_err());
+    }
+}
@@ -1,5 +1,11 @@
+use crate::codecs::CodecRegistry;
 use anyhow::Result;
+use async_trait::async_trait;
 use bytes::{Bytes, BytesMut};
+use futures::future::BoxFuture;
+use selium_common::protocol::{Headers, MessagePayload};
+use std::marker::PhantomData;
+use std::sync::Arc;
 
 pub(crate) trait SeliumCodec {}
 
@@ -8,6 +14,41 @@ pub(crate) trait SeliumCodec {}
 /// See [codecs](crate::codecs) for more information.
 pub trait MessageEncoder<Item> {
     fn encode(&self, item: Item) -> Result<Bytes>;
+
+    /// Encodes `item` directly into `dst`, after any bytes already written to it.
+    ///
+    /// A [Publisher](crate::Publisher) encodes every message into a reusable buffer via this
+    /// method, so that encoders writing straight into `dst` avoid allocating a buffer of their own
+    /// for each message. The default implementation calls [encode](MessageEncoder::encode), and
+    /// copies the result into `dst`. Implementors overriding it must write the same bytes as
+    /// [encode](MessageEncoder::encode).
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if `item` fails to encode, in which case `dst` may contain part of the
+    /// encoded item.
+    fn encode_into(&self, item: Item, dst: &mut BytesMut) -> Result<()> {
+        dst.extend_from_slice(&self.encode(item)?);
+        Ok(())
+    }
+}
+
+/// Provides an `encode_ref` method for encoders that can encode an `Item` by reference, so that
+/// a [Publisher](crate::Publisher) can send borrowed data via
+/// [send_ref](crate::Publisher::send_ref) without cloning it into an owned item first.
+///
+/// This is a separate trait from [MessageEncoder], so that implementing it doesn't affect how the
+/// `Item` type of a [Publisher](crate::Publisher) is inferred from its encoder.
+///
+/// See [codecs](crate::codecs) for more information.
+pub trait BorrowedMessageEncoder<Item: ?Sized> {
+    /// Encodes `item` directly into `dst`, after any bytes already written to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if `item` fails to encode, in which case `dst` may contain part of the
+    /// encoded item.
+    fn encode_ref(&self, item: &Item, dst: &mut BytesMut) -> Result<()>;
 }
 
 /// Provides a `decode` method for implementors to build their own decoder types.
@@ -16,3 +57,289 @@ pub trait MessageEncoder<Item> {
 pub trait MessageDecoder<T> {
     fn decode(&self, buffer: &mut BytesMut) -> Result<T>;
 }
+
+/// Provides an asynchronous `decode` method for implementors to build decoders that need to
+/// await external resources, such as a schema registry, while decoding a message.
+///
+/// An [AsyncMessageDecoder] can be provided to a [Subscriber](crate::Subscriber) in place of a
+/// [MessageDecoder], via the same `with_decoder` builder method.
+///
+/// See [codecs](crate::codecs) for more information.
+#[async_trait]
+pub trait AsyncMessageDecoder<T> {
+    async fn decode(&self, buffer: BytesMut) -> Result<T>;
+}
+
+/// Marker type used to select the [MessageDecoder] implementation of a decoder.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum SyncDecoder {}
+
+/// Marker type used to select the [AsyncMessageDecoder] implementation of a decoder.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum AsyncDecoder {}
+
+/// Marker type used to select the [RawFrames] implementation of a decoder.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum RawDecoder {}
+
+/// Marker type used to select the [CodecRegistry] implementation of a decoder.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum RegistryDecoder {}
+
+/// Marker type used to select the [WithMetadata] implementation of a decoder, wrapping the `Kind`
+/// and `Item` of the inner decoder.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct WithMetadataDecoder<Kind, Item>(PhantomData<(Kind, Item)>);
+
+#[doc(hidden)]
+pub enum DecodedFrame<Item> {
+    Ready(Result<Item>),
+    Pending(BoxFuture<'static, Result<Item>>),
+}
+
+/// A sealed trait implemented for every [MessageDecoder] and [AsyncMessageDecoder], allowing a
+/// [Subscriber](crate::Subscriber) to accept either kind of decoder.
+///
+/// The `Kind` parameter is a marker type that is inferred from the decoder provided to the
+/// [StreamBuilder](crate::StreamBuilder), and never needs to be specified explicitly.
+pub trait SubscriberDecoder<Item, Kind>: private::Sealed<Item, Kind> {
+    #[doc(hidden)]
+    fn decode_frame(this: &Arc<Self>, payload: MessagePayload) -> DecodedFrame<Item>;
+}
+
+impl<D, Item> SubscriberDecoder<Item, SyncDecoder> for D
+where
+    D: MessageDecoder<Item>,
+{
+    fn decode_frame(this: &Arc<Self>, payload: MessagePayload) -> DecodedFrame<Item> {
+        let mut buffer = BytesMut::from(&payload.message[..]);
+        DecodedFrame::Ready(this.decode(&mut buffer))
+    }
+}
+
+impl<D, Item> SubscriberDecoder<Item, AsyncDecoder> for D
+where
+    D: AsyncMessageDecoder<Item> + Send + Sync + 'static,
+    Item: Send + 'static,
+{
+    fn decode_frame(this: &Arc<Self>, payload: MessagePayload) -> DecodedFrame<Item> {
+        let decoder = this.clone();
+        let buffer = BytesMut::from(&payload.message[..]);
+        DecodedFrame::Pending(Box::pin(async move { decoder.decode(buffer).await }))
+    }
+}
+
+/// Yields the payload of each message received by a [Subscriber](crate::Subscriber) as it
+/// arrived over the wire, without decoding or copying it.
+///
+/// Constructed via the `raw` method on the [StreamBuilder](crate::StreamBuilder) of a
+/// [Subscriber](crate::Subscriber).
+#[doc(hidden)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawFrames;
+
+impl SubscriberDecoder<Bytes, RawDecoder> for RawFrames {
+    fn decode_frame(_this: &Arc<Self>, payload: MessagePayload) -> DecodedFrame<Bytes> {
+        DecodedFrame::Ready(Ok(payload.message))
+    }
+}
+
+impl SeliumCodec for RawFrames {}
+
+impl<Item> SubscriberDecoder<Item, RegistryDecoder> for CodecRegistry<Item> {
+    fn decode_frame(this: &Arc<Self>, payload: MessagePayload) -> DecodedFrame<Item> {
+        let mut buffer = BytesMut::from(&payload.message[..]);
+        DecodedFrame::Ready(this.decode(&payload.headers, &mut buffer))
+    }
+}
+
+/// Builds the item yielded by a [Subscriber](crate::Subscriber) from a decoded message and the
+/// metadata it was received with.
+#[doc(hidden)]
+pub trait FromMessageParts<Item> {
+    fn from_parts(topic: String, headers: Headers, offset: Option<u64>, item: Item) -> Self;
+}
+
+impl<Item> FromMessageParts<Item> for (String, Item) {
+    fn from_parts(topic: String, _headers: Headers, _offset: Option<u64>, item: Item) -> Self {
+        (topic, item)
+    }
+}
+
+/// Wraps a decoder, combining each decoded message with the metadata it was received with, such
+/// as the topic it was published to, any attached [Headers], and its offset in the topic's log.
+///
+/// Constructed via the `with_topic`, `with_topic_metadata`, `with_headers` and `with_offsets`
+/// methods on the [StreamBuilder](crate::StreamBuilder) of a [Subscriber](crate::Subscriber).
+/// Messages that have not been tagged with a topic by the server are paired with the topic the
+/// [Subscriber](crate::Subscriber) was opened with.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct WithMetadata<D> {
+    inner: Arc<D>,
+    topic: String,
+}
+
+impl<D> WithMetadata<D> {
+    pub(crate) fn new(inner: D, topic: String) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            topic,
+        }
+    }
+}
+
+impl<D, Item, Kind, Out> SubscriberDecoder<Out, WithMetadataDecoder<Kind, Item>> for WithMetadata<D>
+where
+    D: SubscriberDecoder<Item, Kind>,
+    Item: Send + 'static,
+    Out: FromMessageParts<Item> + Send + 'static,
+{
+    fn decode_frame(this: &Arc<Self>, payload: MessagePayload) -> DecodedFrame<Out> {
+        let MessagePayload {
+            topic,
+            headers,
+            message,
+            offset,
+            ..
+        } = payload;
+        let topic = topic.unwrap_or_else(|| this.topic.clone());
+
+        // Headers are passed on, as the inner decoder may select a codec by them
+        let inner = MessagePayload::with_headers(message, headers.clone());
+
+        match D::decode_frame(&this.inner, inner) {
+            DecodedFrame::Ready(decoded) => DecodedFrame::Ready(
+                decoded.map(|item| Out::from_parts(topic, headers, offset, item)),
+            ),
+            DecodedFrame::Pending(pending) => DecodedFrame::Pending(Box::pin(async move {
+                Ok(Out::from_parts(topic, headers, offset, pending.await?))
+            })),
+        }
+    }
+}
+
+impl<D: SeliumCodec> SeliumCodec for WithMetadata<D> {}
+
+mod private {
+    use super::{
+        AsyncDecoder, AsyncMessageDecoder, CodecRegistry, MessageDecoder, RawDecoder, RawFrames,
+        RegistryDecoder, SubscriberDecoder, SyncDecoder, WithMetadata, WithMetadataDecoder,
+    };
+
+    pub trait Sealed<Item, Kind> {}
+
+    impl<D: MessageDecoder<Item>, Item> Sealed<Item, SyncDecoder> for D {}
+    impl<D: AsyncMessageDecoder<Item>, Item> Sealed<Item, AsyncDecoder> for D {}
+    impl Sealed<bytes::Bytes, RawDecoder> for RawFrames {}
+    impl<Item> Sealed<Item, RegistryDecoder> for CodecRegistry<Item> {}
+    impl<D: SubscriberDecoder<Item, Kind>, Item, Kind, Out>
+        Sealed<Out, WithMetadataDecoder<Kind, Item>> for WithMetadata<D>
+    {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs::StringCodec;
+
+    struct MockAsyncDecoder;
+
+    #[async_trait]
+    impl AsyncMessageDecoder<String> for MockAsyncDecoder {
+        async fn decode(&self, buffer: BytesMut) -> Result<String> {
+            tokio::task::yield_now().await;
+            Ok(String::from_utf8(buffer.to_vec())?.to_uppercase())
+        }
+    }
+
+    #[test]
+    fn decodes_frame_with_sync_decoder() {
+        let decoder = Arc::new(StringCodec);
+        let decoded = SubscriberDecoder::<String, SyncDecoder>::decode_frame(
+            &decoder,
+            MessagePayload::new(Bytes::from("hello")),
+        );
+
+        match decoded {
+            DecodedFrame::Ready(result) => assert_eq!(result.unwrap(), "hello"),
+            DecodedFrame::Pending(_) => panic!("Expected sync decoder to be ready"),
+        }
+    }
+
+    #[tokio::test]
+    async fn decodes_frame_with_async_decoder() {
+        let decoder = Arc::new(MockAsyncDecoder);
+        let decoded = SubscriberDecoder::<String, AsyncDecoder>::decode_frame(
+            &decoder,
+            MessagePayload::new(Bytes::from("hello")),
+        );
+
+        match decoded {
+            DecodedFrame::Pending(future) => assert_eq!(future.await.unwrap(), "HELLO"),
+            DecodedFrame::Ready(_) => panic!("Expected async decoder to be pending"),
+        }
+    }
+
+    #[test]
+    fn yields_raw_frame_without_copying() {
+        let message = Bytes::from_static(&[0x00, 0xff, b'\n']);
+        let decoded = SubscriberDecoder::<Bytes, RawDecoder>::decode_frame(
+            &Arc::new(RawFrames),
+            MessagePayload::new(message.clone()),
+        );
+
+        match decoded {
+            DecodedFrame::Ready(result) => {
+                let frame = result.unwrap();
+                assert_eq!(frame, message);
+                assert_eq!(frame.as_ptr(), message.as_ptr());
+            }
+            DecodedFrame::Pending(_) => panic!("Expected raw frame to be ready"),
+        }
+    }
+
+    #[test]
+    fn pairs_decoded_frame_with_tagged_topic() {
+        let decoder = Arc::new(WithMetadata::new(StringCodec, "/acmeco/+/trades".into()));
+        let payload = MessagePayload {
+            topic: Some("/acmeco/stocks/trades".into()),
+            ..MessagePayload::new(Bytes::from("hello"))
+        };
+        let decoded = SubscriberDecoder::<
+            (String, String),
+            WithMetadataDecoder<SyncDecoder, _>,
+        >::decode_frame(&decoder, payload);
+
+        match decoded {
+            DecodedFrame::Ready(result) => assert_eq!(
+                result.unwrap(),
+                ("/acmeco/stocks/trades".to_owned(), "hello".to_owned())
+            ),
+            DecodedFrame::Pending(_) => panic!("Expected sync decoder to be ready"),
+        }
+    }
+
+    #[tokio::test]
+    async fn pairs_decoded_frame_with_subscribed_topic() {
+        let decoder = Arc::new(WithMetadata::new(MockAsyncDecoder, "/acmeco/stocks".into()));
+        let decoded = SubscriberDecoder::<
+            (String, String),
+            WithMetadataDecoder<AsyncDecoder, _>,
+        >::decode_frame(&decoder, MessagePayload::new(Bytes::from("hello")));
+
+        match decoded {
+            DecodedFrame::Pending(future) => assert_eq!(
+                future.await.unwrap(),
+                ("/acmeco/stocks".to_owned(), "HELLO".to_owned())
+            ),
+            DecodedFrame::Ready(_) => panic!("Expected async decoder to be pending"),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use super::TryIntoU64;
-use anyhow::Result;
+use crate::Result;
 use async_trait::async_trait;
 
 /// Provides an `open` method for [StreamBuilder](crate::StreamBuilder) implementations to
@@ -23,6 +23,26 @@ pub trait Open {
 
 #[doc(hidden)]
 pub trait Retain {
+    /// Specifies how long the `Selium` server retains each message published to the stream's
+    /// topic, in milliseconds, so that subscribers joining the topic later still receive it.
+    ///
+    /// The server retains every message published within the retention period, up to a limit
+    /// of 1024 messages per topic, beyond which the oldest are dropped. When a subscriber joins
+    /// the topic, it receives the retained messages in the order they were published, before
+    /// any new messages. Retaining messages for long enough to outlive the next message gives
+    /// "last known value" semantics, e.g. for configuration topics.
+    ///
+    /// Only a [Publisher](crate::Publisher)'s retention policy determines whether its messages
+    /// are retained. Retained messages are delivered to every [Subscriber](crate::Subscriber)
+    /// of the topic, except those subscribing to a wildcard pattern or as part of a consumer
+    /// group. By default, messages are not retained.
+    ///
+    /// Accepts any `policy` argument that can be *fallibly* converted into a [u64] via the
+    /// [TryIntoU64](crate::traits::TryIntoU64) trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the provided policy fails to be converted to a [u64].
     fn retain<T: TryIntoU64>(self, policy: T) -> Result<Self>
     where
         Self: Sized;
@@ -30,6 +50,28 @@ pub trait Retain {
 
 #[doc(hidden)]
 pub trait Operations {
+    /// Transforms each message delivered to a [Subscriber](crate::Subscriber) with the
+    /// WebAssembly module at `module_path`, which the `Selium` server runs before delivering
+    /// the message.
+    ///
+    /// The path is resolved within the directory that the server was started with via its
+    /// `--modules` option, and the subscription is rejected if the module can't be loaded. The
+    /// module receives the encoded message payload, and returns the payload to deliver in its
+    /// place, so the decoder must be able to decode the transformed payload. Messages that the
+    /// module fails to transform are dropped. See the server's documentation for the ABI that
+    /// modules must implement.
+    ///
+    /// Operations are applied in the order they're specified. Compressed messages are delivered
+    /// without being transformed, as the server doesn't decompress them.
     fn map(self, module_path: &str) -> Self;
+
+    /// Filters the messages delivered to a [Subscriber](crate::Subscriber) with the WebAssembly
+    /// module at `module_path`, which the `Selium` server runs to decide whether each message is
+    /// delivered, so that unwanted messages are never sent to the client.
+    ///
+    /// The path is resolved in the same way as [map](Operations::map). The module receives the
+    /// encoded message payload, and returns whether it should be delivered. If the module fails
+    /// to evaluate a message, the message is dropped, unless the server was started with the
+    /// `--filter-error-policy deliver` option, in which case it's delivered as if it had passed.
     fn filter(self, module_path: &str) -> Self;
 }
@@ -1,50 +1,220 @@
-use super::net::get_socket_addrs;
-use anyhow::Result;
-use quinn::{ClientConfig, Connection, Endpoint, TransportConfig};
+use super::net::ServerAddr;
+use crate::crypto::cert::ClientCertificate;
+use crate::{CongestionController, Error};
+use anyhow::{bail, Context, Result};
+use quinn::congestion::{BbrConfig, CubicConfig, NewRenoConfig};
+use quinn::{ClientConfig, Connection, Endpoint, IdleTimeout, TransportConfig, VarInt};
+use rustls::client::{ClientSessionMemoryCache, Resumption};
 use rustls::RootCertStore;
-use std::sync::Arc;
-use std::{net::SocketAddr, time::Duration};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
-pub(crate) const ALPN_QUIC_HTTP: &[&[u8]] = &[b"hq-29"];
+// The number of TLS sessions cached for 0-RTT resumption
+const SESSION_CACHE_SIZE: usize = 256;
 
-pub(crate) fn configure_client(root_store: &RootCertStore, keep_alive: u64) -> ClientConfig {
-    let mut crypto = rustls::ClientConfig::builder()
-        .with_safe_defaults()
-        .with_root_certificates(root_store.to_owned())
-        .with_no_client_auth();
+/// Determines how the certificate presented by the `Selium` server is verified.
+#[derive(Debug)]
+pub(crate) enum ServerVerification {
+    RootStore(RootCertStore),
+    #[cfg(feature = "dangerous-configuration")]
+    Skip,
+    // Verified as configured by a custom rustls config, which replaces every other TLS option
+    Custom(rustls::ClientConfig),
+}
+
+/// The transport and TLS options configured on the client builder, which each connection to the
+/// `Selium` server is configured with.
+#[derive(Debug)]
+pub(crate) struct ClientOptions {
+    pub keep_alive: u64,
+    pub max_idle_timeout: u64,
+    pub client_cert: Option<ClientCertificate>,
+    pub enable_0rtt: bool,
+    pub congestion_controller: CongestionController,
+    pub max_concurrent_streams: u32,
+    pub stream_receive_window: u64,
+    pub receive_window: u64,
+    pub alpn_protocols: Vec<Vec<u8>>,
+}
+
+pub(crate) fn configure_client(
+    verification: &ServerVerification,
+    options: &ClientOptions,
+) -> Result<ClientConfig> {
+    let ClientOptions {
+        keep_alive,
+        max_idle_timeout,
+        ref client_cert,
+        enable_0rtt,
+        congestion_controller,
+        max_concurrent_streams,
+        stream_receive_window,
+        receive_window,
+        ref alpn_protocols,
+    } = *options;
+    let client_cert = client_cert.as_ref();
+
+    if keep_alive == 0 {
+        bail!(Error::config(
+            "The keep_alive interval must be greater than zero, otherwise keep-alives would be \
+            sent continuously"
+        ));
+    }
 
-    crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
+    if keep_alive >= max_idle_timeout {
+        bail!(Error::config(format!(
+            "The keep_alive interval ({keep_alive}ms) must be less than the max_idle_timeout \
+            ({max_idle_timeout}ms), otherwise idle connections will be closed prematurely"
+        )));
+    }
+
+    if alpn_protocols.is_empty() {
+        bail!(Error::config(
+            "At least one ALPN protocol must be advertised, otherwise the server rejects the \
+            handshake"
+        ));
+    }
+
+    let crypto = match verification {
+        ServerVerification::RootStore(root_store) => configure_crypto(
+            root_store.to_owned(),
+            client_cert,
+            enable_0rtt,
+            alpn_protocols,
+        )?,
+        #[cfg(feature = "dangerous-configuration")]
+        ServerVerification::Skip => {
+            let mut crypto = configure_crypto(
+                RootCertStore::empty(),
+                client_cert,
+                enable_0rtt,
+                alpn_protocols,
+            )?;
+            crypto.dangerous().set_certificate_verifier(Arc::new(
+                crate::crypto::dangerous::SkipServerVerification,
+            ));
+            crypto
+        }
+        ServerVerification::Custom(crypto) => {
+            let mut crypto = crypto.clone();
+            // The custom config's protocols take precedence, but QUIC requires at least one
+            if crypto.alpn_protocols.is_empty() {
+                crypto.alpn_protocols = alpn_protocols.to_vec();
+            }
+            crypto
+        }
+    };
 
     let mut config = ClientConfig::new(Arc::new(crypto));
     let mut transport_config = TransportConfig::default();
     let keep_alive = Duration::from_millis(keep_alive);
+    let max_idle_timeout = IdleTimeout::try_from(Duration::from_millis(max_idle_timeout))?;
+    let stream_receive_window = window("stream_receive_window", stream_receive_window)?;
+    let receive_window = window("receive_window", receive_window)?;
 
     transport_config.keep_alive_interval(Some(keep_alive));
+    transport_config.max_idle_timeout(Some(max_idle_timeout));
+    transport_config.max_concurrent_bidi_streams(VarInt::from_u32(max_concurrent_streams));
+    transport_config.stream_receive_window(stream_receive_window);
+    transport_config.receive_window(receive_window);
+
+    match congestion_controller {
+        CongestionController::Bbr => {
+            transport_config.congestion_controller_factory(Arc::new(BbrConfig::default()))
+        }
+        CongestionController::Cubic => {
+            transport_config.congestion_controller_factory(Arc::new(CubicConfig::default()))
+        }
+        CongestionController::NewReno => {
+            transport_config.congestion_controller_factory(Arc::new(NewRenoConfig::default()))
+        }
+    };
+
     config.transport_config(Arc::new(transport_config));
 
-    config
+    Ok(config)
+}
+
+/// Validates a flow control window, which must be non-zero to allow any data to be received.
+fn window(name: &str, bytes: u64) -> Result<VarInt> {
+    match VarInt::from_u64(bytes) {
+        Ok(window) if bytes > 0 => Ok(window),
+        _ => bail!(Error::config(format!(
+            "The {name} ({bytes} bytes) must be greater than zero, and at most 2^62 - 1 bytes"
+        ))),
+    }
+}
+
+/// Builds the rustls config from the TLS options configured on the client builder.
+fn configure_crypto(
+    root_store: RootCertStore,
+    client_cert: Option<&ClientCertificate>,
+    enable_0rtt: bool,
+    alpn_protocols: &[Vec<u8>],
+) -> Result<rustls::ClientConfig> {
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store);
+
+    let mut crypto = match client_cert {
+        Some(cert) => builder.with_client_auth_cert(cert.certs.clone(), cert.key.clone())?,
+        None => builder.with_no_client_auth(),
+    };
+
+    crypto.alpn_protocols = alpn_protocols.to_vec();
+
+    if enable_0rtt {
+        crypto.enable_early_data = true;
+        crypto.resumption = Resumption::store(session_cache());
+    }
+
+    Ok(crypto)
+}
+
+/// Returns the session cache shared by every client with 0-RTT enabled, so that a session can be
+/// resumed by any later client in the same process, rather than only by reconnections.
+fn session_cache() -> Arc<ClientSessionMemoryCache> {
+    static CACHE: OnceLock<Arc<ClientSessionMemoryCache>> = OnceLock::new();
+
+    CACHE
+        .get_or_init(|| Arc::new(ClientSessionMemoryCache::new(SESSION_CACHE_SIZE)))
+        .clone()
 }
 
 pub(crate) async fn connect_to_endpoint(
     config: ClientConfig,
-    addr: SocketAddr,
-) -> Result<Connection> {
-    let mut endpoint = Endpoint::client("[::]:0".parse()?)?;
+    server: &ServerAddr,
+    connect_timeout: Option<Duration>,
+    enable_0rtt: bool,
+) -> Result<(Endpoint, Connection)> {
+    let addr = server.addr;
+    // Bound to the same address family as the server, as not every host supports dual-stack
+    // sockets
+    let bind_addr = match addr {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+
+    let mut endpoint = Endpoint::client(bind_addr)?;
     endpoint.set_default_client_config(config);
 
-    let connection = endpoint.connect(addr, "localhost")?.await?;
+    let mut connecting = endpoint.connect(addr, &server.server_name)?;
 
-    Ok(connection)
-}
+    // Without a cached session ticket permitting early data, a full handshake is performed
+    if enable_0rtt {
+        match connecting.into_0rtt() {
+            Ok((connection, _)) => return Ok((endpoint, connection)),
+            Err(full_handshake) => connecting = full_handshake,
+        }
+    }
 
-pub(crate) async fn establish_connection(
-    host: &str,
-    root_store: &RootCertStore,
-    keep_alive: u64,
-) -> Result<Connection> {
-    let addr = get_socket_addrs(host)?;
-    let config = configure_client(root_store, keep_alive);
-    let connection = connect_to_endpoint(config, addr).await?;
+    let connection = match connect_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, connecting)
+            .await
+            .with_context(|| format!("Timed out connecting to {addr} after {timeout:?}"))??,
+        None => connecting.await?,
+    };
 
-    Ok(connection)
+    Ok((endpoint, connection))
 }
@@ -1,11 +1,84 @@
+use crate::Error;
 use anyhow::{Context, Result};
-use std::net::{SocketAddr, ToSocketAddrs};
+use quinn::{StreamId, VarInt};
+use std::net::SocketAddr;
 
-pub(crate) fn get_socket_addrs(host: &str) -> Result<SocketAddr> {
-    let addr = host
-        .to_socket_addrs()?
-        .next()
-        .context("Address not available")?;
+/// An address that a server endpoint resolved to, along with the name that the server's
+/// certificate is verified against.
+#[derive(Debug, Clone)]
+pub(crate) struct ServerAddr {
+    pub addr: SocketAddr,
+    pub server_name: String,
+}
+
+/// Resolves a `host:port` endpoint to each of the addresses it refers to, in the order returned
+/// by the resolver.
+///
+/// IP literals, such as `127.0.0.1:7001` or `[::1]:7001`, aren't resolved, and are verified
+/// against the `default_server_name`, as they have no hostname of their own. Hostnames are
+/// resolved via DNS, and are verified against the hostname rather than the resolved address.
+pub(crate) async fn resolve(host: &str, default_server_name: &str) -> Result<Vec<ServerAddr>> {
+    if let Ok(addr) = host.parse::<SocketAddr>() {
+        return Ok(vec![ServerAddr {
+            addr,
+            server_name: default_server_name.to_owned(),
+        }]);
+    }
+
+    let server_name = match host.rsplit_once(':') {
+        Some((name, _)) => name,
+        None => return Err(Error::config(format!("Endpoint {host} is missing a port")).into()),
+    };
+
+    let addrs: Vec<_> = tokio::net::lookup_host(host)
+        .await
+        .with_context(|| format!("Failed to resolve {host}"))?
+        .map(|addr| ServerAddr {
+            addr,
+            server_name: server_name.to_owned(),
+        })
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(Error::connection(format!("{host} did not resolve to any addresses")).into());
+    }
+
+    Ok(addrs)
+}
+
+/// Returns the integer that identifies a stream on the wire, which is also how the server refers
+/// to it.
+pub(crate) fn stream_id(id: StreamId) -> u64 {
+    VarInt::from(id).into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn verifies_ip_literals_against_default_server_name() {
+        for host in ["127.0.0.1:7001", "[::1]:7001"] {
+            let addrs = resolve(host, "localhost").await.unwrap();
+
+            assert_eq!(addrs.len(), 1);
+            assert_eq!(addrs[0].addr, host.parse().unwrap());
+            assert_eq!(addrs[0].server_name, "localhost");
+        }
+    }
+
+    #[tokio::test]
+    async fn verifies_hostnames_against_hostname() {
+        let addrs = resolve("localhost:7001", "selium.example").await.unwrap();
+
+        assert!(!addrs.is_empty());
+        assert!(addrs.iter().all(|addr| addr.addr.ip().is_loopback()));
+        assert!(addrs.iter().all(|addr| addr.server_name == "localhost"));
+    }
 
-    Ok(addr)
+    #[tokio::test]
+    async fn rejects_hosts_without_port() {
+        let err = resolve("localhost", "localhost").await.unwrap_err();
+        assert!(matches!(Error::from(err), Error::Config(_)));
+    }
 }
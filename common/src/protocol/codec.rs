@@ -1,67 +1,341 @@
-use crate::protocol::Frame;
+use crate::protocol::{frame::is_frame_type, Frame};
+use anyhow::bail;
 use bytes::{Buf, BufMut, BytesMut};
+use std::fmt;
 use std::mem::size_of;
 use tokio_util::codec::{Decoder, Encoder};
 
 const LEN_MARKER_SIZE: usize = size_of::<u64>();
 const TYPE_MARKER_SIZE: usize = size_of::<u8>();
-const RESERVED_SIZE: usize = LEN_MARKER_SIZE + TYPE_MARKER_SIZE;
+const CHECKSUM_SIZE: usize = size_of::<u32>();
+const MARKERS_SIZE: usize = LEN_MARKER_SIZE + TYPE_MARKER_SIZE;
 
-#[derive(Debug, Default)]
-pub struct MessageCodec;
+/// The size of a frame's header, in bytes, which holds its length and type markers, followed by a
+/// CRC-32 checksum of the markers.
+pub const HEADER_SIZE: usize = MARKERS_SIZE + CHECKSUM_SIZE;
 
-impl Encoder<Frame> for MessageCodec {
-    type Error = anyhow::Error;
+/// The default maximum size of a frame, in bytes, excluding its header.
+pub const DEFAULT_MAX_MESSAGE_SIZE: u64 = 64 * 1024 * 1024;
 
-    fn encode(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let length = item.get_length()?;
-        let message_type = item.get_type();
+/// The error returned when a frame exceeds the maximum message size of a [MessageCodec].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageTooLarge {
+    /// The length of the frame, in bytes, excluding its header.
+    pub length: u64,
+    /// The maximum message size that the frame exceeded.
+    pub max_message_size: u64,
+}
 
-        dst.reserve(RESERVED_SIZE + length as usize);
-        dst.put_u64(length);
-        dst.put_u8(message_type);
-        item.write_to_bytes(dst)?;
+impl fmt::Display for MessageTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Message of {} bytes exceeds the maximum message size of {} bytes",
+            self.length, self.max_message_size
+        )
+    }
+}
 
-        Ok(())
+impl std::error::Error for MessageTooLarge {}
+
+/// The error returned when a frame would not fit within the maximum buffer capacity of a
+/// [MessageCodec].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferCapacityExceeded {
+    /// The number of bytes needed to buffer the frame, including its header.
+    pub required: u64,
+    /// The maximum buffer capacity that the frame exceeded.
+    pub max_buffer_capacity: u64,
+}
+
+impl fmt::Display for BufferCapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Frame of {} bytes exceeds the maximum buffer capacity of {} bytes",
+            self.required, self.max_buffer_capacity
+        )
     }
 }
 
-impl Decoder for MessageCodec {
-    type Error = anyhow::Error;
-    type Item = Frame;
+impl std::error::Error for BufferCapacityExceeded {}
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        if src.len() < RESERVED_SIZE {
+/// Returns the number of bytes the provided [Frame] occupies on the wire once encoded, including
+/// its header.
+pub fn encoded_length(frame: &Frame) -> anyhow::Result<u64> {
+    Ok(HEADER_SIZE as u64 + frame.get_length()?)
+}
+
+/// Returns the length of the provided [Frame], excluding its header, or a
+/// [MessageTooLarge] error if it exceeds `max_message_size`.
+pub fn check_message_size(frame: &Frame, max_message_size: u64) -> anyhow::Result<u64> {
+    let length = frame.get_length()?;
+
+    if length > max_message_size {
+        return Err(MessageTooLarge {
+            length,
+            max_message_size,
+        }
+        .into());
+    }
+
+    Ok(length)
+}
+
+/// Determines how a [MessageCodec] handles a frame that cannot be decoded, such as one with a
+/// corrupt header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FramingErrorPolicy {
+    /// Decoding fails with the error, which ends the stream, as the position of the next frame is
+    /// unknown.
+    #[default]
+    Fail,
+    /// The codec resynchronizes by skipping a byte at a time until it finds the start of the next
+    /// frame that decodes successfully, discarding everything before it.
+    ///
+    /// The start of a frame is identified by a header whose checksum matches its length and type
+    /// markers, with a length within the maximum message size and a known type, so the body of a
+    /// corrupt frame is only mistaken for the start of another if it happens to contain a valid
+    /// header. An oversized frame is also treated as corrupt, rather than failing with a
+    /// [MessageTooLarge] error.
+    Resync,
+}
+
+/// Encodes and decodes length-delimited [Frame]s, rejecting any frame longer than its maximum
+/// message size.
+///
+/// Each frame starts with a header holding its length and type markers, and a checksum of them,
+/// so that a corrupt header is detected before its length is trusted.
+///
+/// The length of a frame being decoded is checked before its body is buffered, so a peer cannot
+/// force an allocation beyond the limit by sending an oversized length marker.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageCodec {
+    max_message_size: u64,
+    max_buffer_capacity: Option<u64>,
+    framing_error_policy: FramingErrorPolicy,
+}
+
+impl MessageCodec {
+    pub fn new(max_message_size: u64) -> Self {
+        Self {
+            max_message_size,
+            max_buffer_capacity: None,
+            framing_error_policy: FramingErrorPolicy::default(),
+        }
+    }
+
+    /// Caps the buffer that frames are decoded from at `capacity` bytes, including the header of
+    /// the frame being decoded. A frame that wouldn't fit fails to decode with a
+    /// [BufferCapacityExceeded] error before any of its body is buffered, and the buffer is only
+    /// grown by as much as the frame being decoded needs.
+    pub fn with_max_buffer_capacity(mut self, capacity: u64) -> Self {
+        self.max_buffer_capacity = Some(capacity);
+        self
+    }
+
+    /// Overrides how frames that cannot be decoded are handled, which defaults to
+    /// [FramingErrorPolicy::Fail].
+    pub fn with_framing_error_policy(mut self, policy: FramingErrorPolicy) -> Self {
+        self.framing_error_policy = policy;
+        self
+    }
+
+    pub fn max_message_size(&self) -> u64 {
+        self.max_message_size
+    }
+
+    pub fn max_buffer_capacity(&self) -> Option<u64> {
+        self.max_buffer_capacity
+    }
+
+    pub fn framing_error_policy(&self) -> FramingErrorPolicy {
+        self.framing_error_policy
+    }
+
+    /// Returns true if a frame with a body of `length` bytes fits within the maximum buffer
+    /// capacity, if there is one.
+    fn fits_buffer(&self, length: u64) -> bool {
+        !matches!(self.max_buffer_capacity, Some(capacity) if buffered_length(length) > capacity)
+    }
+
+    fn decode_frame(&self, src: &mut BytesMut) -> anyhow::Result<Option<Frame>> {
+        if src.len() < HEADER_SIZE {
             return Ok(None);
         }
 
-        let mut length_bytes = [0u8; LEN_MARKER_SIZE];
-        length_bytes.copy_from_slice(&src[..LEN_MARKER_SIZE]);
+        if !has_valid_checksum(src) {
+            bail!("Frame header is corrupt, as its checksum doesn't match");
+        }
+
+        let length = length_marker(src);
 
-        let length = u64::from_be_bytes(length_bytes);
-        let bytes_read = src.len() - RESERVED_SIZE;
+        if length > self.max_message_size {
+            return Err(MessageTooLarge {
+                length,
+                max_message_size: self.max_message_size,
+            }
+            .into());
+        }
+
+        if let Some(capacity) = self.max_buffer_capacity {
+            let required = buffered_length(length);
+
+            if required > capacity {
+                return Err(BufferCapacityExceeded {
+                    required,
+                    max_buffer_capacity: capacity,
+                }
+                .into());
+            }
+        }
+
+        let bytes_read = src.len() - HEADER_SIZE;
 
         if bytes_read < length as usize {
-            src.reserve(bytes_read);
+            match self.max_buffer_capacity {
+                // Reserves only the rest of the frame, which is known to fit within the capacity
+                Some(_) => src.reserve(length as usize - bytes_read),
+                None => src.reserve(bytes_read),
+            }
             return Ok(None);
         }
 
         src.advance(LEN_MARKER_SIZE);
 
         let message_type = src.get_u8();
+        src.advance(CHECKSUM_SIZE);
         let bytes = src.split_to(length as usize);
         let frame = Frame::try_from((message_type, bytes))?;
 
         Ok(Some(frame))
     }
+
+    fn decode_resync(&self, src: &mut BytesMut) -> anyhow::Result<Option<Frame>> {
+        let mut skipped = 0;
+
+        let decoded = loop {
+            if src.len() < HEADER_SIZE {
+                break None;
+            }
+
+            let length = length_marker(src);
+            let message_type = src[LEN_MARKER_SIZE];
+
+            if has_valid_checksum(src)
+                && length <= self.max_message_size
+                && self.fits_buffer(length)
+                && is_frame_type(message_type)
+            {
+                let end = HEADER_SIZE + length as usize;
+
+                if src.len() < end {
+                    src.reserve(end - src.len());
+                    break None;
+                }
+
+                // Decoded from a copy, so that the bytes can be rescanned if the frame is corrupt
+                let bytes = BytesMut::from(&src[HEADER_SIZE..end]);
+
+                if let Ok(frame) = Frame::try_from((message_type, bytes)) {
+                    src.advance(end);
+                    break Some(frame);
+                }
+            }
+
+            src.advance(1);
+            skipped += 1;
+        };
+
+        #[cfg(feature = "tracing")]
+        if skipped > 0 {
+            tracing::warn!(skipped, "Skipped corrupt bytes to resynchronize framing");
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = skipped;
+
+        Ok(decoded)
+    }
+}
+
+/// Returns the number of bytes needed to buffer a frame with a body of `length` bytes, including
+/// its header.
+fn buffered_length(length: u64) -> u64 {
+    length.saturating_add(HEADER_SIZE as u64)
+}
+
+/// Returns the checksum of a frame's length and type markers.
+fn checksum(markers: &[u8]) -> u32 {
+    crc32fast::hash(markers)
+}
+
+/// Returns true if the checksum in the header at the start of `src`, which must hold at least
+/// [HEADER_SIZE] bytes, matches its length and type markers.
+fn has_valid_checksum(src: &[u8]) -> bool {
+    let mut checksum_bytes = [0u8; CHECKSUM_SIZE];
+    checksum_bytes.copy_from_slice(&src[MARKERS_SIZE..HEADER_SIZE]);
+
+    u32::from_be_bytes(checksum_bytes) == checksum(&src[..MARKERS_SIZE])
+}
+
+/// Reads the length marker at the start of `src`, which must hold at least [LEN_MARKER_SIZE]
+/// bytes.
+fn length_marker(src: &[u8]) -> u64 {
+    let mut length_bytes = [0u8; LEN_MARKER_SIZE];
+    length_bytes.copy_from_slice(&src[..LEN_MARKER_SIZE]);
+
+    u64::from_be_bytes(length_bytes)
+}
+
+impl Default for MessageCodec {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_MESSAGE_SIZE)
+    }
+}
+
+impl Encoder<Frame> for MessageCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let length = check_message_size(&item, self.max_message_size)?;
+        let message_type = item.get_type();
+
+        let start = dst.len();
+
+        dst.reserve(HEADER_SIZE + length as usize);
+        dst.put_u64(length);
+        dst.put_u8(message_type);
+        dst.put_u32(checksum(&dst[start..]));
+        item.write_to_bytes(dst)?;
+
+        Ok(())
+    }
+}
+
+impl Decoder for MessageCodec {
+    type Error = anyhow::Error;
+    type Item = Frame;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.framing_error_policy {
+            FramingErrorPolicy::Fail => self.decode_frame(src),
+            FramingErrorPolicy::Resync => self.decode_resync(src),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::protocol::{PublisherPayload, SubscriberPayload};
+    use crate::protocol::{
+        AcceptPayload, AckPayload, AckThroughPayload, AuthToken, CommitPayload, Compression,
+        Headers, HeartbeatPayload, ListTopicsPayload, MessagePayload, PublisherPayload,
+        ReceiptPayload, ReplayStart, StartPosition, SubscriberPayload, TopicInfo, TopicListPayload,
+        UnauthorizedPayload,
+    };
     use crate::types::Operation;
     use bytes::Bytes;
+    use std::time::{Duration, SystemTime};
 
     #[test]
     fn encodes_register_subscriber_frame() {
@@ -73,11 +347,18 @@ mod tests {
                 Operation::Map("second/module.wasm".into()),
                 Operation::Filter("third/module.wasm".into()),
             ],
+            group: None,
+            acks: false,
+            replay: None,
+            ordered: false,
+            heartbeat: false,
+            token: None,
+            start_position: None,
         });
 
         let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from("\0\0\0\0\0\0\0z\x01\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm");
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0z\x01\x44\xde\xb5\x44\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -94,11 +375,13 @@ mod tests {
                 Operation::Map("second/module.wasm".into()),
                 Operation::Filter("third/module.wasm".into()),
             ],
+            compression: Compression::None,
+            token: None,
         });
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from("\0\0\0\0\0\0\0z\0\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm");
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0z\0\x33\xd9\x85\xd2\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -107,11 +390,11 @@ mod tests {
 
     #[test]
     fn encodes_message_frame() {
-        let frame = Frame::Message(Bytes::from("Hello world"));
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
 
-        let mut codec = MessageCodec;
+        let mut codec = MessageCodec::default();
         let mut buffer = BytesMut::new();
-        let expected = Bytes::from("\0\0\0\0\0\0\0\x0b\x02Hello world");
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x0b\x02\xeb\xf3\xac\x49Hello world");
 
         codec.encode(frame, &mut buffer).unwrap();
 
@@ -121,7 +404,7 @@ mod tests {
     #[test]
     fn decodes_register_subscriber_frame() {
         let mut codec = MessageCodec::default();
-        let mut src = BytesMut::from("\0\0\0\0\0\0\0z\x01\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm");
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0z\x01\x44\xde\xb5\x44\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm"[..]);
 
         let expected = Frame::RegisterSubscriber(SubscriberPayload {
             topic: "Some topic".into(),
@@ -131,6 +414,13 @@ mod tests {
                 Operation::Map("second/module.wasm".into()),
                 Operation::Filter("third/module.wasm".into()),
             ],
+            group: None,
+            acks: false,
+            replay: None,
+            ordered: false,
+            heartbeat: false,
+            token: None,
+            start_position: None,
         });
 
         let result = codec.decode(&mut src).unwrap().unwrap();
@@ -141,7 +431,7 @@ mod tests {
     #[test]
     fn decodes_register_publisher_frame() {
         let mut codec = MessageCodec::default();
-        let mut src = BytesMut::from("\0\0\0\0\0\0\0z\0\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm");
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0z\0\x33\xd9\x85\xd2\n\0\0\0\0\0\0\0Some topic\x05\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x11\0\0\0\0\0\0\0first/module.wasm\0\0\0\0\x12\0\0\0\0\0\0\0second/module.wasm\x01\0\0\0\x11\0\0\0\0\0\0\0third/module.wasm"[..]);
 
         let expected = Frame::RegisterPublisher(PublisherPayload {
             topic: "Some topic".into(),
@@ -151,6 +441,8 @@ mod tests {
                 Operation::Map("second/module.wasm".into()),
                 Operation::Filter("third/module.wasm".into()),
             ],
+            compression: Compression::None,
+            token: None,
         });
 
         let result = codec.decode(&mut src).unwrap().unwrap();
@@ -160,12 +452,612 @@ mod tests {
 
     #[test]
     fn decodes_message_frame() {
-        let mut codec = MessageCodec;
-        let mut src = BytesMut::from("\0\0\0\0\0\0\0\x0b\x02Hello world");
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x0b\x02\xeb\xf3\xac\x49Hello world"[..]);
 
-        let expected = Frame::Message(Bytes::from("Hello world"));
+        let expected = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
         let result = codec.decode(&mut src).unwrap().unwrap();
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn encodes_message_frame_with_topic() {
+        let payload = MessagePayload {
+            topic: Some("/a/b".into()),
+            ..MessagePayload::new(Bytes::from("Hello world"))
+        };
+        let frame = Frame::Message(payload);
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x32\x03\x92\x70\x11\x65\0\0\0\0\0\0\0\x1f\x01\x04\0\0\0\0\0\0\0/a/b\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0Hello world");
+
+        codec.encode(frame, &mut buffer).unwrap();
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn decodes_message_frame_with_topic() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x32\x03\x92\x70\x11\x65\0\0\0\0\0\0\0\x1f\x01\x04\0\0\0\0\0\0\0/a/b\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0Hello world"[..]);
+
+        let expected = Frame::Message(MessagePayload {
+            topic: Some("/a/b".into()),
+            ..MessagePayload::new(Bytes::from("Hello world"))
+        });
+        let result = codec.decode(&mut src).unwrap().unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn encodes_message_frame_with_headers() {
+        let headers = Headers::from([("content-type".to_owned(), "text/plain".to_owned())]);
+        let frame = Frame::Message(MessagePayload::with_headers(
+            Bytes::from("Hello world"),
+            headers,
+        ));
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+        let expected = Bytes::from_static(b"\0\0\0\0\0\0\0\x4c\x03\x23\xcc\x45\x1d\0\0\0\0\0\0\0\x39\0\x01\0\0\0\0\0\0\0\x0c\0\0\0\0\0\0\0content-type\x0a\0\0\0\0\0\0\0text/plain\0\0\0\0\0\0\0\0\0\0Hello world");
+
+        codec.encode(frame, &mut buffer).unwrap();
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn round_trips_message_frame_with_headers() {
+        let headers = Headers::from([
+            ("content-type".to_owned(), "application/json".to_owned()),
+            ("trace-id".to_owned(), "abc123".to_owned()),
+        ]);
+        let frame = Frame::Message(MessagePayload {
+            topic: Some("/acmeco/stocks".into()),
+            headers,
+            message: Bytes::from("{}"),
+            ..Default::default()
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_message_frame_with_correlation() {
+        let frame = Frame::Message(MessagePayload {
+            correlation_id: Some(42),
+            reply_to: Some("/selium/replies/abc".into()),
+            ..MessagePayload::new(Bytes::from("ping"))
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_register_subscriber_frame_with_options() {
+        let frame = Frame::RegisterSubscriber(SubscriberPayload {
+            topic: "Some topic".into(),
+            group: Some("workers".into()),
+            acks: true,
+            ..Default::default()
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_register_subscriber_frame_with_replay() {
+        for replay in [
+            ReplayStart::Earliest,
+            ReplayStart::Offset(42),
+            ReplayStart::Timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            ReplayStart::Committed,
+        ] {
+            let frame = Frame::RegisterSubscriber(SubscriberPayload {
+                topic: "Some topic".into(),
+                replay: Some(replay),
+                ..Default::default()
+            });
+
+            let mut codec = MessageCodec::default();
+            let mut buffer = BytesMut::new();
+
+            codec.encode(frame.clone(), &mut buffer).unwrap();
+            let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+            assert_eq!(result, frame);
+        }
+    }
+
+    #[test]
+    fn round_trips_register_subscriber_frame_with_delivery_options() {
+        let frame = Frame::RegisterSubscriber(SubscriberPayload {
+            topic: "Some topic".into(),
+            ordered: true,
+            heartbeat: true,
+            ..Default::default()
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_register_publisher_frame_with_compression() {
+        let frame = Frame::RegisterPublisher(PublisherPayload {
+            topic: "Some topic".into(),
+            compression: Compression::Zstd,
+            ..Default::default()
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_register_frames_with_token() {
+        let token = Some(AuthToken::new("secret"));
+        let frames = [
+            Frame::RegisterPublisher(PublisherPayload {
+                topic: "Some topic".into(),
+                token: token.clone(),
+                ..Default::default()
+            }),
+            Frame::RegisterSubscriber(SubscriberPayload {
+                topic: "Some topic".into(),
+                token,
+                ..Default::default()
+            }),
+        ];
+
+        for frame in frames {
+            let mut codec = MessageCodec::default();
+            let mut buffer = BytesMut::new();
+
+            codec.encode(frame.clone(), &mut buffer).unwrap();
+            let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+            assert_eq!(result, frame);
+        }
+    }
+
+    #[test]
+    fn round_trips_register_subscriber_frame_with_start_position() {
+        for token in [None, Some(AuthToken::new("secret"))] {
+            for start_position in [StartPosition::Latest, StartPosition::FromConnect] {
+                let frame = Frame::RegisterSubscriber(SubscriberPayload {
+                    topic: "Some topic".into(),
+                    token: token.clone(),
+                    start_position: Some(start_position),
+                    ..Default::default()
+                });
+
+                let mut codec = MessageCodec::default();
+                let mut buffer = BytesMut::new();
+
+                codec.encode(frame.clone(), &mut buffer).unwrap();
+                let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+                assert_eq!(result, frame);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_compressed_message_frame() {
+        let frame = Frame::Message(MessagePayload {
+            delivery_id: Some(3),
+            compression: Compression::Lz4,
+            ..MessagePayload::new(Bytes::from("compressed"))
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_keyed_message_frame() {
+        for compression in [Compression::None, Compression::Zstd] {
+            let frame = Frame::Message(MessagePayload {
+                compression,
+                idempotency_key: Some("order-42".into()),
+                ..MessagePayload::new(Bytes::from("keyed"))
+            });
+
+            let mut codec = MessageCodec::default();
+            let mut buffer = BytesMut::new();
+
+            codec.encode(frame.clone(), &mut buffer).unwrap();
+            let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+            assert_eq!(result, frame);
+        }
+    }
+
+    #[test]
+    fn round_trips_message_frame_with_offset() {
+        for idempotency_key in [None, Some("order-42".to_owned())] {
+            let frame = Frame::Message(MessagePayload {
+                idempotency_key,
+                offset: Some(7),
+                ..MessagePayload::new(Bytes::from("logged"))
+            });
+
+            let mut codec = MessageCodec::default();
+            let mut buffer = BytesMut::new();
+
+            codec.encode(frame.clone(), &mut buffer).unwrap();
+            let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+            assert_eq!(result, frame);
+        }
+    }
+
+    #[test]
+    fn round_trips_delayed_message_frame() {
+        for offset in [None, Some(7)] {
+            let frame = Frame::Message(MessagePayload {
+                offset,
+                delay: Some(Duration::from_millis(500)),
+                ..MessagePayload::new(Bytes::from("delayed"))
+            });
+
+            let mut codec = MessageCodec::default();
+            let mut buffer = BytesMut::new();
+
+            codec.encode(frame.clone(), &mut buffer).unwrap();
+            let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+            assert_eq!(result, frame);
+        }
+    }
+
+    #[test]
+    fn round_trips_commit_frame() {
+        let frame = Frame::Commit(CommitPayload { offset: 5 });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_topic_listing_frames() {
+        let frames = [
+            Frame::ListTopics(ListTopicsPayload {
+                token: Some(AuthToken::new("admin")),
+            }),
+            Frame::TopicList(TopicListPayload {
+                topics: vec![TopicInfo {
+                    topic: "/acmeco/stocks".into(),
+                    publishers: 1,
+                    subscribers: 2,
+                }],
+            }),
+        ];
+
+        for frame in frames {
+            let mut codec = MessageCodec::default();
+            let mut buffer = BytesMut::new();
+
+            codec.encode(frame.clone(), &mut buffer).unwrap();
+            let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+            assert_eq!(result, frame);
+        }
+    }
+
+    #[test]
+    fn round_trips_heartbeat_frame() {
+        let frame = Frame::Heartbeat(HeartbeatPayload { id: 7 });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_receipt_frame() {
+        let frame = Frame::Receipt(ReceiptPayload { id: 42 });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_accept_frame() {
+        let frame = Frame::Accept(AcceptPayload {
+            compression: Compression::Zstd,
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_unauthorized_frame() {
+        let frame = Frame::Unauthorized(UnauthorizedPayload {
+            topic: "Some topic".into(),
+        });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_ack_frame() {
+        let frame = Frame::Ack(AckPayload { delivery_id: 7 });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn round_trips_ack_through_frame() {
+        let frame = Frame::AckThrough(AckThroughPayload { delivery_id: 49 });
+
+        let mut codec = MessageCodec::default();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
+
+    #[test]
+    fn fails_to_decode_truncated_message_metadata() {
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::from(
+            &b"\0\0\0\0\0\0\0\x0a\x03\x85\xef\xad\x9e\0\0\0\0\0\0\0\x15\x01\x04"[..],
+        );
+
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn fails_to_encode_frame_exceeding_max_message_size() {
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
+
+        let mut codec = MessageCodec::new(10);
+        let mut buffer = BytesMut::new();
+
+        let err = codec.encode(frame, &mut buffer).unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<MessageTooLarge>(),
+            Some(&MessageTooLarge {
+                length: 11,
+                max_message_size: 10
+            })
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn fails_to_decode_frame_exceeding_max_message_size() {
+        let mut codec = MessageCodec::new(1024);
+        // Only the header has arrived, claiming a body far larger than the limit
+        let mut src = BytesMut::from(&u64::MAX.to_be_bytes()[..]);
+        src.put_u8(2);
+        src.put_u32(checksum(&src));
+
+        let err = codec.decode(&mut src).unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<MessageTooLarge>(),
+            Some(&MessageTooLarge {
+                length: u64::MAX,
+                max_message_size: 1024
+            })
+        );
+        assert!(src.capacity() < 1024);
+    }
+
+    #[test]
+    fn fails_to_decode_frame_exceeding_max_buffer_capacity() {
+        let mut codec = MessageCodec::default().with_max_buffer_capacity(1024);
+        // Only the header has arrived, claiming a body within the maximum message size, but too
+        // large for the buffer
+        let mut src = BytesMut::from(&1024u64.to_be_bytes()[..]);
+        src.put_u8(2);
+        src.put_u32(checksum(&src));
+
+        let err = codec.decode(&mut src).unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<BufferCapacityExceeded>(),
+            Some(&BufferCapacityExceeded {
+                required: 1037,
+                max_buffer_capacity: 1024
+            })
+        );
+        assert!(src.capacity() < 1024);
+    }
+
+    #[test]
+    fn reserves_only_rest_of_frame_within_max_buffer_capacity() {
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("x".repeat(512))));
+
+        let mut codec = MessageCodec::default().with_max_buffer_capacity(1024);
+        let mut encoded = BytesMut::new();
+        codec.encode(frame.clone(), &mut encoded).unwrap();
+
+        // Only the first half of the frame has arrived
+        let mut src = BytesMut::from(&encoded[..encoded.len() / 2]);
+
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert!(src.capacity() >= encoded.len());
+        assert!(src.capacity() <= 1024);
+
+        src.extend_from_slice(&encoded[encoded.len() / 2..]);
+
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(frame));
+    }
+
+    #[test]
+    fn resyncs_after_corrupt_length_marker() {
+        let first = Frame::Message(MessagePayload::new(Bytes::from("first")));
+        let second = Frame::Message(MessagePayload::new(Bytes::from("second")));
+
+        let mut codec =
+            MessageCodec::new(1024).with_framing_error_policy(FramingErrorPolicy::Resync);
+        let mut src = BytesMut::new();
+
+        codec.encode(first.clone(), &mut src).unwrap();
+        // A corrupt length marker and type, claiming a body far larger than the limit
+        src.put_u64(u64::MAX);
+        src.put_u8(2);
+        codec.encode(second.clone(), &mut src).unwrap();
+
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(first));
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(second));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn resyncs_past_header_without_checksum() {
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
+
+        let mut codec =
+            MessageCodec::default().with_framing_error_policy(FramingErrorPolicy::Resync);
+        // Corrupt bytes resembling a message, but lacking a valid checksum
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x05\x02hello"[..]);
+
+        codec.encode(frame.clone(), &mut src).unwrap();
+
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(frame));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn resyncs_after_unknown_frame_type() {
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
+
+        let mut codec =
+            MessageCodec::default().with_framing_error_policy(FramingErrorPolicy::Resync);
+        let mut src = BytesMut::from(&b"\0\0\0\0\0\0\0\x05\xffjunk"[..]);
+
+        codec.encode(frame.clone(), &mut src).unwrap();
+
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(frame));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn waits_for_frame_while_resyncing() {
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
+
+        let mut codec =
+            MessageCodec::default().with_framing_error_policy(FramingErrorPolicy::Resync);
+        let mut encoded = BytesMut::new();
+        codec.encode(frame.clone(), &mut encoded).unwrap();
+
+        let mut src = BytesMut::from(&encoded[..HEADER_SIZE + 5]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(&encoded[HEADER_SIZE + 5..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(frame));
+    }
+
+    #[test]
+    fn fails_on_corrupt_length_marker_by_default() {
+        let mut codec = MessageCodec::new(1024);
+        let mut src = BytesMut::from(&u64::MAX.to_be_bytes()[..]);
+        src.put_u8(2);
+        src.extend_from_slice(b"\0\0\0\0\0\0\0\x05\x02hello");
+
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn fails_on_corrupt_checksum_by_default() {
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
+
+        let mut codec = MessageCodec::default();
+        let mut src = BytesMut::new();
+        codec.encode(frame, &mut src).unwrap();
+
+        // A length marker corrupted to another plausible length
+        src[LEN_MARKER_SIZE - 1] = 5;
+
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn decodes_frame_at_max_message_size() {
+        let frame = Frame::Message(MessagePayload::new(Bytes::from("Hello world")));
+
+        let mut codec = MessageCodec::new(11);
+        let mut buffer = BytesMut::new();
+
+        codec.encode(frame.clone(), &mut buffer).unwrap();
+        let result = codec.decode(&mut buffer).unwrap().unwrap();
+
+        assert_eq!(result, frame);
+    }
 }
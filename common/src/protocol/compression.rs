@@ -0,0 +1,52 @@
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// An algorithm used to compress message payloads.
+///
+/// A publisher requests an algorithm when it registers with the server, which accepts it if
+/// it's permitted, or falls back to [Compression::None] otherwise. Each message compressed by the
+/// publisher is tagged with the algorithm, so that subscribers can decompress it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Compression {
+    /// Payloads are sent uncompressed.
+    #[default]
+    None,
+    /// Payloads are compressed using [zstd](https://facebook.github.io/zstd).
+    Zstd,
+    /// Payloads are compressed using [LZ4](https://lz4.org), trading compression ratio for
+    /// speed.
+    Lz4,
+}
+
+impl Compression {
+    pub fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::None => "none",
+            Self::Zstd => "zstd",
+            Self::Lz4 => "lz4",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Compression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "zstd" => Ok(Self::Zstd),
+            "lz4" => Ok(Self::Lz4),
+            _ => bail!("Unknown compression algorithm: {s}"),
+        }
+    }
+}
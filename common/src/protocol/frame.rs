@@ -1,25 +1,68 @@
+use super::{AuthToken, Compression, ReplayStart, StartPosition};
 use crate::types::Operation;
 use anyhow::{bail, Result};
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::HashMap, mem::size_of, time::Duration};
 
 const REGISTER_PUBLISHER: u8 = 0x0;
 const REGISTER_SUBSCRIBER: u8 = 0x1;
 const MESSAGE: u8 = 0x2;
+const EXTENDED_MESSAGE: u8 = 0x3;
+const ACK: u8 = 0x4;
+const ACCEPT: u8 = 0x5;
+const HEARTBEAT: u8 = 0x6;
+const UNAUTHORIZED: u8 = 0x7;
+const COMMIT: u8 = 0x8;
+const LIST_TOPICS: u8 = 0x9;
+const TOPIC_LIST: u8 = 0xA;
+const RECEIPT: u8 = 0xB;
+const ACK_THROUGH: u8 = 0xC;
+
+const METADATA_LEN_MARKER_SIZE: usize = size_of::<u64>();
+
+/// Key-value metadata attached to an individual message.
+pub type Headers = HashMap<String, String>;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
     RegisterPublisher(PublisherPayload),
     RegisterSubscriber(SubscriberPayload),
-    Message(Bytes),
+    Message(MessagePayload),
+    Ack(AckPayload),
+    Accept(AcceptPayload),
+    Heartbeat(HeartbeatPayload),
+    Unauthorized(UnauthorizedPayload),
+    Commit(CommitPayload),
+    ListTopics(ListTopicsPayload),
+    TopicList(TopicListPayload),
+    Receipt(ReceiptPayload),
+    AckThrough(AckThroughPayload),
 }
 
 impl Frame {
     pub fn get_length(&self) -> Result<u64> {
         let length = match self {
-            Self::RegisterPublisher(payload) => bincode::serialized_size(payload)?,
-            Self::RegisterSubscriber(payload) => bincode::serialized_size(payload)?,
-            Self::Message(bytes) => bytes.len() as u64,
+            Self::RegisterPublisher(payload) => {
+                bincode::serialized_size(payload)? + payload.options_len()?
+            }
+            Self::RegisterSubscriber(payload) => {
+                bincode::serialized_size(payload)? + payload.options_len()?
+            }
+            Self::Message(payload) if payload.has_metadata() => {
+                let metadata_len = bincode::serialized_size(&payload.metadata())?;
+                METADATA_LEN_MARKER_SIZE as u64 + metadata_len + payload.message.len() as u64
+            }
+            Self::Message(payload) => payload.message.len() as u64,
+            Self::Ack(payload) => bincode::serialized_size(payload)?,
+            Self::Accept(payload) => bincode::serialized_size(payload)?,
+            Self::Heartbeat(payload) => bincode::serialized_size(payload)?,
+            Self::Unauthorized(payload) => bincode::serialized_size(payload)?,
+            Self::Commit(payload) => bincode::serialized_size(payload)?,
+            Self::ListTopics(payload) => bincode::serialized_size(payload)?,
+            Self::TopicList(payload) => bincode::serialized_size(payload)?,
+            Self::Receipt(payload) => bincode::serialized_size(payload)?,
+            Self::AckThrough(payload) => bincode::serialized_size(payload)?,
         };
 
         Ok(length)
@@ -29,7 +72,17 @@ impl Frame {
         match self {
             Self::RegisterPublisher(_) => REGISTER_PUBLISHER,
             Self::RegisterSubscriber(_) => REGISTER_SUBSCRIBER,
+            Self::Message(payload) if payload.has_metadata() => EXTENDED_MESSAGE,
             Self::Message(_) => MESSAGE,
+            Self::Ack(_) => ACK,
+            Self::Accept(_) => ACCEPT,
+            Self::Heartbeat(_) => HEARTBEAT,
+            Self::Unauthorized(_) => UNAUTHORIZED,
+            Self::Commit(_) => COMMIT,
+            Self::ListTopics(_) => LIST_TOPICS,
+            Self::TopicList(_) => TOPIC_LIST,
+            Self::Receipt(_) => RECEIPT,
+            Self::AckThrough(_) => ACK_THROUGH,
         }
     }
 
@@ -37,29 +90,156 @@ impl Frame {
         match self {
             Self::RegisterPublisher(p) => Some(&p.topic),
             Self::RegisterSubscriber(s) => Some(&s.topic),
-            Self::Message(_) => None,
+            Self::Message(payload) => payload.topic.as_deref(),
+            Self::Ack(_)
+            | Self::Accept(_)
+            | Self::Heartbeat(_)
+            | Self::Unauthorized(_)
+            | Self::Commit(_)
+            | Self::ListTopics(_)
+            | Self::TopicList(_)
+            | Self::Receipt(_)
+            | Self::AckThrough(_) => None,
+        }
+    }
+
+    /// Tags a [Frame::Message] with the topic it was published to. All other frames are
+    /// returned unchanged.
+    pub fn with_topic(self, topic: &str) -> Self {
+        match self {
+            Self::Message(payload) => Self::Message(MessagePayload {
+                topic: Some(topic.to_owned()),
+                ..payload
+            }),
+            frame => frame,
         }
     }
 
     pub fn write_to_bytes(self, dst: &mut BytesMut) -> Result<()> {
         match self {
-            Frame::RegisterPublisher(payload) => bincode::serialize_into(dst.writer(), &payload)?,
-            Frame::RegisterSubscriber(payload) => bincode::serialize_into(dst.writer(), &payload)?,
-            Frame::Message(bytes) => dst.extend_from_slice(&bytes),
+            Frame::RegisterPublisher(payload) => {
+                bincode::serialize_into(dst.writer(), &payload)?;
+
+                if payload.has_options() {
+                    bincode::serialize_into(dst.writer(), &payload.compression)?;
+                }
+
+                if let Some(token) = &payload.token {
+                    bincode::serialize_into(dst.writer(), token)?;
+                }
+            }
+            Frame::RegisterSubscriber(payload) => {
+                bincode::serialize_into(dst.writer(), &payload)?;
+
+                if payload.has_options() {
+                    bincode::serialize_into(dst.writer(), &payload.options())?;
+                }
+            }
+            Frame::Message(payload) if payload.has_metadata() => {
+                let metadata = payload.metadata();
+                dst.put_u64(bincode::serialized_size(&metadata)?);
+                bincode::serialize_into(dst.writer(), &metadata)?;
+                dst.extend_from_slice(&payload.message);
+            }
+            Frame::Message(payload) => dst.extend_from_slice(&payload.message),
+            Frame::Ack(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::Accept(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::Heartbeat(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::Unauthorized(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::Commit(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::ListTopics(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::TopicList(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::Receipt(payload) => bincode::serialize_into(dst.writer(), &payload)?,
+            Frame::AckThrough(payload) => bincode::serialize_into(dst.writer(), &payload)?,
         }
 
         Ok(())
     }
 }
 
+/// Returns true if `message_type` is the type marker of a known [Frame].
+pub(crate) fn is_frame_type(message_type: u8) -> bool {
+    message_type <= ACK_THROUGH
+}
+
 impl TryFrom<(u8, BytesMut)> for Frame {
     type Error = anyhow::Error;
 
-    fn try_from((message_type, bytes): (u8, BytesMut)) -> Result<Self> {
+    fn try_from((message_type, mut bytes): (u8, BytesMut)) -> Result<Self> {
         let frame = match message_type {
-            REGISTER_PUBLISHER => Frame::RegisterPublisher(bincode::deserialize(&bytes)?),
-            REGISTER_SUBSCRIBER => Frame::RegisterSubscriber(bincode::deserialize(&bytes)?),
-            MESSAGE => Frame::Message(bytes.into()),
+            REGISTER_PUBLISHER => {
+                let mut bytes = bytes.as_ref();
+                let mut payload: PublisherPayload = bincode::deserialize_from(&mut bytes)?;
+
+                // The options are only present on publishers that request compression or
+                // present a token
+                if !bytes.is_empty() {
+                    payload.compression = bincode::deserialize_from(&mut bytes)?;
+                }
+
+                // The token follows the options on publishers that present one
+                if !bytes.is_empty() {
+                    payload.token = Some(bincode::deserialize(bytes)?);
+                }
+
+                Frame::RegisterPublisher(payload)
+            }
+            REGISTER_SUBSCRIBER => {
+                let mut bytes = bytes.as_ref();
+                let mut payload: SubscriberPayload = bincode::deserialize_from(&mut bytes)?;
+
+                // The options are only present on subscribers that override their defaults
+                if !bytes.is_empty() {
+                    let options: SubscriberOptions = bincode::deserialize(bytes)?;
+                    payload.group = options.group.map(Cow::into_owned);
+                    payload.acks = options.acks;
+                    payload.replay = options.replay;
+                    payload.ordered = options.ordered;
+                    payload.heartbeat = options.heartbeat;
+                    payload.token = options.token.map(Cow::into_owned);
+                    payload.start_position = options.start_position;
+                }
+
+                Frame::RegisterSubscriber(payload)
+            }
+            MESSAGE => Frame::Message(MessagePayload::new(bytes.into())),
+            EXTENDED_MESSAGE => {
+                if bytes.len() < METADATA_LEN_MARKER_SIZE {
+                    bail!("Message frame is missing metadata length");
+                }
+
+                let metadata_len = bytes.get_u64() as usize;
+
+                if bytes.len() < metadata_len {
+                    bail!("Message frame is shorter than metadata length");
+                }
+
+                let metadata = bytes.split_to(metadata_len);
+                let metadata: Metadata = bincode::deserialize(&metadata)?;
+
+                Frame::Message(MessagePayload {
+                    topic: metadata.topic.map(Cow::into_owned),
+                    headers: metadata.headers.into_owned(),
+                    correlation_id: metadata.correlation_id,
+                    reply_to: metadata.reply_to.map(Cow::into_owned),
+                    delivery_id: metadata.delivery_id,
+                    compression: metadata.compression,
+                    idempotency_key: metadata.idempotency_key.map(Cow::into_owned),
+                    offset: metadata.offset,
+                    delay: metadata.delay,
+                    sequence: None,
+                    message: bytes.into(),
+                })
+            }
+            ACK => Frame::Ack(bincode::deserialize(&bytes)?),
+            ACCEPT => Frame::Accept(bincode::deserialize(&bytes)?),
+            HEARTBEAT => Frame::Heartbeat(bincode::deserialize(&bytes)?),
+            UNAUTHORIZED => Frame::Unauthorized(bincode::deserialize(&bytes)?),
+            COMMIT => Frame::Commit(bincode::deserialize(&bytes)?),
+            LIST_TOPICS => Frame::ListTopics(bincode::deserialize(&bytes)?),
+            TOPIC_LIST => Frame::TopicList(bincode::deserialize(&bytes)?),
+            RECEIPT => Frame::Receipt(bincode::deserialize(&bytes)?),
+            ACK_THROUGH => Frame::AckThrough(bincode::deserialize(&bytes)?),
             _ => bail!("Unknown message type"),
         };
 
@@ -67,16 +247,298 @@ impl TryFrom<(u8, BytesMut)> for Frame {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// A message payload, along with any metadata attached to it.
+///
+/// Messages without metadata are written using the original message frame, so that they remain
+/// readable by peers that predate message metadata. Otherwise, the metadata is written as a
+/// single length-prefixed block ahead of the message, holding every field but the `message`
+/// itself and its `sequence`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessagePayload {
+    /// The topic the message was published to, tagged by the server for wildcard subscriptions.
+    pub topic: Option<String>,
+    pub headers: Headers,
+    /// Identifies the request that a reply corresponds to, for request/reply messaging.
+    pub correlation_id: Option<u64>,
+    /// The topic that the reply to a request should be published to.
+    pub reply_to: Option<String>,
+    /// Identifies a message delivered to a subscriber with acknowledgements enabled, which the
+    /// subscriber acknowledges with a [Frame::Ack].
+    pub delivery_id: Option<u64>,
+    /// The algorithm that the message was compressed with by the publisher.
+    pub compression: Compression,
+    /// Identifies a message supplied by the publisher, which the server uses to drop duplicates of
+    /// the message that are published within its deduplication window.
+    pub idempotency_key: Option<String>,
+    /// The offset of the message within its topic's persisted log, assigned by the server as the
+    /// message is logged, which subscribers commit to record their progress through the topic.
+    pub offset: Option<u64>,
+    /// How long the server holds the message for after it's published, before delivering it to
+    /// the topic's subscribers. The server clears the delay before the message is delivered.
+    pub delay: Option<Duration>,
+    /// The position of the message within its topic, assigned by the server as the message is
+    /// published, for ordering its delivery. This is never written to the wire.
+    pub sequence: Option<u64>,
+    pub message: Bytes,
+}
+
+impl MessagePayload {
+    pub fn new(message: Bytes) -> Self {
+        Self {
+            message,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_headers(message: Bytes, headers: Headers) -> Self {
+        Self {
+            headers,
+            message,
+            ..Default::default()
+        }
+    }
+
+    fn has_metadata(&self) -> bool {
+        self.topic.is_some()
+            || !self.headers.is_empty()
+            || self.correlation_id.is_some()
+            || self.reply_to.is_some()
+            || self.delivery_id.is_some()
+            || !self.compression.is_none()
+            || self.idempotency_key.is_some()
+            || self.offset.is_some()
+            || self.delay.is_some()
+    }
+
+    fn metadata(&self) -> Metadata<'_> {
+        Metadata {
+            topic: self.topic.as_deref().map(Cow::Borrowed),
+            headers: Cow::Borrowed(&self.headers),
+            correlation_id: self.correlation_id,
+            reply_to: self.reply_to.as_deref().map(Cow::Borrowed),
+            delivery_id: self.delivery_id,
+            compression: self.compression,
+            idempotency_key: self.idempotency_key.as_deref().map(Cow::Borrowed),
+            offset: self.offset,
+            delay: self.delay,
+        }
+    }
+}
+
+/// The metadata of a [MessagePayload], written ahead of the message in an extended message frame.
+#[derive(Serialize, Deserialize)]
+struct Metadata<'a> {
+    topic: Option<Cow<'a, str>>,
+    headers: Cow<'a, Headers>,
+    correlation_id: Option<u64>,
+    reply_to: Option<Cow<'a, str>>,
+    delivery_id: Option<u64>,
+    compression: Compression,
+    idempotency_key: Option<Cow<'a, str>>,
+    offset: Option<u64>,
+    delay: Option<Duration>,
+}
+
+impl From<Bytes> for MessagePayload {
+    fn from(message: Bytes) -> Self {
+        Self::new(message)
+    }
+}
+
+/// Registers a publisher to a topic.
+///
+/// The `compression` option is written after the rest of the payload, and only when it's set or
+/// followed by a `token`, so that publishers sending uncompressed messages remain readable by
+/// older peers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct PublisherPayload {
     pub topic: String,
     pub retention_policy: u64,
     pub operations: Vec<Operation>,
+    /// The algorithm the publisher requests to compress its messages with. The server replies
+    /// with a [Frame::Accept] carrying the algorithm that the publisher should use.
+    #[serde(skip)]
+    pub compression: Compression,
+    /// The token authorizing the publisher to publish to the topic. The server replies with a
+    /// [Frame::Accept] if it's authorized, or a [Frame::Unauthorized] otherwise.
+    #[serde(skip)]
+    pub token: Option<AuthToken>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl PublisherPayload {
+    fn has_options(&self) -> bool {
+        !self.compression.is_none() || self.token.is_some()
+    }
+
+    fn options_len(&self) -> Result<u64> {
+        let mut len = 0;
+
+        if self.has_options() {
+            len += bincode::serialized_size(&self.compression)?;
+        }
+
+        if let Some(token) = &self.token {
+            len += bincode::serialized_size(token)?;
+        }
+
+        Ok(len)
+    }
+}
+
+/// Registers a subscriber to a topic.
+///
+/// The options are written together after the rest of the payload, and only when any of them
+/// differs from its default, so that subscribers using the defaults remain readable by older
+/// peers, which ignore the options of those that don't.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct SubscriberPayload {
     pub topic: String,
     pub retention_policy: u64,
     pub operations: Vec<Operation>,
+    /// The consumer group the subscriber belongs to. Each message published to the topic is
+    /// delivered to only one member of each group.
+    #[serde(skip)]
+    pub group: Option<String>,
+    /// Whether the subscriber acknowledges each message it receives. Messages that haven't
+    /// been acknowledged when the subscriber disconnects are held for redelivery.
+    #[serde(skip)]
+    pub acks: bool,
+    /// Where the subscriber starts replaying the topic's persisted log from, before receiving
+    /// live messages.
+    #[serde(skip)]
+    pub replay: Option<ReplayStart>,
+    /// Whether the subscriber requires messages to be delivered strictly in the order they were
+    /// sequenced by the topic.
+    #[serde(skip)]
+    pub ordered: bool,
+    /// Whether the subscriber sends [Frame::Heartbeat] frames on its stream, which the server
+    /// echoes back to it.
+    #[serde(skip)]
+    pub heartbeat: bool,
+    /// The token authorizing the subscriber to subscribe to the topic. The server replies with a
+    /// [Frame::Accept] if it's authorized, or a [Frame::Unauthorized] otherwise.
+    #[serde(skip)]
+    pub token: Option<AuthToken>,
+    /// Where the subscriber's live messages start from. The server replies with a
+    /// [Frame::Accept] once the subscriber is attached to the topic if it's set.
+    #[serde(skip)]
+    pub start_position: Option<StartPosition>,
+}
+
+impl SubscriberPayload {
+    fn has_options(&self) -> bool {
+        self.group.is_some()
+            || self.acks
+            || self.replay.is_some()
+            || self.ordered
+            || self.heartbeat
+            || self.token.is_some()
+            || self.start_position.is_some()
+    }
+
+    fn options(&self) -> SubscriberOptions<'_> {
+        SubscriberOptions {
+            group: self.group.as_deref().map(Cow::Borrowed),
+            acks: self.acks,
+            replay: self.replay,
+            ordered: self.ordered,
+            heartbeat: self.heartbeat,
+            token: self.token.as_ref().map(Cow::Borrowed),
+            start_position: self.start_position,
+        }
+    }
+
+    fn options_len(&self) -> Result<u64> {
+        if !self.has_options() {
+            return Ok(0);
+        }
+
+        Ok(bincode::serialized_size(&self.options())?)
+    }
+}
+
+/// The options of a [SubscriberPayload], written after the rest of the payload.
+#[derive(Serialize, Deserialize)]
+struct SubscriberOptions<'a> {
+    group: Option<Cow<'a, str>>,
+    acks: bool,
+    replay: Option<ReplayStart>,
+    ordered: bool,
+    heartbeat: bool,
+    token: Option<Cow<'a, AuthToken>>,
+    start_position: Option<StartPosition>,
+}
+
+/// Acknowledges a message delivered to a subscriber, by its `delivery_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AckPayload {
+    pub delivery_id: u64,
+}
+
+/// Acknowledges every message delivered to a subscriber up to and including `delivery_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AckThroughPayload {
+    pub delivery_id: u64,
+}
+
+/// Commits the offset of the last message in a topic's log that a subscriber in a consumer group
+/// has processed, so that the group's subscribers can resume from the message after it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommitPayload {
+    pub offset: u64,
+}
+
+/// Sent by the server in reply to a publisher that requests compression, carrying the algorithm
+/// that the publisher should compress its messages with.
+///
+/// This is [Compression::None] if the server doesn't permit the requested algorithm, in which
+/// case the publisher falls back to sending uncompressed messages. It's also sent to accept a
+/// publisher or subscriber that presents an authorized token, in which case the algorithm is
+/// [Compression::None] unless the publisher also requested compression.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AcceptPayload {
+    pub compression: Compression,
+}
+
+/// Sent by the server in reply to a publisher or subscriber that isn't authorized to use its
+/// topic, before the server closes the stream.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnauthorizedPayload {
+    pub topic: String,
+}
+
+/// Sent periodically by a subscriber that requests heartbeats, and echoed back to it unchanged by
+/// the server, so that the subscriber can detect a connection that has stopped responding.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeartbeatPayload {
+    pub id: u64,
+}
+
+/// Sent by a publisher after a message that it wants confirmed, and echoed back to it unchanged
+/// by the server once the server has received every message sent before it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReceiptPayload {
+    pub id: u64,
+}
+
+/// Requests the server to list its active topics. The server replies with a [Frame::TopicList]
+/// if the `token` matches its admin token, or a [Frame::Unauthorized] otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListTopicsPayload {
+    pub token: Option<AuthToken>,
+}
+
+/// The server's reply to a [Frame::ListTopics] request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TopicListPayload {
+    pub topics: Vec<TopicInfo>,
+}
+
+/// A snapshot of a topic that is active on the server, along with the number of streams that
+/// are currently attached to it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TopicInfo {
+    pub topic: String,
+    pub publishers: u64,
+    pub subscribers: u64,
 }
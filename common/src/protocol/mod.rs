@@ -1,5 +1,11 @@
 mod codec;
+mod compression;
 mod frame;
+mod replay;
+mod token;
 
 pub use codec::*;
+pub use compression::*;
 pub use frame::*;
+pub use replay::*;
+pub use token::*;
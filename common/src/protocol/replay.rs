@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// Where a subscriber starts replaying a topic's persisted log from, before it begins receiving
+/// live messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayStart {
+    /// Replays every message persisted to the topic's log.
+    Earliest,
+    /// Replays messages from the given offset into the topic's log, where the first message
+    /// published to the topic is at offset 0.
+    Offset(u64),
+    /// Replays messages persisted at or after the given time.
+    Timestamp(SystemTime),
+    /// Replays messages from the offset after the last one committed by the subscriber's
+    /// consumer group, or every message if the group hasn't committed an offset yet.
+    Committed,
+}
+
+/// Where a subscriber's live messages start from, relative to when the server attaches it to the
+/// topic.
+///
+/// A subscriber that chooses a start position is confirmed by the server with a
+/// [Frame::Accept](crate::protocol::Frame::Accept) once it's attached, so the client knows
+/// exactly when its live messages begin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StartPosition {
+    /// Delivers only the messages that the topic dispatches once the subscriber is attached.
+    Latest,
+    /// Delivers the messages retained on the topic as the subscriber connects, followed by the
+    /// messages that the topic dispatches once the subscriber is attached.
+    FromConnect,
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A bearer token presented by a stream when it registers with the server, which authorizes it
+/// to publish or subscribe to its topic.
+///
+/// The [Debug](fmt::Debug) implementation redacts the token, so that it's never logged along with
+/// the payload carrying it.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AuthToken(String);
+
+impl AuthToken {
+    pub fn new(token: &str) -> Self {
+        Self(token.to_owned())
+    }
+
+    /// Compares the token with `expected` in constant time, so that the time taken doesn't
+    /// reveal how much of the token matched.
+    pub fn matches(&self, expected: &str) -> bool {
+        let (token, expected) = (self.0.as_bytes(), expected.as_bytes());
+
+        token.len() == expected.len()
+            && token
+                .iter()
+                .zip(expected)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+impl fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuthToken(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_only_the_same_token() {
+        let token = AuthToken::new("secret");
+
+        assert!(token.matches("secret"));
+        assert!(!token.matches("secreT"));
+        assert!(!token.matches("secret2"));
+        assert!(!token.matches(""));
+    }
+
+    #[test]
+    fn redacts_token_when_debugged() {
+        let token = AuthToken::new("secret");
+
+        assert_eq!(format!("{token:?}"), "AuthToken(<redacted>)");
+    }
+}
@@ -1,45 +1,310 @@
-use crate::protocol::{Frame, MessageCodec};
+use super::tunnel::{RecvHalf, SendHalf, TunnelRecv, TunnelSend};
+use crate::protocol::{encoded_length, Frame, MessageCodec};
 use anyhow::Result;
-use futures::{Sink, SinkExt, Stream, StreamExt};
-use quinn::{Connection, RecvStream, SendStream, StreamId};
+use futures::{FutureExt, Sink, SinkExt, Stream, StreamExt};
+use quinn::{Connection, ReadError, RecvStream, SendStream, StreamId, VarInt};
+use std::fmt;
+use std::io;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio_util::codec::{FramedRead, FramedWrite};
 
-pub type ReadStream = FramedRead<RecvStream, MessageCodec>;
-pub type WriteStream = FramedWrite<SendStream, MessageCodec>;
+pub type ReadStream = FramedRead<RecvHalf, MessageCodec>;
+pub type WriteStream = FramedWrite<SendHalf, MessageCodec>;
 
 pub struct BiStream {
+    write: BiStreamWrite,
+    read: BiStreamRead,
+}
+
+/// The write half of a [BiStream], obtained via [BiStream::split].
+pub struct BiStreamWrite {
     write: WriteStream,
+    counters: Arc<Counters>,
+}
+
+/// The read half of a [BiStream], obtained via [BiStream::split].
+pub struct BiStreamRead {
     read: ReadStream,
+    counters: Arc<Counters>,
+}
+
+/// A snapshot of the cumulative number of frames and bytes sent and received on a [BiStream].
+///
+/// Byte counts include the header of each frame, as it appears on the wire.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    pub frames_sent: u64,
+    pub bytes_sent: u64,
+    pub frames_received: u64,
+    pub bytes_received: u64,
+}
+
+/// The error returned when a [BiStream] cannot be opened, as the peer's limit on concurrent
+/// streams has been reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamLimitReached;
+
+impl fmt::Display for StreamLimitReached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "Cannot open a new stream, as the peer's limit on concurrent streams has been reached",
+        )
+    }
+}
+
+impl std::error::Error for StreamLimitReached {}
+
+/// The error returned when the peer doesn't acknowledge a [BiStream] being finished within the
+/// timeout passed to [BiStreamWrite::finish_with_timeout].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinishTimedOut {
+    pub timeout: Duration,
+}
+
+impl fmt::Display for FinishTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The peer didn't acknowledge the stream being finished within {:?}",
+            self.timeout
+        )
+    }
+}
+
+impl std::error::Error for FinishTimedOut {}
+
+/// The error returned when receiving on a [BiStream] that the peer has reset, carrying the
+/// application error code that the peer reset it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamReset {
+    pub code: u64,
+}
+
+impl StreamReset {
+    /// The code that the server resets a stream with when it rejects the stream's registration,
+    /// such as when a subscriber requests options that the server doesn't support.
+    pub const REJECTED: u64 = 1;
+}
+
+impl fmt::Display for StreamReset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The stream was reset by the peer with code {}",
+            self.code
+        )
+    }
+}
+
+impl std::error::Error for StreamReset {}
+
+/// Returns the code that the peer reset the stream with, if receiving failed because of it.
+fn reset_code(err: &anyhow::Error) -> Option<u64> {
+    let err = err.downcast_ref::<io::Error>()?.get_ref()?;
+
+    match err.downcast_ref::<ReadError>()? {
+        ReadError::Reset(code) => Some(code.into_inner()),
+        _ => None,
+    }
+}
+
+// Shared between both halves of a stream, so that stats are preserved after splitting
+#[derive(Debug, Default)]
+struct Counters {
+    frames_sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    frames_received: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl Counters {
+    fn record_sent(&self, bytes: u64) {
+        self.frames_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn record_received(&self, bytes: u64) {
+        self.frames_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> StreamStats {
+        StreamStats {
+            frames_sent: self.frames_sent.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            frames_received: self.frames_received.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+        }
+    }
 }
 
 impl BiStream {
+    /// Opens a new stream on the provided connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [StreamLimitReached] error if the peer's limit on concurrent streams has been
+    /// reached, rather than waiting for another stream to be closed.
     pub async fn try_from_connection(connection: &Connection) -> Result<Self> {
-        let stream = connection.open_bi().await?;
+        // Opening a stream only waits when the limit has been reached, as no data is exchanged
+        let stream = match connection.open_bi().now_or_never() {
+            Some(stream) => stream?,
+            None => return Err(StreamLimitReached.into()),
+        };
+
         Ok(Self::from(stream))
     }
 
+    /// Wraps a stream that's tunnelled through another protocol, such as a WebTransport stream,
+    /// so that frames can be sent and received on it exactly like on a QUIC stream.
+    pub fn tunnelled(send: impl TunnelSend + 'static, recv: impl TunnelRecv + 'static) -> Self {
+        Self::from_halves(
+            SendHalf::Tunnel(Box::new(send)),
+            RecvHalf::Tunnel(Box::new(recv)),
+        )
+    }
+
+    fn from_halves(send: SendHalf, recv: RecvHalf) -> Self {
+        let counters = Arc::new(Counters::default());
+
+        let write = BiStreamWrite {
+            write: FramedWrite::new(send, MessageCodec::default()),
+            counters: counters.clone(),
+        };
+        let read = BiStreamRead {
+            read: FramedRead::new(recv, MessageCodec::default()),
+            counters,
+        };
+
+        Self { write, read }
+    }
+
     pub fn get_recv_stream_id(&self) -> StreamId {
-        self.read.get_ref().id()
+        self.read.get_recv_stream_id()
     }
 
+    pub fn get_send_stream_id(&self) -> StreamId {
+        self.write.get_send_stream_id()
+    }
+
+    /// See [BiStreamWrite::finish].
+    pub async fn finish(&mut self) -> Result<()> {
+        self.write.finish().await
+    }
+
+    /// See [BiStreamWrite::finish_with_timeout].
+    pub async fn finish_with_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.write.finish_with_timeout(timeout).await
+    }
+
+    /// See [BiStreamWrite::reset].
+    pub fn reset(&mut self, code: u64) -> Result<()> {
+        self.write.reset(code)
+    }
+
+    /// Returns the cumulative number of frames and bytes sent and received on this stream.
+    pub fn stats(&self) -> StreamStats {
+        self.write.stats()
+    }
+
+    /// See [BiStreamWrite::set_priority].
+    pub fn set_priority(&mut self, priority: i32) -> Result<()> {
+        self.write.set_priority(priority)
+    }
+
+    /// Overrides the maximum size of a frame that can be sent or received on this stream, in
+    /// bytes, which defaults to [DEFAULT_MAX_MESSAGE_SIZE](crate::protocol::DEFAULT_MAX_MESSAGE_SIZE).
+    pub fn set_max_message_size(&mut self, max_message_size: u64) {
+        self.set_codec(MessageCodec::new(max_message_size));
+    }
+
+    /// Replaces the [MessageCodec] that frames are sent and received with on this stream, such as
+    /// to override its [FramingErrorPolicy](crate::protocol::FramingErrorPolicy) along with its
+    /// maximum message size.
+    pub fn set_codec(&mut self, codec: MessageCodec) {
+        *self.write.write.encoder_mut() = codec;
+        *self.read.read.decoder_mut() = codec;
+    }
+
+    /// Splits the stream into its write and read halves, so that each can be owned by a separate
+    /// task.
+    pub fn split(self) -> (BiStreamWrite, BiStreamRead) {
+        (self.write, self.read)
+    }
+}
+
+impl BiStreamWrite {
     pub fn get_send_stream_id(&self) -> StreamId {
         self.write.get_ref().id()
     }
 
+    /// Finishes the stream, completing once the peer has acknowledged every frame written to
+    /// it, retransmitting them as needed. If the peer stops responding, this doesn't complete
+    /// until the connection times out.
     pub async fn finish(&mut self) -> Result<()> {
-        self.write.get_mut().finish().await?;
-        Ok(())
+        self.write.get_mut().finish().await
+    }
+
+    /// Flushes any buffered frames and finishes the stream, waiting up to `timeout` for the peer
+    /// to acknowledge every frame written to it, so that once this returns [Ok], the frames are
+    /// known to have reached the peer, even if the connection is closed immediately afterwards.
+    ///
+    /// Returns a [FinishTimedOut] error if the timeout elapses first. The stream is still
+    /// finished, and its frames are retransmitted until they're acknowledged or the connection
+    /// is closed, so the frames may yet be delivered. Calling this again waits for the same
+    /// acknowledgement, while [reset](Self::reset) abandons the frames instead.
+    pub async fn finish_with_timeout(&mut self, timeout: Duration) -> Result<()> {
+        let finish = async {
+            self.flush().await?;
+            self.finish().await
+        };
+
+        match tokio::time::timeout(timeout, finish).await {
+            Ok(result) => result,
+            Err(_) => Err(FinishTimedOut { timeout }.into()),
+        }
+    }
+
+    /// Abandons the stream, discarding any frames that haven't been delivered yet, so that the
+    /// peer fails to receive on it with a [StreamReset] error carrying `code`.
+    pub fn reset(&mut self, code: u64) -> Result<()> {
+        self.write.get_mut().reset(VarInt::from_u64(code)?)
+    }
+
+    /// Sets the priority of the stream relative to the other streams on its connection, which
+    /// defaults to 0. When the connection is congested, buffered data on streams with a higher
+    /// priority is sent before that of streams with a lower priority, while streams with the same
+    /// priority take turns.
+    pub fn set_priority(&mut self, priority: i32) -> Result<()> {
+        self.write.get_ref().set_priority(priority)
+    }
+
+    /// Returns the cumulative number of frames and bytes sent and received on the stream this
+    /// half was split from.
+    pub fn stats(&self) -> StreamStats {
+        self.counters.snapshot()
+    }
+}
+
+impl BiStreamRead {
+    pub fn get_recv_stream_id(&self) -> StreamId {
+        self.read.get_ref().id()
+    }
+
+    /// Returns the cumulative number of frames and bytes sent and received on the stream this
+    /// half was split from.
+    pub fn stats(&self) -> StreamStats {
+        self.counters.snapshot()
     }
 }
 
 impl From<(SendStream, RecvStream)> for BiStream {
     fn from((send, recv): (SendStream, RecvStream)) -> Self {
-        let write = FramedWrite::new(send, MessageCodec);
-        let read = FramedRead::new(recv, MessageCodec);
-
-        Self { write, read }
+        Self::from_halves(SendHalf::Quic(send), RecvHalf::Quic(recv))
     }
 }
 
@@ -74,3 +339,355 @@ impl Stream for BiStream {
         self.read.size_hint()
     }
 }
+
+impl Sink<Frame> for BiStreamWrite {
+    type Error = anyhow::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.write.poll_ready_unpin(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Frame) -> Result<(), Self::Error> {
+        let length = encoded_length(&item)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            stream_id = %self.get_send_stream_id(),
+            frame_type = item.get_type(),
+            length,
+            "Sending frame"
+        );
+
+        self.write.start_send_unpin(item)?;
+        self.counters.record_sent(length);
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.write.poll_flush_unpin(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.write.poll_close_unpin(cx)
+    }
+}
+
+impl Stream for BiStreamRead {
+    type Item = Result<Frame>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let next = match self.read.poll_next_unpin(cx) {
+            Poll::Ready(Some(Err(err))) => match reset_code(&err) {
+                Some(code) => Poll::Ready(Some(Err(StreamReset { code }.into()))),
+                None => Poll::Ready(Some(Err(err))),
+            },
+            next => next,
+        };
+
+        if let Poll::Ready(Some(Ok(frame))) = &next {
+            let length = encoded_length(frame)?;
+            self.counters.record_received(length);
+
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                stream_id = %self.get_recv_stream_id(),
+                frame_type = frame.get_type(),
+                length,
+                "Received frame"
+            );
+        }
+
+        #[cfg(feature = "tracing")]
+        if let Poll::Ready(Some(Err(err))) = &next {
+            tracing::debug!(
+                stream_id = %self.get_recv_stream_id(),
+                error = %err,
+                "Failed to receive frame"
+            );
+        }
+
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.read.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::MessagePayload;
+    use bytes::{Bytes, BytesMut};
+    use quinn::{ClientConfig, Endpoint, ServerConfig};
+    use rustls::{Certificate, PrivateKey, RootCertStore};
+    use std::net::SocketAddr;
+    use std::sync::atomic::AtomicBool;
+    use tokio::net::UdpSocket;
+    use tokio_util::codec::Encoder;
+
+    const FRAME_COUNT: usize = 100;
+    // Matches the default limit applied by quinn
+    const MAX_CONCURRENT_STREAMS: u32 = 100;
+
+    // Opens a loopback QUIC connection, returning the client and server sides, with the server
+    // accepting at most `max_streams` concurrent streams.
+    async fn connection_pair(max_streams: u32) -> (Connection, Connection) {
+        let (client, server) = endpoints(max_streams);
+        let addr = server.local_addr().unwrap();
+
+        connect(&client, &server, addr).await
+    }
+
+    // Opens a loopback QUIC connection via a UDP proxy, which drops every packet in either
+    // direction while the returned flag is set, as if the network had gone down.
+    async fn lossy_connection_pair() -> (Connection, Connection, Arc<AtomicBool>) {
+        let (client, server) = endpoints(MAX_CONCURRENT_STREAMS);
+        let server_addr = server.local_addr().unwrap();
+        let proxy = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = proxy.local_addr().unwrap();
+        let dropping = Arc::new(AtomicBool::new(false));
+        let dropping_packets = dropping.clone();
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0; u16::MAX as usize];
+            let mut client_addr = None;
+
+            loop {
+                let (len, from) = proxy.recv_from(&mut buffer).await.unwrap();
+
+                let to = if from == server_addr {
+                    match client_addr {
+                        Some(addr) => addr,
+                        None => continue,
+                    }
+                } else {
+                    client_addr = Some(from);
+                    server_addr
+                };
+
+                if !dropping_packets.load(Ordering::Relaxed) {
+                    let _ = proxy.send_to(&buffer[..len], to).await;
+                }
+            }
+        });
+
+        let (client_conn, server_conn) = connect(&client, &server, proxy_addr).await;
+
+        (client_conn, server_conn, dropping)
+    }
+
+    fn endpoints(max_streams: u32) -> (Endpoint, Endpoint) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let cert_der = Certificate(cert.serialize_der().unwrap());
+        let key = PrivateKey(cert.serialize_private_key_der());
+
+        let mut server_config =
+            ServerConfig::with_single_cert(vec![cert_der.clone()], key).unwrap();
+        Arc::get_mut(&mut server_config.transport)
+            .unwrap()
+            .max_concurrent_bidi_streams(VarInt::from_u32(max_streams));
+        let server = Endpoint::server(server_config, "127.0.0.1:0".parse().unwrap()).unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(&cert_der).unwrap();
+
+        let mut client = Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+        client.set_default_client_config(ClientConfig::with_root_certificates(roots));
+
+        (client, server)
+    }
+
+    async fn connect(
+        client: &Endpoint,
+        server: &Endpoint,
+        addr: SocketAddr,
+    ) -> (Connection, Connection) {
+        let connecting = client.connect(addr, "localhost").unwrap();
+        let (client_conn, server_conn) =
+            tokio::join!(connecting, async { server.accept().await.unwrap().await });
+
+        (client_conn.unwrap(), server_conn.unwrap())
+    }
+
+    // Opens a connected pair of BiStreams over a loopback QUIC connection. The connections are
+    // returned too, as they are closed once dropped.
+    async fn stream_pair() -> (BiStream, BiStream, (Connection, Connection)) {
+        let (client_conn, server_conn) = connection_pair(MAX_CONCURRENT_STREAMS).await;
+        open_stream_pair(client_conn, server_conn).await
+    }
+
+    async fn open_stream_pair(
+        client_conn: Connection,
+        server_conn: Connection,
+    ) -> (BiStream, BiStream, (Connection, Connection)) {
+        let mut local = BiStream::try_from_connection(&client_conn).await.unwrap();
+
+        // The peer only learns about a stream once data has been sent on it
+        local
+            .send(Frame::Message(MessagePayload::new(Bytes::from("hello"))))
+            .await
+            .unwrap();
+
+        let mut remote = BiStream::from(server_conn.accept_bi().await.unwrap());
+        remote.next().await.unwrap().unwrap();
+
+        (local, remote, (client_conn, server_conn))
+    }
+
+    fn message(i: usize) -> Frame {
+        Frame::Message(MessagePayload::new(Bytes::from(format!("message {i}"))))
+    }
+
+    #[tokio::test]
+    async fn stats_count_frames_and_bytes() {
+        let (mut local, mut remote, _connections) = stream_pair().await;
+        let local_before = local.stats();
+        let remote_before = remote.stats();
+
+        let mut expected_bytes = 0;
+
+        for i in 0..FRAME_COUNT {
+            let mut encoded = BytesMut::new();
+            MessageCodec::default()
+                .encode(message(i), &mut encoded)
+                .unwrap();
+            expected_bytes += encoded.len() as u64;
+
+            local.send(message(i)).await.unwrap();
+        }
+
+        for _ in 0..FRAME_COUNT {
+            remote.next().await.unwrap().unwrap();
+        }
+
+        let sent = local.stats();
+        assert_eq!(
+            sent.frames_sent - local_before.frames_sent,
+            FRAME_COUNT as u64
+        );
+        assert_eq!(sent.bytes_sent - local_before.bytes_sent, expected_bytes);
+
+        let received = remote.stats();
+        assert_eq!(
+            received.frames_received - remote_before.frames_received,
+            FRAME_COUNT as u64
+        );
+        assert_eq!(
+            received.bytes_received - remote_before.bytes_received,
+            expected_bytes
+        );
+        assert_eq!(received.frames_sent, 0);
+    }
+
+    #[tokio::test]
+    async fn split_halves_can_be_driven_concurrently() {
+        let (local, remote, _connections) = stream_pair().await;
+        let (mut local_write, mut local_read) = local.split();
+        let (mut remote_write, mut remote_read) = remote.split();
+
+        assert_eq!(
+            local_write.get_send_stream_id(),
+            local_read.get_recv_stream_id()
+        );
+
+        // The remote echoes every frame back, with each half driven by its own task
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let echo_read = tokio::spawn(async move {
+            while let Some(frame) = remote_read.next().await {
+                sender.unbounded_send(frame.unwrap()).unwrap();
+            }
+        });
+        let echo_write = tokio::spawn(async move {
+            remote_write.send_all(&mut receiver.map(Ok)).await.unwrap();
+        });
+
+        let writer = tokio::spawn(async move {
+            for i in 0..FRAME_COUNT {
+                local_write.send(message(i)).await.unwrap();
+            }
+            local_write.finish().await.unwrap();
+        });
+        let reader = tokio::spawn(async move {
+            let mut frames = Vec::new();
+            while frames.len() < FRAME_COUNT {
+                frames.push(local_read.next().await.unwrap().unwrap());
+            }
+            frames
+        });
+
+        writer.await.unwrap();
+        let frames = reader.await.unwrap();
+        echo_read.await.unwrap();
+        echo_write.abort();
+
+        let expected: Vec<Frame> = (0..FRAME_COUNT).map(message).collect();
+        assert_eq!(frames, expected);
+    }
+
+    #[tokio::test]
+    async fn fails_to_open_stream_beyond_peer_limit() {
+        let (client_conn, _server_conn) = connection_pair(2).await;
+
+        let _first = BiStream::try_from_connection(&client_conn).await.unwrap();
+        let _second = BiStream::try_from_connection(&client_conn).await.unwrap();
+        let err = BiStream::try_from_connection(&client_conn)
+            .await
+            .err()
+            .expect("Expected stream limit to be reached");
+
+        assert_eq!(
+            err.downcast_ref::<StreamLimitReached>(),
+            Some(&StreamLimitReached)
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_to_receive_on_reset_stream() {
+        let (mut local, mut remote, _connections) = stream_pair().await;
+
+        remote.reset(StreamReset::REJECTED).unwrap();
+
+        let err = local.next().await.unwrap().unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<StreamReset>(),
+            Some(&StreamReset {
+                code: StreamReset::REJECTED
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn finishing_waits_for_peer_to_acknowledge() {
+        let (client_conn, server_conn, dropping) = lossy_connection_pair().await;
+        let (mut local, mut remote, _connections) =
+            open_stream_pair(client_conn, server_conn).await;
+
+        dropping.store(true, Ordering::Relaxed);
+        local.feed(message(0)).await.unwrap();
+
+        // The frame can't be acknowledged while the network is down
+        let err = local
+            .finish_with_timeout(Duration::from_millis(200))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<FinishTimedOut>(),
+            Some(&FinishTimedOut {
+                timeout: Duration::from_millis(200)
+            })
+        );
+
+        dropping.store(false, Ordering::Relaxed);
+
+        local
+            .finish_with_timeout(Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(remote.next().await.unwrap().unwrap(), message(0));
+        assert!(remote.next().await.is_none());
+    }
+}
@@ -1,5 +1,7 @@
 mod bistream;
 mod operation;
+mod tunnel;
 
 pub use bistream::*;
 pub use operation::*;
+pub use tunnel::*;
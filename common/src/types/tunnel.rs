@@ -0,0 +1,122 @@
+use anyhow::Result;
+use futures::future::poll_fn;
+use quinn::{RecvStream, SendStream, StreamId, VarInt};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The send half of a stream that's tunnelled through another protocol carried by a QUIC stream,
+/// such as a WebTransport stream, which a [BiStream](super::BiStream) can send frames on in place
+/// of a [SendStream].
+pub trait TunnelSend: AsyncWrite + Send + Unpin {
+    /// Returns the ID of the QUIC stream that carries the tunnelled stream.
+    fn id(&self) -> StreamId;
+
+    /// Abandons the stream, so that the peer fails to receive on it with the error `code`.
+    fn reset(&mut self, code: VarInt) -> Result<()>;
+}
+
+/// The receive half of a tunnelled stream. See [TunnelSend].
+pub trait TunnelRecv: AsyncRead + Send + Unpin {
+    /// Returns the ID of the QUIC stream that carries the tunnelled stream.
+    fn id(&self) -> StreamId;
+}
+
+/// The stream that a [BiStream](super::BiStream) sends frames on.
+pub enum SendHalf {
+    Quic(SendStream),
+    Tunnel(Box<dyn TunnelSend>),
+}
+
+/// The stream that a [BiStream](super::BiStream) receives frames on.
+pub enum RecvHalf {
+    Quic(RecvStream),
+    Tunnel(Box<dyn TunnelRecv>),
+}
+
+impl SendHalf {
+    pub fn id(&self) -> StreamId {
+        match self {
+            Self::Quic(stream) => stream.id(),
+            Self::Tunnel(stream) => stream.id(),
+        }
+    }
+
+    pub async fn finish(&mut self) -> Result<()> {
+        match self {
+            Self::Quic(stream) => stream.finish().await?,
+            Self::Tunnel(stream) => poll_fn(|cx| Pin::new(&mut *stream).poll_shutdown(cx)).await?,
+        }
+
+        Ok(())
+    }
+
+    pub fn reset(&mut self, code: VarInt) -> Result<()> {
+        match self {
+            Self::Quic(stream) => stream.reset(code)?,
+            Self::Tunnel(stream) => stream.reset(code)?,
+        }
+
+        Ok(())
+    }
+
+    /// Sets the priority of the stream. Tunnelled streams are sent with the priority of the
+    /// stream that carries them, so the priority is ignored.
+    pub fn set_priority(&self, priority: i32) -> Result<()> {
+        if let Self::Quic(stream) = self {
+            stream.set_priority(priority)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RecvHalf {
+    pub fn id(&self) -> StreamId {
+        match self {
+            Self::Quic(stream) => stream.id(),
+            Self::Tunnel(stream) => stream.id(),
+        }
+    }
+}
+
+impl AsyncWrite for SendHalf {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Quic(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tunnel(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Quic(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tunnel(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Quic(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tunnel(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+impl AsyncRead for RecvHalf {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Quic(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tunnel(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Acknowledgement tracking for subscribers that opt in to at-least-once delivery
+
+use crate::offsets::Committer;
+use anyhow::anyhow;
+use futures::{channel::mpsc::Sender, Sink, SinkExt, StreamExt};
+use selium_common::{
+    protocol::{AckPayload, AckThroughPayload, CommitPayload, Frame, MessagePayload},
+    types::{BiStreamRead, BiStreamWrite},
+};
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+// Messages awaiting acknowledgement, keyed by delivery id. This is taken once the subscriber
+// disconnects, after which the sink refuses any further messages.
+type Unacked = Arc<Mutex<Option<BTreeMap<u64, Frame>>>>;
+
+/// Wraps the write half of a subscriber's stream, tagging each message with a delivery id and
+/// holding onto it until the subscriber acknowledges it.
+pub struct AckSink {
+    write: BiStreamWrite,
+    next_id: u64,
+    unacked: Unacked,
+}
+
+/// Receives acknowledgements on the read half of a subscriber's stream.
+pub struct AckReader {
+    read: BiStreamRead,
+    unacked: Unacked,
+    // Receives the heartbeats to echo, for subscribers that send them
+    echoes: Option<Sender<Frame>>,
+    // Commits the offsets received, for subscribers in a consumer group
+    committer: Option<Committer>,
+}
+
+/// Splits a subscriber's stream into a sink for delivering messages, and a reader for
+/// receiving their acknowledgements.
+pub fn track(write: BiStreamWrite, read: BiStreamRead) -> (AckSink, AckReader) {
+    let unacked = Arc::new(Mutex::new(Some(BTreeMap::new())));
+
+    let sink = AckSink {
+        write,
+        next_id: 0,
+        unacked: unacked.clone(),
+    };
+
+    let reader = AckReader {
+        read,
+        unacked,
+        echoes: None,
+        committer: None,
+    };
+
+    (sink, reader)
+}
+
+impl AckReader {
+    /// Echoes the heartbeats received alongside acknowledgements via `echoes`.
+    pub fn echo_heartbeats(mut self, echoes: Sender<Frame>) -> Self {
+        self.echoes = Some(echoes);
+        self
+    }
+
+    /// Commits the offsets received alongside acknowledgements via `committer`.
+    pub fn commit_offsets(mut self, committer: Committer) -> Self {
+        self.committer = Some(committer);
+        self
+    }
+
+    /// Removes each acknowledged message until the subscriber disconnects, then returns the
+    /// messages that are still unacknowledged, in the order they were delivered.
+    pub async fn run(mut self) -> Vec<Frame> {
+        while let Some(Ok(frame)) = self.read.next().await {
+            match frame {
+                Frame::Ack(AckPayload { delivery_id }) => {
+                    if let Some(unacked) = self.unacked.lock().unwrap().as_mut() {
+                        unacked.remove(&delivery_id);
+                    }
+                }
+                Frame::AckThrough(AckThroughPayload { delivery_id }) => {
+                    if let Some(unacked) = self.unacked.lock().unwrap().as_mut() {
+                        unacked.retain(|id, _| *id > delivery_id);
+                    }
+                }
+                Frame::Heartbeat(_) => {
+                    if let Some(echoes) = self.echoes.as_mut() {
+                        // The subscriber has disconnected if its heartbeats can't be echoed
+                        let _ = echoes.send(frame).await;
+                    }
+                }
+                Frame::Commit(CommitPayload { offset }) => {
+                    if let Some(committer) = self.committer.as_ref() {
+                        committer.apply(offset).await;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        self.unacked
+            .lock()
+            .unwrap()
+            .take()
+            .map(|unacked| unacked.into_values().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Sink<Frame> for AckSink {
+    type Error = anyhow::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.unacked.lock().unwrap().is_none() {
+            return Poll::Ready(Err(anyhow!("Subscriber has disconnected")));
+        }
+
+        self.write.poll_ready_unpin(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, frame: Frame) -> Result<(), Self::Error> {
+        let frame = match frame {
+            Frame::Message(payload) => {
+                let id = self.next_id;
+                self.next_id += 1;
+
+                let frame = Frame::Message(MessagePayload {
+                    delivery_id: Some(id),
+                    ..payload
+                });
+
+                self.unacked
+                    .lock()
+                    .unwrap()
+                    .as_mut()
+                    .ok_or(anyhow!("Subscriber has disconnected"))?
+                    .insert(id, frame.clone());
+
+                frame
+            }
+            frame => frame,
+        };
+
+        self.write.start_send_unpin(frame)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.write.poll_flush_unpin(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.write.poll_close_unpin(cx)
+    }
+}
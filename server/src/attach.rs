@@ -0,0 +1,140 @@
+//! Confirmation of subscribers that choose a start position, once they're attached to their
+//! topic
+
+use futures::{ready, Sink};
+use selium_common::protocol::{AcceptPayload, Compression, Frame};
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A subscriber's sink that writes a [Frame::Accept], followed by any messages retained on the
+/// topic, the first time it's polled by the topic.
+///
+/// As the topic only polls its sinks once they're attached, the subscriber is confirmed ahead of
+/// the first live message it's delivered, and no earlier than the point from which every message
+/// published to the topic is delivered to it.
+pub struct Confirm<Si> {
+    sink: Si,
+    pending: VecDeque<Frame>,
+}
+
+impl<Si> Confirm<Si> {
+    pub fn new(sink: Si, retained: Vec<Frame>) -> Self {
+        let accept = Frame::Accept(AcceptPayload {
+            compression: Compression::None,
+        });
+        let mut pending = VecDeque::from(retained);
+        pending.push_front(accept);
+
+        Self { sink, pending }
+    }
+
+    /// Writes `frames` the first time the sink is polled by the topic, without confirming the
+    /// subscriber, for subscribers that are confirmed further down their sink.
+    pub fn deliver(sink: Si, frames: Vec<Frame>) -> Self {
+        Self {
+            sink,
+            pending: VecDeque::from(frames),
+        }
+    }
+}
+
+impl<Si> Confirm<Si>
+where
+    Si: Sink<Frame> + Unpin,
+{
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        while !self.pending.is_empty() {
+            ready!(Pin::new(&mut self.sink).poll_ready(cx))?;
+            let frame = self.pending.pop_front().unwrap();
+            Pin::new(&mut self.sink).start_send(frame)?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<Si> Sink<Frame> for Confirm<Si>
+where
+    Si: Sink<Frame> + Unpin,
+{
+    type Error = Si::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        ready!(self.poll_pending(cx))?;
+        Pin::new(&mut self.sink).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, frame: Frame) -> Result<(), Si::Error> {
+        Pin::new(&mut self.sink).start_send(frame)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        ready!(self.poll_pending(cx))?;
+        Pin::new(&mut self.sink).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        ready!(self.poll_pending(cx))?;
+        Pin::new(&mut self.sink).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::{channel::mpsc, SinkExt, StreamExt};
+    use selium_common::protocol::MessagePayload;
+
+    fn message(message: &'static str) -> Frame {
+        Frame::Message(MessagePayload::new(Bytes::from(message)))
+    }
+
+    #[tokio::test]
+    async fn confirms_before_retained_and_live_messages() {
+        let (tx, rx) = mpsc::unbounded();
+        let mut sink = Confirm::new(tx, vec![message("retained")]);
+
+        sink.send(message("live")).await.unwrap();
+        drop(sink);
+
+        let frames: Vec<Frame> = rx.collect().await;
+
+        assert_eq!(
+            frames,
+            vec![
+                Frame::Accept(AcceptPayload {
+                    compression: Compression::None
+                }),
+                message("retained"),
+                message("live"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn delivers_without_confirming() {
+        let (tx, rx) = mpsc::unbounded();
+        let mut sink = Confirm::deliver(tx, vec![message("retained")]);
+
+        sink.send(message("live")).await.unwrap();
+        drop(sink);
+
+        let frames: Vec<Frame> = rx.collect().await;
+
+        assert_eq!(frames, vec![message("retained"), message("live")]);
+    }
+
+    #[tokio::test]
+    async fn confirms_when_flushed() {
+        let (tx, mut rx) = mpsc::unbounded();
+        let mut sink = Confirm::new(tx, Vec::new());
+
+        sink.flush().await.unwrap();
+
+        assert!(matches!(rx.next().await, Some(Frame::Accept(_))));
+    }
+}
@@ -0,0 +1,112 @@
+//! Authorization of the streams registered to each topic, by the bearer token they present
+
+use crate::wildcard::TopicPattern;
+use anyhow::{bail, Context, Result};
+use selium_common::protocol::AuthToken;
+use std::{fs, path::Path};
+
+/// The tokens permitted to use each topic, loaded from the file provided to the server's
+/// `--auth-tokens` option.
+///
+/// Each line of the file contains a topic pattern, followed by a token permitted to publish
+/// and subscribe to the topics matching it, separated by whitespace. Blank lines and lines
+/// starting with `#` are ignored. A pattern may appear on more than one line, to permit more
+/// than one token.
+///
+/// Topics that don't match any pattern can't be used by any stream, so each tenant's topics are
+/// isolated from those of the others.
+#[derive(Debug, Default)]
+pub struct Authorizer {
+    rules: Vec<(TopicPattern, String)>,
+}
+
+impl Authorizer {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read auth tokens from {}", path.display()))?;
+
+        Self::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // The token is deliberately left out of any error, so that it's never logged
+            let (pattern, token) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [pattern, token] => (pattern, token),
+                _ => bail!("Expected a topic pattern and a token on line {}", idx + 1),
+            };
+            let pattern = TopicPattern::parse(pattern)
+                .with_context(|| format!("Invalid topic pattern on line {}", idx + 1))?;
+
+            rules.push((pattern, token.to_owned()));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Returns true if the `token` permits a stream to use the `topic`.
+    ///
+    /// Wildcard subscriptions are authorized by their pattern, so a subscriber can only use a
+    /// wildcard within the topics that its token is permitted to use.
+    pub fn authorize(&self, topic: &str, token: Option<&AuthToken>) -> bool {
+        let Some(token) = token else {
+            return false;
+        };
+
+        self.rules
+            .iter()
+            .any(|(pattern, expected)| pattern.matches(topic) && token.matches(expected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKENS: &str = "
+        # Each tenant may only use its own topics
+        /acmeco/# acme-token
+        /globex/# globex-token
+        /globex/# globex-rotated
+    ";
+
+    #[test]
+    fn authorizes_tokens_permitted_to_use_topic() {
+        let auth = Authorizer::parse(TOKENS).unwrap();
+        let acme = AuthToken::new("acme-token");
+
+        assert!(auth.authorize("/acmeco/stocks", Some(&acme)));
+        assert!(auth.authorize("/acmeco/+", Some(&acme)));
+        assert!(auth.authorize("/globex/stocks", Some(&AuthToken::new("globex-rotated"))));
+    }
+
+    #[test]
+    fn rejects_tokens_not_permitted_to_use_topic() {
+        let auth = Authorizer::parse(TOKENS).unwrap();
+        let acme = AuthToken::new("acme-token");
+
+        assert!(!auth.authorize("/globex/stocks", Some(&acme)));
+        assert!(!auth.authorize("/#", Some(&acme)));
+        assert!(!auth.authorize("/initech/stocks", Some(&acme)));
+        assert!(!auth.authorize("/acmeco/stocks", None));
+    }
+
+    #[test]
+    fn fails_to_parse_line_without_token() {
+        let err = Authorizer::parse("/acmeco/#").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Expected a topic pattern and a token on line 1"
+        );
+    }
+}
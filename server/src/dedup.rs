@@ -0,0 +1,98 @@
+//! Deduplication of messages published with an idempotency key, so that a publisher retrying
+//! after an ambiguous failure doesn't deliver the same message twice
+
+use anyhow::Result;
+use futures::{future, Stream, StreamExt};
+use log::debug;
+use selium_common::protocol::Frame;
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Default)]
+struct SeenKeys {
+    keys: HashSet<String>,
+    // The time each key was first seen, in the order they were seen, for expiring them
+    expiry: VecDeque<(Instant, String)>,
+}
+
+/// The idempotency keys seen on a topic within the deduplication window. Shared between all of
+/// the topic's publishers, so that a retry is deduplicated even if it's sent on a new stream.
+#[derive(Clone, Default)]
+pub struct Deduplicator {
+    seen: Arc<Mutex<SeenKeys>>,
+}
+
+impl Deduplicator {
+    /// Records the key, returning false if it has already been seen within the `window`.
+    pub fn check(&self, key: &str, window: Duration) -> bool {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap();
+
+        while let Some((first_seen, _)) = seen.expiry.front() {
+            if now.duration_since(*first_seen) < window {
+                break;
+            }
+
+            let (_, key) = seen.expiry.pop_front().unwrap();
+            seen.keys.remove(&key);
+        }
+
+        if seen.keys.contains(key) {
+            return false;
+        }
+
+        seen.keys.insert(key.to_owned());
+        seen.expiry.push_back((now, key.to_owned()));
+
+        true
+    }
+
+    /// Drops each message received from a publisher's stream whose idempotency key has already
+    /// been seen on the topic within the `window`. Messages without a key are passed on as is.
+    pub fn dedup_stream<S>(&self, stream: S, window: Duration) -> impl Stream<Item = Result<Frame>>
+    where
+        S: Stream<Item = Result<Frame>>,
+    {
+        let dedup = self.clone();
+
+        stream.filter(move |frame| {
+            let unique = match frame {
+                Ok(Frame::Message(payload)) => match &payload.idempotency_key {
+                    Some(key) if !dedup.check(key, window) => {
+                        debug!("Dropping duplicate message with key {key}");
+                        false
+                    }
+                    _ => true,
+                },
+                _ => true,
+            };
+
+            future::ready(unique)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_keys_seen_within_window() {
+        let dedup = Deduplicator::default();
+
+        assert!(dedup.check("first", Duration::from_secs(60)));
+        assert!(dedup.check("second", Duration::from_secs(60)));
+        assert!(!dedup.check("first", Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn accepts_keys_after_window() {
+        let dedup = Deduplicator::default();
+
+        assert!(dedup.check("first", Duration::ZERO));
+        assert!(dedup.check("first", Duration::ZERO));
+    }
+}
@@ -0,0 +1,86 @@
+//! Echoing of the heartbeats sent by subscribers, so that they can detect a connection that has
+//! stopped responding faster than the QUIC idle timeout
+
+use futures::{
+    channel::mpsc::{self, Sender},
+    stream, Sink, SinkExt, Stream, StreamExt,
+};
+use log::info;
+use selium_common::{protocol::Frame, types::BiStream};
+
+/// Interleaves the heartbeats echoed to a subscriber with the messages delivered to it.
+///
+/// Returns a sink for the topic to deliver messages to, along with a sender for the heartbeats
+/// to echo, both of which are forwarded to the subscriber's `sink` until it disconnects.
+pub fn echo<Si>(sink: Si) -> (impl Sink<Frame, Error = anyhow::Error>, Sender<Frame>)
+where
+    Si: Sink<Frame, Error = anyhow::Error> + Send + 'static,
+{
+    let (messages, message_rx) = mpsc::channel(0);
+    let (echoes, echo_rx) = mpsc::channel(0);
+
+    tokio::spawn(async move {
+        if let Err(e) = stream::select(message_rx, echo_rx)
+            .map(Ok)
+            .forward(sink)
+            .await
+        {
+            info!("Heartbeat subscriber closed: {:?}", e);
+        }
+    });
+
+    (messages.sink_map_err(anyhow::Error::from), echoes)
+}
+
+/// Echoes each heartbeat received on a subscriber's stream, until the subscriber disconnects.
+pub async fn read<S>(mut stream: S, mut echoes: Sender<Frame>)
+where
+    S: Stream<Item = anyhow::Result<Frame>> + Unpin,
+{
+    while let Some(Ok(frame)) = stream.next().await {
+        if let Frame::Heartbeat(_) = frame {
+            if echoes.send(frame).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Splits a subscriber's stream into a sink for delivering messages, while echoing the
+/// heartbeats received on it.
+pub fn track(stream: BiStream) -> impl Sink<Frame, Error = anyhow::Error> {
+    let (write, read) = stream.split();
+    let (sink, echoes) = echo(write);
+
+    tokio::spawn(self::read(read, echoes));
+
+    sink
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use selium_common::protocol::{AckPayload, HeartbeatPayload, MessagePayload};
+
+    #[tokio::test]
+    async fn echoes_heartbeats_alongside_messages() {
+        let (tx, rx) = mpsc::unbounded();
+        let (mut sink, echoes) = echo(tx.sink_map_err(anyhow::Error::from));
+
+        let heartbeat = Frame::Heartbeat(HeartbeatPayload { id: 0 });
+        let message = Frame::Message(MessagePayload::new(Bytes::from("Hello")));
+        let received = stream::iter([
+            Ok(Frame::Ack(AckPayload { delivery_id: 0 })),
+            Ok(heartbeat.clone()),
+        ]);
+
+        read(received, echoes).await;
+        sink.send(message.clone()).await.unwrap();
+        drop(sink);
+
+        let delivered: Vec<_> = rx.collect().await;
+
+        assert_eq!(delivered, vec![heartbeat, message]);
+    }
+}
@@ -1,22 +1,113 @@
-use crate::topic::Topic;
+use crate::attach::Confirm;
+use crate::auth::Authorizer;
+use crate::dedup::Deduplicator;
+use crate::offsets::Committer;
+use crate::retain::Retained;
+use crate::sequence::{InSequence, Sequencer};
+use crate::topic::{Log, Topic, TopicCounts};
+use crate::topic_log::TopicLog;
+use crate::wasm::{FilterErrorPolicy, Modules, Pipeline};
 use anyhow::{anyhow, bail, Context, Result};
 use clap::{Args, Parser};
 use clap_verbosity_flag::Verbosity;
 use env_logger::Builder;
-use futures::{channel::mpsc::Sender, SinkExt, StreamExt};
+use futures::{
+    channel::mpsc::{self, Sender},
+    future, Sink, SinkExt, Stream, StreamExt,
+};
 use log::{error, info};
 use quinn::{IdleTimeout, VarInt};
-use selium_common::{protocol::Frame, types::BiStream};
-use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc};
+use selium_common::{
+    protocol::{
+        AcceptPayload, Compression, Frame, ListTopicsPayload, PublisherPayload, ReplayStart,
+        StartPosition, SubscriberPayload, TopicInfo, TopicListPayload, UnauthorizedPayload,
+    },
+    types::{BiStream, StreamReset},
+};
+use std::{
+    collections::HashMap, net::SocketAddr, path::PathBuf, pin::Pin, sync::Arc, time::Duration,
+};
 use tokio::sync::Mutex;
 use tokio_stream::StreamNotifyClose;
 use topic::Socket;
+use wildcard::TopicPattern;
 
+mod ack;
+mod attach;
+mod auth;
+mod dedup;
+mod heartbeat;
+mod offsets;
 mod quic;
+mod receipt;
+mod retain;
+mod schedule;
+mod sequence;
 mod sink;
 mod topic;
+mod topic_log;
+mod wasm;
+#[cfg(feature = "webtransport")]
+mod webtransport;
+mod wildcard;
 
-type TopicChannel = Sender<Socket<StreamNotifyClose<BiStream>, BiStream>>;
+const WILDCARD_CHANNEL_SIZE: usize = 100;
+
+type PublisherStream = Pin<Box<dyn Stream<Item = Result<Frame>> + Send>>;
+type SubscriberSink = Pin<Box<dyn Sink<Frame, Error = anyhow::Error> + Send>>;
+type TopicChannel = Sender<Socket<StreamNotifyClose<PublisherStream>, SubscriberSink>>;
+/// Identifies the subscribers that unacknowledged messages are redelivered to, by their topic
+/// and consumer group.
+type RedeliveryKey = (String, Option<String>);
+
+#[derive(Default)]
+struct Topics {
+    channels: HashMap<String, TopicChannel>,
+    counts: HashMap<String, Arc<TopicCounts>>,
+    wildcards: Vec<WildcardSubscriber>,
+    redeliveries: HashMap<RedeliveryKey, Vec<Frame>>,
+    retained: HashMap<String, Retained>,
+    deduplicators: HashMap<String, Deduplicator>,
+    sequencers: HashMap<String, Sequencer>,
+    committers: HashMap<(String, String), Committer>,
+}
+
+/// The configuration applied to the streams opened by every client.
+struct Settings {
+    /// The compression algorithms that publishers may negotiate.
+    compression: Vec<Compression>,
+    /// The modules that subscribers' operations are loaded from.
+    modules: Modules,
+    /// The directory that each topic's log is persisted to, for subscribers to replay, along with
+    /// the offsets committed by consumer groups.
+    log_dir: Option<PathBuf>,
+    /// How long an idempotency key is remembered for, to drop duplicate messages.
+    dedup_window: Duration,
+    /// The tokens that streams must present to use each topic, if any are required.
+    auth: Option<Authorizer>,
+    /// The token that clients must present to list the server's topics.
+    admin_token: Option<String>,
+}
+
+/// A subscriber registered against a [TopicPattern]. Every topic matching the pattern forwards
+/// its messages to `sender`, tagged with the name of the topic that they were published to.
+struct WildcardSubscriber {
+    pattern: TopicPattern,
+    sender: Sender<Frame>,
+}
+
+impl WildcardSubscriber {
+    fn sink_for(&self, topic: &str) -> SubscriberSink {
+        let topic = topic.to_owned();
+
+        Box::pin(
+            self.sender
+                .clone()
+                .sink_map_err(anyhow::Error::from)
+                .with(move |frame: Frame| future::ready(Ok(frame.with_topic(&topic)))),
+        )
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -26,12 +117,72 @@ struct UserArgs {
     bind_addr: SocketAddr,
     #[clap(flatten)]
     cert: CertGroup,
+    /// CA certificate used to verify client certificates. When provided, clients must present a
+    /// valid certificate signed by this CA
+    #[clap(long = "client-ca")]
+    client_ca: Option<PathBuf>,
     /// Enable stateless retries
     #[clap(long = "stateless-retry")]
     stateless_retry: bool,
     /// File to log TLS keys to for debugging
     #[clap(long = "keylog")]
     keylog: bool,
+    /// Accept 0-RTT early data from clients resuming a previous session. Early data is not
+    /// protected against replay, so this should only be enabled if clients send idempotent
+    /// messages immediately after connecting
+    #[clap(long = "enable-0rtt")]
+    enable_0rtt: bool,
+    /// Maximum number of concurrent streams, such as publishers and subscribers, that each client
+    /// can open
+    #[clap(long = "max-concurrent-streams", default_value_t = 100, value_parser = clap::value_parser!(u32))]
+    max_concurrent_streams: u32,
+    /// Comma-separated ALPN protocols accepted from clients, in order of preference. Clients that
+    /// advertise none of these protocols fail the handshake
+    #[clap(
+        long = "alpn",
+        value_delimiter = ',',
+        default_value = "hq-29",
+        value_parser = clap::builder::NonEmptyStringValueParser::new()
+    )]
+    alpn: Vec<String>,
+    /// Comma-separated compression algorithms that publishers may negotiate (zstd, lz4). Publishers
+    /// requesting any other algorithm fall back to sending uncompressed messages
+    #[clap(
+        long = "compression",
+        value_delimiter = ',',
+        default_value = "zstd,lz4"
+    )]
+    compression: Vec<Compression>,
+    /// Directory that the WebAssembly modules used by subscribers' map and filter operations are
+    /// loaded from. When omitted, subscribers requesting operations are rejected
+    #[clap(long = "modules")]
+    modules: Option<PathBuf>,
+    /// Whether a message is dropped or delivered when a filter module fails to evaluate it
+    #[clap(long = "filter-error-policy", value_enum, default_value_t = FilterErrorPolicy::Drop)]
+    filter_error_policy: FilterErrorPolicy,
+    /// Directory to persist a log of each topic's messages to, which subscribers can replay,
+    /// along with the offsets committed by consumer groups. When omitted, subscribers requesting
+    /// a replay are rejected, and committed offsets are ignored
+    #[clap(long = "log-dir")]
+    log_dir: Option<PathBuf>,
+    /// Time in ms that the idempotency key of each message is remembered for. Messages published
+    /// to the same topic with a key that was seen within this window are dropped
+    #[clap(long = "dedup-window", default_value_t = 60000)]
+    dedup_window: u64,
+    /// File listing the tokens permitted to use each topic, as a topic pattern and a token on
+    /// each line. When provided, streams are rejected unless they present a token permitted to
+    /// use their topic
+    #[clap(long = "auth-tokens")]
+    auth_tokens: Option<PathBuf>,
+    /// Token that clients must present to list the server's topics. When omitted, clients can't
+    /// list topics
+    #[clap(long = "admin-token")]
+    admin_token: Option<String>,
+    /// Accept WebTransport sessions over HTTP/3 from browsers, alongside native clients, by
+    /// adding h3 to the accepted ALPN protocols
+    #[cfg(feature = "webtransport")]
+    #[clap(long = "webtransport")]
+    webtransport: bool,
     /// Maximum time in ms a client can idle waiting for data - default to 15 seconds
     #[clap(long = "max-idle-timeout", default_value_t = 15000, value_parser = clap::value_parser!(u32))]
     max_idle_timeout: u32,
@@ -74,22 +225,51 @@ async fn main() -> Result<()> {
         // Clap ensures that either --cert + --key or --self-signed are present
         unreachable!();
     };
+    let alpn_protocols: Vec<Vec<u8>> = args.alpn.into_iter().map(String::into_bytes).collect();
+
+    #[cfg(feature = "webtransport")]
+    let (alpn_protocols, max_concurrent_uni_streams) = if args.webtransport {
+        let mut alpn_protocols = alpn_protocols;
+        if !alpn_protocols.iter().any(|p| p == webtransport::ALPN) {
+            alpn_protocols.push(webtransport::ALPN.to_vec());
+        }
+        (alpn_protocols, webtransport::MAX_CLIENT_UNI_STREAMS.into())
+    } else {
+        (alpn_protocols, VarInt::from_u32(0))
+    };
+    #[cfg(not(feature = "webtransport"))]
+    let max_concurrent_uni_streams = VarInt::from_u32(0);
+
     let opts = quic::ConfigOptions {
         keylog: args.keylog,
         stateless_retry: args.stateless_retry,
         max_idle_timeout: IdleTimeout::from(VarInt::from_u32(args.max_idle_timeout)),
+        client_ca: args.client_ca.map(quic::read_client_ca).transpose()?,
+        enable_0rtt: args.enable_0rtt,
+        max_concurrent_streams: VarInt::from_u32(args.max_concurrent_streams),
+        max_concurrent_uni_streams,
+        alpn_protocols,
     };
     let config = quic::server_config(certs, key, opts)?;
     let endpoint = quinn::Endpoint::server(config, args.bind_addr)?;
 
     // Create hash to store message ordering data
-    let topics = Arc::new(Mutex::new(HashMap::new()));
+    let topics = Arc::new(Mutex::new(Topics::default()));
+    let settings = Arc::new(Settings {
+        compression: args.compression,
+        modules: Modules::new(args.modules, args.filter_error_policy),
+        log_dir: args.log_dir,
+        dedup_window: Duration::from_millis(args.dedup_window),
+        auth: args.auth_tokens.map(Authorizer::load).transpose()?,
+        admin_token: args.admin_token,
+    });
 
     while let Some(conn) = endpoint.accept().await {
         info!("connection incoming");
         let topics_clone = topics.clone();
+        let settings = settings.clone();
         tokio::spawn(async move {
-            if let Err(e) = handle_connection(topics_clone, conn).await {
+            if let Err(e) = handle_connection(topics_clone, settings, conn).await {
                 error!("connection failed: {:?}", e);
             }
         });
@@ -99,81 +279,290 @@ async fn main() -> Result<()> {
 }
 
 async fn handle_connection(
-    topics: Arc<Mutex<HashMap<String, TopicChannel>>>,
+    topics: Arc<Mutex<Topics>>,
+    settings: Arc<Settings>,
     conn: quinn::Connecting,
 ) -> Result<()> {
     let connection = conn.await?;
+    let protocol = connection
+        .handshake_data()
+        .unwrap()
+        .downcast::<quinn::crypto::rustls::HandshakeData>()
+        .unwrap()
+        .protocol;
     info!(
         "Connection {} - {}",
         connection.remote_address(),
-        connection
-            .handshake_data()
-            .unwrap()
-            .downcast::<quinn::crypto::rustls::HandshakeData>()
-            .unwrap()
-            .protocol
-            .map_or_else(
-                || "<none>".into(),
-                |x| String::from_utf8_lossy(&x).into_owned()
-            )
+        protocol.as_ref().map_or_else(
+            || "<none>".into(),
+            |x| String::from_utf8_lossy(x).into_owned()
+        )
     );
 
+    // WebTransport clients establish their session before opening any Selium streams
+    #[cfg(feature = "webtransport")]
+    let session = match protocol.as_deref() {
+        Some(webtransport::ALPN) => Some(webtransport::Session::accept(&connection).await?),
+        _ => None,
+    };
+
     loop {
-        let connection = connection.clone();
-        let stream = connection.accept_bi().await;
-        let stream = match stream {
-            Err(quinn::ConnectionError::ApplicationClosed { .. }) => {
-                info!("Connection closed ({})", connection.remote_address());
-                return Ok(());
-            }
-            Err(e) => {
-                bail!(e)
-            }
-            Ok(stream) => BiStream::from(stream),
+        #[cfg(feature = "webtransport")]
+        let stream = match &session {
+            Some(session) => session.accept_stream().await?,
+            None => accept_stream(&connection).await?,
+        };
+        #[cfg(not(feature = "webtransport"))]
+        let stream = accept_stream(&connection).await?;
+
+        let Some(stream) = stream else {
+            info!("Connection closed ({})", connection.remote_address());
+            return Ok(());
         };
 
         let topics_clone = topics.clone();
+        let settings = settings.clone();
+        // Logged as the integer that clients see, so that their logs can be correlated
+        let stream_id = VarInt::from(stream.get_recv_stream_id()).into_inner();
 
         tokio::spawn(async move {
-            if let Err(e) = handle_stream(topics_clone, stream).await {
-                error!("Request failed: {:?}", e);
+            if let Err(e) = handle_stream(topics_clone, &settings, stream).await {
+                error!("Request failed on stream {}: {:?}", stream_id, e);
             }
         });
     }
 }
 
+/// Accepts the next stream that a native client opens, returning [None] once the client closes
+/// the connection.
+async fn accept_stream(connection: &quinn::Connection) -> Result<Option<BiStream>> {
+    match connection.accept_bi().await {
+        Ok(stream) => Ok(Some(BiStream::from(stream))),
+        Err(quinn::ConnectionError::ApplicationClosed { .. }) => Ok(None),
+        Err(e) => bail!(e),
+    }
+}
+
 async fn handle_stream(
-    topics: Arc<Mutex<HashMap<String, TopicChannel>>>,
+    topics: Arc<Mutex<Topics>>,
+    settings: &Settings,
     mut stream: BiStream,
 ) -> Result<()> {
     // Receive header
     if let Some(result) = stream.next().await {
         let frame = result?;
+
+        if let Frame::ListTopics(payload) = frame {
+            return list_topics(topics, settings, stream, payload).await;
+        }
+
         let topic_name = frame.get_topic().ok_or(anyhow!("Expected header frame"))?;
 
-        // Spawn new topic if it doesn't exist yet
+        if let Some(auth) = &settings.auth {
+            authorize(&mut stream, &frame, auth).await?;
+        }
+
+        // Rejected subscribers have their stream reset, so that they can tell the rejection apart
+        // from a lost connection
+        if let Frame::RegisterSubscriber(payload) = &frame {
+            if let Err(e) = validate_subscriber(payload, settings) {
+                stream.reset(StreamReset::REJECTED)?;
+                return Err(e);
+            }
+        }
+
+        let pipeline = match &frame {
+            Frame::RegisterPublisher(payload) => {
+                negotiate_compression(&mut stream, payload, &settings.compression).await?;
+                None
+            }
+            Frame::RegisterSubscriber(payload) => {
+                // Subscribers presenting a token wait to be accepted before receiving messages.
+                // Those choosing a start position are instead accepted once they're attached.
+                if payload.token.is_some() && payload.start_position.is_none() {
+                    stream
+                        .send(Frame::Accept(AcceptPayload {
+                            compression: Compression::None,
+                        }))
+                        .await
+                        .context("Failed to accept Subscriber")?;
+                }
+
+                settings.modules.pipeline(&payload.operations)?
+            }
+            _ => None,
+        };
+
         let mut ts = topics.lock().await;
-        if !ts.contains_key(topic_name) {
-            let (fut, tx) = Topic::pair();
-            tokio::spawn(fut);
 
-            ts.insert(topic_name.to_owned(), tx);
+        if TopicPattern::is_wildcard(topic_name) {
+            return match frame {
+                Frame::RegisterSubscriber(SubscriberPayload {
+                    heartbeat,
+                    start_position,
+                    ..
+                }) => {
+                    let pattern = TopicPattern::parse(topic_name)?;
+                    let sink: SubscriberSink = if heartbeat {
+                        Box::pin(heartbeat::track(stream))
+                    } else {
+                        Box::pin(stream)
+                    };
+
+                    register_wildcard(&mut ts, pattern, sink, pipeline, start_position.is_some())
+                        .await
+                }
+                _ => bail!("Only subscribers may use wildcard topics"),
+            };
         }
 
-        let tx = ts.get_mut(topic_name).unwrap();
+        // Spawn new topic if it doesn't exist yet
+        if !ts.channels.contains_key(topic_name) {
+            let log: Option<Box<dyn Log<Frame> + Send>> = match &settings.log_dir {
+                Some(dir) => Some(Box::new(TopicLog::open(dir, topic_name))),
+                None => None,
+            };
+            let counts = Arc::new(TopicCounts::default());
+            let (fut, mut tx) = Topic::pair(log, counts.clone());
+            tokio::spawn(fut);
+
+            // Attach any wildcard subscribers that are still listening
+            ts.wildcards.retain(|sub| !sub.sender.is_closed());
+
+            for sub in ts.wildcards.iter() {
+                if sub.pattern.matches(topic_name) {
+                    tx.send(Socket::Sink(sub.sink_for(topic_name)))
+                        .await
+                        .context("Failed to add wildcard Subscriber sink")?;
+                }
+            }
+
+            ts.channels.insert(topic_name.to_owned(), tx);
+            ts.counts.insert(topic_name.to_owned(), counts);
+        }
 
         match frame {
-            Frame::RegisterPublisher(_) => {
+            Frame::RegisterPublisher(payload) => {
+                // Receipts are echoed as they're read, once the messages before them have been
+                // handed on. Duplicates are dropped before they can be retained, and delayed
+                // messages are sequenced once they're due.
+                let stream = receipt::track(stream);
+                let dedup = ts.deduplicators.entry(payload.topic.clone()).or_default();
+                let stream = dedup.dedup_stream(stream, settings.dedup_window);
+                let stream = schedule::schedule_stream(stream);
+                let sequencer = ts.sequencers.entry(payload.topic.clone()).or_default();
+                let stream = sequencer.sequence_stream(stream);
+
+                let stream: PublisherStream = if payload.retention_policy > 0 {
+                    let retention = Duration::from_millis(payload.retention_policy);
+                    let retained = ts.retained.entry(payload.topic.clone()).or_default();
+
+                    Box::pin(retained.retain_stream(stream, retention))
+                } else {
+                    Box::pin(stream)
+                };
+
+                let tx = ts.channels.get_mut(&payload.topic).unwrap();
+
                 tx.send(Socket::Stream(StreamNotifyClose::new(stream)))
                     .await
                     .context("Failed to add Publisher sink")?;
             }
-            Frame::RegisterSubscriber(_) => {
-                tx.send(Socket::Sink(stream))
+            Frame::RegisterSubscriber(payload) => {
+                // Consumer groups commit their offsets into topics' logs, sharing a committer
+                // between their members
+                let committer = match (&payload.group, &settings.log_dir) {
+                    (Some(group), Some(dir)) => Some(
+                        ts.committers
+                            .entry((payload.topic.clone(), group.clone()))
+                            .or_insert_with(|| Committer::new(dir, &payload.topic, group))
+                            .clone(),
+                    ),
+                    _ => None,
+                };
+
+                // Messages left unacknowledged by the subscribers before it are redelivered first
+                let key = (payload.topic.clone(), payload.group.clone());
+                let pending = if payload.acks {
+                    ts.redeliveries.remove(&key).unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+
+                // Consumer groups share the topic's live messages between their members, so
+                // retained messages are only delivered to subscribers outside of a group. Replayed
+                // logs already include any retained messages.
+                let retained = match (&payload.group, &payload.replay) {
+                    (None, None) => ts.retained.get(&payload.topic).map(Retained::messages),
+                    _ => None,
+                };
+
+                let mut tx = ts.channels.get(&payload.topic).unwrap().clone();
+
+                // Released before writing to the subscriber, so that a slow subscriber can't
+                // stall the registration of streams to every other topic
+                drop(ts);
+
+                let replay = match (payload.replay, &committer) {
+                    (Some(ReplayStart::Committed), Some(committer)) => {
+                        Some(committer.resume_from().await?)
+                    }
+                    (replay, _) => replay,
+                };
+
+                // Heartbeats are echoed straight to the subscriber's stream, ahead of any operations
+                let sink: SubscriberSink = if payload.acks {
+                    track_acks(
+                        topics.clone(),
+                        key,
+                        stream,
+                        pending,
+                        payload.start_position.is_some(),
+                        payload.heartbeat,
+                        committer,
+                    )
+                    .await?
+                } else if let Some(committer) = committer {
+                    track_commits(stream, committer, payload.heartbeat)
+                } else if payload.heartbeat {
+                    Box::pin(heartbeat::track(stream))
+                } else {
+                    Box::pin(stream)
+                };
+
+                let mut sink = with_pipeline(sink, pipeline);
+
+                if payload.ordered {
+                    sink = Box::pin(InSequence::new(sink));
+                }
+
+                // Subscribers choosing a start position are confirmed once the topic attaches
+                // them, ahead of any retained messages they start from. Those acknowledging their
+                // messages were already confirmed ahead of their redelivered messages.
+                match (payload.start_position, retained) {
+                    (Some(StartPosition::FromConnect), Some(retained)) if payload.acks => {
+                        sink = Box::pin(Confirm::deliver(sink, retained));
+                    }
+                    (Some(_), _) if payload.acks => (),
+                    (Some(StartPosition::FromConnect), Some(retained)) => {
+                        sink = Box::pin(Confirm::new(sink, retained));
+                    }
+                    (Some(_), _) => sink = Box::pin(Confirm::new(sink, Vec::new())),
+                    (None, Some(retained)) => deliver_retained(&mut sink, retained).await?,
+                    (None, None) => (),
+                }
+
+                let socket = match (payload.group, replay) {
+                    (group, Some(start)) => Socket::ReplaySink(start, group, sink),
+                    (Some(group), None) => Socket::GroupSink(group, sink),
+                    (None, None) => Socket::Sink(sink),
+                };
+
+                tx.send(socket)
                     .await
                     .context("Failed to add Subscriber sink")?;
             }
-            _ => unreachable!(), // because of `topic_name` instantiation
+            _ => bail!("Expected header frame"),
         }
     } else {
         info!("Stream closed");
@@ -181,3 +570,279 @@ async fn handle_stream(
 
     Ok(())
 }
+
+/// Rejects a stream registering to a topic that its token doesn't permit it to use, replying
+/// with a [Frame::Unauthorized] before the stream is closed.
+async fn authorize(stream: &mut BiStream, frame: &Frame, auth: &Authorizer) -> Result<()> {
+    let (topic, token) = match frame {
+        Frame::RegisterPublisher(payload) => (&payload.topic, payload.token.as_ref()),
+        Frame::RegisterSubscriber(payload) => (&payload.topic, payload.token.as_ref()),
+        _ => return Ok(()),
+    };
+
+    if auth.authorize(topic, token) {
+        return Ok(());
+    }
+
+    let topic = topic.clone();
+    let reply = Frame::Unauthorized(UnauthorizedPayload {
+        topic: topic.clone(),
+    });
+
+    stream.send(reply).await?;
+    stream.finish().await?;
+
+    bail!("Stream is not authorized to use topic {topic}")
+}
+
+/// Replies to an admin client with the server's topics, and the number of streams attached to
+/// each, rejecting it with a [Frame::Unauthorized] unless its token matches the admin token.
+async fn list_topics(
+    topics: Arc<Mutex<Topics>>,
+    settings: &Settings,
+    mut stream: BiStream,
+    payload: ListTopicsPayload,
+) -> Result<()> {
+    let authorized = match (&payload.token, &settings.admin_token) {
+        (Some(token), Some(expected)) => token.matches(expected),
+        _ => false,
+    };
+
+    if !authorized {
+        let reply = Frame::Unauthorized(UnauthorizedPayload {
+            topic: "#".to_owned(),
+        });
+
+        stream.send(reply).await?;
+        stream.finish().await?;
+
+        bail!("Stream is not authorized to list topics");
+    }
+
+    let mut topics: Vec<TopicInfo> = topics
+        .lock()
+        .await
+        .counts
+        .iter()
+        .map(|(topic, counts)| TopicInfo {
+            topic: topic.clone(),
+            publishers: counts.publishers(),
+            subscribers: counts.subscribers(),
+        })
+        .collect();
+    topics.sort_by(|a, b| a.topic.cmp(&b.topic));
+
+    stream
+        .send(Frame::TopicList(TopicListPayload { topics }))
+        .await
+        .context("Failed to send topic list")?;
+    stream.finish().await?;
+
+    Ok(())
+}
+
+/// Replies to a publisher that requests compression with the algorithm it should use, falling
+/// back to [Compression::None] if the requested algorithm isn't permitted. Publishers that
+/// present a token, but don't request compression, are accepted with [Compression::None].
+async fn negotiate_compression(
+    stream: &mut BiStream,
+    payload: &PublisherPayload,
+    permitted: &[Compression],
+) -> Result<()> {
+    if payload.compression.is_none() && payload.token.is_none() {
+        return Ok(());
+    }
+
+    let compression = if payload.compression.is_none() || permitted.contains(&payload.compression) {
+        payload.compression
+    } else {
+        info!(
+            "Publisher on {} requested unsupported {} compression",
+            payload.topic, payload.compression
+        );
+        Compression::None
+    };
+
+    stream
+        .send(Frame::Accept(AcceptPayload { compression }))
+        .await
+        .context("Failed to accept Publisher")
+}
+
+/// Checks that the options requested by a subscriber are compatible with each other, and with
+/// the server's settings.
+fn validate_subscriber(payload: &SubscriberPayload, settings: &Settings) -> Result<()> {
+    if TopicPattern::is_wildcard(&payload.topic) {
+        if payload.group.is_some() {
+            bail!("Consumer groups may not use wildcard topics");
+        }
+
+        if payload.acks {
+            bail!("Acknowledgements may not be used with wildcard topics");
+        }
+
+        if payload.replay.is_some() {
+            bail!("Wildcard topics may not be replayed");
+        }
+
+        if payload.ordered {
+            bail!("Ordered delivery may not be used with wildcard topics");
+        }
+    }
+
+    if let Some(replay) = &payload.replay {
+        match (replay, &payload.group) {
+            (ReplayStart::Committed, None) => {
+                bail!("Only consumer groups may replay from a committed offset")
+            }
+            (ReplayStart::Committed, Some(_)) | (_, None) => (),
+            (_, Some(_)) => {
+                bail!("Consumer groups may only replay from their committed offset")
+            }
+        }
+
+        if settings.log_dir.is_none() {
+            bail!("Server is not configured to persist topic logs");
+        }
+    }
+
+    Ok(())
+}
+
+/// Tracks acknowledgements for a subscriber, after first redelivering any messages that were left
+/// unacknowledged by earlier subscribers with the same topic and group. Once the subscriber
+/// disconnects, its own unacknowledged messages are held for redelivery in turn. If the
+/// subscriber sends `heartbeats`, they're echoed alongside its messages, and if it's in a consumer
+/// group, the offsets it commits are applied via the `committer`.
+///
+/// Subscribers that are `confirmed` once they're attached to the topic are redelivered their
+/// messages after the confirmation, rather than straight away.
+#[allow(clippy::too_many_arguments)]
+async fn track_acks(
+    topics: Arc<Mutex<Topics>>,
+    key: RedeliveryKey,
+    stream: BiStream,
+    pending: Vec<Frame>,
+    confirmed: bool,
+    heartbeats: bool,
+    committer: Option<Committer>,
+) -> Result<SubscriberSink> {
+    let (write, read) = stream.split();
+    let (mut sink, mut reader) = ack::track(write, read);
+
+    if let Some(committer) = committer {
+        reader = reader.commit_offsets(committer);
+    }
+
+    let sink: SubscriberSink = if confirmed {
+        Box::pin(Confirm::new(sink, pending))
+    } else {
+        for frame in pending {
+            sink.feed(frame).await?;
+        }
+
+        sink.flush().await?;
+        Box::pin(sink)
+    };
+
+    let sink: SubscriberSink = if heartbeats {
+        let (sink, echoes) = heartbeat::echo(sink);
+        reader = reader.echo_heartbeats(echoes);
+        Box::pin(sink)
+    } else {
+        sink
+    };
+
+    tokio::spawn(async move {
+        let unacked = reader.run().await;
+
+        if !unacked.is_empty() {
+            info!(
+                "Holding {} unacknowledged message(s) for redelivery on {}",
+                unacked.len(),
+                key.0
+            );
+
+            let mut ts = topics.lock().await;
+            ts.redeliveries.entry(key).or_default().extend(unacked);
+        }
+    });
+
+    Ok(sink)
+}
+
+/// Splits the stream of a subscriber in a consumer group into a sink for delivering messages,
+/// while applying the offsets it commits. If the subscriber sends `heartbeats`, they're echoed
+/// alongside its messages.
+fn track_commits(stream: BiStream, committer: Committer, heartbeats: bool) -> SubscriberSink {
+    let (write, read) = stream.split();
+
+    if heartbeats {
+        let (sink, echoes) = heartbeat::echo(write);
+        tokio::spawn(committer.read(read, Some(echoes)));
+        Box::pin(sink)
+    } else {
+        tokio::spawn(committer.read(read, None));
+        Box::pin(write)
+    }
+}
+
+/// Delivers the messages retained on a topic to a subscriber that has just joined it.
+async fn deliver_retained(sink: &mut SubscriberSink, retained: Vec<Frame>) -> Result<()> {
+    for frame in retained {
+        sink.feed(frame).await?;
+    }
+
+    sink.flush()
+        .await
+        .context("Failed to deliver retained messages")
+}
+
+/// Applies a subscriber's operations to each message before it's written to the sink.
+fn with_pipeline<Si>(sink: Si, pipeline: Option<Pipeline>) -> SubscriberSink
+where
+    Si: Sink<Frame, Error = anyhow::Error> + Send + 'static,
+{
+    match pipeline {
+        Some(pipeline) => Box::pin(pipeline.wrap(sink)),
+        None => Box::pin(sink),
+    }
+}
+
+/// Registers a wildcard subscriber with every matching topic. If the subscriber chose a start
+/// position, it's `confirm`ed once registered, before any messages are forwarded to it.
+async fn register_wildcard(
+    topics: &mut Topics,
+    pattern: TopicPattern,
+    sink: SubscriberSink,
+    pipeline: Option<Pipeline>,
+    confirm: bool,
+) -> Result<()> {
+    let (sender, receiver) = mpsc::channel(WILDCARD_CHANNEL_SIZE);
+    let sub = WildcardSubscriber { pattern, sender };
+
+    for (topic_name, tx) in topics.channels.iter_mut() {
+        if sub.pattern.matches(topic_name) {
+            tx.send(Socket::Sink(sub.sink_for(topic_name)))
+                .await
+                .context("Failed to add wildcard Subscriber sink")?;
+        }
+    }
+
+    topics.wildcards.push(sub);
+
+    let mut sink = with_pipeline(sink, pipeline);
+
+    if confirm {
+        sink = Box::pin(Confirm::new(sink, Vec::new()));
+    }
+
+    // Forward messages from every matching topic to the subscriber until it disconnects
+    tokio::spawn(async move {
+        if let Err(e) = receiver.map(Ok).forward(sink).await {
+            info!("Wildcard subscriber closed: {:?}", e);
+        }
+    });
+
+    Ok(())
+}
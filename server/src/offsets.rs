@@ -0,0 +1,249 @@
+//! Offsets committed by the subscribers in a consumer group, so that the group can resume
+//! replaying a topic's log from where it left off
+
+use anyhow::{Context, Result};
+use futures::{channel::mpsc::Sender, SinkExt, Stream, StreamExt};
+use log::error;
+use selium_common::protocol::{CommitPayload, Frame, ReplayStart};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{sync::Mutex, task};
+
+/// The offset committed by a consumer group to a topic, persisted alongside the topic's log.
+///
+/// The offset is stored as a big-endian [u64], in a file named after the hex-encoded topic and
+/// group names. The group's members share a single offset, which only ever moves forwards: a
+/// commit that isn't ahead of the committed offset is ignored, so that the members of a group,
+/// which each receive a share of its messages, can commit in any order.
+///
+/// As a result, a group's committed offset may pass messages that another member has been
+/// delivered, but not yet processed. If that member disconnects first, the group skips those
+/// messages when it resumes from its committed offset. Groups that need every message processed
+/// should also enable acknowledgements, so that any message left unacknowledged by a member is
+/// redelivered to the rest of the group.
+///
+/// Clones share the offset, and commits are applied one at a time off the executor, so each
+/// group has a single Committer that's cloned for each of its members.
+#[derive(Clone, Debug)]
+pub struct Committer {
+    path: PathBuf,
+    // The group's committed offset, once it has been read, which commits are serialized on
+    committed: Arc<Mutex<Option<Option<u64>>>>,
+}
+
+impl Committer {
+    pub fn new(dir: &Path, topic: &str, group: &str) -> Self {
+        let path = dir.join(format!(
+            "{}.{}.offset",
+            hex::encode(topic),
+            hex::encode(group)
+        ));
+        Self {
+            path,
+            committed: Arc::default(),
+        }
+    }
+
+    /// Returns the last offset committed by the group, if it has committed one.
+    pub async fn committed(&self) -> Result<Option<u64>> {
+        let mut committed = self.committed.lock().await;
+
+        if let Some(offset) = *committed {
+            return Ok(offset);
+        }
+
+        let path = self.path.clone();
+        let offset = task::spawn_blocking(move || read(&path)).await??;
+        *committed = Some(offset);
+
+        Ok(offset)
+    }
+
+    /// Returns where the group resumes replaying the topic's log from: the offset after the one
+    /// it last committed, or the start of the log.
+    pub async fn resume_from(&self) -> Result<ReplayStart> {
+        Ok(self
+            .committed()
+            .await?
+            .map_or(ReplayStart::Earliest, |offset| {
+                ReplayStart::Offset(offset + 1)
+            }))
+    }
+
+    /// Commits `offset` for the group, unless it's behind the group's committed offset. The
+    /// offset is written to a temporary file first, so that a crash mid-write can't leave a
+    /// partially written offset behind.
+    pub async fn commit(&self, offset: u64) -> Result<()> {
+        let mut committed = self.committed.lock().await;
+
+        let current = match *committed {
+            Some(current) => current,
+            None => {
+                let path = self.path.clone();
+                task::spawn_blocking(move || read(&path)).await??
+            }
+        };
+
+        if current.is_some_and(|current| offset <= current) {
+            *committed = Some(current);
+            return Ok(());
+        }
+
+        let path = self.path.clone();
+        task::spawn_blocking(move || write(&path, offset)).await??;
+        *committed = Some(Some(offset));
+
+        Ok(())
+    }
+
+    /// Commits each offset received on a subscriber's stream, until the subscriber disconnects.
+    /// If the subscriber sends heartbeats, they're echoed via `echoes`.
+    pub async fn read<S>(self, mut stream: S, mut echoes: Option<Sender<Frame>>)
+    where
+        S: Stream<Item = Result<Frame>> + Unpin,
+    {
+        while let Some(Ok(frame)) = stream.next().await {
+            match frame {
+                Frame::Commit(CommitPayload { offset }) => self.apply(offset).await,
+                Frame::Heartbeat(_) => {
+                    if let Some(echoes) = echoes.as_mut() {
+                        if echoes.send(frame).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Commits an offset received from a subscriber, logging any failure, as the subscriber
+    /// isn't told whether its commits succeed.
+    pub async fn apply(&self, offset: u64) {
+        if let Err(e) = self.commit(offset).await {
+            error!("{e:?}");
+        }
+    }
+}
+
+/// Reads the offset stored at `path`, if there is one.
+fn read(path: &Path) -> Result<Option<u64>> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            let bytes = bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Malformed offset {}", path.display()))?;
+
+            Ok(Some(u64::from_be_bytes(bytes)))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read offset {}", path.display())),
+    }
+}
+
+/// Replaces the offset stored at `path`, via a temporary file that's renamed over it.
+fn write(path: &Path, offset: u64) -> Result<()> {
+    let temp = path.with_extension("offset.tmp");
+
+    fs::write(&temp, offset.to_be_bytes())
+        .and_then(|_| fs::rename(&temp, path))
+        .with_context(|| format!("Failed to commit offset {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{channel::mpsc, stream};
+    use selium_common::protocol::HeartbeatPayload;
+
+    fn offset_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("selium-offsets-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn resumes_after_committed_offset() {
+        let dir = offset_dir("resume");
+        let committer = Committer::new(&dir, "/acmeco/stocks", "billing");
+
+        assert_eq!(
+            committer.resume_from().await.unwrap(),
+            ReplayStart::Earliest
+        );
+
+        committer.commit(5).await.unwrap();
+        assert_eq!(committer.committed().await.unwrap(), Some(5));
+        assert_eq!(
+            committer.resume_from().await.unwrap(),
+            ReplayStart::Offset(6)
+        );
+
+        // The offset is read back from its file once the server restarts
+        let committer = Committer::new(&dir, "/acmeco/stocks", "billing");
+        assert_eq!(committer.committed().await.unwrap(), Some(5));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ignores_commits_behind_committed_offset() {
+        let dir = offset_dir("monotonic");
+        let first = Committer::new(&dir, "/acmeco/stocks", "billing");
+        let second = first.clone();
+
+        first.commit(5).await.unwrap();
+        second.commit(2).await.unwrap();
+
+        assert_eq!(first.committed().await.unwrap(), Some(5));
+        assert_eq!(
+            Committer::new(&dir, "/acmeco/stocks", "billing")
+                .committed()
+                .await
+                .unwrap(),
+            Some(5)
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stores_offsets_per_group() {
+        let dir = offset_dir("groups");
+        let billing = Committer::new(&dir, "/acmeco/stocks", "billing");
+        let audit = Committer::new(&dir, "/acmeco/stocks", "audit");
+
+        billing.commit(5).await.unwrap();
+
+        assert_eq!(billing.committed().await.unwrap(), Some(5));
+        assert_eq!(audit.committed().await.unwrap(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn commits_offsets_and_echoes_heartbeats() {
+        let dir = offset_dir("read");
+        let committer = Committer::new(&dir, "/acmeco/stocks", "billing");
+        let (echoes, echoed) = mpsc::channel(2);
+
+        let heartbeat = Frame::Heartbeat(HeartbeatPayload { id: 0 });
+        let received = stream::iter([
+            Ok(Frame::Commit(CommitPayload { offset: 3 })),
+            Ok(heartbeat.clone()),
+            Ok(Frame::Commit(CommitPayload { offset: 4 })),
+        ]);
+
+        committer.clone().read(received, Some(echoes)).await;
+
+        assert_eq!(committer.committed().await.unwrap(), Some(4));
+        assert_eq!(echoed.collect::<Vec<_>>().await, vec![heartbeat]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,18 +4,28 @@
 use std::{fs, path::PathBuf, sync::Arc};
 
 use anyhow::{bail, Context, Result};
-use quinn::{IdleTimeout, ServerConfig};
+use quinn::{IdleTimeout, ServerConfig, VarInt};
 use rcgen::generate_simple_self_signed;
-use rustls::{Certificate, PrivateKey};
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{Certificate, PrivateKey, RootCertStore};
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
 
-const ALPN_QUIC_HTTP: &[&[u8]] = &[b"hq-29"];
-
 #[derive(Default)]
 pub struct ConfigOptions {
     pub keylog: bool,
     pub stateless_retry: bool,
     pub max_idle_timeout: IdleTimeout,
+    /// When present, clients must present a certificate signed by one of these roots
+    pub client_ca: Option<RootCertStore>,
+    /// Accept 0-RTT early data from clients resuming a previous session
+    pub enable_0rtt: bool,
+    /// The maximum number of concurrent bidirectional streams that each client may open
+    pub max_concurrent_streams: VarInt,
+    /// The maximum number of concurrent unidirectional streams that each client may open, which
+    /// only WebTransport clients use
+    pub max_concurrent_uni_streams: VarInt,
+    /// The ALPN protocols accepted from clients, in order of preference
+    pub alpn_protocols: Vec<Vec<u8>>,
 }
 
 pub fn server_config(
@@ -23,18 +33,28 @@ pub fn server_config(
     key: PrivateKey,
     options: ConfigOptions,
 ) -> Result<ServerConfig> {
-    let mut server_crypto = rustls::ServerConfig::builder()
-        .with_safe_defaults()
-        .with_no_client_auth()
-        .with_single_cert(certs, key)?;
-    server_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
+    let builder = rustls::ServerConfig::builder().with_safe_defaults();
+    let builder = match options.client_ca {
+        Some(roots) => {
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_crypto = builder.with_single_cert(certs, key)?;
+    server_crypto.alpn_protocols = options.alpn_protocols;
     if options.keylog {
         server_crypto.key_log = Arc::new(rustls::KeyLogFile::new());
     }
+    if options.enable_0rtt {
+        // QUIC requires the limit to be either 0 or u32::MAX
+        server_crypto.max_early_data_size = u32::MAX;
+    }
 
     let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
     let transport_config = Arc::get_mut(&mut server_config.transport).unwrap();
-    transport_config.max_concurrent_uni_streams(0_u8.into());
+    transport_config.max_concurrent_uni_streams(options.max_concurrent_uni_streams);
+    transport_config.max_concurrent_bidi_streams(options.max_concurrent_streams);
     transport_config.max_idle_timeout(Some(options.max_idle_timeout));
     if options.stateless_retry {
         server_config.use_retry(true);
@@ -45,7 +65,7 @@ pub fn server_config(
 
 pub fn read_certs(cert_path: PathBuf, key_path: PathBuf) -> Result<(Vec<Certificate>, PrivateKey)> {
     let key = fs::read(key_path.clone()).context("failed to read private key")?;
-    let key = if key_path.extension().map_or(false, |x| x == "der") {
+    let key = if key_path.extension().is_some_and(|x| x == "der") {
         PrivateKey(key)
     } else {
         let pkcs8 = pkcs8_private_keys(&mut &*key).context("malformed PKCS #8 private key")?;
@@ -63,7 +83,7 @@ pub fn read_certs(cert_path: PathBuf, key_path: PathBuf) -> Result<(Vec<Certific
         }
     };
     let cert_chain = fs::read(cert_path.clone()).context("failed to read certificate chain")?;
-    let cert_chain = if cert_path.extension().map_or(false, |x| x == "der") {
+    let cert_chain = if cert_path.extension().is_some_and(|x| x == "der") {
         vec![Certificate(cert_chain)]
     } else {
         certs(&mut &*cert_chain)
@@ -76,6 +96,20 @@ pub fn read_certs(cert_path: PathBuf, key_path: PathBuf) -> Result<(Vec<Certific
     Ok((cert_chain, key))
 }
 
+pub fn read_client_ca(ca_path: PathBuf) -> Result<RootCertStore> {
+    let ca = fs::read(ca_path).context("failed to read client CA certificate")?;
+    let ca = certs(&mut &*ca).context("invalid PEM-encoded client CA certificate")?;
+
+    let mut store = RootCertStore::empty();
+    store.add_parsable_certificates(&ca);
+
+    if store.is_empty() {
+        bail!("no valid client CA certificates found");
+    }
+
+    Ok(store)
+}
+
 pub fn generate_self_signed_cert() -> Result<(Vec<Certificate>, PrivateKey)> {
     let cert = generate_simple_self_signed(vec!["localhost".to_string()])?;
     let key = PrivateKey(cert.serialize_private_key_der());
@@ -0,0 +1,120 @@
+//! Echoing of the receipts sent by publishers after the messages that they want confirmed, so
+//! that they can tell once the server has received them
+
+use futures::{
+    channel::mpsc::{self, Sender},
+    ready, Stream, StreamExt,
+};
+use log::info;
+use pin_project_lite::pin_project;
+use selium_common::{protocol::Frame, types::BiStream};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pin_project! {
+    /// Passes on the messages read from a publisher's stream, echoing each receipt read in
+    /// between them back to the publisher.
+    ///
+    /// As the stream is ordered, and each message is passed on as soon as it's read, every
+    /// message sent before a receipt has been handed to the topic by the time it's echoed.
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Receipts<S> {
+        #[pin]
+        stream: S,
+        echoes: Sender<Frame>,
+        // A receipt waiting for the echoes to have room for it
+        pending: Option<Frame>,
+    }
+}
+
+/// Echoes the receipts read from `stream` to the `echoes` sender.
+pub fn echo<S>(stream: S, echoes: Sender<Frame>) -> Receipts<S>
+where
+    S: Stream<Item = anyhow::Result<Frame>>,
+{
+    Receipts {
+        stream,
+        echoes,
+        pending: None,
+    }
+}
+
+/// Splits a publisher's stream into a stream of the messages it sends, while echoing the
+/// receipts received on it.
+pub fn track(stream: BiStream) -> impl Stream<Item = anyhow::Result<Frame>> {
+    let (write, read) = stream.split();
+    let (echoes, echo_rx) = mpsc::channel(0);
+
+    tokio::spawn(async move {
+        if let Err(e) = echo_rx.map(Ok).forward(write).await {
+            info!("Receipt publisher closed: {:?}", e);
+        }
+    });
+
+    echo(read, echoes)
+}
+
+impl<S> Stream for Receipts<S>
+where
+    S: Stream<Item = anyhow::Result<Frame>>,
+{
+    type Item = anyhow::Result<Frame>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            if let Some(receipt) = this.pending.take() {
+                match this.echoes.poll_ready(cx) {
+                    // The send can only fail if the publisher has disconnected, in which case
+                    // there's no one left to echo the receipt to
+                    Poll::Ready(Ok(())) => {
+                        let _ = this.echoes.start_send(receipt);
+                    }
+                    Poll::Ready(Err(_)) => (),
+                    // Stops reading from the publisher until it reads its echoes
+                    Poll::Pending => {
+                        *this.pending = Some(receipt);
+                        return Poll::Pending;
+                    }
+                }
+            }
+
+            match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(receipt @ Frame::Receipt(_))) => *this.pending = Some(receipt),
+                polled => return Poll::Ready(polled),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::stream;
+    use selium_common::protocol::{MessagePayload, ReceiptPayload};
+
+    #[tokio::test]
+    async fn echoes_receipts_after_preceding_messages() {
+        let (echoes, echo_rx) = mpsc::channel(8);
+
+        let first = Frame::Message(MessagePayload::new(Bytes::from("first")));
+        let second = Frame::Message(MessagePayload::new(Bytes::from("second")));
+        let receipt = Frame::Receipt(ReceiptPayload { id: 1 });
+        let received = stream::iter([Ok(first.clone()), Ok(receipt.clone()), Ok(second.clone())]);
+
+        let mut messages = echo(received, echoes);
+
+        assert_eq!(messages.next().await.unwrap().unwrap(), first);
+        assert_eq!(messages.next().await.unwrap().unwrap(), second);
+        assert!(messages.next().await.is_none());
+        drop(messages);
+
+        let echoed: Vec<_> = echo_rx.collect().await;
+
+        assert_eq!(echoed, vec![receipt]);
+    }
+}
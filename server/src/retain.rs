@@ -0,0 +1,122 @@
+//! Retention of recently published messages, for delivery to subscribers that join a topic after
+//! the messages were published
+
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+use selium_common::protocol::Frame;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The maximum number of messages retained on each topic. Once reached, the oldest message is
+/// dropped to make room for each new one.
+const MAX_RETAINED_MESSAGES: usize = 1024;
+
+/// The messages retained on a topic, along with when each of them expires. Shared between the
+/// publishers that retain messages and the subscribers that they're delivered to.
+#[derive(Clone, Default)]
+pub struct Retained {
+    messages: Arc<Mutex<VecDeque<(Instant, Frame)>>>,
+}
+
+impl Retained {
+    /// Retains a message until its retention period has elapsed.
+    pub fn push(&self, frame: Frame, retention: Duration) {
+        let now = Instant::now();
+        let mut messages = self.messages.lock().unwrap();
+
+        while messages.front().is_some_and(|(expires, _)| *expires <= now) {
+            messages.pop_front();
+        }
+
+        if messages.len() == MAX_RETAINED_MESSAGES {
+            messages.pop_front();
+        }
+
+        messages.push_back((now + retention, frame));
+    }
+
+    /// Returns the messages that haven't yet expired, in the order they were published.
+    pub fn messages(&self) -> Vec<Frame> {
+        let now = Instant::now();
+
+        self.messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(expires, _)| *expires > now)
+            .map(|(_, frame)| frame.clone())
+            .collect()
+    }
+
+    /// Retains each message received from a publisher's stream for the `retention` period,
+    /// before passing it on to the topic.
+    pub fn retain_stream<S>(
+        &self,
+        stream: S,
+        retention: Duration,
+    ) -> impl Stream<Item = Result<Frame>>
+    where
+        S: Stream<Item = Result<Frame>>,
+    {
+        let retained = self.clone();
+
+        stream.inspect(move |frame| {
+            if let Ok(frame @ Frame::Message(_)) = frame {
+                retained.push(frame.clone(), retention);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use selium_common::protocol::MessagePayload;
+
+    fn message(message: &'static str) -> Frame {
+        Frame::Message(MessagePayload::new(Bytes::from(message)))
+    }
+
+    #[test]
+    fn returns_messages_in_published_order() {
+        let retained = Retained::default();
+        retained.push(message("first"), Duration::from_secs(60));
+        retained.push(message("second"), Duration::from_secs(60));
+
+        assert_eq!(
+            retained.messages(),
+            vec![message("first"), message("second")]
+        );
+    }
+
+    #[test]
+    fn drops_expired_messages() {
+        let retained = Retained::default();
+        retained.push(message("expired"), Duration::ZERO);
+        retained.push(message("retained"), Duration::from_secs(60));
+
+        assert_eq!(retained.messages(), vec![message("retained")]);
+    }
+
+    #[test]
+    fn drops_oldest_message_at_capacity() {
+        let retained = Retained::default();
+
+        retained.push(message("oldest"), Duration::from_secs(60));
+
+        for _ in 1..MAX_RETAINED_MESSAGES {
+            retained.push(message("older"), Duration::from_secs(60));
+        }
+
+        retained.push(message("newest"), Duration::from_secs(60));
+        let messages = retained.messages();
+
+        assert_eq!(messages.len(), MAX_RETAINED_MESSAGES);
+        assert_eq!(messages.first(), Some(&message("older")));
+        assert_eq!(messages.last(), Some(&message("newest")));
+    }
+}
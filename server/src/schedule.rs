@@ -0,0 +1,129 @@
+//! Scheduled delivery of messages published with a delay, which the server holds in a timer wheel
+//! until their delay has elapsed
+//!
+//! Delayed messages are only held in memory, so any that are still pending when the server stops
+//! are lost.
+
+use anyhow::{anyhow, Result};
+use futures::{stream::Fuse, Stream, StreamExt};
+use pin_project_lite::pin_project;
+use selium_common::protocol::Frame;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio_util::time::DelayQueue;
+
+/// The longest delay that a message may be published with.
+pub const MAX_DELAY: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+pin_project! {
+    /// Holds each delayed message received from a publisher's stream until its delay has elapsed,
+    /// passing every other message straight on to the topic.
+    ///
+    /// The stream ends once the publisher's stream has ended and every delayed message has been
+    /// passed on, so that the messages outlive the publisher that sent them.
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Scheduled<S> {
+        #[pin]
+        stream: Fuse<S>,
+        queue: DelayQueue<Frame>,
+    }
+}
+
+/// Schedules the delayed messages received from a publisher's stream.
+pub fn schedule_stream<S>(stream: S) -> Scheduled<S>
+where
+    S: Stream<Item = Result<Frame>>,
+{
+    Scheduled {
+        stream: stream.fuse(),
+        queue: DelayQueue::new(),
+    }
+}
+
+impl<S> Stream for Scheduled<S>
+where
+    S: Stream<Item = Result<Frame>>,
+{
+    type Item = Result<Frame>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            // Polled before the publisher's stream, so that the queue wakes the topic once the
+            // next message is due
+            if let Poll::Ready(Some(expired)) = this.queue.poll_expired(cx) {
+                return Poll::Ready(Some(Ok(expired.into_inner())));
+            }
+
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(Frame::Message(mut payload)))) if payload.delay.is_some() => {
+                    let delay = payload.delay.take().unwrap();
+
+                    if delay > MAX_DELAY {
+                        return Poll::Ready(Some(Err(anyhow!(
+                            "Message delay of {delay:?} exceeds maximum of {MAX_DELAY:?}"
+                        ))));
+                    }
+
+                    this.queue.insert(Frame::Message(payload), delay);
+                }
+                Poll::Ready(None) if !this.queue.is_empty() => return Poll::Pending,
+                poll => return poll,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::stream;
+    use selium_common::protocol::MessagePayload;
+    use std::time::Instant;
+
+    fn message(message: &'static str, delay: Option<Duration>) -> Result<Frame> {
+        Ok(Frame::Message(MessagePayload {
+            delay,
+            ..MessagePayload::new(Bytes::from(message))
+        }))
+    }
+
+    #[tokio::test]
+    async fn delivers_delayed_messages_once_due() {
+        let started = Instant::now();
+        let messages = stream::iter([
+            message("later", Some(Duration::from_millis(100))),
+            message("soon", Some(Duration::from_millis(50))),
+            message("now", None),
+        ]);
+
+        let frames: Vec<Frame> = schedule_stream(messages)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(
+            frames,
+            vec![
+                message("now", None).unwrap(),
+                message("soon", None).unwrap(),
+                message("later", None).unwrap(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_delays_beyond_maximum() {
+        let messages = stream::iter([message("never", Some(MAX_DELAY * 2))]);
+        let results: Vec<Result<Frame>> = schedule_stream(messages).collect().await;
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+}
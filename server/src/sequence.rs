@@ -0,0 +1,157 @@
+//! Sequencing of the messages published to a topic, for subscribers that require them to be
+//! delivered strictly in order
+
+use anyhow::Result;
+use futures::{Sink, Stream, StreamExt};
+use log::debug;
+use selium_common::protocol::{Frame, MessagePayload};
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+};
+
+/// Assigns each message published to a topic the next position within it. Shared between all
+/// of the topic's publishers.
+#[derive(Clone, Default)]
+pub struct Sequencer {
+    next: Arc<AtomicU64>,
+}
+
+impl Sequencer {
+    /// Tags each message received from a publisher's stream with its position within the topic.
+    pub fn sequence_stream<S>(&self, stream: S) -> impl Stream<Item = Result<Frame>>
+    where
+        S: Stream<Item = Result<Frame>>,
+    {
+        let next = self.next.clone();
+
+        stream.map(move |frame| match frame {
+            Ok(Frame::Message(payload)) => Ok(Frame::Message(MessagePayload {
+                sequence: Some(next.fetch_add(1, Ordering::Relaxed)),
+                ..payload
+            })),
+            frame => frame,
+        })
+    }
+}
+
+/// Wraps an ordered subscriber's sink, so that messages are delivered one at a time in the order
+/// they were sequenced.
+///
+/// Each message is flushed before the next is accepted, rather than being batched with the
+/// messages that follow it. Any message sequenced before the last one delivered, such as a
+/// retained message that was also delivered live, is dropped.
+pub struct InSequence<Si> {
+    sink: Si,
+    last_delivered: Option<u64>,
+    unflushed: bool,
+}
+
+impl<Si> InSequence<Si> {
+    pub fn new(sink: Si) -> Self {
+        Self {
+            sink,
+            last_delivered: None,
+            unflushed: false,
+        }
+    }
+}
+
+impl<Si> Sink<Frame> for InSequence<Si>
+where
+    Si: Sink<Frame> + Unpin,
+{
+    type Error = Si::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        if self.unflushed {
+            ready!(Pin::new(&mut self.sink).poll_flush(cx))?;
+            self.unflushed = false;
+        }
+
+        Pin::new(&mut self.sink).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, frame: Frame) -> Result<(), Si::Error> {
+        if let Frame::Message(MessagePayload {
+            sequence: Some(sequence),
+            ..
+        }) = &frame
+        {
+            if self.last_delivered.is_some_and(|last| *sequence <= last) {
+                debug!("Dropping message {sequence} sequenced before the last delivered");
+                return Ok(());
+            }
+
+            self.last_delivered = Some(*sequence);
+        }
+
+        self.unflushed = true;
+        Pin::new(&mut self.sink).start_send(frame)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        ready!(Pin::new(&mut self.sink).poll_flush(cx))?;
+        self.unflushed = false;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        Pin::new(&mut self.sink).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::{channel::mpsc, stream, SinkExt};
+
+    fn message(sequence: u64) -> Frame {
+        Frame::Message(MessagePayload {
+            sequence: Some(sequence),
+            ..MessagePayload::new(Bytes::from(sequence.to_string()))
+        })
+    }
+
+    #[tokio::test]
+    async fn sequences_published_messages() {
+        let sequencer = Sequencer::default();
+        let first = stream::iter([Ok(message(0)), Ok(message(0))]);
+        let second = stream::iter([Ok(message(0))]);
+
+        let mut sequences = Vec::new();
+
+        for frame in sequencer
+            .sequence_stream(first)
+            .chain(sequencer.sequence_stream(second))
+            .collect::<Vec<_>>()
+            .await
+        {
+            if let Ok(Frame::Message(payload)) = frame {
+                sequences.extend(payload.sequence);
+            }
+        }
+
+        assert_eq!(sequences, vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn drops_messages_sequenced_before_last_delivered() {
+        let (tx, rx) = mpsc::unbounded();
+        let mut sink = InSequence::new(tx);
+
+        for sequence in [1, 2, 2, 1, 4] {
+            sink.send(message(sequence)).await.unwrap();
+        }
+
+        drop(sink);
+        let delivered: Vec<_> = rx.collect().await;
+
+        assert_eq!(delivered, vec![message(1), message(2), message(4)]);
+    }
+}
@@ -3,6 +3,9 @@ use futures::{sink::With, Future, Sink, SinkExt as _};
 mod fanout_many;
 pub use fanout_many::*;
 
+mod round_robin;
+pub use round_robin::*;
+
 // @TODO - awaiting selium#22
 // mod filter;
 // pub use filter::Filter;
@@ -13,6 +16,7 @@ pub use fanout_many::*;
 
 impl<T: ?Sized, Item> SinkExt<Item> for T where T: Sink<Item> {}
 
+#[allow(dead_code)]
 pub trait SinkExt<Item>: Sink<Item> {
     // This is a wrapper around `with` for conceptual symmetry with `StreamExt::map`
     fn map<U, Fut, F, E>(self, f: F) -> With<Self, Item, U, Fut, F>
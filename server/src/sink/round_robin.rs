@@ -0,0 +1,180 @@
+//! RoundRobin dispatches each item to a single sink, taking turns between its sinks
+
+use std::{
+    fmt::Debug,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use anyhow::Result;
+use futures::Sink;
+use log::error;
+use tokio::pin;
+
+#[must_use = "sinks do nothing unless you poll them"]
+pub struct RoundRobin<K, V> {
+    entries: Vec<(K, V)>,
+    next: usize,
+}
+
+impl<K, V> RoundRobin<K, V> {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            next: 0,
+        }
+    }
+
+    pub fn insert(&mut self, k: K, sink: V) {
+        self.entries.push((k, sink));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Evicts a broken sink, preserving the order of the remaining sinks so that the turn passes
+    /// to the sink that followed it.
+    fn evict(&mut self, idx: usize) {
+        self.entries.remove(idx);
+
+        if idx < self.next {
+            self.next -= 1;
+        } else if self.next >= self.entries.len() {
+            self.next = 0;
+        }
+    }
+}
+
+impl<K, V> Default for RoundRobin<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, Item> Sink<Item> for RoundRobin<K, V>
+where
+    K: Unpin,
+    V: Sink<Item> + Unpin,
+    V::Error: Debug,
+{
+    type Error = V::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        while !self.entries.is_empty() {
+            let idx = self.next;
+            let (_, sink) = &mut self.entries[idx];
+            pin!(sink);
+            match sink.poll_ready(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(_)) => self.evict(idx),
+                Poll::Ready(Ok(())) => break,
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+        // With no sinks to dispatch to, the item is dropped
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+
+        let idx = self.next;
+        let (_, sink) = &mut self.entries[idx];
+        pin!(sink);
+        if let Err(e) = sink.start_send(item) {
+            error!("Evicting broken sink from RoundRobin::start_send with err: {e:?}");
+            self.evict(idx);
+        } else {
+            self.next = (idx + 1) % self.entries.len();
+        }
+
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            let (_, sink) = &mut self.entries[idx];
+            pin!(sink);
+            match sink.poll_flush(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(_)) => self.evict(idx),
+                Poll::Ready(Ok(())) => idx += 1,
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            let (_, sink) = &mut self.entries[idx];
+            pin!(sink);
+            match sink.poll_close(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(_)) => self.evict(idx),
+                Poll::Ready(Ok(())) => idx += 1,
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{
+        channel::mpsc::{self, Receiver},
+        executor::block_on,
+        SinkExt,
+    };
+
+    fn drain(rx: &mut Receiver<usize>) -> Vec<usize> {
+        std::iter::from_fn(|| rx.try_next().ok().flatten()).collect()
+    }
+
+    #[test]
+    fn dispatches_items_in_turn() {
+        let (tx1, mut rx1) = mpsc::channel(10);
+        let (tx2, mut rx2) = mpsc::channel(10);
+        let mut sink = RoundRobin::new();
+        sink.insert(0, tx1);
+        sink.insert(1, tx2);
+
+        block_on(async {
+            for item in 0..4 {
+                sink.send(item).await.unwrap();
+            }
+        });
+
+        assert_eq!(drain(&mut rx1), vec![0, 2]);
+        assert_eq!(drain(&mut rx2), vec![1, 3]);
+    }
+
+    #[test]
+    fn rebalances_when_sink_is_dropped() {
+        let (tx1, rx1) = mpsc::channel(10);
+        let (tx2, mut rx2) = mpsc::channel(10);
+        let (tx3, mut rx3) = mpsc::channel(10);
+        let mut sink = RoundRobin::new();
+        sink.insert(0, tx1);
+        sink.insert(1, tx2);
+        sink.insert(2, tx3);
+
+        drop(rx1);
+
+        block_on(async {
+            for item in 0..4 {
+                sink.send(item).await.unwrap();
+            }
+        });
+
+        assert_eq!(drain(&mut rx2), vec![0, 2]);
+        assert_eq!(drain(&mut rx3), vec![1, 3]);
+    }
+}
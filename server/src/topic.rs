@@ -1,25 +1,143 @@
 use std::{
+    collections::VecDeque,
     fmt::Debug,
     pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use futures::{
     channel::mpsc::{self, Receiver, Sender},
-    ready, Future, Sink, Stream,
+    ready,
+    stream::BoxStream,
+    Future, Sink, Stream, StreamExt,
 };
 use log::error;
 use pin_project_lite::pin_project;
+use selium_common::protocol::ReplayStart;
 use tokio_stream::StreamMap;
 
-use crate::sink::FanoutMany;
+use crate::sink::{FanoutMany, RoundRobin};
 
 const SOCK_CHANNEL_SIZE: usize = 100;
 
 pub enum Socket<St, Si> {
     Stream(St),
     Sink(Si),
+    /// A subscriber [Sink](Socket::Sink) that belongs to the named consumer group
+    GroupSink(String, Si),
+    /// A subscriber [Sink](Socket::Sink) that replays the topic's [Log] before receiving live
+    /// messages, either alone or as a member of the named consumer group
+    ReplaySink(ReplayStart, Option<String>, Si),
+}
+
+/// A persistent record of the items dispatched by a [Topic], which subscribers can replay.
+///
+/// Items are appended as they're dispatched to the topic's sinks, so the log always holds
+/// exactly the items that a newly attached sink has missed. Appending an item tags it with its
+/// offset, the position in the log, before it's dispatched, while the log may persist it later.
+pub trait Log<Item> {
+    /// Polls whether the log is ready to accept another item, which it may not be until it's
+    /// been opened, or while earlier items are waiting to be persisted.
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>>;
+    fn append(&mut self, item: &mut Item) -> Result<()>;
+    /// The offset that the next item appended is tagged with.
+    fn next_offset(&self) -> u64;
+    /// Reads the log from `start` in [Chunk]s, waiting for items that haven't been persisted yet
+    /// once it catches up, until it's dropped.
+    fn replay(&self, start: ReplayStart) -> BoxStream<'static, Result<Chunk<Item>>>;
+}
+
+/// A run of consecutive items read from a [Log], followed by the offset of the item after them.
+///
+/// The first chunk of a replay may be empty, marking where the replay starts from.
+#[derive(Debug)]
+pub struct Chunk<Item> {
+    pub items: Vec<Item>,
+    pub next_offset: u64,
+}
+
+/// The number of publishers and subscribers attached to a [Topic], which the topic updates as
+/// they come and go, so that they can be read without interrupting it.
+#[derive(Debug, Default)]
+pub struct TopicCounts {
+    publishers: AtomicU64,
+    subscribers: AtomicU64,
+}
+
+impl TopicCounts {
+    pub fn publishers(&self) -> u64 {
+        self.publishers.load(Ordering::Relaxed)
+    }
+
+    pub fn subscribers(&self) -> u64 {
+        self.subscribers.load(Ordering::Relaxed)
+    }
+
+    fn update(&self, publishers: usize, subscribers: usize) {
+        self.publishers.store(publishers as u64, Ordering::Relaxed);
+        self.subscribers
+            .store(subscribers as u64, Ordering::Relaxed);
+    }
+}
+
+/// A sink that is replaying the topic's log. Items dispatched while it's replaying are read back
+/// from the log rather than queued, so only the chunk being written to the sink is held in
+/// memory. Once the sink has been written every item appended to the log, it joins the topic's
+/// other sinks.
+struct Replay<Si, Item> {
+    id: usize,
+    group: Option<String>,
+    reader: BoxStream<'static, Result<Chunk<Item>>>,
+    chunk: VecDeque<Item>,
+    // The offset of the item after the last one read, once the reader has found its start
+    next_offset: Option<u64>,
+    sink: Si,
+}
+
+impl<Si, Item> Replay<Si, Item>
+where
+    Si: Sink<Item> + Unpin,
+    Si::Error: Debug,
+{
+    /// Writes the log to the sink until it has caught up with `log_end`, the offset of the next
+    /// item to be appended.
+    fn poll_drain(&mut self, cx: &mut Context<'_>, log_end: u64) -> Poll<Result<()>> {
+        loop {
+            while !self.chunk.is_empty() {
+                ready!(Pin::new(&mut self.sink).poll_ready(cx)).map_err(sink_error)?;
+                let item = self.chunk.pop_front().unwrap();
+                Pin::new(&mut self.sink)
+                    .start_send(item)
+                    .map_err(sink_error)?;
+            }
+
+            if self.next_offset == Some(log_end) {
+                return Pin::new(&mut self.sink).poll_flush(cx).map_err(sink_error);
+            }
+
+            match self.reader.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.chunk.extend(chunk.items);
+                    self.next_offset = Some(chunk.next_offset);
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+                Poll::Ready(None) => return Poll::Ready(Err(anyhow!("Log was closed"))),
+                Poll::Pending => {
+                    ready!(Pin::new(&mut self.sink).poll_flush(cx)).map_err(sink_error)?;
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+fn sink_error<E: Debug>(e: E) -> anyhow::Error {
+    anyhow!("Failed to write to replaying sink: {e:?}")
 }
 
 pin_project! {
@@ -31,15 +149,24 @@ pin_project! {
         next_stream_id: usize,
         #[pin]
         sink: FanoutMany<usize, Si>,
+        // Each message is dispatched to one member of each consumer group
+        #[pin]
+        groups: FanoutMany<String, RoundRobin<usize, Si>>,
         next_sink_id: usize,
         #[pin]
         handle: Receiver<Socket<St, Si>>,
         buffered_item: Option<Item>,
+        log: Option<Box<dyn Log<Item> + Send>>,
+        replays: Vec<Replay<Si, Item>>,
+        counts: Arc<TopicCounts>,
     }
 }
 
 impl<St, Si, Item> Topic<St, Si, Item> {
-    pub fn pair() -> (Self, Sender<Socket<St, Si>>) {
+    pub fn pair(
+        log: Option<Box<dyn Log<Item> + Send>>,
+        counts: Arc<TopicCounts>,
+    ) -> (Self, Sender<Socket<St, Si>>) {
         let (tx, rx) = mpsc::channel(SOCK_CHANNEL_SIZE);
 
         (
@@ -47,51 +174,120 @@ impl<St, Si, Item> Topic<St, Si, Item> {
                 stream: StreamMap::new(),
                 next_stream_id: 0,
                 sink: FanoutMany::new(),
+                groups: FanoutMany::new(),
                 next_sink_id: 0,
                 handle: rx,
                 buffered_item: None,
+                log,
+                replays: Vec::new(),
+                counts,
             },
             tx,
         )
     }
 }
 
-impl<St, Si, Item> Future for Topic<St, Si, Item>
+impl<St, Si, Item> Topic<St, Si, Item>
 where
     St: Stream<Item = Option<Result<Item, Si::Error>>> + Unpin,
     Si: Sink<Item> + Unpin,
     Si::Error: Debug,
     Item: Clone + Unpin,
 {
-    type Output = ();
-
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll_dispatch(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
         let TopicProj {
             mut stream,
             next_stream_id,
             mut sink,
+            mut groups,
             next_sink_id,
             mut handle,
             buffered_item,
+            log,
+            replays,
+            ..
         } = self.project();
 
         loop {
+            // Sinks that have caught up with the log join the topic's other sinks
+            let log_end = log.as_ref().map_or(0, |log| log.next_offset());
+            let mut i = 0;
+            while i < replays.len() {
+                match replays[i].poll_drain(cx, log_end) {
+                    Poll::Ready(Ok(())) => {
+                        let replay = replays.swap_remove(i);
+
+                        match replay.group {
+                            Some(group) => {
+                                join_group(groups.as_mut().get_mut(), group, replay.id, replay.sink)
+                            }
+                            None => {
+                                sink.as_mut().insert(replay.id, replay.sink);
+                            }
+                        }
+                    }
+                    Poll::Ready(Err(e)) => {
+                        error!("Failed to replay log: {e:?}");
+                        replays.swap_remove(i);
+                    }
+                    Poll::Pending => i += 1,
+                }
+            }
+
             match handle.as_mut().poll_next(cx) {
+                // Each arm polls the handle again until it's pending, so that it wakes the topic
+                // when the next socket arrives
                 Poll::Ready(Some(sock)) => match sock {
                     Socket::Stream(st) => {
                         stream.as_mut().insert(*next_stream_id, st);
                         *next_stream_id += 1;
+                        continue;
                     }
                     Socket::Sink(si) => {
                         sink.as_mut().insert(*next_sink_id, si);
                         *next_sink_id += 1;
+                        continue;
+                    }
+                    Socket::GroupSink(group, si) => {
+                        join_group(groups.as_mut().get_mut(), group, *next_sink_id, si);
+                        *next_sink_id += 1;
+                        continue;
+                    }
+                    Socket::ReplaySink(start, group, si) => {
+                        match log.as_ref() {
+                            Some(log) => replays.push(Replay {
+                                id: *next_sink_id,
+                                group,
+                                reader: log.replay(start),
+                                chunk: VecDeque::new(),
+                                next_offset: None,
+                                sink: si,
+                            }),
+                            // Without a log, there's nothing to replay
+                            None => match group {
+                                Some(group) => {
+                                    join_group(groups.as_mut().get_mut(), group, *next_sink_id, si)
+                                }
+                                None => {
+                                    sink.as_mut().insert(*next_sink_id, si);
+                                }
+                            },
+                        }
+                        *next_sink_id += 1;
+
+                        // Start replaying straight away
+                        continue;
                     }
                 },
                 // If handle is terminated, the stream is dead
                 Poll::Ready(None) => return Poll::Ready(()),
-                // If no messages are available and there's no work to do, block this future
+                // If no messages are available and there's no work to do, block this future once
+                // the last message written is flushed, as its stream may have just finished
                 Poll::Pending if stream.is_empty() && buffered_item.is_none() => {
-                    return Poll::Pending
+                    // Unwrapping is safe as the underlying sinks are guaranteed not to error
+                    ready!(sink.as_mut().poll_flush(cx)).unwrap();
+                    ready!(groups.as_mut().poll_flush(cx)).unwrap();
+                    return Poll::Pending;
                 }
                 // Otherwise, move on with running the stream
                 Poll::Pending => (),
@@ -100,11 +296,28 @@ where
             // If we've got an item buffered already, we need to write it to the sink
             // before we can do anything else.
             if buffered_item.is_some() {
-                // Unwrapping is safe as the underlying sink is guaranteed not to error
+                // Unwrapping is safe as the underlying sinks are guaranteed not to error
                 ready!(sink.as_mut().poll_ready(cx)).unwrap();
-                sink.as_mut()
-                    .start_send(buffered_item.take().unwrap())
-                    .unwrap();
+                ready!(groups.as_mut().poll_ready(cx)).unwrap();
+
+                // A log that fails is abandoned, so that the topic's live messages keep flowing
+                if let Some(poll) = log.as_mut().map(|log| log.poll_ready(cx)) {
+                    if let Err(e) = ready!(poll) {
+                        error!("Abandoning log: {e:?}");
+                        *log = None;
+                    }
+                }
+
+                let mut item = buffered_item.take().unwrap();
+
+                if let Some(log) = log.as_mut() {
+                    if let Err(e) = log.append(&mut item) {
+                        error!("Failed to append to log: {e:?}");
+                    }
+                }
+
+                groups.as_mut().start_send(item.clone()).unwrap();
+                sink.as_mut().start_send(item).unwrap();
             }
 
             match stream.as_mut().poll_next(cx) {
@@ -117,15 +330,59 @@ where
                 // An inner stream has finished
                 Poll::Ready(Some((_, None))) => (),
                 // All streams have finished
-                // Unwrapping is safe as the underlying sink is guaranteed not to error
-                Poll::Ready(None) => ready!(sink.as_mut().poll_flush(cx)).unwrap(),
+                // Unwrapping is safe as the underlying sinks are guaranteed not to error
+                Poll::Ready(None) => {
+                    ready!(sink.as_mut().poll_flush(cx)).unwrap();
+                    ready!(groups.as_mut().poll_flush(cx)).unwrap();
+                }
                 // No messages are available at this time
                 Poll::Pending => {
-                    // Unwrapping is safe as the underlying sink is guaranteed not to error
+                    // Unwrapping is safe as the underlying sinks are guaranteed not to error
                     ready!(sink.poll_flush(cx)).unwrap();
+                    ready!(groups.poll_flush(cx)).unwrap();
                     return Poll::Pending;
                 }
             }
         }
     }
 }
+
+impl<St, Si, Item> Future for Topic<St, Si, Item>
+where
+    St: Stream<Item = Option<Result<Item, Si::Error>>> + Unpin,
+    Si: Sink<Item> + Unpin,
+    Si::Error: Debug,
+    Item: Clone + Unpin,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let poll = self.as_mut().poll_dispatch(cx);
+
+        // Streams and sinks are removed as they finish or break, so the counts are refreshed
+        // whenever the topic yields
+        let this = self.project();
+        let members: usize = this.groups.values().map(RoundRobin::len).sum();
+        let subscribers = this.sink.len() + members + this.replays.len();
+        this.counts.update(this.stream.len(), subscribers);
+
+        poll
+    }
+}
+
+/// Adds a sink to the named consumer group, creating the group if it has no other members.
+fn join_group<Si>(
+    groups: &mut FanoutMany<String, RoundRobin<usize, Si>>,
+    group: String,
+    id: usize,
+    si: Si,
+) {
+    match groups.get_mut(&group) {
+        Some(members) => members.insert(id, si),
+        None => {
+            let mut members = RoundRobin::new();
+            members.insert(id, si);
+            groups.insert(group, members);
+        }
+    }
+}
@@ -0,0 +1,642 @@
+//! Persistent per-topic logs of published messages, which subscribers can replay from an offset
+//! or timestamp before receiving live messages
+
+use crate::topic::{Chunk, Log};
+use anyhow::{anyhow, bail, Context as _, Result};
+use bytes::{Buf, BufMut, BytesMut};
+use futures::{
+    channel::{mpsc, oneshot},
+    ready,
+    stream::{self, BoxStream},
+    FutureExt, StreamExt,
+};
+use log::{error, warn};
+use selium_common::protocol::{Frame, MessageCodec, MessagePayload, ReplayStart, HEADER_SIZE};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+    mem::size_of,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::{sync::watch, task};
+use tokio_util::codec::{Decoder, Encoder};
+
+const TIMESTAMP_SIZE: usize = size_of::<u64>();
+// The timestamp, followed by the frame's header
+const RECORD_HEADER_SIZE: usize = TIMESTAMP_SIZE + HEADER_SIZE;
+// The number of records that can be queued for writing before the topic waits for the writer
+const WRITE_QUEUE_SIZE: usize = 1024;
+// Replays read at most this many records at a time, and stop adding records to a chunk once it
+// exceeds this many bytes
+const MAX_CHUNK_RECORDS: usize = 256;
+const MAX_CHUNK_BYTES: u64 = 1024 * 1024;
+
+/// An append-only file containing every message published to a topic.
+///
+/// Each record is the time the message was logged, in milliseconds since the Unix epoch,
+/// followed by the message frame as it's written to the wire. A message's offset is its position
+/// in the log, starting from 0, which is tagged on the message as it's appended or replayed,
+/// rather than being stored in the record. Logs outlive server restarts, and are only truncated
+/// to discard a corrupt tail.
+///
+/// The file is only read and written off the topic's task. Appended records are queued for a
+/// background writer, which indexes where each record starts as it's written, so that replays
+/// can seek straight to their start and read the log a chunk at a time. When the log is opened,
+/// a partially written or corrupt record at the end of the file, such as one left behind by a
+/// crash, is truncated along with anything after it.
+pub struct TopicLog {
+    path: PathBuf,
+    index: Arc<RwLock<Index>>,
+    records: mpsc::Sender<Record>,
+    // Resolves to the number of records in the log once it has been opened
+    opened: Option<oneshot::Receiver<Result<u64>>>,
+    // The number of records written to the file, once it has been opened
+    written: watch::Receiver<Option<u64>>,
+    // The offset of the next message to be appended
+    next_offset: u64,
+}
+
+/// The position of each record within a log's file, and the time it was logged.
+#[derive(Debug, Default)]
+struct Index {
+    records: Vec<(u64, u64)>,
+    // The length of the file, up to the end of the last record written
+    len: u64,
+}
+
+impl Index {
+    fn position(&self, offset: u64) -> u64 {
+        self.records
+            .get(offset as usize)
+            .map_or(self.len, |(position, _)| *position)
+    }
+}
+
+struct Record {
+    timestamp: u64,
+    bytes: BytesMut,
+}
+
+impl TopicLog {
+    /// Opens the log for `topic` within `dir`, creating it if it doesn't exist yet. As topic
+    /// names contain slashes, the log's file name is the hex-encoded topic name.
+    ///
+    /// The file is opened in the background, so the log isn't ready to be appended to until it
+    /// has been, and fails to become ready if it can't be opened.
+    pub fn open(dir: &Path, topic: &str) -> Self {
+        let path = dir.join(format!("{}.log", hex::encode(topic)));
+        let index = Arc::new(RwLock::new(Index::default()));
+        let (records, queued) = mpsc::channel(WRITE_QUEUE_SIZE);
+        let (opened_tx, opened) = oneshot::channel();
+        let (written_tx, written) = watch::channel(None);
+
+        tokio::spawn(write(
+            dir.to_owned(),
+            path.clone(),
+            index.clone(),
+            queued,
+            opened_tx,
+            written_tx,
+        ));
+
+        Self {
+            path,
+            index,
+            records,
+            opened: Some(opened),
+            written,
+            next_offset: 0,
+        }
+    }
+}
+
+impl Log<Frame> for TopicLog {
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if let Some(opened) = self.opened.as_mut() {
+            let opened = ready!(opened.poll_unpin(cx));
+            self.opened = None;
+            self.next_offset =
+                opened.map_err(|_| anyhow!("Log {} was closed", self.path.display()))??;
+        }
+
+        self.records
+            .poll_ready(cx)
+            .map_err(|_| anyhow!("Log {} is no longer being written", self.path.display()))
+    }
+
+    fn append(&mut self, frame: &mut Frame) -> Result<()> {
+        if !matches!(frame, Frame::Message(_)) {
+            return Ok(());
+        }
+
+        let timestamp = millis_since_epoch(SystemTime::now());
+        let mut bytes = BytesMut::new();
+        bytes.put_u64(timestamp);
+        MessageCodec::new(u64::MAX).encode(frame.clone(), &mut bytes)?;
+
+        self.records
+            .start_send(Record { timestamp, bytes })
+            .with_context(|| format!("Failed to append to log {}", self.path.display()))?;
+
+        *frame = with_offset(frame.clone(), self.next_offset);
+        self.next_offset += 1;
+
+        Ok(())
+    }
+
+    fn next_offset(&self) -> u64 {
+        self.next_offset
+    }
+
+    fn replay(&self, start: ReplayStart) -> BoxStream<'static, Result<Chunk<Frame>>> {
+        let reader = Reader {
+            path: self.path.clone(),
+            index: self.index.clone(),
+            written: self.written.clone(),
+            start: Some(start),
+            // Nothing is appended until the log has been opened
+            appended: self.next_offset,
+            position: 0,
+            file: None,
+        };
+
+        // The replay ends after its first error
+        stream::unfold(Some(reader), |reader| async move {
+            let mut reader = reader?;
+
+            match reader.next_chunk().await {
+                Ok(chunk) => Some((Ok(chunk), Some(reader))),
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+        .boxed()
+    }
+}
+
+/// Opens the log's file and indexes it, then writes each record queued for the log to it, until
+/// the log is dropped or a write fails.
+async fn write(
+    dir: PathBuf,
+    path: PathBuf,
+    index: Arc<RwLock<Index>>,
+    mut queued: mpsc::Receiver<Record>,
+    opened: oneshot::Sender<Result<u64>>,
+    written: watch::Sender<Option<u64>>,
+) {
+    let loading = path.clone();
+    let (mut file, loaded) = match task::spawn_blocking(move || load(&dir, &loading)).await {
+        Ok(Ok(loaded)) => loaded,
+        Ok(Err(e)) => {
+            let _ = opened.send(Err(e));
+            return;
+        }
+        Err(e) => {
+            let _ = opened.send(Err(e.into()));
+            return;
+        }
+    };
+
+    let count = loaded.records.len() as u64;
+    *index.write().unwrap() = loaded;
+    let _ = opened.send(Ok(count));
+    written.send_replace(Some(count));
+
+    while let Some(record) = queued.next().await {
+        // Records that are already queued are written along with the first
+        let mut batch = vec![record];
+
+        while batch.len() < MAX_CHUNK_RECORDS {
+            match queued.try_next() {
+                Ok(Some(record)) => batch.push(record),
+                _ => break,
+            }
+        }
+
+        let mut bytes = BytesMut::new();
+        let mut records = Vec::with_capacity(batch.len());
+
+        for record in batch {
+            records.push((record.timestamp, record.bytes.len() as u64));
+            bytes.extend_from_slice(&record.bytes);
+        }
+
+        file = match task::spawn_blocking(move || file.write_all(&bytes).map(|_| file)).await {
+            Ok(Ok(file)) => file,
+            Ok(Err(e)) => {
+                error!("Failed to append to log {}: {e:?}", path.display());
+                return;
+            }
+            Err(e) => {
+                error!("Failed to append to log {}: {e:?}", path.display());
+                return;
+            }
+        };
+
+        let mut index = index.write().unwrap();
+
+        for (timestamp, len) in records {
+            let position = index.len;
+            index.records.push((position, timestamp));
+            index.len += len;
+        }
+
+        written.send_replace(Some(index.records.len() as u64));
+    }
+}
+
+/// Opens the log's file, creating it if it doesn't exist yet, and indexes the records within it
+/// by reading their headers. Anything after the last complete record is truncated.
+fn load(dir: &Path, path: &Path) -> Result<(File, Index)> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+
+    let file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log {}", path.display()))?;
+
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::new(&file);
+    let mut index = Index::default();
+    let mut header = [0; RECORD_HEADER_SIZE];
+
+    while len - index.len >= RECORD_HEADER_SIZE as u64 {
+        reader.read_exact(&mut header)?;
+
+        let mut fields = &header[..];
+        let timestamp = fields.get_u64();
+        let body = fields.get_u64();
+
+        // A record whose body runs past the end of the file was only partially written, or its
+        // length marker is corrupt
+        if body > len - index.len - RECORD_HEADER_SIZE as u64 {
+            break;
+        }
+
+        reader.seek_relative(body as i64)?;
+        index.records.push((index.len, timestamp));
+        index.len += RECORD_HEADER_SIZE as u64 + body;
+    }
+
+    if index.len < len {
+        warn!(
+            "Truncating {} bytes of corrupt or partially written records from log {}",
+            len - index.len,
+            path.display()
+        );
+        file.set_len(index.len)
+            .with_context(|| format!("Failed to truncate log {}", path.display()))?;
+    }
+
+    Ok((file, index))
+}
+
+/// Reads a log's records in chunks, from the offset that a replay starts from.
+struct Reader {
+    path: PathBuf,
+    index: Arc<RwLock<Index>>,
+    written: watch::Receiver<Option<u64>>,
+    // Where the replay starts from, until it's been resolved to an offset
+    start: Option<ReplayStart>,
+    // The number of records appended when the replay began, which are written before it starts
+    appended: u64,
+    // The offset of the next record to be read
+    position: u64,
+    file: Option<File>,
+}
+
+impl Reader {
+    /// Reads the next chunk of records, waiting for one to be written if the reader has caught
+    /// up with the writer. The first chunk is returned as soon as the records appended before the
+    /// replay began have been written, even if it's empty, so that the offset the replay starts
+    /// from is known.
+    async fn next_chunk(&mut self) -> Result<Chunk<Frame>> {
+        let next = match self.start {
+            Some(_) => self.appended,
+            None => self.position + 1,
+        };
+        let written = *self
+            .written
+            .wait_for(|written| matches!(written, Some(written) if *written >= next))
+            .await
+            .map_err(|_| anyhow!("Log {} was closed", self.path.display()))?;
+        let written = written.unwrap();
+
+        if let Some(start) = self.start.take() {
+            self.position = self.resolve(&start, written)?;
+        }
+
+        if self.position == written {
+            return Ok(Chunk {
+                items: Vec::new(),
+                next_offset: self.position,
+            });
+        }
+
+        let (from, to, count) = {
+            let index = self.index.read().unwrap();
+            let records = &index.records[self.position as usize..written as usize];
+            let from = records[0].0;
+            let mut count = 1;
+
+            while count < records.len().min(MAX_CHUNK_RECORDS)
+                && records[count].0 - from < MAX_CHUNK_BYTES
+            {
+                count += 1;
+            }
+
+            (from, index.position(self.position + count as u64), count)
+        };
+
+        let path = self.path.clone();
+        let file = self.file.take();
+        let (file, bytes) = task::spawn_blocking(move || read(&path, file, from, to)).await??;
+        self.file = Some(file);
+
+        let mut bytes = BytesMut::from(bytes.as_slice());
+        let mut codec = MessageCodec::new(u64::MAX);
+        let mut items = Vec::with_capacity(count);
+
+        for offset in self.position..self.position + count as u64 {
+            bytes.advance(TIMESTAMP_SIZE);
+
+            let frame = codec.decode(&mut bytes)?.with_context(|| {
+                format!("Record {offset} of log {} is corrupt", self.path.display())
+            })?;
+            items.push(with_offset(frame, offset));
+        }
+
+        self.position += count as u64;
+
+        Ok(Chunk {
+            items,
+            next_offset: self.position,
+        })
+    }
+
+    /// Returns the offset of the first record to replay from `start`, given the number of records
+    /// that have been written. Replays that start past the end of the log start from its end.
+    fn resolve(&self, start: &ReplayStart, written: u64) -> Result<u64> {
+        let offset = match *start {
+            ReplayStart::Earliest => 0,
+            ReplayStart::Offset(offset) => offset.min(written),
+            ReplayStart::Timestamp(time) => {
+                let since = millis_since_epoch(time);
+                let index = self.index.read().unwrap();
+
+                // Records are logged in the order of their timestamps
+                index.records[..written as usize].partition_point(|(_, logged)| *logged < since)
+                    as u64
+            }
+            ReplayStart::Committed => bail!("Committed offsets must be resolved before replaying"),
+        };
+
+        Ok(offset)
+    }
+}
+
+/// Reads the bytes between the positions `from` and `to` of the log's file, opening it unless
+/// it's already open.
+fn read(path: &Path, file: Option<File>, from: u64, to: u64) -> Result<(File, Vec<u8>)> {
+    let mut file = match file {
+        Some(file) => file,
+        None => {
+            File::open(path).with_context(|| format!("Failed to open log {}", path.display()))?
+        }
+    };
+    let mut bytes = vec![0; (to - from) as usize];
+
+    file.seek(SeekFrom::Start(from))
+        .and_then(|_| file.read_exact(&mut bytes))
+        .with_context(|| format!("Failed to read log {}", path.display()))?;
+
+    Ok((file, bytes))
+}
+
+fn with_offset(frame: Frame, offset: u64) -> Frame {
+    match frame {
+        Frame::Message(payload) => Frame::Message(MessagePayload {
+            offset: Some(offset),
+            ..payload
+        }),
+        frame => frame,
+    }
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::future;
+    use selium_common::protocol::SubscriberPayload;
+    use std::time::Duration;
+
+    fn message(message: &'static str) -> Frame {
+        Frame::Message(MessagePayload::new(Bytes::from(message)))
+    }
+
+    fn logged(message: &'static str, offset: u64) -> Frame {
+        with_offset(self::message(message), offset)
+    }
+
+    fn log_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("selium-log-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    async fn open(dir: &Path) -> TopicLog {
+        let mut log = TopicLog::open(dir, "/acmeco/stocks");
+        future::poll_fn(|cx| log.poll_ready(cx)).await.unwrap();
+        log
+    }
+
+    async fn append(log: &mut TopicLog, mut frame: Frame) -> Frame {
+        future::poll_fn(|cx| log.poll_ready(cx)).await.unwrap();
+        log.append(&mut frame).unwrap();
+        frame
+    }
+
+    // Waits for every message appended to be written, before dropping the log
+    async fn close(mut log: TopicLog) {
+        let end = log.next_offset();
+        log.written
+            .wait_for(|written| *written == Some(end))
+            .await
+            .unwrap();
+    }
+
+    // Replays the log up to the last message appended
+    async fn replay(log: &TopicLog, start: ReplayStart) -> Result<Vec<Frame>> {
+        let end = log.next_offset();
+        let mut reader = log.replay(start);
+        let mut frames = Vec::new();
+
+        loop {
+            let chunk = reader.next().await.unwrap()?;
+            frames.extend(chunk.items);
+
+            if chunk.next_offset == end {
+                return Ok(frames);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_messages_from_start() {
+        let dir = log_dir("start");
+        let mut log = open(&dir).await;
+
+        append(&mut log, message("first")).await;
+        append(&mut log, message("second")).await;
+        append(&mut log, message("third")).await;
+
+        assert_eq!(
+            replay(&log, ReplayStart::Earliest).await.unwrap(),
+            vec![logged("first", 0), logged("second", 1), logged("third", 2)]
+        );
+        assert_eq!(
+            replay(&log, ReplayStart::Offset(1)).await.unwrap(),
+            vec![logged("second", 1), logged("third", 2)]
+        );
+        assert_eq!(replay(&log, ReplayStart::Offset(3)).await.unwrap(), vec![]);
+
+        let future = SystemTime::now() + Duration::from_secs(60);
+        assert_eq!(
+            replay(&log, ReplayStart::Timestamp(future)).await.unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            replay(&log, ReplayStart::Timestamp(UNIX_EPOCH))
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn persists_messages_across_reopens() {
+        let dir = log_dir("reopen");
+
+        let mut log = open(&dir).await;
+        append(&mut log, message("first")).await;
+        close(log).await;
+
+        let mut log = open(&dir).await;
+        append(&mut log, message("second")).await;
+
+        assert_eq!(
+            replay(&log, ReplayStart::Earliest).await.unwrap(),
+            vec![logged("first", 0), logged("second", 1)]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_logs_messages() {
+        let dir = log_dir("messages");
+        let mut log = open(&dir).await;
+
+        append(
+            &mut log,
+            Frame::RegisterSubscriber(SubscriberPayload::default()),
+        )
+        .await;
+        append(&mut log, message("first")).await;
+
+        assert_eq!(
+            replay(&log, ReplayStart::Earliest).await.unwrap(),
+            vec![logged("first", 0)]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tags_messages_with_offsets() {
+        let dir = log_dir("offsets");
+
+        let mut log = open(&dir).await;
+        append(&mut log, message("first")).await;
+        close(log).await;
+
+        // Offsets continue from the end of the log once it's reopened
+        let mut log = open(&dir).await;
+        let frame = append(&mut log, message("second")).await;
+
+        assert_eq!(frame, logged("second", 1));
+        assert!(replay(&log, ReplayStart::Committed).await.is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_in_chunks() {
+        let dir = log_dir("chunks");
+        let mut log = open(&dir).await;
+
+        for _ in 0..=MAX_CHUNK_RECORDS {
+            append(&mut log, message("message")).await;
+        }
+
+        let mut reader = log.replay(ReplayStart::Offset(1));
+
+        let chunk = reader.next().await.unwrap().unwrap();
+        assert_eq!(chunk.items.len(), MAX_CHUNK_RECORDS);
+        assert_eq!(chunk.items[0], logged("message", 1));
+        assert_eq!(chunk.next_offset, MAX_CHUNK_RECORDS as u64 + 1);
+
+        // Once the reader catches up, it waits for the next message to be appended
+        let mut next = reader.next();
+        assert!((&mut next).now_or_never().is_none());
+        append(&mut log, message("last")).await;
+
+        let chunk = next.await.unwrap().unwrap();
+        assert_eq!(
+            chunk.items,
+            vec![logged("last", MAX_CHUNK_RECORDS as u64 + 1)]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn truncates_corrupt_tail() {
+        let dir = log_dir("corrupt");
+
+        let mut log = open(&dir).await;
+        append(&mut log, message("first")).await;
+        close(log).await;
+
+        // A record whose length marker runs past the end of the file
+        let path = dir.join(format!("{}.log", hex::encode("/acmeco/stocks")));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0; TIMESTAMP_SIZE]).unwrap();
+        file.write_all(&[0xff; HEADER_SIZE + 3]).unwrap();
+        drop(file);
+
+        let mut log = open(&dir).await;
+        append(&mut log, message("second")).await;
+
+        assert_eq!(
+            replay(&log, ReplayStart::Earliest).await.unwrap(),
+            vec![logged("first", 0), logged("second", 1)]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
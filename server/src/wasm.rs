@@ -0,0 +1,449 @@
+//! Operations that transform or filter the messages delivered to a subscriber with WebAssembly
+//! modules
+//!
+//! Modules are referenced by their path within the server's modules directory, e.g. the
+//! `/selium/bonanza.wasm` operation loads `<modules dir>/selium/bonanza.wasm`. Each module is
+//! compiled once, when it's first referenced, and instantiated separately for each subscriber.
+//!
+//! # ABI
+//!
+//! A module must export its linear `memory`, and may not import anything from the host. Every
+//! module must export an `alloc(len: i32) -> i32` function, which returns a pointer to `len`
+//! bytes of memory that the message payload is written to, along with either:
+//!
+//! - `map(ptr: i32, len: i32) -> i64` for a map module, which transforms the payload of `len`
+//!   bytes at `ptr`, returning a pointer to the transformed payload in the upper 32 bits, and its
+//!   length in the lower 32.
+//! - `filter(ptr: i32, len: i32) -> i32` for a filter module, which returns a non-zero value if
+//!   the payload of `len` bytes at `ptr` should be delivered, or zero if it should be dropped.
+//!
+//! If a map module traps, runs out of fuel, or returns a payload outside of its memory, the
+//! message is dropped rather than delivered. If a filter module fails, the message is either
+//! dropped or delivered, according to the server's [FilterErrorPolicy]. Either way, the
+//! subscriber continues to receive later messages.
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
+use futures::{stream, Sink, SinkExt};
+use log::{error, warn};
+use selium_common::{
+    protocol::{Frame, MessagePayload},
+    types::Operation,
+};
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+use wasmi::{Config, Engine, Linker, Memory, Module, Store, TypedFunc};
+
+/// The fuel available to a module to transform each message, which bounds the time it can run
+/// for, so that a module stuck in a loop can't stall the subscriber.
+const FUEL_PER_MESSAGE: u64 = 10_000_000;
+
+/// Determines what happens to a message when a filter module fails to evaluate it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum FilterErrorPolicy {
+    // Drop the message, so that subscribers only receive messages known to pass the filter
+    #[default]
+    Drop,
+    // Deliver the message, as if it had passed the filter
+    Deliver,
+}
+
+/// Loads and compiles the modules referenced by subscribers' operations.
+pub struct Modules {
+    dir: Option<PathBuf>,
+    filter_error_policy: FilterErrorPolicy,
+    engine: Engine,
+    compiled: Mutex<HashMap<String, Module>>,
+}
+
+impl Modules {
+    /// Loads modules from `dir`. If no directory is provided, subscribers requesting operations
+    /// are rejected.
+    pub fn new(dir: Option<PathBuf>, filter_error_policy: FilterErrorPolicy) -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+
+        Self {
+            dir,
+            filter_error_policy,
+            engine: Engine::new(&config),
+            compiled: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Instantiates the modules for a subscriber's operations, returning [None] if it has none.
+    pub fn pipeline(&self, operations: &[Operation]) -> Result<Option<Pipeline>> {
+        if operations.is_empty() {
+            return Ok(None);
+        }
+
+        let mut store = Store::new(&self.engine, ());
+        let linker = Linker::new(&self.engine);
+        let mut stages = Vec::with_capacity(operations.len());
+
+        for operation in operations {
+            let (Operation::Map(path) | Operation::Filter(path)) = operation;
+            let module = self.load(path)?;
+            let instance = linker
+                .instantiate_and_start(&mut store, &module)
+                .with_context(|| format!("Failed to instantiate module {path}"))?;
+            let memory = instance
+                .get_memory(&store, "memory")
+                .ok_or_else(|| anyhow!("Module {path} does not export its memory"))?;
+            let export = |name| format!("Module {path} does not export a valid `{name}` function");
+
+            let alloc = instance
+                .get_typed_func(&store, "alloc")
+                .with_context(|| export("alloc"))?;
+            let kind = match operation {
+                Operation::Map(_) => StageKind::Map(
+                    instance
+                        .get_typed_func(&store, "map")
+                        .with_context(|| export("map"))?,
+                ),
+                Operation::Filter(_) => StageKind::Filter(
+                    instance
+                        .get_typed_func(&store, "filter")
+                        .with_context(|| export("filter"))?,
+                ),
+            };
+
+            stages.push(Stage {
+                kind,
+                alloc,
+                memory,
+            });
+        }
+
+        Ok(Some(Pipeline {
+            store,
+            stages,
+            filter_error_policy: self.filter_error_policy,
+        }))
+    }
+
+    fn load(&self, path: &str) -> Result<Module> {
+        if let Some(module) = self.compiled.lock().unwrap().get(path) {
+            return Ok(module.clone());
+        }
+
+        let dir = self
+            .dir
+            .as_ref()
+            .ok_or_else(|| anyhow!("Server is not configured to load modules"))?;
+        let file = resolve(dir, path)?;
+        let bytes = std::fs::read(&file)
+            .with_context(|| format!("Failed to read module {}", file.display()))?;
+        let module = Module::new(&self.engine, bytes)
+            .with_context(|| format!("Failed to compile module {path}"))?;
+
+        self.compiled
+            .lock()
+            .unwrap()
+            .insert(path.to_owned(), module.clone());
+
+        Ok(module)
+    }
+}
+
+/// Resolves a module's path within the modules directory, rejecting any path that could escape
+/// it.
+fn resolve(dir: &Path, path: &str) -> Result<PathBuf> {
+    let mut resolved = dir.to_path_buf();
+
+    for component in Path::new(path).components() {
+        match component {
+            Component::RootDir => (),
+            Component::Normal(name) => resolved.push(name),
+            _ => bail!("Module path {path} must be within the modules directory"),
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// A module instantiated for one of a subscriber's operations.
+struct Stage {
+    kind: StageKind,
+    alloc: TypedFunc<i32, i32>,
+    memory: Memory,
+}
+
+enum StageKind {
+    Map(TypedFunc<(i32, i32), i64>),
+    Filter(TypedFunc<(i32, i32), i32>),
+}
+
+impl Stage {
+    /// Writes a message payload to the module's memory, returning its pointer and length.
+    fn write(&self, store: &mut Store<()>, message: &[u8]) -> Result<(i32, i32)> {
+        store.set_fuel(FUEL_PER_MESSAGE)?;
+
+        let len = i32::try_from(message.len()).context("Message is too large")?;
+        let ptr = self.alloc.call(&mut *store, len)?;
+        self.memory
+            .write(&mut *store, ptr as u32 as usize, message)?;
+
+        Ok((ptr, len))
+    }
+
+    fn map(
+        &self,
+        store: &mut Store<()>,
+        map: &TypedFunc<(i32, i32), i64>,
+        message: &[u8],
+    ) -> Result<Vec<u8>> {
+        let args = self.write(store, message)?;
+        let result = map.call(&mut *store, args)? as u64;
+        let (ptr, len) = ((result >> 32) as usize, result as u32 as usize);
+
+        let mut output = vec![0; len];
+        self.memory.read(&*store, ptr, &mut output)?;
+
+        Ok(output)
+    }
+
+    fn filter(
+        &self,
+        store: &mut Store<()>,
+        filter: &TypedFunc<(i32, i32), i32>,
+        message: &[u8],
+    ) -> Result<bool> {
+        let args = self.write(store, message)?;
+        Ok(filter.call(&mut *store, args)? != 0)
+    }
+}
+
+/// The modules instantiated for a single subscriber, which are applied to each message in the
+/// order that the subscriber's operations were specified.
+pub struct Pipeline {
+    store: Store<()>,
+    stages: Vec<Stage>,
+    filter_error_policy: FilterErrorPolicy,
+}
+
+impl Pipeline {
+    /// Wraps a subscriber's sink, so that each message is transformed or filtered before it's
+    /// delivered.
+    pub fn wrap<Si>(mut self, sink: Si) -> impl Sink<Frame, Error = anyhow::Error>
+    where
+        Si: Sink<Frame, Error = anyhow::Error>,
+    {
+        sink.with_flat_map(move |frame| stream::iter(self.apply(frame).map(Ok)))
+    }
+
+    /// Applies each operation to a message, returning [None] if it should be dropped.
+    fn apply(&mut self, frame: Frame) -> Option<Frame> {
+        let payload = match frame {
+            // The server can't transform compressed payloads, so they're delivered as published
+            Frame::Message(payload) if !payload.compression.is_none() => {
+                warn!("Delivering compressed message without applying operations");
+                return Some(Frame::Message(payload));
+            }
+            Frame::Message(payload) => payload,
+            frame => return Some(frame),
+        };
+
+        match self.transform(&payload.message) {
+            Ok(Some(message)) => Some(Frame::Message(MessagePayload {
+                message: message.into(),
+                ..payload
+            })),
+            Ok(None) => None,
+            Err(e) => {
+                error!("Dropping message that an operation failed on: {e:?}");
+                None
+            }
+        }
+    }
+
+    fn transform(&mut self, message: &[u8]) -> Result<Option<Vec<u8>>> {
+        let mut message = message.to_vec();
+
+        for stage in self.stages.iter() {
+            match &stage.kind {
+                StageKind::Map(map) => message = stage.map(&mut self.store, map, &message)?,
+                StageKind::Filter(filter) => {
+                    match stage.filter(&mut self.store, filter, &message) {
+                        Ok(true) => (),
+                        Ok(false) => return Ok(None),
+                        Err(e) if self.filter_error_policy == FilterErrorPolicy::Deliver => {
+                            warn!("Delivering message that failed to filter: {e:?}")
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+
+        Ok(Some(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    // Uppercases ASCII payloads in place
+    const UPPERCASE: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "map") (param $ptr i32) (param $len i32) (result i64)
+            (local $i i32)
+            (local $byte i32)
+            (block $done
+              (loop $next
+                (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                (local.set $byte (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+                (if (i32.and
+                      (i32.ge_u (local.get $byte) (i32.const 97))
+                      (i32.le_u (local.get $byte) (i32.const 122)))
+                  (then
+                    (i32.store8
+                      (i32.add (local.get $ptr) (local.get $i))
+                      (i32.sub (local.get $byte) (i32.const 32)))))
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br $next)))
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len)))))
+    "#;
+
+    // Passes payloads ending in an even ASCII digit
+    const EVEN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "filter") (param $ptr i32) (param $len i32) (result i32)
+            (if (result i32) (i32.eqz (local.get $len))
+              (then (i32.const 0))
+              (else
+                (i32.eqz
+                  (i32.and
+                    (i32.load8_u (i32.sub (i32.add (local.get $ptr) (local.get $len)) (i32.const 1)))
+                    (i32.const 1)))))))
+    "#;
+
+    const TRAPPING_FILTER: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "filter") (param i32 i32) (result i32) (unreachable)))
+    "#;
+
+    const TRAP: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "map") (param i32 i32) (result i64) (unreachable)))
+    "#;
+
+    fn modules(files: &[(&str, &str)]) -> (Modules, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "selium-modules-{}-{}",
+            std::process::id(),
+            files[0].0
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for (name, module) in files {
+            std::fs::write(dir.join(name), module).unwrap();
+        }
+
+        (
+            Modules::new(Some(dir.clone()), FilterErrorPolicy::Drop),
+            dir,
+        )
+    }
+
+    fn message(message: &'static str) -> Frame {
+        Frame::Message(MessagePayload::new(Bytes::from(message)))
+    }
+
+    #[test]
+    fn maps_message_payload() {
+        let (modules, dir) = modules(&[("uppercase.wat", UPPERCASE)]);
+        let operations = [Operation::Map("/uppercase.wat".into())];
+        let mut pipeline = modules.pipeline(&operations).unwrap().unwrap();
+
+        assert_eq!(pipeline.apply(message("hello")), Some(message("HELLO")));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn drops_message_when_module_traps() {
+        let (modules, dir) = modules(&[("trap.wat", TRAP)]);
+        let operations = [Operation::Map("/trap.wat".into())];
+        let mut pipeline = modules.pipeline(&operations).unwrap().unwrap();
+
+        assert_eq!(pipeline.apply(message("hello")), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn filters_message_payloads() {
+        let (modules, dir) = modules(&[("even.wat", EVEN)]);
+        let operations = [Operation::Filter("/even.wat".into())];
+        let mut pipeline = modules.pipeline(&operations).unwrap().unwrap();
+
+        assert_eq!(pipeline.apply(message("42")), Some(message("42")));
+        assert_eq!(pipeline.apply(message("43")), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn applies_operations_in_order() {
+        let (modules, dir) = modules(&[("uppercase-even.wat", UPPERCASE), ("even.wat", EVEN)]);
+        let operations = [
+            Operation::Map("/uppercase-even.wat".into()),
+            Operation::Filter("/even.wat".into()),
+        ];
+        let mut pipeline = modules.pipeline(&operations).unwrap().unwrap();
+
+        assert_eq!(pipeline.apply(message("abc2")), Some(message("ABC2")));
+        assert_eq!(pipeline.apply(message("abc3")), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn applies_filter_error_policy() {
+        let (_, dir) = modules(&[("trapping-filter.wat", TRAPPING_FILTER)]);
+        let operations = [Operation::Filter("/trapping-filter.wat".into())];
+
+        let drop = Modules::new(Some(dir.clone()), FilterErrorPolicy::Drop);
+        let mut pipeline = drop.pipeline(&operations).unwrap().unwrap();
+        assert_eq!(pipeline.apply(message("42")), None);
+
+        let deliver = Modules::new(Some(dir.clone()), FilterErrorPolicy::Deliver);
+        let mut pipeline = deliver.pipeline(&operations).unwrap().unwrap();
+        assert_eq!(pipeline.apply(message("42")), Some(message("42")));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_module_outside_of_modules_directory() {
+        assert!(resolve(Path::new("/modules"), "/../secret.wasm").is_err());
+        assert_eq!(
+            resolve(Path::new("/modules"), "/selium/bonanza.wasm").unwrap(),
+            Path::new("/modules/selium/bonanza.wasm")
+        );
+    }
+
+    #[test]
+    fn rejects_operations_without_modules_directory() {
+        let modules = Modules::new(None, FilterErrorPolicy::Drop);
+        let operations = [Operation::Map("/uppercase.wasm".into())];
+
+        assert!(modules.pipeline(&operations).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! A compatibility mode that accepts WebTransport sessions over HTTP/3, so that browsers can open
+//! Selium streams with the WebTransport API, alongside native clients.
+//!
+//! Connections negotiating the `h3` ALPN protocol are served by the [h3] crate, and mapped onto
+//! the native protocol as follows:
+//!
+//! 1. The client's first request must be an extended CONNECT request with the `webtransport`
+//!    protocol, which the server accepts with a `200` status. Any other request is rejected with
+//!    a `400` status, and the connection is closed.
+//! 2. Every bidirectional stream that the client then opens within the session carries Selium
+//!    frames exactly like a native client's stream, once its WebTransport stream header has been
+//!    read.
+//!
+//! Each connection carries a single session, which lasts for the lifetime of the connection.
+//! Datagrams, unidirectional WebTransport streams, and any further HTTP/3 requests aren't
+//! supported.
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use h3::error::Code;
+use h3::ext::Protocol;
+use h3::quic::{BidiStream as _, RecvStream as _, SendStream as _};
+use h3_webtransport::server::{AcceptedBi, WebTransportSession};
+use http::{Method, Request, Response, StatusCode};
+use log::warn;
+use quinn::{Connection, StreamId, VarInt};
+use selium_common::types::{BiStream, TunnelRecv, TunnelSend};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The ALPN protocol negotiated by WebTransport clients.
+pub const ALPN: &[u8] = b"h3";
+
+/// The maximum number of concurrent unidirectional streams that each WebTransport client may
+/// open. Clients open their control and QPACK streams for the lifetime of the connection, so a
+/// few more are allowed for the streams that the server stops.
+pub const MAX_CLIENT_UNI_STREAMS: u8 = 16;
+
+type H3Connection = h3_quinn::Connection;
+type SendStream = h3_webtransport::stream::SendStream<h3_quinn::SendStream<Bytes>, Bytes>;
+type RecvStream = h3_webtransport::stream::RecvStream<h3_quinn::RecvStream, Bytes>;
+
+/// A WebTransport session established on a client's connection.
+pub struct Session {
+    session: WebTransportSession<H3Connection, Bytes>,
+}
+
+impl Session {
+    /// Performs the HTTP/3 handshake on a connection that negotiated the [ALPN] protocol, and
+    /// accepts the client's WebTransport session.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Err] if the client's first request isn't a WebTransport CONNECT request, in which
+    /// case the request is rejected with a `400` status.
+    pub async fn accept(connection: &Connection) -> Result<Self> {
+        let mut h3 = h3::server::builder()
+            .enable_webtransport(true)
+            .enable_connect(true)
+            .enable_datagram(true)
+            .max_webtransport_sessions(1)
+            .build(H3Connection::new(connection.clone()))
+            .await?;
+
+        let Some((request, mut stream)) = h3.accept().await? else {
+            bail!("Connection closed before a WebTransport session was requested");
+        };
+
+        if let Err(e) = validate_connect(&request) {
+            let response = Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(())?;
+            stream.send_response(response).await?;
+            stream.finish().await?;
+            return Err(e);
+        }
+
+        let session = WebTransportSession::accept(request, stream, h3).await?;
+
+        Ok(Self { session })
+    }
+
+    /// Accepts the next bidirectional stream that the client opens within the session, once its
+    /// WebTransport stream header has been read, returning [None] once the connection is closed.
+    ///
+    /// Streams belonging to other sessions are stopped, and further requests are rejected with a
+    /// `400` status.
+    pub async fn accept_stream(&self) -> Result<Option<BiStream>> {
+        loop {
+            let accepted = match self.session.accept_bi().await {
+                Err(e) if closed_without_error(&e) => return Ok(None),
+                accepted => accepted?,
+            };
+
+            match accepted {
+                Some(AcceptedBi::BidiStream(id, stream)) if id == self.session.session_id() => {
+                    let (send, recv) = stream.split();
+                    return Ok(Some(BiStream::tunnelled(Send(send), Recv(recv))));
+                }
+                Some(AcceptedBi::BidiStream(_, mut stream)) => {
+                    warn!("Stopping stream that belongs to an unknown WebTransport session");
+                    stream.stop_sending(Code::H3_ID_ERROR.value());
+                }
+                Some(AcceptedBi::Request(request, mut stream)) => {
+                    warn!(
+                        "Rejecting {} request within WebTransport session",
+                        request.method()
+                    );
+                    tokio::spawn(async move {
+                        let response = Response::builder()
+                            .status(StatusCode::BAD_REQUEST)
+                            .body(())
+                            .unwrap();
+                        if stream.send_response(response).await.is_ok() {
+                            let _ = stream.finish().await;
+                        }
+                    });
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+fn validate_connect(request: &Request<()>) -> Result<()> {
+    let protocol = request.extensions().get::<Protocol>();
+
+    match (request.method(), protocol) {
+        (&Method::CONNECT, Some(&Protocol::WEB_TRANSPORT)) => Ok(()),
+        (method, protocol) => bail!(
+            "Expected a WebTransport CONNECT request, found method {} and protocol {}",
+            method,
+            protocol.map_or("<none>", Protocol::as_str)
+        ),
+    }
+}
+
+/// Returns whether the connection was closed without an error, such as when the client ends the
+/// session.
+fn closed_without_error(err: &h3::Error) -> bool {
+    match err.try_get_code() {
+        Some(code) => code == Code::H3_NO_ERROR || code.value() == 0,
+        None => false,
+    }
+}
+
+/// Returns the ID of the QUIC stream that carries a WebTransport stream, which is always a
+/// bidirectional stream opened by the client.
+fn quic_stream_id(id: h3::quic::StreamId) -> StreamId {
+    // The ID is shifted back from its index, so it can't exceed the largest VarInt
+    StreamId::from(VarInt::from_u64(id.index() << 2).unwrap_or(VarInt::MAX))
+}
+
+/// The send half of a WebTransport stream.
+struct Send(SendStream);
+
+/// The receive half of a WebTransport stream.
+struct Recv(RecvStream);
+
+impl TunnelSend for Send {
+    fn id(&self) -> StreamId {
+        quic_stream_id(self.0.send_id())
+    }
+
+    fn reset(&mut self, code: VarInt) -> Result<()> {
+        self.0.reset(code.into_inner());
+        Ok(())
+    }
+}
+
+impl TunnelRecv for Recv {
+    fn id(&self) -> StreamId {
+        quic_stream_id(self.0.recv_id())
+    }
+}
+
+impl AsyncWrite for Send {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+impl AsyncRead for Recv {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::{bail, Result};
+
+const LEVEL_SEPARATOR: char = '/';
+const SINGLE_LEVEL: &str = "+";
+const MULTI_LEVEL: &str = "#";
+
+/// A topic pattern used by wildcard subscriptions.
+///
+/// Topics are split into levels on the `/` separator. A pattern may use these wildcards:
+///
+/// - `+` matches exactly one level, e.g. `/acmeco/+/trades` matches `/acmeco/stocks/trades`,
+///   but not `/acmeco/stocks/nyse/trades`.
+/// - `#` matches any number of trailing levels, including none, e.g. `/acmeco/#` matches
+///   `/acmeco`, `/acmeco/stocks` and `/acmeco/stocks/trades`. It may only be used as the
+///   final level of a pattern.
+///
+/// A wildcard must occupy a whole level, so patterns such as `/acmeco/stocks+` are invalid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicPattern {
+    levels: Vec<String>,
+}
+
+impl TopicPattern {
+    /// Returns true if the topic contains any wildcard characters.
+    pub fn is_wildcard(topic: &str) -> bool {
+        topic.contains(['+', '#'])
+    }
+
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let levels: Vec<String> = pattern.split(LEVEL_SEPARATOR).map(String::from).collect();
+        let last = levels.len() - 1;
+
+        for (idx, level) in levels.iter().enumerate() {
+            match level.as_str() {
+                SINGLE_LEVEL => (),
+                MULTI_LEVEL if idx == last => (),
+                MULTI_LEVEL => {
+                    bail!("Multi-level wildcard must be the last level of a topic pattern")
+                }
+                level if Self::is_wildcard(level) => {
+                    bail!("Wildcards must occupy an entire topic level")
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Self { levels })
+    }
+
+    pub fn matches(&self, topic: &str) -> bool {
+        let mut topic_levels = topic.split(LEVEL_SEPARATOR);
+
+        for level in self.levels.iter() {
+            match level.as_str() {
+                MULTI_LEVEL => return true,
+                SINGLE_LEVEL => {
+                    if topic_levels.next().is_none() {
+                        return false;
+                    }
+                }
+                level => {
+                    if topic_levels.next() != Some(level) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        topic_levels.next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, topic: &str) -> bool {
+        TopicPattern::parse(pattern).unwrap().matches(topic)
+    }
+
+    #[test]
+    fn detects_wildcard_topics() {
+        assert!(TopicPattern::is_wildcard("/acmeco/+/trades"));
+        assert!(TopicPattern::is_wildcard("/acmeco/#"));
+        assert!(!TopicPattern::is_wildcard("/acmeco/stocks"));
+    }
+
+    #[test]
+    fn single_level_wildcard_matches_one_level() {
+        assert!(matches("/acmeco/+/trades", "/acmeco/stocks/trades"));
+        assert!(matches("/acmeco/+/trades", "/acmeco/bonds/trades"));
+        assert!(matches("/+/+", "/acmeco/stocks"));
+    }
+
+    #[test]
+    fn single_level_wildcard_rejects_other_depths() {
+        assert!(!matches("/acmeco/+/trades", "/acmeco/trades"));
+        assert!(!matches("/acmeco/+/trades", "/acmeco/stocks/nyse/trades"));
+        assert!(!matches("/acmeco/+/trades", "/acmeco/stocks/quotes"));
+        assert!(!matches("/acmeco/+", "/acmeco/stocks/trades"));
+    }
+
+    #[test]
+    fn multi_level_wildcard_matches_tail() {
+        assert!(matches("/acmeco/#", "/acmeco"));
+        assert!(matches("/acmeco/#", "/acmeco/stocks"));
+        assert!(matches("/acmeco/#", "/acmeco/stocks/nyse/trades"));
+        assert!(matches("/acmeco/+/#", "/acmeco/stocks/trades"));
+    }
+
+    #[test]
+    fn multi_level_wildcard_rejects_other_prefixes() {
+        assert!(!matches("/acmeco/#", "/globex/stocks"));
+        assert!(!matches("/acmeco/#", "/acmecorp/stocks"));
+        assert!(!matches("/acmeco/+/#", "/acmeco"));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(TopicPattern::parse("/acmeco/#/trades").is_err());
+        assert!(TopicPattern::parse("/acmeco/stocks+").is_err());
+        assert!(TopicPattern::parse("/acmeco/st#").is_err());
+    }
+}
@@ -0,0 +1,12 @@
+;; Passes message payloads ending in an even ASCII digit
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "filter") (param $ptr i32) (param $len i32) (result i32)
+    (if (result i32) (i32.eqz (local.get $len))
+      (then (i32.const 0))
+      (else
+        (i32.eqz
+          (i32.and
+            (i32.load8_u (i32.sub (i32.add (local.get $ptr) (local.get $len)) (i32.const 1)))
+            (i32.const 1)))))))
@@ -0,0 +1,23 @@
+;; Uppercases ASCII message payloads in place
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "map") (param $ptr i32) (param $len i32) (result i64)
+    (local $i i32)
+    (local $byte i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+        (local.set $byte (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+        (if (i32.and
+              (i32.ge_u (local.get $byte) (i32.const 97))
+              (i32.le_u (local.get $byte) (i32.const 122)))
+          (then
+            (i32.store8
+              (i32.add (local.get $ptr) (local.get $i))
+              (i32.sub (local.get $byte) (i32.const 32)))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len)))))
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{connect, eventually, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, AckSubscriber, Client, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7085";
+const MESSAGE_COUNT: usize = 50;
+
+#[tokio::test]
+async fn test_ack_through_acknowledges_every_message() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    // Only the message after those acknowledged is redelivered
+    assert_eq!(result.unwrap(), format!("message {}", MESSAGE_COUNT - 1));
+}
+
+// Returns the first message redelivered after acknowledging all but the last message
+async fn run() -> Result<String, Box<dyn Error>> {
+    let publishing = connect(SERVER_ADDR).await?;
+    let mut publisher = publishing
+        .publisher("/acmeco/ack_through")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let client = connect(SERVER_ADDR).await?;
+    let mut subscriber = subscribe(&client).await?;
+
+    for i in 0..MESSAGE_COUNT {
+        publisher.send(format!("message {i}")).await?;
+    }
+
+    let mut last = None;
+
+    for i in 0..MESSAGE_COUNT {
+        let delivery = subscriber.try_next().await?.unwrap();
+        assert_eq!(delivery.payload, format!("message {i}"));
+        last = Some(delivery);
+    }
+
+    let last_delivery_id = last.unwrap().delivery_id();
+
+    // Acknowledging a message that hasn't been received yet is rejected
+    assert!(subscriber.ack_through(last_delivery_id + 1).await.is_err());
+
+    // Acknowledge all of the messages but the last at once, without acknowledging any
+    // individually
+    subscriber.ack_through(last_delivery_id - 1).await?;
+
+    // Dropping the subscriber finishes its stream behind the acknowledgement, so the server reads
+    // the acknowledgement before it notices that the subscriber has disconnected
+    drop(subscriber);
+
+    // The unacknowledged message is only redelivered to subscribers registered after that
+    let client = &client;
+    let redelivered = eventually(|| async move {
+        let mut subscriber = subscribe(client).await?;
+
+        match tokio::time::timeout(Duration::from_millis(200), subscriber.try_next()).await {
+            Ok(delivery) => {
+                let delivery = delivery?.ok_or("Subscriber ended early")?;
+                delivery.ack().await?;
+                Ok(Some(delivery.payload))
+            }
+            Err(_) => Ok(None),
+        }
+    })
+    .await?;
+
+    publisher.finish().await?;
+
+    Ok(redelivered)
+}
+
+async fn subscribe(client: &Client) -> Result<AckSubscriber<StringCodec, String>, Box<dyn Error>> {
+    let subscriber = client
+        .subscriber("/acmeco/ack_through")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_acks()
+        .open()
+        .await?;
+
+    Ok(subscriber)
+}
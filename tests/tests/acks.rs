@@ -0,0 +1,159 @@
+mod common;
+
+use common::{connect, eventually, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, AckSubscriber, Client, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7017";
+const SLOW_SUBSCRIBER_ADDR: &str = "127.0.0.1:7087";
+const UNACKED_COUNT: usize = 16;
+const UNACKED_SIZE: usize = 4 * 1024;
+
+#[tokio::test]
+async fn test_unacked_message_is_redelivered() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (redelivered, next) = result.unwrap();
+    assert_eq!(redelivered, "second");
+    assert_eq!(next, "third");
+}
+
+async fn run() -> Result<(String, String), Box<dyn Error>> {
+    let publishing = connect(SERVER_ADDR).await?;
+    let mut publisher = publishing
+        .publisher("/acmeco/acks")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let client = connect(SERVER_ADDR).await?;
+    let mut subscriber = subscribe(&client, "/acmeco/acks").await?;
+
+    publisher.send("first".to_owned()).await?;
+    publisher.send("second".to_owned()).await?;
+
+    let first = subscriber.try_next().await?.unwrap();
+    assert_eq!(first.payload, "first");
+    first.ack().await?;
+
+    // Receive the second message, but disconnect without acknowledging it. Dropping the
+    // subscriber finishes its stream behind the acknowledgement of the first message, so the
+    // server reads the acknowledgement before it notices that the subscriber has disconnected.
+    let second = subscriber.try_next().await?.unwrap();
+    assert_eq!(second.payload, "second");
+    drop((subscriber, first, second));
+
+    // The unacknowledged message is only redelivered to subscribers registered after that
+    let client = &client;
+    let (mut subscriber, redelivered) = eventually(|| async move {
+        let mut subscriber = subscribe(client, "/acmeco/acks").await?;
+
+        match tokio::time::timeout(Duration::from_millis(200), subscriber.try_next()).await {
+            Ok(delivery) => {
+                let delivery = delivery?.ok_or("Subscriber ended early")?;
+                Ok(Some((subscriber, delivery)))
+            }
+            Err(_) => Ok(None),
+        }
+    })
+    .await?;
+    redelivered.ack().await?;
+
+    publisher.send("third".to_owned()).await?;
+    let next = subscriber.try_next().await?.unwrap();
+    next.ack().await?;
+
+    publisher.finish().await?;
+
+    Ok((redelivered.payload, next.payload))
+}
+
+#[tokio::test]
+async fn test_slow_subscriber_does_not_stall_other_registrations() {
+    let _server = Server::start(SLOW_SUBSCRIBER_ADDR);
+
+    let result = register_alongside_slow_subscriber().await;
+
+    result.unwrap();
+}
+
+async fn register_alongside_slow_subscriber() -> Result<(), Box<dyn Error>> {
+    let publishing = connect(SLOW_SUBSCRIBER_ADDR).await?;
+    let mut publisher = publishing
+        .publisher("/acmeco/redelivered")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let first_client = connect(SLOW_SUBSCRIBER_ADDR).await?;
+    let mut subscriber = first_client
+        .subscriber("/acmeco/redelivered")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_acks()
+        .open()
+        .await?;
+
+    for _ in 0..UNACKED_COUNT {
+        publisher.send("x".repeat(UNACKED_SIZE)).await?;
+    }
+
+    // Receive every message, but disconnect without acknowledging any of them
+    for _ in 0..UNACKED_COUNT {
+        subscriber.try_next().await?;
+    }
+
+    first_client.graceful_shutdown(1_000).await?;
+
+    // The subscriber's window is too small for the redelivered messages, and it stops reading
+    // them after the first, so redelivering the rest stalls until it reads them
+    let slow = selium::client()
+        .stream_receive_window(1024)
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SLOW_SUBSCRIBER_ADDR)
+        .await?;
+
+    // The messages are only redelivered to subscribers registered once the server has noticed
+    // that the first subscriber disconnected
+    let slow = &slow;
+    let _stalled = eventually(|| async move {
+        let mut subscriber = subscribe(slow, "/acmeco/redelivered").await?;
+        let next = tokio::time::timeout(Duration::from_millis(200), subscriber.try_next()).await;
+
+        Ok::<_, Box<dyn Error>>(next.is_ok().then_some(subscriber))
+    })
+    .await?;
+
+    let _subscriber = tokio::time::timeout(
+        Duration::from_secs(5),
+        publishing
+            .subscriber("/acmeco/other")
+            .with_decoder(StringCodec)
+            .start_position(StartPosition::Latest)
+            .open(),
+    )
+    .await
+    .map_err(|_| "Registration stalled behind the slow subscriber")??;
+
+    publisher.finish().await?;
+
+    Ok(())
+}
+
+async fn subscribe(
+    client: &Client,
+    topic: &str,
+) -> Result<AckSubscriber<StringCodec, String>, Box<dyn Error>> {
+    let subscriber = client
+        .subscriber(topic)
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_acks()
+        .open()
+        .await?;
+
+    Ok(subscriber)
+}
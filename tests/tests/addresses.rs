@@ -0,0 +1,90 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, ClientBuilder, ClientWantsCert, StartPosition};
+use std::error::Error;
+
+const IPV6_SERVER_ADDR: &str = "[::1]:7057";
+const HOSTNAME_SERVER_ADDR: &str = "127.0.0.1:7058";
+
+#[tokio::test]
+async fn test_ipv6_address() {
+    let _server = Server::start(IPV6_SERVER_ADDR);
+
+    let result = run(selium::client(), IPV6_SERVER_ADDR).await;
+
+    let (endpoint, message) = result.unwrap();
+    assert_eq!(endpoint, IPV6_SERVER_ADDR);
+    assert_eq!(message, "Hello, [::1]:7057!");
+}
+
+#[tokio::test]
+async fn test_hostname_address() {
+    let _server = Server::start(HOSTNAME_SERVER_ADDR);
+
+    // The server's certificate is only valid for localhost, so the hostname must be verified
+    // rather than the server name, which only applies to IP addresses
+    let hostname = run(
+        selium::client().server_name("selium.invalid"),
+        "localhost:7058",
+    )
+    .await;
+    let ip = run(
+        selium::client().server_name("selium.invalid"),
+        HOSTNAME_SERVER_ADDR,
+    )
+    .await;
+
+    let (endpoint, message) = hostname.unwrap();
+    assert_eq!(endpoint, HOSTNAME_SERVER_ADDR);
+    assert_eq!(message, "Hello, localhost:7058!");
+
+    let err = ip.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<selium::Error>(),
+        Some(selium::Error::Tls(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_address_without_port() {
+    let result = selium::client()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect("localhost")
+        .await;
+
+    assert!(matches!(result, Err(selium::Error::Config(_))));
+}
+
+async fn run(
+    builder: ClientBuilder<ClientWantsCert>,
+    addr: &str,
+) -> Result<(String, String), Box<dyn Error>> {
+    let connection = builder
+        .keep_alive(5_000)?
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(addr)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/addresses")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/addresses")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send(format!("Hello, {addr}!")).await?;
+    publisher.finish().await?;
+
+    let message = subscriber.try_next().await?.unwrap_or_default();
+
+    Ok((connection.endpoint().to_string(), message))
+}
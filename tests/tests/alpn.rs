@@ -0,0 +1,66 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, ClientBuilder, ClientWantsCert, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7052";
+const SERVER_ARGS: &[&str] = &["--alpn", "selium-test,hq-29"];
+
+#[tokio::test]
+async fn test_alpn() {
+    let _server = Server::start_with_args(SERVER_ADDR, SERVER_ARGS);
+
+    let custom = run(selium::client().alpn(&[b"unknown", b"selium-test"])).await;
+    let default = run(selium::client()).await;
+    let mismatched = selium::client()
+        .alpn(&[b"unknown"])
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await;
+
+    assert_eq!(custom.unwrap(), "Hello, ALPN!");
+    assert_eq!(default.unwrap(), "Hello, ALPN!");
+    assert!(matches!(mismatched, Err(selium::Error::Tls(_))));
+}
+
+#[tokio::test]
+async fn test_empty_alpn() {
+    let result = selium::client()
+        .alpn(&[])
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await;
+
+    assert!(matches!(result, Err(selium::Error::Config(_))));
+}
+
+async fn run(builder: ClientBuilder<ClientWantsCert>) -> Result<String, Box<dyn Error>> {
+    let connection = builder
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/alpn")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/alpn")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, ALPN!".to_owned()).await?;
+    publisher.finish().await?;
+
+    let message = subscriber.try_next().await?.unwrap_or_default();
+
+    Ok(message)
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7051";
+const REQUEST_REPLY_SERVER_ADDR: &str = "127.0.0.1:7088";
+
+#[tokio::test]
+async fn test_auth_token() {
+    let _server = Server::start_with_args(SERVER_ADDR, &["--auth-tokens", "tests/auth/tokens"]);
+
+    let result = run().await;
+
+    let messages = result.unwrap();
+    assert_eq!(messages, vec!["authorized"]);
+}
+
+#[tokio::test]
+async fn test_request_reply_auth_token() {
+    let _server = Server::start_with_args(
+        REQUEST_REPLY_SERVER_ADDR,
+        &["--auth-tokens", "tests/auth/tokens"],
+    );
+
+    let result = run_request_reply().await;
+
+    assert_eq!(result.unwrap(), "echo: authorized");
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let result = connection
+        .subscriber("/acmeco/auth")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_token("wrong-token")
+        .open()
+        .await;
+    assert!(matches!(result, Err(selium::Error::Unauthorized(_))));
+
+    let result = connection
+        .publisher("/acmeco/auth")
+        .with_encoder(StringCodec)
+        .with_token("wrong-token")
+        .open()
+        .await;
+    assert!(matches!(result, Err(selium::Error::Unauthorized(_))));
+
+    // The token isn't permitted to use topics outside of its own namespace
+    let result = connection
+        .publisher("/globex/auth")
+        .with_encoder(StringCodec)
+        .with_token("acme-token")
+        .open()
+        .await;
+    assert!(matches!(result, Err(selium::Error::Unauthorized(_))));
+
+    let subscriber = connection
+        .subscriber("/acmeco/auth")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_token("acme-token")
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/auth")
+        .with_encoder(StringCodec)
+        .with_token("acme-token")
+        .open()
+        .await?;
+
+    publisher.send("authorized".to_owned()).await?;
+    publisher.finish().await?;
+
+    let messages = subscriber.take(1).try_collect().await?;
+
+    Ok(messages)
+}
+
+async fn run_request_reply() -> Result<String, Box<dyn Error>> {
+    let connection = connect(REQUEST_REPLY_SERVER_ADDR).await?;
+
+    let result = connection
+        .replier("/acmeco/echo")
+        .with_decoder(StringCodec)
+        .with_encoder(StringCodec)
+        .with_token("wrong-token")
+        .open()
+        .await;
+    assert!(matches!(result, Err(selium::Error::Unauthorized(_))));
+
+    let result = connection
+        .requestor("/acmeco/echo")
+        .with_encoder(StringCodec)
+        .with_decoder(StringCodec)
+        .with_token("wrong-token")
+        .open()
+        .await;
+    assert!(matches!(result, Err(selium::Error::Unauthorized(_))));
+
+    let replier = connection
+        .replier("/acmeco/echo")
+        .with_decoder(StringCodec)
+        .with_encoder(StringCodec)
+        .with_token("acme-token")
+        .open()
+        .await?;
+
+    tokio::spawn(replier.serve(|request: String| async move { Ok(format!("echo: {request}")) }));
+
+    let mut requestor = connection
+        .requestor("/acmeco/echo")
+        .with_encoder(StringCodec)
+        .with_decoder(StringCodec)
+        .with_token("acme-token")
+        .open()
+        .await?;
+
+    let reply = requestor.request("authorized".to_owned()).await?;
+    requestor.finish().await?;
+
+    Ok(reply)
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{connect, Server};
+use futures::SinkExt;
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7043";
+const MESSAGE_SIZE: usize = 256 * 1024;
+const MAX_MESSAGES: usize = 200;
+
+#[tokio::test]
+async fn test_stalled_subscriber_blocks_publisher() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let sent = result.unwrap().expect("Publisher was never blocked");
+    assert!(sent > 0);
+}
+
+// Returns the number of messages sent before the publisher was blocked, if it was
+async fn run() -> Result<Option<usize>, Box<dyn Error>> {
+    let subscriber_connection = connect(SERVER_ADDR).await?;
+
+    // The subscriber is never polled, so it stalls once its stream's flow control window fills
+    let _subscriber = subscriber_connection
+        .subscriber("/acmeco/firehose")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let publisher_connection = connect(SERVER_ADDR).await?;
+
+    let mut publisher = publisher_connection
+        .publisher("/acmeco/firehose")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let message = "x".repeat(MESSAGE_SIZE);
+
+    for sent in 0..MAX_MESSAGES {
+        let send = publisher.send(message.clone());
+
+        if tokio::time::timeout(Duration::from_secs(2), send)
+            .await
+            .is_err()
+        {
+            return Ok(Some(sent));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use clap::Parser;
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use selium_benchmarks::{args::Args, payload::Payloads};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7061";
+const MESSAGE_COUNT: u64 = 10;
+const PAYLOAD_SIZE: usize = 4096;
+
+#[tokio::test]
+async fn test_benchmark_payload_size() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let lengths = result.unwrap();
+    assert_eq!(lengths, vec![PAYLOAD_SIZE; MESSAGE_COUNT as usize]);
+}
+
+async fn run() -> Result<Vec<usize>, Box<dyn Error>> {
+    let args = Args::parse_from(["selium-benchmarks", "--payload-size", "4096"]);
+    let payloads = Payloads::generate(&args)?;
+
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/benchmark")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/benchmark")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for index in 0..MESSAGE_COUNT {
+        publisher.send(payloads.get(index).to_owned()).await?;
+    }
+
+    publisher.finish().await?;
+
+    let mut lengths = Vec::new();
+
+    for _ in 0..MESSAGE_COUNT {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+            .await?
+            .ok_or("Subscriber ended before receiving every message")??;
+        lengths.push(message.len());
+    }
+
+    Ok(lengths)
+}
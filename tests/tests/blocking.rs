@@ -0,0 +1,49 @@
+mod common;
+
+use common::Server;
+use selium::{codecs::StringCodec, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7044";
+
+#[test]
+fn test_blocking_round_trip() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run();
+
+    assert_eq!(result.unwrap(), vec!["Hello", "world!"]);
+}
+
+fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let client = selium::blocking::client()
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)?;
+
+    let mut subscriber = client
+        .subscriber("/acmeco/blocking")
+        .with_decoder(StringCodec)
+        .configure(|builder| Ok(builder.start_position(StartPosition::Latest)))?
+        .open()?;
+
+    let mut publisher = client
+        .publisher("/acmeco/blocking")
+        .with_encoder(StringCodec)
+        .open()?;
+
+    publisher.send("Hello".to_owned())?;
+    publisher.send("world!".to_owned())?;
+    publisher.finish()?;
+
+    let mut received = Vec::new();
+
+    while let Some(message) = subscriber.recv()? {
+        received.push(message);
+
+        if received.len() == 2 {
+            break;
+        }
+    }
+
+    Ok(received)
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7010";
+const CA_PEM: &[u8] = include_bytes!("../certs/ca.crt");
+
+#[tokio::test]
+async fn test_certificate_authority_from_bytes() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), "Hello, world!");
+}
+
+async fn run() -> Result<String, Box<dyn Error>> {
+    let connection = selium::client()
+        .with_certificate_authority_pem(CA_PEM)?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/ca_bytes")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/ca_bytes")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, world!".to_owned()).await?;
+    publisher.finish().await?;
+
+    let message = subscriber.try_next().await?.unwrap_or_default();
+
+    Ok(message)
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7078";
+const MESSAGE_COUNT: usize = 50;
+// Large enough that each message arrives over several reads, so that receives are cancelled part
+// way through a frame
+const MESSAGE_SIZE: usize = 64 * 1024;
+
+#[tokio::test]
+async fn test_cancelled_receives_do_not_drop_messages() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (messages, cancelled) = result.unwrap();
+    assert_eq!(messages, expected_messages());
+    assert!(cancelled > 0);
+}
+
+fn expected_messages() -> Vec<String> {
+    (0..MESSAGE_COUNT)
+        .map(|i| format!("{i}:{}", "x".repeat(MESSAGE_SIZE)))
+        .collect()
+}
+
+// Returns the messages received by a subscriber whose receives are cancelled whenever they don't
+// complete straight away, along with the number of receives that were cancelled
+async fn run() -> Result<(Vec<String>, usize), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/cancel_safety")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/cancel_safety")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    tokio::spawn(async move {
+        for message in expected_messages() {
+            publisher.send(message).await.unwrap();
+        }
+
+        // Keep the publisher open until every message has been received
+        tokio::time::sleep(Duration::from_secs(10)).await;
+    });
+
+    let mut messages = Vec::new();
+    let mut cancelled = 0;
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while messages.len() < MESSAGE_COUNT {
+            tokio::select! {
+                biased;
+                message = subscriber.try_next() => messages.extend(message?),
+                // Completes on its second poll, cancelling the receive if it's still pending
+                _ = tokio::task::yield_now() => cancelled += 1,
+            }
+        }
+
+        Ok::<_, selium::Error>(())
+    })
+    .await??;
+
+    Ok((messages, cancelled))
+}
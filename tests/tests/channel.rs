@@ -0,0 +1,108 @@
+mod common;
+
+use common::{connect, Server};
+use futures::SinkExt;
+use selium::prelude::*;
+use selium::ReplayStart;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7069";
+const REJECTED_ADDR: &str = "127.0.0.1:7070";
+
+#[tokio::test]
+async fn test_drains_subscriber_into_channel() {
+    let mut server = Server::start(SERVER_ADDR);
+
+    let result = run(&mut server).await;
+
+    let expected: Vec<_> = (1..=5).map(|i| i.to_string()).collect();
+    assert_eq!(result.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn test_sends_errors_through_channel() {
+    let _server = Server::start(REJECTED_ADDR);
+
+    let result = receive_rejected().await;
+
+    let (first, next) = result.unwrap();
+    assert!(
+        matches!(first, Some(Err(selium::Error::StreamReset(_)))),
+        "{first:?}"
+    );
+    assert!(next.is_none());
+}
+
+// Returns the messages received through the channel, once it closes after the stream ends
+async fn run(server: &mut Server) -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = selium::client()
+        .keep_alive(250)?
+        .max_idle_timeout(1_000)?
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/channel")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    // Smaller than the number of messages, so the subscriber waits for the channel to drain
+    let mut receiver = subscriber.into_channel(2);
+
+    let mut publisher = connection
+        .publisher("/acmeco/channel")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 1..=5 {
+        publisher.send(i.to_string()).await?;
+    }
+
+    let mut messages = Vec::new();
+
+    for _ in 0..5 {
+        let message = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await?;
+        messages.push(message.expect("Expected channel to be open")?);
+    }
+
+    // Losing the connection ends the subscriber's stream once it idles out, which closes the
+    // channel
+    server.kill();
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while receiver.recv().await.is_some() {}
+    })
+    .await?;
+
+    Ok(messages)
+}
+
+// Returns the first two items received from a subscriber that the server rejects
+async fn receive_rejected() -> Result<
+    (
+        Option<selium::Result<String>>,
+        Option<selium::Result<String>>,
+    ),
+    Box<dyn Error>,
+> {
+    let connection = connect(REJECTED_ADDR).await?;
+
+    // The server isn't persisting topic logs, so it rejects the subscription
+    let subscriber = connection
+        .subscriber("/acmeco/channel")
+        .with_decoder(StringCodec)
+        .replay_from(ReplayStart::Earliest)
+        .open()
+        .await?;
+
+    let mut receiver = subscriber.into_channel(1);
+    let first = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await?;
+    let next = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await?;
+
+    Ok((first, next))
+}
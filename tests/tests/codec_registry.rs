@@ -0,0 +1,74 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::codecs::{BincodeCodec, CodecRegistry, JsonCodec, StringCodec, CONTENT_TYPE};
+use selium::Headers;
+use selium::{prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7068";
+
+#[tokio::test]
+async fn test_selects_decoder_by_content_type() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let mut messages = result.unwrap();
+    messages.sort();
+
+    assert_eq!(messages, vec!["bincode", "json", "untyped"]);
+}
+
+fn content_type(content_type: &str) -> Headers {
+    Headers::from([(CONTENT_TYPE.to_owned(), content_type.to_owned())])
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let registry = CodecRegistry::new()
+        .register("application/json", JsonCodec::default())
+        .register("application/bincode", BincodeCodec::default())
+        .with_default(StringCodec);
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/formats")
+        .with_codec_registry(registry)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut json = connection
+        .publisher("/acmeco/formats")
+        .with_encoder(JsonCodec::default())
+        .open()
+        .await?;
+    let mut bincode = connection
+        .publisher("/acmeco/formats")
+        .with_encoder(BincodeCodec::default())
+        .open()
+        .await?;
+    let mut untyped = connection
+        .publisher("/acmeco/formats")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    json.send_with_headers("json".to_owned(), content_type("application/json"))
+        .await?;
+    bincode
+        .send_with_headers("bincode".to_owned(), content_type("application/bincode"))
+        .await?;
+    untyped.send("untyped".to_owned()).await?;
+
+    let mut messages = Vec::new();
+
+    for _ in 0..3 {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await?;
+        messages.extend(message?);
+    }
+
+    Ok(messages)
+}
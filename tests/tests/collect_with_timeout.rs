@@ -0,0 +1,68 @@
+mod common;
+
+use common::{connect, Server};
+use futures::SinkExt;
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+const EARLY_STOP_ADDR: &str = "127.0.0.1:7045";
+const PARTIAL_ADDR: &str = "127.0.0.1:7046";
+
+#[tokio::test]
+async fn test_collect_stops_once_n_messages_arrive() {
+    let _server = Server::start(EARLY_STOP_ADDR);
+
+    let result = run(EARLY_STOP_ADDR, 3, 2, Duration::from_secs(10)).await;
+
+    let (collected, elapsed) = result.unwrap();
+    assert_eq!(collected, vec!["0", "1"]);
+    assert!(elapsed < Duration::from_secs(10));
+}
+
+#[tokio::test]
+async fn test_collect_returns_partial_results_on_timeout() {
+    let _server = Server::start(PARTIAL_ADDR);
+
+    let result = run(PARTIAL_ADDR, 3, 5, Duration::from_millis(500)).await;
+
+    let (collected, elapsed) = result.unwrap();
+    assert_eq!(collected, vec!["0", "1", "2"]);
+    assert!(elapsed >= Duration::from_millis(500));
+}
+
+// Publishes `published` messages, then collects up to `n` of them within the `timeout`,
+// returning the collected messages and how long collecting took
+async fn run(
+    addr: &str,
+    published: usize,
+    n: usize,
+    timeout: Duration,
+) -> Result<(Vec<String>, Duration), Box<dyn Error>> {
+    let connection = connect(addr).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/batches")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/batches")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 0..published {
+        publisher.send(i.to_string()).await?;
+    }
+
+    let started = Instant::now();
+    let collected = subscriber.collect_with_timeout(n, timeout).await?;
+
+    Ok((collected, started.elapsed()))
+}
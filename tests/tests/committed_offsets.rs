@@ -0,0 +1,101 @@
+mod common;
+
+use common::{connect, eventually, Server};
+use futures::SinkExt;
+use selium::codecs::StringCodec;
+use selium::prelude::*;
+use selium::{OffsetMessage, ReplayStart};
+use std::{error::Error, fs, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7066";
+
+#[tokio::test]
+async fn test_resumes_after_committed_offset() {
+    let log_dir = std::env::temp_dir().join(format!("selium-offsets-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&log_dir);
+
+    let mut server =
+        Server::start_with_args(SERVER_ADDR, &["--log-dir", log_dir.to_str().unwrap()]);
+
+    let result = run().await;
+
+    server.kill();
+    let _ = fs::remove_dir_all(&log_dir);
+
+    let (first, resumed) = result.unwrap();
+    assert_eq!(first, (0..10).map(offset_message).collect::<Vec<_>>());
+    assert_eq!(resumed, (6..10).map(offset_message).collect::<Vec<_>>());
+}
+
+fn offset_message(i: u64) -> OffsetMessage<String> {
+    OffsetMessage {
+        offset: Some(i),
+        payload: i.to_string(),
+    }
+}
+
+// Returns the messages received before committing offset 5, and those received after resuming
+async fn run() -> Result<(Vec<OffsetMessage<String>>, Vec<OffsetMessage<String>>), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/invoices")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 0..9 {
+        publisher.send(i.to_string()).await?;
+    }
+
+    // Confirmed once the server has appended every message to the topic's log
+    publisher.send_confirmed(9.to_string()).await?;
+
+    let not_grouped = connection
+        .subscriber("/acmeco/invoices")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    assert!(matches!(
+        not_grouped.commit(5).await,
+        Err(selium::Error::Config(_))
+    ));
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/invoices")
+        .with_decoder(StringCodec)
+        .group("billing")
+        .replay_from(ReplayStart::Committed)
+        .with_offsets()
+        .open()
+        .await?;
+    let first = subscriber
+        .collect_with_timeout(10, Duration::from_secs(5))
+        .await?;
+
+    subscriber.commit(5).await?;
+    drop(subscriber);
+
+    // Commits aren't confirmed, so rejoin the group until it resumes after the commit
+    let resumed = eventually(|| async {
+        let connection = connect(SERVER_ADDR).await?;
+        let mut subscriber = connection
+            .subscriber("/acmeco/invoices")
+            .with_decoder(StringCodec)
+            .group("billing")
+            .replay_from(ReplayStart::Committed)
+            .with_offsets()
+            .open()
+            .await?;
+        let resumed = subscriber
+            .collect_with_timeout(4, Duration::from_secs(5))
+            .await?;
+        let committed = resumed.first().and_then(|message| message.offset) == Some(6);
+
+        Ok::<_, Box<dyn Error>>(committed.then_some(resumed))
+    })
+    .await?;
+
+    Ok((first, resumed))
+}
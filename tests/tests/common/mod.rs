@@ -0,0 +1,218 @@
+use selium::Client;
+use std::collections::{hash_map::Entry, HashMap};
+use std::error::Error;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+// How long to keep retrying an attempt passed to `eventually`
+const EVENTUALLY_TIMEOUT: Duration = Duration::from_secs(5);
+const EVENTUALLY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A `Selium` server run for a test, which is killed once it's dropped, so that it's stopped
+/// even if the test fails.
+pub struct Server(Child);
+
+#[allow(dead_code)]
+impl Server {
+    pub fn start(addr: &str) -> Self {
+        Self::start_with_args(addr, &[])
+    }
+
+    pub fn start_with_args(addr: &str, args: &[&str]) -> Self {
+        let child = Command::new(env!("CARGO"))
+            .args([
+                "run",
+                "--",
+                "--bind-addr",
+                addr,
+                "--cert",
+                "tests/certs/ca.crt",
+                "--key",
+                "tests/certs/ca.key",
+                "-vvvv",
+            ])
+            .args(args)
+            .current_dir("..")
+            .spawn()
+            .expect("Failed to start server");
+
+        Self(child)
+    }
+
+    pub fn start_self_signed(addr: &str) -> Self {
+        let child = Command::new(env!("CARGO"))
+            .args(["run", "--", "--bind-addr", addr, "--self-signed", "-vvvv"])
+            .current_dir("..")
+            .spawn()
+            .expect("Failed to start server");
+
+        Self(child)
+    }
+
+    /// Kills the server and waits for it to exit, such as to simulate an outage.
+    pub fn kill(&mut self) {
+        // Fails if the server has already exited, which leaves nothing to kill
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Connects to the server at `addr`, trusting the test CA.
+#[allow(dead_code)]
+pub async fn connect(addr: &str) -> Result<Client, Box<dyn Error>> {
+    let client = selium::client()
+        .keep_alive(5_000)?
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(addr)
+        .await?;
+
+    Ok(client)
+}
+
+/// Repeats `attempt` until it returns [Some], for waiting on server state that clients aren't
+/// told about, such as whether a disconnected subscriber has been noticed. Attempts are retried
+/// after a short interval, so each one may give up straight away.
+///
+/// Returns [Err] if `attempt` fails, or doesn't succeed within a few seconds.
+#[allow(dead_code)]
+pub async fn eventually<T, F, Fut>(mut attempt: F) -> Result<T, Box<dyn Error>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>, Box<dyn Error>>>,
+{
+    tokio::time::timeout(EVENTUALLY_TIMEOUT, async {
+        loop {
+            if let Some(value) = attempt().await? {
+                return Ok(value);
+            }
+
+            tokio::time::sleep(EVENTUALLY_INTERVAL).await;
+        }
+    })
+    .await
+    .map_err(|_| "Timed out waiting for the server")?
+}
+
+/// A UDP proxy in front of a server, which can stall every connection through it by dropping
+/// their packets in both directions, as if the server had stopped responding without closing
+/// its connections.
+#[allow(dead_code)]
+pub struct StallingProxy {
+    addr: String,
+    stalled: Arc<AtomicBool>,
+    task: JoinHandle<()>,
+}
+
+#[allow(dead_code)]
+impl StallingProxy {
+    pub async fn start(server_addr: &str) -> Self {
+        let server_addr: SocketAddr = server_addr.parse().expect("Invalid server address");
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let addr = socket.local_addr().unwrap().to_string();
+        let stalled = Arc::new(AtomicBool::new(false));
+
+        let task = tokio::spawn(forward(socket, server_addr, stalled.clone()));
+
+        Self {
+            addr,
+            stalled,
+            task,
+        }
+    }
+
+    /// The address to connect to the server through the proxy.
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Drops every packet until the proxy is resumed.
+    pub fn stall(&self) {
+        self.stalled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.stalled.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Drop for StallingProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// Forwards each client's packets to the server from a socket of its own, so that the server's
+// replies can be routed back to the client they're addressed to
+async fn forward(socket: Arc<UdpSocket>, server_addr: SocketAddr, stalled: Arc<AtomicBool>) {
+    let mut upstreams = HashMap::new();
+    let mut replies = Vec::new();
+    let mut buffer = vec![0; u16::MAX as usize];
+
+    loop {
+        let Ok((len, client_addr)) = socket.recv_from(&mut buffer).await else {
+            continue;
+        };
+
+        let upstream = match upstreams.entry(client_addr) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let upstream = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+                upstream.connect(server_addr).await.unwrap();
+
+                replies.push(AbortOnDrop(tokio::spawn(reply(
+                    upstream.clone(),
+                    socket.clone(),
+                    client_addr,
+                    stalled.clone(),
+                ))));
+
+                entry.insert(upstream)
+            }
+        };
+
+        if !stalled.load(Ordering::Relaxed) {
+            let _ = upstream.send(&buffer[..len]).await;
+        }
+    }
+}
+
+async fn reply(
+    upstream: Arc<UdpSocket>,
+    socket: Arc<UdpSocket>,
+    client_addr: SocketAddr,
+    stalled: Arc<AtomicBool>,
+) {
+    let mut buffer = vec![0; u16::MAX as usize];
+
+    loop {
+        // Fails if the server isn't listening yet, such as while it's still starting up
+        let Ok(len) = upstream.recv(&mut buffer).await else {
+            continue;
+        };
+
+        if !stalled.load(Ordering::Relaxed) {
+            let _ = socket.send_to(&buffer[..len], client_addr).await;
+        }
+    }
+}
+
+// Stops forwarding the server's replies once the proxy is dropped
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::Compression;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7035";
+const FALLBACK_ADDR: &str = "127.0.0.1:7036";
+const LEVEL_ADDR: &str = "127.0.0.1:7047";
+
+#[tokio::test]
+async fn test_compressed_messages_are_decompressed() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let zstd = run(SERVER_ADDR, Compression::Zstd).await;
+    let lz4 = run(SERVER_ADDR, Compression::Lz4).await;
+
+    assert_eq!(zstd.unwrap(), message());
+    assert_eq!(lz4.unwrap(), message());
+}
+
+#[tokio::test]
+async fn test_unsupported_compression_falls_back_to_none() {
+    let _server = Server::start_with_args(FALLBACK_ADDR, &["--compression", "lz4"]);
+
+    let result = run(FALLBACK_ADDR, Compression::Zstd).await;
+
+    assert_eq!(result.unwrap(), message());
+}
+
+#[tokio::test]
+async fn test_out_of_range_compression_level_fails_open() {
+    let _server = Server::start(LEVEL_ADDR);
+
+    let result = open_at_level(LEVEL_ADDR, 23).await;
+
+    assert!(matches!(result.unwrap_err(), selium::Error::Config(_)));
+}
+
+fn message() -> String {
+    "Hello, world! ".repeat(100)
+}
+
+async fn run(addr: &str, compression: Compression) -> Result<String, Box<dyn Error>> {
+    let connection = connect(addr).await?;
+
+    let topic = format!("/acmeco/{compression}");
+
+    let mut subscriber = connection
+        .subscriber(&topic)
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher(&topic)
+        .with_encoder(StringCodec)
+        .compression(compression)
+        .open()
+        .await?;
+
+    publisher.send(message()).await?;
+
+    let received = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok(received.unwrap_or_default())
+}
+
+async fn open_at_level(addr: &str, level: i32) -> std::result::Result<(), selium::Error> {
+    let connection = selium::client()
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(addr)
+        .await?;
+
+    connection
+        .publisher("/acmeco/levels")
+        .with_encoder(StringCodec)
+        .compression(Compression::Zstd)
+        .compression_level(level)
+        .open()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::Server;
+use futures::StreamExt;
+use selium::prelude::*;
+use selium::CongestionController;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7033";
+const MESSAGE_COUNT: usize = 100;
+const MESSAGE_SIZE: usize = 1024;
+
+#[tokio::test]
+async fn test_congestion_controllers() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let mut results = Vec::new();
+
+    for controller in [
+        CongestionController::Bbr,
+        CongestionController::Cubic,
+        CongestionController::NewReno,
+    ] {
+        results.push((controller, run(controller).await));
+    }
+
+    for (controller, result) in results {
+        let received = result.unwrap_or_else(|err| panic!("{controller:?} failed: {err}"));
+        assert_eq!(received, MESSAGE_COUNT, "{controller:?}");
+    }
+}
+
+// Returns the number of messages received intact
+async fn run(controller: CongestionController) -> Result<usize, Box<dyn Error>> {
+    let connection = selium::client()
+        .congestion_controller(controller)
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let topic = format!("/acmeco/{controller:?}").to_lowercase();
+    let message = "x".repeat(MESSAGE_SIZE);
+
+    let subscriber = connection
+        .subscriber(&topic)
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher(&topic)
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_batch(vec![message.clone(); MESSAGE_COUNT])
+        .await?;
+
+    let received = tokio::time::timeout(
+        Duration::from_secs(5),
+        subscriber.take(MESSAGE_COUNT).collect::<Vec<_>>(),
+    )
+    .await?;
+
+    let intact = received
+        .into_iter()
+        .filter(|received| received.as_ref().is_ok_and(|received| *received == message))
+        .count();
+
+    Ok(intact)
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::ConnectConfig;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7077";
+
+#[tokio::test]
+async fn test_connect_convenience_functions() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), "Hello, world!");
+}
+
+async fn run() -> Result<String, Box<dyn Error>> {
+    let subscribing = selium::connect(SERVER_ADDR, "certs/ca.crt").await?;
+    let publishing = selium::connect_with(ConnectConfig {
+        connect_timeout: Some(Duration::from_secs(5)),
+        ..ConnectConfig::new(SERVER_ADDR, "certs/ca.crt")
+    })
+    .await?;
+
+    let mut subscriber = subscribing
+        .subscriber("/acmeco/greetings")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = publishing
+        .publisher("/acmeco/greetings")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, world!".to_owned()).await?;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok(message.unwrap_or_default())
+}
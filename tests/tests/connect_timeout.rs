@@ -0,0 +1,24 @@
+use std::time::{Duration, Instant};
+
+// Nothing listens on this port, so the handshake never completes
+const DEAD_ADDR: &str = "127.0.0.1:7012";
+
+#[tokio::test]
+async fn test_connect_timeout() {
+    let started = Instant::now();
+
+    let result = selium::client()
+        .connect_timeout(Duration::from_millis(500))
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(DEAD_ADDR)
+        .await;
+
+    let elapsed = started.elapsed();
+    let err = result.err().expect("Expected connection to time out");
+
+    assert!(err.to_string().starts_with("Timed out connecting"));
+    assert!(elapsed >= Duration::from_millis(500));
+    assert!(elapsed < Duration::from_secs(2));
+}
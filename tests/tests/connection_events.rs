@@ -0,0 +1,78 @@
+mod common;
+
+use common::Server;
+use futures::StreamExt;
+use selium::{codecs::StringCodec, prelude::*, ConnectionEvent, ReconnectPolicy};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7065";
+
+#[tokio::test]
+async fn test_connection_events() {
+    let mut server = Server::start(SERVER_ADDR);
+
+    let events = run(&mut server).await;
+
+    let events = events.unwrap();
+
+    assert!(
+        matches!(&events[0], ConnectionEvent::Disconnected { reason } if !reason.is_empty()),
+        "{events:?}"
+    );
+    // Further attempts may be made while the server is starting up
+    assert_eq!(events[1], ConnectionEvent::Reconnecting { attempt: 1 });
+    assert_eq!(events.last(), Some(&ConnectionEvent::Connected));
+}
+
+async fn run(server: &mut Server) -> Result<Vec<ConnectionEvent>, Box<dyn Error>> {
+    let policy = ReconnectPolicy::default()
+        .initial_backoff(100u64)?
+        .max_retries(30);
+
+    let connection = selium::client()
+        .keep_alive(250)?
+        .max_idle_timeout(1_000)?
+        .reconnect_policy(policy)
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut events = connection.events();
+
+    server.kill();
+
+    // The connection is only noticed to be lost once it idles out
+    let disconnected = tokio::time::timeout(Duration::from_secs(5), events.next())
+        .await?
+        .ok_or("Events ended early")?;
+
+    *server = Server::start(SERVER_ADDR);
+
+    // Opening a stream re-establishes the connection
+    tokio::time::timeout(Duration::from_secs(15), async {
+        loop {
+            let opened = connection
+                .publisher("/acmeco/events")
+                .with_encoder(StringCodec)
+                .open()
+                .await;
+
+            if opened.is_ok() {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await?;
+
+    let mut received = vec![disconnected];
+
+    while let Ok(Some(event)) =
+        tokio::time::timeout(Duration::from_millis(200), events.next()).await
+    {
+        received.push(event);
+    }
+
+    Ok(received)
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7082";
+const MAX_CONCURRENT_STREAMS: &str = "2";
+const MESSAGE_COUNT: usize = 100;
+const MESSAGE_SIZE: usize = 16 * 1024;
+const BULK_BYTES: u64 = (MESSAGE_COUNT * MESSAGE_SIZE) as u64;
+
+#[tokio::test]
+async fn test_connection_per_stream() {
+    let _server = Server::start_with_args(
+        SERVER_ADDR,
+        &["--max-concurrent-streams", MAX_CONCURRENT_STREAMS],
+    );
+
+    let result = run().await;
+
+    let outcome = result.unwrap();
+
+    // Bulk traffic on a dedicated connection doesn't touch the client's connection
+    assert!(outcome.dedicated_bytes < BULK_BYTES, "{outcome:?}");
+    assert!(outcome.shared_bytes >= BULK_BYTES, "{outcome:?}");
+
+    // Each dedicated connection has its own stream limit
+    assert!(matches!(
+        outcome.shared_over_limit,
+        Some(selium::Error::Connection(_))
+    ));
+    assert!(outcome.dedicated_over_limit.is_none());
+}
+
+#[derive(Debug)]
+struct Outcome {
+    // The bytes sent on the client's connection while publishing on each kind of connection
+    dedicated_bytes: u64,
+    shared_bytes: u64,
+    // The errors opening a publisher once the client's connection is at its stream limit
+    shared_over_limit: Option<selium::Error>,
+    dedicated_over_limit: Option<selium::Error>,
+}
+
+async fn run() -> Result<Outcome, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/bulk")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let message = "x".repeat(MESSAGE_SIZE);
+
+    let mut dedicated = connection
+        .publisher("/acmeco/bulk")
+        .with_encoder(StringCodec)
+        .connection_per_stream(true)
+        .open()
+        .await?;
+
+    // The first stream on its own connection
+    assert_eq!(dedicated.stream_id(), 0);
+
+    let dedicated_bytes = bytes_sent_while(&connection, async {
+        for _ in 0..MESSAGE_COUNT {
+            dedicated.send(message.clone()).await?;
+        }
+        receive(&mut subscriber, MESSAGE_COUNT).await
+    })
+    .await?;
+
+    let mut shared = connection
+        .publisher("/acmeco/bulk")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // Opened after the subscriber, on the client's connection
+    assert_ne!(shared.stream_id(), 0);
+
+    let shared_bytes = bytes_sent_while(&connection, async {
+        for _ in 0..MESSAGE_COUNT {
+            shared.send(message.clone()).await?;
+        }
+        receive(&mut subscriber, MESSAGE_COUNT).await
+    })
+    .await?;
+
+    // The subscriber and the shared publisher are using both of the client connection's streams
+    let shared_over_limit = open_publisher(&connection, false).await.err();
+    let dedicated_over_limit = open_publisher(&connection, true).await.err();
+
+    dedicated.finish().await?;
+    shared.finish().await?;
+    connection.graceful_shutdown(5_000).await?;
+
+    Ok(Outcome {
+        dedicated_bytes,
+        shared_bytes,
+        shared_over_limit,
+        dedicated_over_limit,
+    })
+}
+
+/// Returns the number of bytes sent on the client's connection while `f` runs.
+async fn bytes_sent_while<F>(connection: &selium::Client, f: F) -> Result<u64, Box<dyn Error>>
+where
+    F: std::future::Future<Output = Result<(), Box<dyn Error>>>,
+{
+    let before = connection.connection_stats().await.bytes_sent;
+    f.await?;
+    let after = connection.connection_stats().await.bytes_sent;
+
+    Ok(after - before)
+}
+
+async fn receive(
+    subscriber: &mut (impl futures::Stream<Item = selium::Result<String>> + Unpin),
+    count: usize,
+) -> Result<(), Box<dyn Error>> {
+    for _ in 0..count {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+            .await?
+            .ok_or("Subscriber closed")??;
+        assert_eq!(message.len(), MESSAGE_SIZE);
+    }
+
+    Ok(())
+}
+
+async fn open_publisher(
+    connection: &selium::Client,
+    connection_per_stream: bool,
+) -> Result<(), selium::Error> {
+    let publisher = connection
+        .publisher("/acmeco/bulk")
+        .with_encoder(StringCodec)
+        .connection_per_stream(connection_per_stream)
+        .open();
+
+    // Opening a stream beyond the limit should fail, rather than waiting for a free stream
+    tokio::time::timeout(Duration::from_secs(5), publisher)
+        .await
+        .expect("Timed out opening publisher")?
+        .finish()
+        .await
+}
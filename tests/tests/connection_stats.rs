@@ -0,0 +1,43 @@
+mod common;
+
+use common::{connect, Server};
+use futures::SinkExt;
+use selium::{codecs::StringCodec, prelude::*, ConnectionStats};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7020";
+
+#[tokio::test]
+async fn test_connection_stats() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (before, after) = result.unwrap();
+    assert!(after.bytes_sent > before.bytes_sent);
+    assert!(after.datagrams_sent > 0);
+    assert!(after.bytes_received > 0);
+    assert!(after.sent_packets > 0);
+}
+
+async fn run() -> Result<(ConnectionStats, ConnectionStats), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let before = connection.connection_stats().await;
+
+    let mut publisher = connection
+        .publisher("/acmeco/stats")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 0..100 {
+        publisher.send(format!("message {i}")).await?;
+    }
+
+    publisher.finish().await?;
+
+    let after = connection.connection_stats().await;
+
+    Ok((before, after))
+}
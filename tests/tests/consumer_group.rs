@@ -0,0 +1,78 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition, Subscriber};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7016";
+const MESSAGE_COUNT: usize = 100;
+
+#[tokio::test]
+async fn test_consumer_group_shares_messages() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (first, second) = result.unwrap();
+    assert_eq!(first + second, MESSAGE_COUNT);
+    assert!((40..=60).contains(&first), "first member received {first}");
+    assert!(
+        (40..=60).contains(&second),
+        "second member received {second}"
+    );
+}
+
+async fn run() -> Result<(usize, usize), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut members = Vec::new();
+
+    for _ in 0..2 {
+        let subscriber = connection
+            .subscriber("/acmeco/jobs")
+            .with_decoder(StringCodec)
+            .start_position(StartPosition::Latest)
+            .group("workers")
+            .open()
+            .await?;
+
+        members.push(tokio::spawn(count_messages(subscriber)));
+    }
+
+    let mut publisher = connection
+        .publisher("/acmeco/jobs")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 0..MESSAGE_COUNT {
+        publisher.send(format!("job {i}")).await?;
+    }
+
+    publisher.finish().await?;
+
+    let second = members.pop().unwrap().await??;
+    let first = members.pop().unwrap().await??;
+
+    Ok((first, second))
+}
+
+/// Counts the messages received by a group member, until no more arrive.
+async fn count_messages(
+    mut subscriber: Subscriber<StringCodec, String>,
+) -> Result<usize, anyhow::Error> {
+    let mut count = 0;
+
+    while let Ok(message) =
+        tokio::time::timeout(Duration::from_millis(1_000), subscriber.try_next()).await
+    {
+        if message?.is_none() {
+            break;
+        }
+
+        count += 1;
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use bytes::Bytes;
+use common::{connect, Server};
+use futures::{SinkExt, Stream, StreamExt};
+use selium::codecs::{RawBytesCodec, StringCodec};
+use selium::{prelude::*, Client, DecodeErrorPolicy, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7027";
+
+#[tokio::test]
+async fn test_decode_error_policy() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (skipped, failed) = result.unwrap();
+    assert_eq!(skipped, vec!["first", "second"]);
+    assert_eq!(
+        failed,
+        vec![Ok("first".to_owned()), Err(true), Ok("second".to_owned())]
+    );
+}
+
+// Each failed message is replaced with whether its error was a codec error
+async fn run() -> Result<(Vec<String>, Vec<Result<String, bool>>), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let skipping = subscribe(&connection, DecodeErrorPolicy::SkipAndContinue).await?;
+    let failing = subscribe(&connection, DecodeErrorPolicy::Fail).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/decode_error")
+        .with_encoder(RawBytesCodec)
+        .open()
+        .await?;
+
+    publisher.send(Bytes::from("first")).await?;
+    // Not valid UTF-8, so the StringCodec fails to decode it
+    publisher.send(Bytes::from_static(&[0xff, 0xfe])).await?;
+    publisher.send(Bytes::from("second")).await?;
+
+    let skipped = tokio::time::timeout(
+        Duration::from_secs(5),
+        skipping.take(2).map(|message| message.unwrap()).collect(),
+    )
+    .await?;
+
+    let failed = tokio::time::timeout(
+        Duration::from_secs(5),
+        failing
+            .take(3)
+            .map(|message| message.map_err(|err| matches!(err, selium::Error::Codec(_))))
+            .collect(),
+    )
+    .await?;
+
+    Ok((skipped, failed))
+}
+
+async fn subscribe(
+    connection: &Client,
+    policy: DecodeErrorPolicy,
+) -> Result<impl Stream<Item = selium::Result<String>>, Box<dyn Error>> {
+    let subscriber = connection
+        .subscriber("/acmeco/decode_error")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .on_decode_error(policy)
+        .open()
+        .await?;
+
+    Ok(subscriber)
+}
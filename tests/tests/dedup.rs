@@ -0,0 +1,59 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7041";
+
+#[tokio::test]
+async fn test_duplicate_keys_are_delivered_once() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), vec!["order", "unkeyed", "unkeyed"]);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/orders")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/orders")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_with_key("order".to_owned(), "order-1")
+        .await?;
+
+    // Retries are deduplicated across publishers on the same topic
+    let mut retry = publisher.duplicate().await?;
+    retry.send_with_key("order".to_owned(), "order-1").await?;
+
+    // Messages without a key are never deduplicated
+    retry.send("unkeyed".to_owned()).await?;
+    retry.send("unkeyed".to_owned()).await?;
+
+    let mut received = Vec::new();
+
+    for _ in 0..3 {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        received.extend(message);
+    }
+
+    // The publishers' messages may be interleaved
+    received.sort();
+
+    Ok(received)
+}
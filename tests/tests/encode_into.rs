@@ -0,0 +1,74 @@
+use bytes::{Bytes, BytesMut};
+use selium::codecs::{LinesCodec, VersionedCodec};
+use selium::traits::MessageEncoder;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const MESSAGES: usize = 10_000;
+// Mirrors the buffer that a Publisher encodes its messages into
+const BUFFER_CAPACITY: usize = 8 * 1024;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// Counts every allocation made by this test binary
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_encode_into_allocates_less_than_encode() {
+    let codec = VersionedCodec::new(LinesCodec::default(), 1, []);
+    let items: Vec<String> = (0..MESSAGES).map(|i| format!("message {i}")).collect();
+    let items_into = items.clone();
+
+    let mut encoded = Vec::with_capacity(MESSAGES);
+    let encode_allocations = count_allocations(|| {
+        for item in items {
+            encoded.push(codec.encode(item).unwrap());
+        }
+    });
+
+    let mut encoded_into: Vec<Bytes> = Vec::with_capacity(MESSAGES);
+    let mut buffer = BytesMut::new();
+    let encode_into_allocations = count_allocations(|| {
+        for item in items_into {
+            if buffer.capacity() < BUFFER_CAPACITY / 4 {
+                buffer.reserve(BUFFER_CAPACITY);
+            }
+
+            codec.encode_into(item, &mut buffer).unwrap();
+            encoded_into.push(buffer.split().freeze());
+        }
+    });
+
+    assert_eq!(encoded, encoded_into);
+    // Encoding into a shared buffer only allocates once the buffer runs low
+    assert!(encode_allocations >= MESSAGES, "{encode_allocations}");
+    assert!(
+        encode_into_allocations < MESSAGES / 100,
+        "{encode_into_allocations}"
+    );
+}
+
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use bytes::Bytes;
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::codecs::{RawBytesCodec, StringCodec};
+use selium::prelude::*;
+use selium::{ReplayStart, StartPosition, StreamReset};
+use std::time::Duration;
+
+const SERVER_ADDR: &str = "127.0.0.1:7024";
+const SELF_SIGNED_ADDR: &str = "127.0.0.1:7025";
+const RESET_ADDR: &str = "127.0.0.1:7067";
+// Nothing listens on this port, so the handshake never completes
+const DEAD_ADDR: &str = "127.0.0.1:7026";
+
+#[tokio::test]
+async fn test_connect_timeout_error() {
+    let err = selium::client()
+        .connect_timeout(Duration::from_millis(200))
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(DEAD_ADDR)
+        .await
+        .err()
+        .expect("Expected connection to time out");
+
+    assert!(matches!(err, selium::Error::Timeout(_)), "{err:?}");
+}
+
+#[test]
+fn test_missing_certificate_authority_error() {
+    let err = selium::client()
+        .with_certificate_authority("certs/missing.crt")
+        .expect_err("Expected CA certificate to be missing");
+
+    assert!(matches!(err, selium::Error::Tls(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn test_untrusted_server_error() {
+    let _server = Server::start_self_signed(SELF_SIGNED_ADDR);
+
+    let result = selium::client()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SELF_SIGNED_ADDR)
+        .await;
+
+    let err = result.err().expect("Expected server to be untrusted");
+    assert!(matches!(err, selium::Error::Tls(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn test_invalid_keep_alive_error() {
+    let err = selium::client()
+        .keep_alive(10_000)
+        .unwrap()
+        .max_idle_timeout(5_000)
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(DEAD_ADDR)
+        .await
+        .err()
+        .expect("Expected keep_alive to be rejected");
+
+    assert!(matches!(err, selium::Error::Config(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn test_decode_error() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = decode_invalid_message().await;
+
+    let err = result.expect_err("Expected message to fail to decode");
+    assert!(matches!(err, selium::Error::Codec(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn test_stream_reset_error() {
+    let _server = Server::start(RESET_ADDR);
+
+    let result = receive_on_rejected_stream().await;
+
+    match result.expect_err("Expected stream to be reset") {
+        selium::Error::StreamReset(err) => assert_eq!(err.code, StreamReset::REJECTED),
+        err => panic!("Unexpected error: {err:?}"),
+    }
+}
+
+async fn receive_on_rejected_stream() -> Result<Option<String>, selium::Error> {
+    let connection = selium::client()
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(RESET_ADDR)
+        .await?;
+
+    // The server isn't persisting topic logs, so it rejects the subscription
+    let mut subscriber = connection
+        .subscriber("/acmeco/errors")
+        .with_decoder(StringCodec)
+        .replay_from(ReplayStart::Earliest)
+        .open()
+        .await?;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next())
+        .await
+        .expect("Expected the server to reset the stream")?;
+
+    Ok(message)
+}
+
+async fn decode_invalid_message() -> Result<String, selium::Error> {
+    let connection = selium::client()
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/errors")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/errors")
+        .with_encoder(RawBytesCodec)
+        .open()
+        .await?;
+
+    // Not valid UTF-8, so the StringCodec fails to decode it
+    publisher.send(Bytes::from_static(&[0xff, 0xfe])).await?;
+
+    let message = subscriber.try_next().await?;
+
+    Ok(message.unwrap_or_default())
+}
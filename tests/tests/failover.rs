@@ -0,0 +1,76 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+// Nothing listens on these ports, so the handshake never completes
+const DEAD_ADDR: &str = "127.0.0.1:7021";
+const OTHER_DEAD_ADDR: &str = "127.0.0.1:7023";
+const SERVER_ADDR: &str = "127.0.0.1:7022";
+
+#[tokio::test]
+async fn test_fails_over_to_next_endpoint() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (endpoint, message) = result.unwrap();
+    assert_eq!(endpoint.to_string(), SERVER_ADDR);
+    assert_eq!(message, "via fallback");
+}
+
+#[tokio::test]
+async fn test_reports_each_endpoint_failure() {
+    let result = selium::client()
+        .connect_timeout(500)
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .add_endpoint(OTHER_DEAD_ADDR)
+        .connect(DEAD_ADDR)
+        .await;
+
+    let err = result
+        .err()
+        .expect("Expected every endpoint to fail")
+        .to_string();
+
+    assert!(err.starts_with("Failed to connect to any endpoint"));
+    assert!(err.contains(&format!("{DEAD_ADDR}: Timed out connecting")));
+    assert!(err.contains(&format!("{OTHER_DEAD_ADDR}: Timed out connecting")));
+}
+
+async fn run() -> Result<(std::net::SocketAddr, String), Box<dyn Error>> {
+    // Wait for the server to start, so that only the dead endpoint times out
+    connect(SERVER_ADDR).await?;
+
+    let connection = selium::client()
+        .keep_alive(5_000)?
+        .connect_timeout(500)?
+        .with_certificate_authority("certs/ca.crt")?
+        .add_endpoint(SERVER_ADDR)
+        .connect(DEAD_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/failover")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/failover")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("via fallback".to_owned()).await?;
+    let message = subscriber.try_next().await?.unwrap_or_default();
+
+    publisher.finish().await?;
+
+    Ok((connection.endpoint(), message))
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7039";
+
+#[tokio::test]
+async fn test_filter_drops_rejected_messages() {
+    let _server = Server::start_with_args(SERVER_ADDR, &["--modules", "tests/modules"]);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), vec!["2", "4", "6", "8", "10"]);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/filter")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .filter("/even.wat")
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/filter")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 1..=10 {
+        publisher.send(i.to_string()).await?;
+    }
+
+    let received = tokio::time::timeout(
+        Duration::from_secs(5),
+        subscriber.take(5).collect::<Vec<_>>(),
+    )
+    .await?;
+
+    Ok(received.into_iter().collect::<Result<_, _>>()?)
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7054";
+
+#[tokio::test]
+async fn test_filter_map() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), [2, 4, 6]);
+}
+
+async fn run() -> Result<Vec<u32>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/filter_map")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/filter_map")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for number in 1..=6 {
+        publisher.send(number.to_string()).await?;
+    }
+
+    publisher.finish().await?;
+
+    let mut evens = subscriber.filter_map(|message| {
+        let number = message.parse::<u32>().ok()?;
+        number.is_multiple_of(2).then_some(number)
+    });
+
+    let mut received = Vec::new();
+
+    for _ in 0..3 {
+        received.extend(evens.try_next().await?);
+    }
+
+    Ok(received)
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7055";
+
+#[tokio::test]
+async fn test_finish_on_drop() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), ["first", "last"]);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/finish_on_drop")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/finish_on_drop")
+        .with_encoder(StringCodec)
+        .flush_interval(60_000)?
+        .finish_on_drop()
+        .open()
+        .await?;
+
+    publisher.send("first".to_owned()).await?;
+    publisher.send("last".to_owned()).await?;
+
+    // The messages are still buffered awaiting the flush interval when the publisher is dropped
+    drop(publisher);
+
+    let window = Duration::from_secs(5);
+    let mut messages = Vec::new();
+    messages.extend(tokio::time::timeout(window, subscriber.try_next()).await??);
+    messages.extend(tokio::time::timeout(window, subscriber.try_next()).await??);
+
+    Ok(messages)
+}
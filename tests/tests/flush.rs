@@ -0,0 +1,60 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7053";
+// Long enough that messages can only arrive within the test because of an explicit flush
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[tokio::test]
+async fn test_flush() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let messages = result.unwrap();
+    assert_eq!(messages, ["foo", "bar", "baz"]);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/explicit_flush")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/explicit_flush")
+        .with_encoder(StringCodec)
+        .flush_interval(FLUSH_INTERVAL)?
+        .open()
+        .await?;
+
+    // The first interval elapses immediately, so wait for it to pass before buffering messages
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    publisher.send("foo".to_owned()).await?;
+    publisher.send("bar".to_owned()).await?;
+    publisher.flush().await?;
+
+    // Messages should arrive once flushed, without finishing the publisher
+    let window = Duration::from_secs(5);
+    let mut messages = Vec::new();
+    messages.extend(tokio::time::timeout(window, subscriber.try_next()).await??);
+    messages.extend(tokio::time::timeout(window, subscriber.try_next()).await??);
+
+    // The publisher remains usable after flushing
+    publisher.send("baz".to_owned()).await?;
+    publisher.flush().await?;
+    messages.extend(tokio::time::timeout(window, subscriber.try_next()).await??);
+
+    publisher.finish().await?;
+
+    Ok(messages)
+}
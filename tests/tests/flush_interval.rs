@@ -0,0 +1,49 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7003";
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn test_flush_interval() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let messages = result.unwrap();
+    assert_eq!(messages, ["foo", "bar"]);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/flush")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/flush")
+        .with_encoder(StringCodec)
+        .flush_interval(FLUSH_INTERVAL)?
+        .open()
+        .await?;
+
+    publisher.send("foo".to_owned()).await?;
+    publisher.send("bar".to_owned()).await?;
+
+    // Messages should arrive once the interval elapses, without finishing the publisher
+    let window = FLUSH_INTERVAL * 10;
+    let first = tokio::time::timeout(window, subscriber.try_next()).await??;
+    let second = tokio::time::timeout(window, subscriber.try_next()).await??;
+
+    publisher.finish().await?;
+
+    Ok(first.into_iter().chain(second).collect())
+}
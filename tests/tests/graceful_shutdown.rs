@@ -0,0 +1,71 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7014";
+const BUFFERED_COUNT: usize = 100;
+
+#[tokio::test]
+async fn test_graceful_shutdown() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (mut messages, send_after_shutdown) = result.unwrap();
+    messages.sort();
+
+    let mut expected: Vec<String> = (0..BUFFERED_COUNT)
+        .map(|i| format!("buffered {i:03}"))
+        .collect();
+    expected.push("direct".to_owned());
+
+    assert_eq!(messages, expected);
+    assert!(send_after_shutdown.is_err());
+}
+
+async fn run() -> Result<(Vec<String>, Result<(), selium::Error>), Box<dyn Error>> {
+    let subscriber = connect(SERVER_ADDR)
+        .await?
+        .subscriber("/acmeco/shutdown")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let client = connect(SERVER_ADDR).await?;
+
+    // A long flush interval means these messages are still buffered when shutting down
+    let mut buffered = client
+        .publisher("/acmeco/shutdown")
+        .with_encoder(StringCodec)
+        .flush_interval(60_000)?
+        .open()
+        .await?;
+
+    let mut direct = client
+        .publisher("/acmeco/shutdown")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 0..BUFFERED_COUNT {
+        buffered.send(format!("buffered {i:03}")).await?;
+    }
+
+    direct.send("direct".to_owned()).await?;
+
+    client.graceful_shutdown(5_000).await?;
+
+    let send_after_shutdown = direct.send("too late".to_owned()).await;
+
+    let messages = tokio::time::timeout(
+        Duration::from_secs(5),
+        subscriber.take(BUFFERED_COUNT + 1).try_collect(),
+    )
+    .await??;
+
+    Ok((messages, send_after_shutdown))
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, Headers, MessageWithHeaders, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7006";
+
+#[tokio::test]
+async fn test_message_headers() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let messages = result.unwrap();
+
+    assert_eq!(
+        messages,
+        vec![
+            MessageWithHeaders {
+                topic: "/acmeco/headers".to_owned(),
+                headers: headers(),
+                payload: "with headers".to_owned(),
+            },
+            MessageWithHeaders {
+                topic: "/acmeco/headers".to_owned(),
+                headers: Headers::new(),
+                payload: "without headers".to_owned(),
+            },
+        ]
+    );
+}
+
+fn headers() -> Headers {
+    Headers::from([
+        ("content-type".to_owned(), "text/plain".to_owned()),
+        ("trace-id".to_owned(), "4bf92f3577b34da6".to_owned()),
+    ])
+}
+
+async fn run() -> Result<Vec<MessageWithHeaders<String>>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/headers")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_headers()
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/headers")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_with_headers("with headers".to_owned(), headers())
+        .await?;
+    publisher.send("without headers".to_owned()).await?;
+    publisher.finish().await?;
+
+    let messages = subscriber.take(2).try_collect().await?;
+
+    Ok(messages)
+}
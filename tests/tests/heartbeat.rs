@@ -0,0 +1,52 @@
+mod common;
+
+use common::Server;
+use futures::StreamExt;
+use selium::{codecs::StringCodec, prelude::*};
+use std::time::{Duration, Instant};
+
+const SERVER_ADDR: &str = "127.0.0.1:7048";
+
+#[tokio::test]
+async fn test_unanswered_heartbeats_surface_connection_lost() {
+    let mut server = Server::start(SERVER_ADDR);
+
+    let connection = selium::client()
+        .max_idle_timeout(30_000)
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await
+        .unwrap();
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/heartbeat")
+        .with_decoder(StringCodec)
+        .heartbeat(Duration::from_millis(200), 3)
+        .unwrap()
+        .open()
+        .await
+        .unwrap();
+
+    // Heartbeats are answered while the server is running, so the idle subscriber stays open
+    let idle = tokio::time::timeout(Duration::from_secs(1), subscriber.next()).await;
+    assert!(idle.is_err(), "Expected no messages, got {idle:?}");
+
+    server.kill();
+    let killed = Instant::now();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+        .await
+        .expect("Expected unanswered heartbeats to be detected");
+
+    // Detected within (max_missed + 1) * interval, allowing for scheduling delays
+    assert!(killed.elapsed() < Duration::from_millis(1_500));
+    assert!(matches!(
+        result,
+        Some(Err(selium::Error::ConnectionLost(_)))
+    ));
+
+    // The stream ends once the connection is lost
+    assert!(subscriber.next().await.is_none());
+}
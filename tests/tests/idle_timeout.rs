@@ -0,0 +1,122 @@
+mod common;
+
+use common::{Server, StallingProxy};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7013";
+
+#[tokio::test]
+async fn test_max_idle_timeout() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let proxy = StallingProxy::start(SERVER_ADDR).await;
+
+    let result = run(&proxy).await;
+
+    let (below_timeout, above_timeout_closed) = result.unwrap();
+    assert_eq!(below_timeout, "still alive");
+    assert!(above_timeout_closed);
+}
+
+#[tokio::test]
+async fn test_keep_alive_must_be_less_than_idle_timeout() {
+    let result = selium::client()
+        .keep_alive(2_000)
+        .unwrap()
+        .max_idle_timeout(1_000)
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await;
+
+    let err = result
+        .err()
+        .expect("Expected invalid keep_alive to be rejected");
+    assert!(err
+        .to_string()
+        .contains("must be less than the max_idle_timeout"));
+}
+
+#[tokio::test]
+async fn test_keep_alive_duration_must_be_less_than_idle_timeout() {
+    let result = selium::client()
+        .keep_alive(Duration::from_secs(2))
+        .unwrap()
+        .max_idle_timeout(Duration::from_secs(1))
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await;
+
+    let err = result
+        .err()
+        .expect("Expected invalid keep_alive to be rejected");
+    assert!(matches!(err, selium::Error::Config(_)), "{err:?}");
+    assert_eq!(
+        err.to_string(),
+        "The keep_alive interval (2000ms) must be less than the max_idle_timeout (1000ms), \
+        otherwise idle connections will be closed prematurely"
+    );
+}
+
+#[tokio::test]
+async fn test_keep_alive_must_be_positive() {
+    let result = selium::client()
+        .keep_alive(Duration::ZERO)
+        .unwrap()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await;
+
+    let err = result
+        .err()
+        .expect("Expected zero keep_alive to be rejected");
+    assert!(matches!(err, selium::Error::Config(_)), "{err:?}");
+    assert!(err.to_string().contains("must be greater than zero"));
+}
+
+async fn run(proxy: &StallingProxy) -> Result<(String, bool), Box<dyn Error>> {
+    let connection = selium::client()
+        .keep_alive(Duration::from_millis(250))?
+        .max_idle_timeout(Duration::from_secs(1))?
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(proxy.addr())
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/idle")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/idle")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // Idle for less than the timeout, which the connection should survive
+    proxy.stall();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    proxy.resume();
+
+    publisher.send("still alive".to_owned()).await?;
+    let below_timeout = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next())
+        .await??
+        .unwrap_or_default();
+
+    // Idle for longer than the timeout, which should close the connection
+    proxy.stall();
+    tokio::time::sleep(Duration::from_millis(2_000)).await;
+
+    let next = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await?;
+    let above_timeout_closed = !matches!(next, Ok(Some(_)));
+
+    Ok((below_timeout, above_timeout_closed))
+}
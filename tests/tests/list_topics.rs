@@ -0,0 +1,58 @@
+mod common;
+
+use common::{connect, eventually, Server};
+use selium::prelude::*;
+use selium::TopicInfo;
+use selium::{codecs::StringCodec, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7071";
+const ADMIN_TOKEN: &str = "admin-token";
+
+#[tokio::test]
+async fn test_lists_active_topics() {
+    let _server = Server::start_with_args(SERVER_ADDR, &["--admin-token", ADMIN_TOKEN]);
+
+    let result = run().await;
+
+    let (topics, unauthorized) = result.unwrap();
+
+    assert_eq!(
+        topics,
+        vec![TopicInfo {
+            topic: "/acmeco/stocks".to_owned(),
+            publishers: 1,
+            subscribers: 1,
+        }]
+    );
+    assert!(matches!(unauthorized, Err(selium::Error::Unauthorized(_))));
+}
+
+async fn run() -> Result<(Vec<TopicInfo>, selium::Result<Vec<TopicInfo>>), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let _subscriber = connection
+        .subscriber("/acmeco/stocks")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+    let _publisher = connection
+        .publisher("/acmeco/stocks")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let topics = eventually(|| async {
+        let topics = connection.list_topics(ADMIN_TOKEN).await?;
+        let registered = topics
+            .iter()
+            .all(|t| t.publishers == 1 && t.subscribers == 1);
+
+        Ok((!topics.is_empty() && registered).then_some(topics))
+    })
+    .await?;
+    let unauthorized = connection.list_topics("not-the-admin-token").await;
+
+    Ok((topics, unauthorized))
+}
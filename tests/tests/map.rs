@@ -0,0 +1,42 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7038";
+
+#[tokio::test]
+async fn test_map_transforms_delivered_messages() {
+    let _server = Server::start_with_args(SERVER_ADDR, &["--modules", "tests/modules"]);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), "HELLO, WORLD!");
+}
+
+async fn run() -> Result<String, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/map")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .map("/uppercase.wat")
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/map")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, world!".to_owned()).await?;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok(message.unwrap_or_default())
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use selium::prelude::*;
+use selium::BufferCapacityExceeded;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7079";
+const MAX_BUFFER_CAPACITY: u64 = 64;
+
+#[tokio::test]
+async fn test_oversized_frame_exceeds_max_buffer_capacity() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (received, err, ended) = result.unwrap();
+    assert_eq!(received, "small");
+    assert_eq!(
+        err,
+        Some(BufferCapacityExceeded {
+            required: 141,
+            max_buffer_capacity: MAX_BUFFER_CAPACITY
+        })
+    );
+    assert!(ended);
+}
+
+// Returns the message received before the oversized message, the error for the oversized
+// message, and whether the stream ended after it
+async fn run() -> Result<(String, Option<BufferCapacityExceeded>, bool), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/max_buffer_capacity")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .max_buffer_capacity(MAX_BUFFER_CAPACITY)?
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/max_buffer_capacity")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("small".to_owned()).await?;
+    publisher.send("x".repeat(128)).await?;
+
+    let timeout = Duration::from_secs(5);
+    let received = tokio::time::timeout(timeout, subscriber.next()).await?;
+    let oversized = tokio::time::timeout(timeout, subscriber.next()).await?;
+    let ended = tokio::time::timeout(timeout, subscriber.next())
+        .await?
+        .is_none();
+
+    let err = match oversized {
+        Some(Err(selium::Error::BufferCapacityExceeded(err))) => Some(err),
+        _ => None,
+    };
+
+    Ok((received.transpose()?.unwrap_or_default(), err, ended))
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::Server;
+use selium::codecs::StringCodec;
+use selium::prelude::*;
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7034";
+const MAX_CONCURRENT_STREAMS: usize = 2;
+
+#[tokio::test]
+async fn test_stream_limit_error() {
+    let limit = MAX_CONCURRENT_STREAMS.to_string();
+    let _server = Server::start_with_args(SERVER_ADDR, &["--max-concurrent-streams", &limit]);
+
+    let result = open_publishers().await;
+
+    let (opened, err) = result.unwrap();
+    assert_eq!(opened, MAX_CONCURRENT_STREAMS);
+    assert!(matches!(err, Some(selium::Error::Connection(_))), "{err:?}");
+}
+
+// Returns the number of publishers opened, and the error opening the one after them
+async fn open_publishers() -> Result<(usize, Option<selium::Error>), Box<dyn Error>> {
+    let connection = selium::client()
+        .max_concurrent_streams(10)
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut publishers = Vec::new();
+
+    for i in 0..=MAX_CONCURRENT_STREAMS {
+        let publisher = connection
+            .publisher(&format!("/acmeco/streams_{i}"))
+            .with_encoder(StringCodec)
+            .open();
+
+        // Opening a stream beyond the limit should fail, rather than waiting for a free stream
+        match tokio::time::timeout(Duration::from_secs(5), publisher).await? {
+            Ok(publisher) => publishers.push(publisher),
+            Err(err) => return Ok((publishers.len(), Some(err))),
+        }
+    }
+
+    Ok((publishers.len(), None))
+}
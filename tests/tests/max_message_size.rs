@@ -0,0 +1,103 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::prelude::*;
+use selium::MessageTooLarge;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const ENCODE_ADDR: &str = "127.0.0.1:7030";
+const DECODE_ADDR: &str = "127.0.0.1:7031";
+const MAX_MESSAGE_SIZE: u64 = 16;
+
+#[tokio::test]
+async fn test_oversized_message_is_rejected_on_encode() {
+    let _server = Server::start(ENCODE_ADDR);
+
+    let result = encode_oversized_message().await;
+
+    let (err, received) = result.unwrap();
+    assert_eq!(
+        err,
+        Some(MessageTooLarge {
+            length: 32,
+            max_message_size: MAX_MESSAGE_SIZE
+        })
+    );
+    assert_eq!(received, Some("small".to_owned()));
+}
+
+#[tokio::test]
+async fn test_oversized_message_is_rejected_on_decode() {
+    let _server = Server::start(DECODE_ADDR);
+
+    let result = decode_oversized_message().await;
+
+    assert_eq!(
+        result.unwrap(),
+        Some(MessageTooLarge {
+            length: 32,
+            max_message_size: MAX_MESSAGE_SIZE
+        })
+    );
+}
+
+// Returns the error for the oversized message, and the message received after it
+async fn encode_oversized_message(
+) -> Result<(Option<MessageTooLarge>, Option<String>), Box<dyn Error>> {
+    let connection = connect(ENCODE_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/max_message_size")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/max_message_size")
+        .with_encoder(StringCodec)
+        .max_message_size(MAX_MESSAGE_SIZE)?
+        .open()
+        .await?;
+
+    let err = publisher.send("x".repeat(32)).await.err();
+    // The publisher remains usable after rejecting the oversized message
+    publisher.send("small".to_owned()).await?;
+
+    let received = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok((too_large(err), received))
+}
+
+async fn decode_oversized_message() -> Result<Option<MessageTooLarge>, Box<dyn Error>> {
+    let connection = connect(DECODE_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/max_message_size")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .max_message_size(MAX_MESSAGE_SIZE)?
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/max_message_size")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("x".repeat(32)).await?;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next()).await?;
+
+    Ok(too_large(message.and_then(Result::err)))
+}
+
+fn too_large(err: Option<selium::Error>) -> Option<MessageTooLarge> {
+    match err? {
+        selium::Error::MessageTooLarge(err) => Some(err),
+        _ => None,
+    }
+}
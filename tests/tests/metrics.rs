@@ -0,0 +1,71 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use metrics_util::{
+    debugging::{DebugValue, DebuggingRecorder},
+    CompositeKey,
+};
+use selium::metrics::{MESSAGES_CONSUMED, MESSAGES_PUBLISHED, TOPIC_LABEL};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7029";
+const TOPIC: &str = "/acmeco/metrics";
+
+#[tokio::test]
+async fn test_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::set_global_recorder(recorder).unwrap();
+
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), "Hello, world!");
+
+    // Taking a snapshot resets the counters, so both are read from the same one
+    let snapshot = snapshotter.snapshot().into_vec();
+    assert_eq!(counter(&snapshot, MESSAGES_PUBLISHED), Some(1));
+    assert_eq!(counter(&snapshot, MESSAGES_CONSUMED), Some(1));
+}
+
+// Returns the value of the named counter for the topic, if it has been recorded
+fn counter<U, D>(snapshot: &[(CompositeKey, U, D, DebugValue)], name: &str) -> Option<u64> {
+    snapshot.iter().find_map(|(key, _, _, value)| {
+        let key = key.key();
+        let topic = key.labels().find(|label| label.key() == TOPIC_LABEL)?;
+
+        match value {
+            DebugValue::Counter(count) if key.name() == name && topic.value() == TOPIC => {
+                Some(*count)
+            }
+            _ => None,
+        }
+    })
+}
+
+async fn run() -> Result<String, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber(TOPIC)
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher(TOPIC)
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, world!".to_owned()).await?;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok(message.unwrap_or_default())
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, ClientBuilder, ClientWantsCert, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7009";
+const SERVER_ARGS: &[&str] = &["--client-ca", "tests/certs/ca.crt"];
+
+#[tokio::test]
+async fn test_mutual_tls() {
+    let _server = Server::start_with_args(SERVER_ADDR, SERVER_ARGS);
+
+    let authenticated = selium::client().with_client_certificate("certs/ca.crt", "certs/ca.key");
+    let authenticated = match authenticated {
+        Ok(builder) => run(builder).await,
+        Err(err) => Err(err.into()),
+    };
+    let unauthenticated = run(selium::client()).await;
+
+    assert_eq!(authenticated.unwrap(), "Hello, mTLS!");
+    assert!(unauthenticated.is_err());
+}
+
+#[test]
+fn test_client_certificate_key_mismatch() {
+    let result = selium::client().with_client_certificate("certs/ca.crt", "certs/mismatched.key");
+
+    let err = result.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Client private key does not match the client certificate"
+    );
+}
+
+async fn run(builder: ClientBuilder<ClientWantsCert>) -> Result<String, Box<dyn Error>> {
+    let connection = builder
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/mtls")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/mtls")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, mTLS!".to_owned()).await?;
+    publisher.finish().await?;
+
+    let message = subscriber.try_next().await?.unwrap_or_default();
+
+    Ok(message)
+}
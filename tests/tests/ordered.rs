@@ -0,0 +1,51 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7042";
+const MESSAGE_COUNT: u64 = 500;
+
+#[tokio::test]
+async fn test_ordered_subscriber_receives_messages_in_sequence() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let expected: Vec<_> = (0..MESSAGE_COUNT).collect();
+    assert_eq!(result.unwrap(), expected);
+}
+
+async fn run() -> Result<Vec<u64>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/events")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .ordered()
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/events")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 0..MESSAGE_COUNT {
+        publisher.send(i.to_string()).await?;
+    }
+
+    let mut received = Vec::new();
+
+    for _ in 0..MESSAGE_COUNT {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        received.extend(message.map(|message| message.parse::<u64>()).transpose()?);
+    }
+
+    Ok(received)
+}
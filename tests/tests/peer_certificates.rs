@@ -0,0 +1,35 @@
+mod common;
+
+use common::{connect, Server};
+use std::{error::Error, fs, time::SystemTime};
+
+const SERVER_ADDR: &str = "127.0.0.1:7059";
+
+#[tokio::test]
+async fn test_peer_certificates() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let certs = result.unwrap();
+    let ca = fs::read("certs/ca.crt").unwrap();
+    let expected = rustls_pemfile::certs(&mut &*ca).unwrap().remove(0);
+
+    assert_eq!(certs.len(), 1);
+
+    let cert = &certs[0];
+    let now = SystemTime::now();
+
+    // The test server presents the self-signed CA certificate
+    assert_eq!(cert.subject, "CN=rcgen self signed cert");
+    assert_eq!(cert.issuer, cert.subject);
+    assert!(cert.not_before < now && now < cert.not_after);
+    assert_eq!(cert.fingerprint.len(), 32 * 3 - 1);
+    assert_eq!(cert.der, expected);
+}
+
+async fn run() -> Result<Vec<selium::CertInfo>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    Ok(connection.peer_certificates().await)
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{future::poll_fn, SinkExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7073";
+
+#[tokio::test]
+async fn test_drives_publisher_sink_manually() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let expected: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+    assert_eq!(result.unwrap(), expected);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/poll_sink")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/poll_sink")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 0..10 {
+        poll_fn(|cx| publisher.poll_ready_unpin(cx)).await?;
+        publisher.start_send_unpin(i.to_string())?;
+    }
+
+    poll_fn(|cx| publisher.poll_flush_unpin(cx)).await?;
+
+    let received = subscriber
+        .collect_with_timeout(10, Duration::from_secs(5))
+        .await?;
+
+    publisher.finish().await?;
+
+    Ok(received)
+}
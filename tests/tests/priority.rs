@@ -0,0 +1,91 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7083";
+const MESSAGE_SIZE: usize = 64 * 1024;
+const BULK_COUNT: usize = 200;
+const URGENT_COUNT: usize = 16;
+// The most bulk messages that may be delivered between the first and last urgent message
+const MAX_INTERLEAVED: usize = URGENT_COUNT / 4;
+
+#[tokio::test]
+async fn test_priority() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let order = result.unwrap();
+    let urgent: Vec<usize> = order
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, message)| message.starts_with('u').then_some(idx))
+        .collect();
+
+    assert_eq!(urgent.len(), URGENT_COUNT);
+
+    // This is a best effort, as the streams only contend while the bulk messages are queued.
+    // Without a priority, the streams take turns, so every other message would be a bulk one.
+    let interleaved = urgent[URGENT_COUNT - 1] - urgent[0] + 1 - URGENT_COUNT;
+    assert!(interleaved <= MAX_INTERLEAVED, "{urgent:?}");
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    // Subscribes from a separate connection, so that receiving doesn't contend with publishing
+    let subscriber = connect(SERVER_ADDR)
+        .await?
+        .subscriber("/acmeco/priority")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut bulk = connection
+        .publisher("/acmeco/priority")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut urgent = connection
+        .publisher("/acmeco/priority")
+        .with_encoder(StringCodec)
+        .priority(10)
+        .open()
+        .await?;
+
+    let bulk_task = tokio::spawn(async move {
+        let message = "b".repeat(MESSAGE_SIZE);
+
+        for _ in 0..BULK_COUNT {
+            bulk.send(message.clone()).await?;
+        }
+
+        bulk.finish().await
+    });
+
+    // Give the bulk messages a moment to queue up behind the connection's congestion window
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let message = "u".repeat(MESSAGE_SIZE);
+
+    for _ in 0..URGENT_COUNT {
+        urgent.send(message.clone()).await?;
+    }
+
+    let order = tokio::time::timeout(
+        Duration::from_secs(30),
+        subscriber.take(BULK_COUNT + URGENT_COUNT).try_collect(),
+    )
+    .await??;
+
+    bulk_task.await??;
+    urgent.finish().await?;
+
+    Ok(order)
+}
@@ -1,21 +1,19 @@
-use std::{
-    error::Error,
-    process::{Child, Command},
-};
+mod common;
+
+use common::{connect, Server};
+use std::error::Error;
 
 use futures::{stream::iter, FutureExt, SinkExt, StreamExt, TryStreamExt};
-use selium::{codecs::StringCodec, prelude::*, Subscriber};
+use selium::{codecs::StringCodec, prelude::*, StartPosition, Subscriber};
 
-const SERVER_ADDR: &'static str = "127.0.0.1:7001";
+const SERVER_ADDR: &str = "127.0.0.1:7001";
 
 #[tokio::test]
 async fn test_pub_sub() {
-    let mut handle = start_server();
+    let _server = Server::start(SERVER_ADDR);
 
     let result = run().await;
 
-    handle.kill().unwrap();
-
     let messages = result.unwrap();
     assert_eq!(messages[0], Some("foo".to_owned()));
     assert_eq!(messages[1], Some("bar".to_owned()));
@@ -41,11 +39,7 @@ async fn run() -> Result<[Option<String>; 16], Box<dyn Error>> {
     let subscriber3 = start_subscriber("/acmeco/something_else").await?;
     let subscriber4 = start_subscriber("/bluthco/stocks").await?;
 
-    let connection = selium::client()
-        .keep_alive(5_000)?
-        .with_certificate_authority("certs/ca.crt")?
-        .connect("127.0.0.1:7001")
-        .await?;
+    let connection = connect(SERVER_ADDR).await?;
 
     let mut publisher = connection
         .publisher("/acmeco/stocks")
@@ -98,35 +92,14 @@ async fn run() -> Result<[Option<String>; 16], Box<dyn Error>> {
 }
 
 async fn start_subscriber(topic: &str) -> Result<Subscriber<StringCodec, String>, Box<dyn Error>> {
-    let connection = selium::client()
-        .keep_alive(5_000)?
-        .with_certificate_authority("certs/ca.crt")?
-        .connect(SERVER_ADDR)
-        .await?;
+    let connection = connect(SERVER_ADDR).await?;
 
     Ok(connection
         .subscriber(topic)
         // .map("/selium/bonanza.wasm")
         // .filter("/selium/dodgy_stuff.wasm")
         .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
         .open()
         .await?)
 }
-
-fn start_server() -> Child {
-    Command::new(env!("CARGO"))
-        .args([
-            "run",
-            "--",
-            "--bind-addr",
-            SERVER_ADDR,
-            "--cert",
-            "tests/certs/ca.crt",
-            "--key",
-            "tests/certs/ca.key",
-            "-vvvv",
-        ])
-        .current_dir("..")
-        .spawn()
-        .expect("Failed to start server")
-}
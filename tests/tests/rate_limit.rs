@@ -0,0 +1,54 @@
+mod common;
+
+use common::{connect, Server};
+use futures::SinkExt;
+use selium::{codecs::StringCodec, prelude::*};
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+const SERVER_ADDR: &str = "127.0.0.1:7072";
+
+#[tokio::test]
+async fn test_rate_limited_publisher() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let elapsed = result.unwrap();
+
+    // 100 messages at 50 per second are spaced across roughly 2 seconds
+    assert!(elapsed >= Duration::from_millis(1_900), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(3), "{elapsed:?}");
+}
+
+async fn run() -> Result<Duration, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/throttled")
+        .with_encoder(StringCodec)
+        .rate_limit(50)?
+        .open()
+        .await?;
+
+    let started = Instant::now();
+
+    for i in 0..100 {
+        publisher.send(i.to_string()).await?;
+    }
+
+    let elapsed = started.elapsed();
+    publisher.finish().await?;
+
+    assert!(matches!(
+        connection
+            .publisher("/acmeco/throttled")
+            .with_encoder::<_, String>(StringCodec)
+            .rate_limit(0),
+        Err(selium::Error::Config(_))
+    ));
+
+    Ok(elapsed)
+}
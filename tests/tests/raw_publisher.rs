@@ -0,0 +1,110 @@
+mod common;
+
+use bytes::Bytes;
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7064";
+
+#[tokio::test]
+async fn test_raw_publisher() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let sent_raw = send_pre_encoded().await;
+    let forwarded = forward_raw_messages().await;
+
+    assert_eq!(
+        sent_raw.unwrap(),
+        vec!["pre-encoded".to_owned(), "encoded".to_owned()]
+    );
+    assert_eq!(forwarded.unwrap(), tickers());
+}
+
+fn tickers() -> Vec<String> {
+    vec!["MSFT".to_owned(), "INTC".to_owned(), "AAPL".to_owned()]
+}
+
+async fn send_pre_encoded() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/pre_encoded")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/pre_encoded")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher
+        .send_raw(Bytes::from_static(b"pre-encoded"))
+        .await?;
+    publisher.send("encoded".to_owned()).await?;
+    publisher.finish().await?;
+
+    receive(&mut subscriber, 2).await
+}
+
+async fn forward_raw_messages() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut raw = connection
+        .subscriber("/acmeco/upstream")
+        .raw()
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+    let mut subscriber = connection
+        .subscriber("/acmeco/downstream")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut upstream = connection
+        .publisher("/acmeco/upstream")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+    let mut downstream = connection
+        .publisher("/acmeco/downstream")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for ticker in tickers() {
+        upstream.send(ticker).await?;
+    }
+
+    upstream.finish().await?;
+
+    for bytes in receive(&mut raw, tickers().len()).await? {
+        downstream.send_raw(bytes).await?;
+    }
+
+    downstream.finish().await?;
+
+    receive(&mut subscriber, tickers().len()).await
+}
+
+async fn receive<S, T>(stream: &mut S, count: usize) -> Result<Vec<T>, Box<dyn Error>>
+where
+    S: StreamExt<Item = selium::Result<T>> + Unpin,
+{
+    let mut received = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let message = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await?
+            .ok_or("Subscriber ended early")??;
+        received.push(message);
+    }
+
+    Ok(received)
+}
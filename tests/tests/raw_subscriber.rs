@@ -0,0 +1,58 @@
+mod common;
+
+use bytes::Bytes;
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use selium::{codecs::RawBytesCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7063";
+
+#[tokio::test]
+async fn test_raw_subscriber_yields_published_bytes() {
+    let _server = Server::start(SERVER_ADDR);
+    let messages = vec![
+        Bytes::from_static(&[0x00, 0xff, 0x7f, 0x80, b'\n', 0xfe]),
+        Bytes::from_static(b"not valid \xc3\x28 utf-8"),
+        Bytes::new(),
+        Bytes::from(vec![0xab; 64 * 1024]),
+    ];
+
+    let received = run(messages.clone()).await;
+
+    assert_eq!(received.unwrap(), messages);
+}
+
+async fn run(messages: Vec<Bytes>) -> Result<Vec<Bytes>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/raw")
+        .raw()
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/raw")
+        .with_encoder(RawBytesCodec)
+        .open()
+        .await?;
+
+    for message in messages.iter() {
+        publisher.send(message.clone()).await?;
+    }
+
+    publisher.finish().await?;
+
+    let mut received = Vec::new();
+
+    for _ in 0..messages.len() {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+            .await?
+            .ok_or("Subscriber ended early")??;
+        received.push(message);
+    }
+
+    Ok(received)
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, StreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7060";
+const MESSAGE_COUNT: usize = 32;
+const MESSAGE_SIZE: usize = 256 * 1024;
+// Far smaller than a single message, so the transfer relies on the window being replenished
+const SMALL_WINDOW: u64 = 16 * 1024;
+
+#[tokio::test]
+async fn test_large_transfer_with_receive_windows() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let small = transfer(selium::client().stream_receive_window(SMALL_WINDOW)).await;
+    let large = transfer(
+        selium::client()
+            .stream_receive_window(64 * 1024 * 1024)
+            .receive_window(256 * 1024 * 1024),
+    )
+    .await;
+
+    assert_eq!(small.unwrap(), MESSAGE_COUNT * MESSAGE_SIZE);
+    assert_eq!(large.unwrap(), MESSAGE_COUNT * MESSAGE_SIZE);
+}
+
+#[tokio::test]
+async fn test_invalid_receive_windows() {
+    let zero = selium::client()
+        .stream_receive_window(0)
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await;
+    let too_large = selium::client()
+        .receive_window(u64::MAX)
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(SERVER_ADDR)
+        .await;
+
+    assert!(matches!(zero, Err(selium::Error::Config(_))));
+    assert!(matches!(too_large, Err(selium::Error::Config(_))));
+}
+
+async fn transfer(
+    builder: selium::ClientBuilder<selium::ClientWantsCert>,
+) -> Result<usize, Box<dyn Error>> {
+    let connection = builder
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/windows")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/windows")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // Consumed concurrently, as the server stops reading from the publisher once the
+    // subscriber's window is full
+    let receiver = tokio::spawn(async move {
+        let mut received = 0;
+
+        for _ in 0..MESSAGE_COUNT {
+            match subscriber.next().await {
+                Some(Ok(message)) => received += message.len(),
+                _ => break,
+            }
+        }
+
+        received
+    });
+
+    let message = "x".repeat(MESSAGE_SIZE);
+
+    for _ in 0..MESSAGE_COUNT {
+        publisher.send(message.clone()).await?;
+    }
+
+    publisher.finish().await?;
+
+    let received = tokio::time::timeout(Duration::from_secs(30), receiver).await??;
+
+    Ok(received)
+}
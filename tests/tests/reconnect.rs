@@ -0,0 +1,72 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, PublisherStrategy, ReconnectPolicy};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7007";
+// Keep the idle timeout short so that the client notices the server going away quickly
+const SERVER_ARGS: &[&str] = &["--max-idle-timeout", "1000"];
+
+#[tokio::test]
+async fn test_reconnect_after_server_restart() {
+    let mut server = Server::start_with_args(SERVER_ADDR, SERVER_ARGS);
+
+    let result = run(&mut server).await;
+
+    assert_eq!(result.unwrap(), "after restart");
+}
+
+async fn run(server: &mut Server) -> Result<String, Box<dyn Error>> {
+    let policy = ReconnectPolicy::default()
+        .initial_backoff(100u64)?
+        .max_backoff(1_000u64)?
+        .max_retries(30)
+        .publisher_strategy(PublisherStrategy::Buffer);
+
+    let connection = selium::client()
+        .keep_alive(250)?
+        .reconnect_policy(policy)
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/reconnect")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("before restart".to_owned()).await?;
+
+    server.kill();
+    *server = Server::start_with_args(SERVER_ADDR, SERVER_ARGS);
+
+    // Wait for the old connection to idle out
+    tokio::time::sleep(Duration::from_millis(2_000)).await;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/reconnect")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    // Keep publishing until the subscriber receives a message over the new connection
+    let received = tokio::time::timeout(Duration::from_secs(15), async {
+        loop {
+            publisher.send("after restart".to_owned()).await?;
+
+            let next = tokio::time::timeout(Duration::from_millis(200), subscriber.try_next());
+
+            if let Ok(message) = next.await {
+                return Ok::<_, Box<dyn Error>>(message?.unwrap_or_default());
+            }
+        }
+    })
+    .await??;
+
+    publisher.finish().await?;
+
+    Ok(received)
+}
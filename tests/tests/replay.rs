@@ -0,0 +1,67 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::codecs::StringCodec;
+use selium::prelude::*;
+use selium::ReplayStart;
+use std::{error::Error, fs, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7040";
+
+#[tokio::test]
+async fn test_replays_persisted_messages_before_live_messages() {
+    let log_dir = std::env::temp_dir().join(format!("selium-replay-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&log_dir);
+
+    let mut server =
+        Server::start_with_args(SERVER_ADDR, &["--log-dir", log_dir.to_str().unwrap()]);
+
+    let result = run().await;
+
+    server.kill();
+    let _ = fs::remove_dir_all(&log_dir);
+
+    let (replayed, live) = result.unwrap();
+    let expected: Vec<_> = (1..=10).map(|i| i.to_string()).collect();
+    assert_eq!(replayed, expected);
+    assert_eq!(live, "live");
+}
+
+// Returns the messages replayed by the late subscriber, and the live message after them
+async fn run() -> Result<(Vec<String>, String), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/audit")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    for i in 1..10 {
+        publisher.send(i.to_string()).await?;
+    }
+
+    // Confirmed once the server has appended every message to the topic's log
+    publisher.send_confirmed(10.to_string()).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/audit")
+        .with_decoder(StringCodec)
+        .replay_from(ReplayStart::Earliest)
+        .open()
+        .await?;
+
+    let mut replayed = Vec::new();
+
+    for _ in 0..10 {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        replayed.extend(message);
+    }
+
+    publisher.send("live".to_owned()).await?;
+
+    let live = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok((replayed, live.unwrap_or_default()))
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{connect, Server};
+use selium::{codecs::StringCodec, prelude::*};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7015";
+
+#[tokio::test]
+async fn test_request_reply() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (replies, unanswered) = result.unwrap();
+    assert_eq!(replies, vec!["echo: first", "echo: second", "echo: third"]);
+    assert!(unanswered.contains("Timed out waiting for a reply"));
+}
+
+async fn run() -> Result<(Vec<String>, String), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let replier = connection
+        .replier("/acmeco/echo")
+        .with_decoder(StringCodec)
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    tokio::spawn(replier.serve(|request: String| async move { Ok(format!("echo: {request}")) }));
+
+    let mut requestor = connection
+        .requestor("/acmeco/echo")
+        .with_encoder(StringCodec)
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut replies = Vec::new();
+
+    for request in ["first", "second", "third"] {
+        replies.push(requestor.request(request.to_owned()).await?);
+    }
+
+    requestor.finish().await?;
+
+    let mut unanswered = connection
+        .requestor("/acmeco/nobody-home")
+        .with_encoder(StringCodec)
+        .with_decoder(StringCodec)
+        .request_timeout(250)?
+        .open()
+        .await?;
+
+    let error = unanswered
+        .request("hello?".to_owned())
+        .await
+        .unwrap_err()
+        .to_string();
+
+    Ok((replies, error))
+}
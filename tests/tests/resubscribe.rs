@@ -0,0 +1,73 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, ReconnectPolicy, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7008";
+// Keep the idle timeout short so that the client notices the server going away quickly
+const SERVER_ARGS: &[&str] = &["--max-idle-timeout", "1000"];
+
+#[tokio::test]
+async fn test_subscriber_survives_connection_reset() {
+    let mut server = Server::start_with_args(SERVER_ADDR, SERVER_ARGS);
+
+    let result = run(&mut server).await;
+
+    let (before, after) = result.unwrap();
+    assert_eq!(before, "before reset");
+    assert_eq!(after, "after reset");
+}
+
+async fn run(server: &mut Server) -> Result<(String, String), Box<dyn Error>> {
+    let policy = ReconnectPolicy::default()
+        .initial_backoff(100u64)?
+        .max_backoff(1_000u64)?
+        .max_retries(30);
+
+    let connection = selium::client()
+        .keep_alive(250)?
+        .reconnect_policy(policy)
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/resubscribe")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/resubscribe")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("before reset".to_owned()).await?;
+    let before = subscriber.try_next().await?.unwrap_or_default();
+
+    // Simulate a connection reset by restarting the server
+    server.kill();
+    *server = Server::start_with_args(SERVER_ADDR, SERVER_ARGS);
+
+    // Keep publishing until the same subscriber handle yields a message again
+    let after = tokio::time::timeout(Duration::from_secs(15), async {
+        loop {
+            publisher.send("after reset".to_owned()).await?;
+
+            let next = tokio::time::timeout(Duration::from_millis(200), subscriber.try_next());
+
+            if let Ok(message) = next.await {
+                return Ok::<_, Box<dyn Error>>(message?.unwrap_or_default());
+            }
+        }
+    })
+    .await??;
+
+    publisher.finish().await?;
+
+    Ok((before, after))
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::codecs::StringCodec;
+use selium::prelude::*;
+use selium::StartPosition;
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7037";
+const SLOW_SUBSCRIBER_ADDR: &str = "127.0.0.1:7086";
+const RETAINED_COUNT: usize = 16;
+const RETAINED_SIZE: usize = 4 * 1024;
+
+#[tokio::test]
+async fn test_retained_messages_are_delivered_to_late_subscribers() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (retained, live) = result.unwrap();
+    assert_eq!(retained, vec!["first", "second"]);
+    assert_eq!(live, "third");
+}
+
+// Returns the retained messages received by the late subscriber, and the live message after them
+async fn run() -> Result<(Vec<String>, String), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/config")
+        .with_encoder(StringCodec)
+        .retain(Duration::from_secs(600))?
+        .open()
+        .await?;
+
+    publisher.send("first".to_owned()).await?;
+    // Confirmed once the server has retained both messages
+    publisher.send_confirmed("second".to_owned()).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/config")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::FromConnect)
+        .open()
+        .await?;
+
+    let mut retained = Vec::new();
+
+    for _ in 0..2 {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        retained.extend(message);
+    }
+
+    publisher.send("third".to_owned()).await?;
+
+    let live = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok((retained, live.unwrap_or_default()))
+}
+
+#[tokio::test]
+async fn test_slow_subscriber_does_not_stall_other_registrations() {
+    let _server = Server::start(SLOW_SUBSCRIBER_ADDR);
+
+    let result = register_alongside_slow_subscriber().await;
+
+    result.unwrap();
+}
+
+async fn register_alongside_slow_subscriber() -> Result<(), Box<dyn Error>> {
+    let connection = connect(SLOW_SUBSCRIBER_ADDR).await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/retained")
+        .with_encoder(StringCodec)
+        .retain(Duration::from_secs(600))?
+        .open()
+        .await?;
+
+    for _ in 0..RETAINED_COUNT {
+        publisher.send("x".repeat(RETAINED_SIZE)).await?;
+    }
+
+    publisher.send_confirmed("x".repeat(RETAINED_SIZE)).await?;
+
+    // The subscriber's window is too small for the retained messages, and it never reads them,
+    // so delivering them stalls until it does
+    let slow = selium::client()
+        .stream_receive_window(1024)
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(SLOW_SUBSCRIBER_ADDR)
+        .await?;
+
+    // Opened once the server has attached it, ahead of the retained messages
+    let _stalled = slow
+        .subscriber("/acmeco/retained")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::FromConnect)
+        .open()
+        .await?;
+
+    let _subscriber = tokio::time::timeout(
+        Duration::from_secs(5),
+        connection
+            .subscriber("/acmeco/other")
+            .with_decoder(StringCodec)
+            .start_position(StartPosition::Latest)
+            .open(),
+    )
+    .await
+    .map_err(|_| "Registration stalled behind the slow subscriber")??;
+
+    publisher.finish().await?;
+
+    Ok(())
+}
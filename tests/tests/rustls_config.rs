@@ -0,0 +1,58 @@
+mod common;
+
+use common::Server;
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, fs};
+
+const SERVER_ADDR: &str = "127.0.0.1:7056";
+
+#[tokio::test]
+async fn test_rustls_config() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    assert_eq!(result.unwrap(), "Hello, rustls!");
+}
+
+fn rustls_config() -> Result<rustls::ClientConfig, Box<dyn Error>> {
+    let ca = fs::read("certs/ca.crt")?;
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_parsable_certificates(&rustls_pemfile::certs(&mut &*ca)?);
+
+    let config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    Ok(config)
+}
+
+async fn run() -> Result<String, Box<dyn Error>> {
+    let connection = selium::client()
+        .keep_alive(5_000)?
+        .with_rustls_config(rustls_config()?)
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/rustls_config")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/rustls_config")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, rustls!".to_owned()).await?;
+    publisher.finish().await?;
+
+    let message = subscriber.try_next().await?.unwrap_or_default();
+
+    Ok(message)
+}
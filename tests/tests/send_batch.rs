@@ -0,0 +1,45 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{StreamExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7002";
+const BATCH_SIZE: usize = 1_000;
+
+#[tokio::test]
+async fn test_send_batch() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let messages = result.unwrap();
+    let expected: Vec<String> = (0..BATCH_SIZE).map(|i| format!("message {i}")).collect();
+    assert_eq!(messages, expected);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/batch")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/batch")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let batch = (0..BATCH_SIZE).map(|i| format!("message {i}")).collect();
+    publisher.send_batch(batch).await?;
+    publisher.finish().await?;
+
+    let messages = subscriber.take(BATCH_SIZE).try_collect().await?;
+
+    Ok(messages)
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use quinn::Endpoint;
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use selium_common::protocol::Frame;
+use selium_common::types::BiStream;
+use std::{error::Error, fs, sync::Arc, time::Duration};
+use tokio::sync::oneshot;
+
+const SERVER_ADDR: &str = "127.0.0.1:7080";
+const FAKE_SERVER_ADDR: &str = "127.0.0.1:7081";
+
+#[tokio::test]
+async fn test_send_confirmed() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let messages = result.unwrap();
+    assert_eq!(messages, vec!["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn test_send_confirmed_waits_for_receipt() {
+    let endpoint = fake_server().unwrap();
+    let (received, receipt_received) = oneshot::channel();
+    let (release, echo_released) = oneshot::channel();
+
+    // Holds the receipt until released, rather than echoing it as soon as it's read
+    let server = tokio::spawn(async move {
+        let connection = endpoint.accept().await.unwrap().await.unwrap();
+        let mut stream = BiStream::from(connection.accept_bi().await.unwrap());
+
+        assert!(matches!(
+            stream.next().await,
+            Some(Ok(Frame::RegisterPublisher(_)))
+        ));
+        assert!(matches!(stream.next().await, Some(Ok(Frame::Message(_)))));
+
+        let Some(Ok(receipt @ Frame::Receipt(_))) = stream.next().await else {
+            panic!("Expected a receipt after the message");
+        };
+
+        received.send(()).unwrap();
+        echo_released.await.unwrap();
+        stream.send(receipt).await.unwrap();
+
+        // Keeps the connection open until the publisher has finished with it
+        stream.next().await;
+    });
+
+    let connection = selium::client()
+        .with_certificate_authority("certs/ca.crt")
+        .unwrap()
+        .connect(FAKE_SERVER_ADDR)
+        .await
+        .unwrap();
+
+    let mut publisher = connection
+        .publisher("/acmeco/send_confirmed")
+        .with_encoder(StringCodec)
+        .open()
+        .await
+        .unwrap();
+
+    let confirmed = tokio::spawn(async move {
+        publisher.send_confirmed("held".to_owned()).await?;
+        publisher.finish().await
+    });
+
+    receipt_received.await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!confirmed.is_finished());
+
+    release.send(()).unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), confirmed)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    server.await.unwrap();
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/send_confirmed")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/send_confirmed")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut buffered = connection
+        .publisher("/acmeco/send_confirmed")
+        .with_encoder(StringCodec)
+        .flush_interval(Duration::from_secs(60))?
+        .open()
+        .await?;
+
+    publisher.send_confirmed("first".to_owned()).await?;
+    publisher.send_confirmed("second".to_owned()).await?;
+    // Confirmed without waiting for the flush interval to elapse
+    buffered.send_confirmed("third".to_owned()).await?;
+
+    let mut messages = Vec::new();
+
+    for _ in 0..3 {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+            .await?
+            .ok_or("Subscriber closed")??;
+        messages.push(message);
+    }
+
+    publisher.finish().await?;
+    buffered.finish().await?;
+
+    Ok(messages)
+}
+
+/// Binds a bare QUIC endpoint that stands in for the `Selium` server, so that the test controls
+/// when receipts are echoed.
+fn fake_server() -> Result<Endpoint, Box<dyn Error>> {
+    let certs = rustls_pemfile::certs(&mut &*fs::read("certs/ca.crt")?)?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+    let key = rustls_pemfile::pkcs8_private_keys(&mut &*fs::read("certs/ca.key")?)?
+        .pop()
+        .ok_or("Missing private key")?;
+
+    let mut crypto = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, rustls::PrivateKey(key))?;
+    crypto.alpn_protocols = vec![b"hq-29".to_vec()];
+
+    let config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+
+    Ok(Endpoint::server(config, FAKE_SERVER_ADDR.parse()?)?)
+}
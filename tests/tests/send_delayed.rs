@@ -0,0 +1,57 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+const SERVER_ADDR: &str = "127.0.0.1:7076";
+
+#[tokio::test]
+async fn test_delayed_messages_are_delivered_after_delay() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (messages, elapsed) = result.unwrap();
+    assert_eq!(messages, vec!["immediate", "delayed"]);
+    assert!(elapsed >= Duration::from_millis(500), "{elapsed:?}");
+}
+
+// Returns the messages in the order they were received, along with how long the delayed message
+// took to arrive after it was sent
+async fn run() -> Result<(Vec<String>, Duration), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/reminders")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/reminders")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let sent = Instant::now();
+    publisher
+        .send_delayed("delayed".to_owned(), Duration::from_millis(500))
+        .await?;
+    publisher.send("immediate".to_owned()).await?;
+
+    let mut messages = Vec::new();
+
+    for _ in 0..2 {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        messages.extend(message);
+    }
+
+    Ok((messages, sent.elapsed()))
+}
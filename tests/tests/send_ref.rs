@@ -0,0 +1,46 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7049";
+
+#[tokio::test]
+async fn test_send_ref() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let messages = result.unwrap();
+    assert_eq!(messages, vec!["borrowed", "owned", "borrowed"]);
+}
+
+async fn run() -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/send_ref")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/send_ref")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let message = "borrowed";
+
+    publisher.send_ref(message).await?;
+    publisher.send("owned".to_owned()).await?;
+    publisher.send_ref(message).await?;
+    publisher.finish().await?;
+
+    let messages = subscriber.take(3).try_collect().await?;
+
+    Ok(messages)
+}
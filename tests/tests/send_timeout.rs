@@ -0,0 +1,83 @@
+mod common;
+
+use common::{Server, StallingProxy};
+use futures::TryStreamExt;
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7018";
+// Large enough that the stream's flow control window fills quickly once the server stops responding
+const MESSAGE_SIZE: usize = 64 * 1024;
+const MAX_ATTEMPTS: usize = 1_000;
+
+#[tokio::test]
+async fn test_send_with_timeout() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let proxy = StallingProxy::start(SERVER_ADDR).await;
+
+    let result = run(&proxy).await;
+
+    let (timeout_err, after_resume) = result.unwrap();
+    assert!(timeout_err.contains("Timed out sending message"));
+    assert_eq!(after_resume, "after resume");
+}
+
+async fn run(proxy: &StallingProxy) -> Result<(String, String), Box<dyn Error>> {
+    let connection = selium::client()
+        .keep_alive(1_000)?
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(proxy.addr())
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/send_timeout")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/send_timeout")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    proxy.stall();
+
+    // Keep sending until backpressure from the stalled server stalls a flush
+    let mut timeout_err = None;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let message = "x".repeat(MESSAGE_SIZE);
+
+        if let Err(e) = publisher.send_with_timeout(message, 200).await {
+            timeout_err = Some(e.to_string());
+            break;
+        }
+    }
+
+    proxy.resume();
+
+    let timeout_err = timeout_err.ok_or("Expected a send to time out")?;
+
+    // The publisher should remain usable once the server responds again
+    publisher
+        .send_with_timeout("after resume".to_owned(), 5_000)
+        .await?;
+
+    let after_resume = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(message) = subscriber.try_next().await? {
+            if message == "after resume" {
+                return Ok::<_, Box<dyn Error>>(message);
+            }
+        }
+
+        Err("Subscriber closed before receiving the message".into())
+    })
+    .await??;
+
+    publisher.finish().await?;
+
+    Ok((timeout_err, after_resume))
+}
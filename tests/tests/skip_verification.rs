@@ -0,0 +1,52 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7011";
+
+#[tokio::test]
+async fn test_skip_server_verification() {
+    let _server = Server::start_self_signed(SERVER_ADDR);
+
+    let untrusted = untrusted().await;
+    let result = run().await;
+
+    assert!(untrusted.is_err());
+    assert_eq!(result.unwrap(), "Hello, world!");
+}
+
+async fn untrusted() -> Result<(), Box<dyn Error>> {
+    connect(SERVER_ADDR).await?;
+
+    Ok(())
+}
+
+async fn run() -> Result<String, Box<dyn Error>> {
+    let connection = selium::client()
+        .dangerous_skip_verification()
+        .connect(SERVER_ADDR)
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/skip_verification")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/skip_verification")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send("Hello, world!".to_owned()).await?;
+    publisher.finish().await?;
+
+    let message = subscriber.try_next().await?.unwrap_or_default();
+
+    Ok(message)
+}
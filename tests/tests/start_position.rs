@@ -0,0 +1,60 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::codecs::StringCodec;
+use selium::prelude::*;
+use selium::StartPosition;
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7075";
+
+#[tokio::test]
+async fn test_start_positions() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let latest = run("/acmeco/latest", StartPosition::Latest).await;
+    let from_connect = run("/acmeco/from_connect", StartPosition::FromConnect).await;
+
+    assert_eq!(latest.unwrap(), vec!["live"]);
+    assert_eq!(from_connect.unwrap(), vec!["retained", "live"]);
+}
+
+// Returns the messages received by a subscriber that starts from `position`, after a message is
+// retained on the topic before it connects, and a live message is published as soon as it opens
+async fn run(topic: &str, position: StartPosition) -> Result<Vec<String>, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut publisher = connection
+        .publisher(topic)
+        .with_encoder(StringCodec)
+        .retain(Duration::from_secs(600))?
+        .open()
+        .await?;
+
+    // Confirmed once the server has retained the message
+    publisher.send_confirmed("retained".to_owned()).await?;
+
+    let mut subscriber = connection
+        .subscriber(topic)
+        .with_decoder(StringCodec)
+        .start_position(position)
+        .open()
+        .await?;
+
+    // No need to wait for the server to register the subscription, as it's attached once opened
+    publisher.send("live".to_owned()).await?;
+
+    let mut messages = Vec::new();
+
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+        let message = message.unwrap_or_default();
+        let live = message == "live";
+        messages.push(message);
+
+        if live {
+            return Ok(messages);
+        }
+    }
+}
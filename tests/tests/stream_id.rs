@@ -0,0 +1,55 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7050";
+
+#[tokio::test]
+async fn test_stream_id() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    result.unwrap();
+}
+
+async fn run() -> Result<(), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/stream_id")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/stream_id")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let publisher_id = publisher.stream_id();
+    let subscriber_id = subscriber.stream_id();
+
+    for i in 0..10 {
+        publisher.send(format!("message {i}")).await?;
+        assert_eq!(publisher.stream_id(), publisher_id);
+    }
+
+    let duplicate = publisher.duplicate().await?;
+    assert_ne!(duplicate.stream_id(), publisher_id);
+    assert_ne!(subscriber_id, publisher_id);
+
+    publisher.finish().await?;
+    duplicate.finish().await?;
+
+    let received: Vec<_> = subscriber.by_ref().take(10).collect().await;
+    assert_eq!(received.len(), 10);
+    assert_eq!(subscriber.stream_id(), subscriber_id);
+
+    Ok(())
+}
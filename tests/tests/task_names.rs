@@ -0,0 +1,111 @@
+mod common;
+
+use common::{connect, eventually, Server};
+use futures::SinkExt;
+use selium::{codecs::StringCodec, prelude::*, tasks, StartPosition};
+use std::error::Error;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::Registry;
+
+const SERVER_ADDR: &str = "127.0.0.1:7084";
+
+// The name of each task spawned, as recorded by its span
+type Tasks = Arc<Mutex<Vec<String>>>;
+
+struct CaptureTasks(Tasks);
+
+impl<S: Subscriber> Layer<S> for CaptureTasks {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        if attrs.metadata().name() == "selium_task" {
+            attrs.record(&mut CaptureTask(self.0.clone()));
+        }
+    }
+}
+
+struct CaptureTask(Tasks);
+
+impl Visit for CaptureTask {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "task" {
+            self.0.lock().unwrap().push(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn Debug) {}
+}
+
+#[tokio::test]
+async fn test_task_names() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let tasks = Tasks::default();
+    let subscriber = Registry::default().with(CaptureTasks(tasks.clone()));
+    let guard = tracing::subscriber::set_default(subscriber);
+
+    let result = run(&tasks).await;
+
+    drop(guard);
+
+    result.unwrap();
+
+    let tasks = tasks.lock().unwrap();
+
+    for name in [
+        tasks::CTRL_C,
+        tasks::CONNECTION_WATCHER,
+        tasks::PUBLISHER_WRITER,
+        tasks::PUBLISHER_FINISH,
+        tasks::HEARTBEAT,
+        tasks::SUBSCRIBER_CHANNEL,
+    ] {
+        assert!(tasks.iter().any(|task| task == name), "{name} in {tasks:?}");
+    }
+}
+
+async fn run(tasks: &Tasks) -> Result<(), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let subscriber = connection
+        .subscriber("/acmeco/tasks")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .heartbeat(Duration::from_millis(10), 10)?
+        .open()
+        .await?;
+
+    let mut receiver = subscriber.into_channel(1);
+
+    let mut publisher = connection
+        .publisher("/acmeco/tasks")
+        .with_encoder(StringCodec)
+        .flush_interval(Duration::from_millis(10))?
+        .finish_on_drop()
+        .open()
+        .await?;
+
+    publisher.send("hello".to_owned()).await?;
+    drop(publisher);
+
+    let message = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+        .await?
+        .ok_or("Subscriber closed")??;
+    assert_eq!(message, "hello");
+
+    // Heartbeats are sent from the subscriber's channel once their interval elapses
+    eventually(|| async {
+        let spawned = tasks.lock().unwrap();
+        Ok(spawned
+            .iter()
+            .any(|task| task == tasks::HEARTBEAT)
+            .then_some(()))
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, Client, StartPosition, TopicMessage};
+use std::error::Error;
+
+const SERVER_ADDR: &str = "127.0.0.1:7005";
+
+#[tokio::test]
+async fn test_topic_metadata() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (mut fanned_in, exact) = result.unwrap();
+    fanned_in.sort_by(|a, b| a.topic.cmp(&b.topic));
+
+    assert_eq!(
+        fanned_in,
+        vec![
+            TopicMessage {
+                topic: "/acmeco/bonds".to_owned(),
+                payload: "bonds".to_owned(),
+            },
+            TopicMessage {
+                topic: "/acmeco/stocks".to_owned(),
+                payload: "stocks".to_owned(),
+            },
+        ]
+    );
+    assert_eq!(
+        exact,
+        vec![TopicMessage {
+            topic: "/acmeco/stocks".to_owned(),
+            payload: "stocks".to_owned(),
+        }]
+    );
+}
+
+async fn run() -> Result<(Vec<TopicMessage<String>>, Vec<TopicMessage<String>>), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let fanned_in = connection
+        .subscriber("/acmeco/#")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_topic_metadata()
+        .open()
+        .await?;
+
+    let exact = connection
+        .subscriber("/acmeco/stocks")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_topic_metadata()
+        .open()
+        .await?;
+
+    publish(&connection, "/acmeco/stocks", "stocks").await?;
+    publish(&connection, "/acmeco/bonds", "bonds").await?;
+
+    let fanned_in = fanned_in.take(2).try_collect().await?;
+    let exact = exact.take(1).try_collect().await?;
+
+    Ok((fanned_in, exact))
+}
+
+async fn publish(connection: &Client, topic: &str, message: &str) -> Result<(), Box<dyn Error>> {
+    let mut publisher = connection
+        .publisher(topic)
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send(message.to_owned()).await?;
+    publisher.finish().await?;
+
+    Ok(())
+}
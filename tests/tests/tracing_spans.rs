@@ -0,0 +1,83 @@
+mod common;
+
+use common::{connect, Server};
+use selium::{codecs::StringCodec, prelude::*};
+use std::error::Error;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::Registry;
+
+const SERVER_ADDR: &str = "127.0.0.1:7028";
+
+// The name of each span created, along with its recorded fields
+type Spans = Arc<Mutex<Vec<(String, Vec<String>)>>>;
+
+struct CaptureSpans(Spans);
+
+impl<S: Subscriber> Layer<S> for CaptureSpans {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = CaptureFields(Vec::new());
+        attrs.record(&mut fields);
+
+        let name = attrs.metadata().name().to_owned();
+        self.0.lock().unwrap().push((name, fields.0));
+    }
+}
+
+struct CaptureFields(Vec<String>);
+
+impl Visit for CaptureFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push(format!("{}={value:?}", field.name()));
+    }
+}
+
+#[tokio::test]
+async fn test_tracing_spans() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let spans = Spans::default();
+    let subscriber = Registry::default().with(CaptureSpans(spans.clone()));
+    let guard = tracing::subscriber::set_default(subscriber);
+
+    let result = run().await;
+
+    drop(guard);
+
+    result.unwrap();
+
+    let spans = spans.lock().unwrap();
+    let span = |name: &str| {
+        spans
+            .iter()
+            .find(|(span, _)| span == name)
+            .map(|(_, fields)| fields.clone())
+            .unwrap_or_else(|| panic!("Expected a {name} span"))
+    };
+
+    assert_eq!(span("connect"), vec![format!("addr={SERVER_ADDR}")]);
+    assert_eq!(span("open_publisher"), vec!["topic=/acmeco/tracing"]);
+    assert!(span("open_subscriber").contains(&"topic=/acmeco/tracing".to_owned()));
+}
+
+async fn run() -> Result<(), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    connection
+        .subscriber("/acmeco/tracing")
+        .with_decoder(StringCodec)
+        .open()
+        .await?;
+
+    connection
+        .publisher("/acmeco/tracing")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{Server, StallingProxy};
+use futures::{SinkExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, StartPosition, TrySendError};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7019";
+// Large enough that the stream's flow control window fills quickly once the server stops responding
+const MESSAGE_SIZE: usize = 64 * 1024;
+const MAX_ATTEMPTS: usize = 1_000;
+
+#[tokio::test]
+async fn test_try_send() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let proxy = StallingProxy::start(SERVER_ADDR).await;
+
+    let result = run(&proxy).await;
+
+    let (ready, returned, after_resume) = result.unwrap();
+    assert_eq!(ready, "ready");
+    assert_eq!(returned, "not ready");
+    assert_eq!(after_resume, "after resume");
+}
+
+async fn run(proxy: &StallingProxy) -> Result<(String, String, String), Box<dyn Error>> {
+    let connection = selium::client()
+        .keep_alive(1_000)?
+        .with_certificate_authority("certs/ca.crt")?
+        .connect(proxy.addr())
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/try_send")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/try_send")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    // A fresh stream is ready, so the message is sent
+    publisher.try_send("ready".to_owned())?;
+    publisher.flush().await?;
+    let ready = subscriber.try_next().await?.unwrap_or_default();
+
+    proxy.stall();
+
+    // Keep sending until backpressure from the stalled server leaves the stream not ready
+    let mut returned = None;
+
+    for _ in 0..MAX_ATTEMPTS {
+        match publisher.try_send("x".repeat(MESSAGE_SIZE)) {
+            Ok(()) => continue,
+            Err(TrySendError::Full(_)) => {
+                // The stream should stay not ready, returning each item to the caller
+                match publisher.try_send("not ready".to_owned()) {
+                    Err(TrySendError::Full(item)) => returned = Some(item),
+                    result => result?,
+                }
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    proxy.resume();
+
+    let returned = returned.ok_or("Expected the stream to become not ready")?;
+
+    publisher.send("after resume".to_owned()).await?;
+
+    let after_resume = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(message) = subscriber.try_next().await? {
+            if message == "after resume" {
+                return Ok::<_, Box<dyn Error>>(message);
+            }
+        }
+
+        Err("Subscriber closed before receiving the message".into())
+    })
+    .await??;
+
+    publisher.finish().await?;
+
+    Ok((ready, returned, after_resume))
+}
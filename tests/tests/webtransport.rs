@@ -0,0 +1,192 @@
+mod common;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt};
+use quinn::{Connection, Endpoint, RecvStream, SendStream, VarInt};
+use selium::prelude::*;
+use selium::{codecs::StringCodec, StartPosition};
+use selium_common::protocol::{Frame, MessagePayload, PublisherPayload};
+use selium_common::types::BiStream;
+use std::{error::Error, fs, sync::Arc, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7074";
+
+#[tokio::test]
+async fn test_webtransport_sessions() {
+    let _server = Server::start_with_args(SERVER_ADDR, &["--webtransport"]);
+
+    let published = publish_over_webtransport().await;
+    let rejected = open_session(Method::Get).await;
+
+    assert_eq!(published.unwrap(), "Hello from the browser");
+
+    // The HEADERS frame of a `:status 400` response
+    let (_, response) = rejected.unwrap();
+    assert_eq!(response, vec![0x00, 0x00, 0xff, 0x04]);
+}
+
+async fn publish_over_webtransport() -> Result<String, Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/stocks")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let (session, response) = open_session(Method::Connect).await?;
+    // The HEADERS frame of a `:status 200` response
+    assert_eq!(response[..3], [0x00, 0x00, 0xd9]);
+
+    // The session ID is the ID of the CONNECT stream, which is the first bidirectional stream
+    let (mut send, recv) = session.connection.open_bi().await?;
+    send.write_all(&[0x40, 0x41, 0x00]).await?;
+
+    let mut stream = BiStream::from((send, recv));
+    stream
+        .send(Frame::RegisterPublisher(PublisherPayload {
+            topic: "/acmeco/stocks".into(),
+            ..Default::default()
+        }))
+        .await?;
+    stream
+        .send(Frame::Message(MessagePayload {
+            message: Bytes::from("Hello from the browser"),
+            ..Default::default()
+        }))
+        .await?;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+        .await?
+        .ok_or("Subscriber closed")??;
+
+    session
+        .connection
+        .close(VarInt::from_u32(0), b"WebTransport session closed");
+
+    Ok(message)
+}
+
+/// A WebTransport client connection, which holds its control and CONNECT streams open for the
+/// lifetime of the session.
+struct Session {
+    connection: Connection,
+    _control: SendStream,
+    _connect: (SendStream, RecvStream),
+}
+
+/// The method of the request that opens a session.
+enum Method {
+    /// An extended CONNECT request for the `webtransport` protocol.
+    Connect,
+    Get,
+}
+
+/// Opens a WebTransport client connection, and sends a request with the given method, returning
+/// the session along with the QPACK field section of the response.
+async fn open_session(method: Method) -> Result<(Session, Vec<u8>), Box<dyn Error>> {
+    let connection = connect_h3().await?;
+
+    // The client's control stream, with a SETTINGS frame enabling WebTransport and HTTP datagrams
+    let mut control = connection.open_uni().await?;
+    control
+        .write_all(&[0x00, 0x04, 0x07, 0x33, 0x01, 0xab, 0x60, 0x37, 0x42, 0x01])
+        .await?;
+
+    let (mut send, mut recv) = connection.open_bi().await?;
+    assert_eq!(VarInt::from(send.id()).into_inner(), 0);
+    send.write_all(&request(method)).await?;
+
+    let response = read_headers(&mut recv).await?;
+
+    let session = Session {
+        connection,
+        _control: control,
+        _connect: (send, recv),
+    };
+
+    Ok((session, response))
+}
+
+async fn connect_h3() -> Result<Connection, Box<dyn Error>> {
+    let ca = fs::read("certs/ca.crt")?;
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut &*ca)? {
+        roots.add(&rustls::Certificate(cert))?;
+    }
+
+    let mut crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    crypto.alpn_protocols = vec![b"h3".to_vec()];
+
+    let mut endpoint = Endpoint::client("127.0.0.1:0".parse()?)?;
+    endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(crypto)));
+
+    Ok(endpoint.connect(SERVER_ADDR.parse()?, "localhost")?.await?)
+}
+
+/// Encodes the HEADERS frame of a request, as a browser would, using the QPACK static table and
+/// Huffman encoding.
+fn request(method: Method) -> BytesMut {
+    let mut authority = Vec::new();
+    httlib_huffman::encode(b"localhost:7074", &mut authority).unwrap();
+
+    // Required insert count and base, followed by `:method` and `:scheme https`
+    let method = match method {
+        Method::Connect => 0xcf,
+        Method::Get => 0xd1,
+    };
+    let mut fields = BytesMut::from(&[0x00, 0x00, method, 0xd7][..]);
+    // `:authority`, with a Huffman-encoded value
+    fields.put_u8(0x50);
+    fields.put_u8(0x80 | authority.len() as u8);
+    fields.put_slice(&authority);
+    // `:path`
+    fields.put_u8(0x51);
+    fields.put_u8(7);
+    fields.put_slice(b"/selium");
+    // `:protocol`, which has no static table entry
+    if method == 0xcf {
+        fields.put_slice(&[0x27, 0x02]);
+        fields.put_slice(b":protocol");
+        fields.put_u8(12);
+        fields.put_slice(b"webtransport");
+    }
+
+    let mut frame = BytesMut::from(&[0x01, fields.len() as u8][..]);
+    frame.extend_from_slice(&fields);
+    frame
+}
+
+/// Reads the field section of the first HEADERS frame, skipping any reserved frames that the
+/// server sends to exercise the client's handling of unknown frame types.
+async fn read_headers(recv: &mut RecvStream) -> Result<Vec<u8>, Box<dyn Error>> {
+    loop {
+        let frame_type = read_varint(recv).await?;
+        let mut payload = vec![0; read_varint(recv).await? as usize];
+        recv.read_exact(&mut payload).await?;
+
+        if frame_type == 0x01 {
+            return Ok(payload);
+        }
+    }
+}
+
+async fn read_varint(recv: &mut RecvStream) -> Result<u64, Box<dyn Error>> {
+    let mut byte = [0];
+    recv.read_exact(&mut byte).await?;
+
+    let len = 1 << (byte[0] >> 6);
+    let mut value = u64::from(byte[0] & 0x3f);
+
+    for _ in 1..len {
+        recv.read_exact(&mut byte).await?;
+        value = (value << 8) | u64::from(byte[0]);
+    }
+
+    Ok(value)
+}
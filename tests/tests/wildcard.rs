@@ -0,0 +1,77 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use selium::{codecs::StringCodec, prelude::*, Client, StartPosition};
+use std::{error::Error, time::Duration};
+
+const SERVER_ADDR: &str = "127.0.0.1:7004";
+
+#[tokio::test]
+async fn test_wildcard_subscriber() {
+    let _server = Server::start(SERVER_ADDR);
+
+    let result = run().await;
+
+    let (mut messages, unmatched) = result.unwrap();
+    messages.sort();
+
+    assert_eq!(
+        messages,
+        vec![
+            ("/acmeco/bonds/trades".to_owned(), "bonds".to_owned()),
+            ("/acmeco/stocks/trades".to_owned(), "stocks".to_owned()),
+        ]
+    );
+    assert!(unmatched.is_none());
+}
+
+async fn run() -> Result<(Vec<(String, String)>, Option<(String, String)>), Box<dyn Error>> {
+    let connection = connect(SERVER_ADDR).await?;
+
+    // Topic exists before the wildcard subscription is registered
+    let mut stocks = connection
+        .publisher("/acmeco/stocks/trades")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    let mut subscriber = connection
+        .subscriber("/acmeco/+/trades")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .with_topic()
+        .open()
+        .await?;
+
+    publish(&connection, "/acmeco/stocks/quotes", "quotes").await?;
+    publish(&connection, "/acmeco/bonds/trades", "bonds").await?;
+
+    stocks.send("stocks".to_owned()).await?;
+    stocks.finish().await?;
+
+    let mut messages = Vec::new();
+    messages.push(subscriber.try_next().await?.unwrap());
+    messages.push(subscriber.try_next().await?.unwrap());
+
+    let unmatched = match tokio::time::timeout(Duration::from_millis(500), subscriber.next()).await
+    {
+        Ok(Some(message)) => Some(message?),
+        _ => None,
+    };
+
+    Ok((messages, unmatched))
+}
+
+async fn publish(connection: &Client, topic: &str, message: &str) -> Result<(), Box<dyn Error>> {
+    let mut publisher = connection
+        .publisher(topic)
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send(message.to_owned()).await?;
+    publisher.finish().await?;
+
+    Ok(())
+}
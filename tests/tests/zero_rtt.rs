@@ -0,0 +1,73 @@
+mod common;
+
+use common::{connect, Server};
+use futures::{SinkExt, TryStreamExt};
+use selium::prelude::*;
+use selium::Client;
+use selium::{codecs::StringCodec, StartPosition};
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+const SERVER_ADDR: &str = "127.0.0.1:7032";
+
+#[tokio::test]
+async fn test_0rtt_resumption() {
+    let _server = Server::start_with_args(SERVER_ADDR, &["--enable-0rtt"]);
+
+    let result = run().await;
+
+    let (full_handshake, resumed, messages) = result.unwrap();
+    assert!(
+        resumed < full_handshake,
+        "Expected resumed connection ({resumed:?}) to be faster than the full handshake ({full_handshake:?})"
+    );
+    assert_eq!(messages, vec!["first", "second"]);
+}
+
+async fn run() -> Result<(Duration, Duration, Vec<String>), Box<dyn Error>> {
+    // Wait for the server to start, without caching a session ticket, so that only the
+    // handshakes are timed
+    connect(SERVER_ADDR).await?.graceful_shutdown(1_000).await?;
+
+    let (first, full_handshake) = connect_timed().await?;
+    // Round-tripping a message ensures the session ticket has been received
+    let mut messages = vec![publish_and_receive(&first, "first").await?];
+    first.graceful_shutdown(1_000).await?;
+
+    let (second, resumed) = connect_timed().await?;
+    messages.push(publish_and_receive(&second, "second").await?);
+
+    Ok((full_handshake, resumed, messages))
+}
+
+async fn connect_timed() -> Result<(Client, Duration), Box<dyn Error>> {
+    let builder = selium::client()
+        .enable_0rtt()
+        .with_certificate_authority("certs/ca.crt")?;
+
+    let started = Instant::now();
+    let connection = builder.connect(SERVER_ADDR).await?;
+
+    Ok((connection, started.elapsed()))
+}
+
+async fn publish_and_receive(connection: &Client, message: &str) -> Result<String, Box<dyn Error>> {
+    let mut subscriber = connection
+        .subscriber("/acmeco/zero_rtt")
+        .with_decoder(StringCodec)
+        .start_position(StartPosition::Latest)
+        .open()
+        .await?;
+
+    let mut publisher = connection
+        .publisher("/acmeco/zero_rtt")
+        .with_encoder(StringCodec)
+        .open()
+        .await?;
+
+    publisher.send(message.to_owned()).await?;
+
+    let received = tokio::time::timeout(Duration::from_secs(5), subscriber.try_next()).await??;
+
+    Ok(received.unwrap_or_default())
+}